@@ -1,6 +1,9 @@
 use core::arch::asm;
 
 
+/// # Safety
+/// `n` must be a system call that takes no arguments, it runs with the
+/// privileges of the process.
 #[inline]
 pub unsafe fn syscall0(n: usize) -> usize {
     let mut ret: usize;
@@ -12,6 +15,9 @@ pub unsafe fn syscall0(n: usize) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall1(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
@@ -25,6 +31,9 @@ pub unsafe fn syscall1(n: usize, args: &[usize]) -> usize {
 }
 
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall2(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
@@ -38,6 +47,9 @@ pub unsafe fn syscall2(n: usize, args: &[usize]) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall3(
     n: usize,
@@ -55,6 +67,9 @@ pub unsafe fn syscall3(
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall4(
     n: usize,
@@ -75,6 +90,9 @@ pub unsafe fn syscall4(
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall5(
     n: usize,
@@ -96,6 +114,9 @@ pub unsafe fn syscall5(
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall6(
     n: usize,
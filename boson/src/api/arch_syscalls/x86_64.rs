@@ -1,11 +1,14 @@
 use core::arch::asm;
 
+/// # Safety
+/// `n` must be a system call that takes no arguments, it runs with the
+/// privileges of the process.
 #[inline]
 pub unsafe fn syscall0(n: usize) -> usize {
     let mut ret: usize;
     asm!(
         "syscall",
-        inlateout("rax") n as usize => ret,
+        inlateout("rax") n => ret,
         out("rcx") _,
         out("r11") _,
         options(nostack, preserves_flags)
@@ -13,12 +16,15 @@ pub unsafe fn syscall0(n: usize) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall1(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
     asm!(
         "syscall",
-        inlateout("rax") n as usize => ret,
+        inlateout("rax") n => ret,
         in("rdi") args[0],
         out("rcx") _,
         out("r11") _,
@@ -27,12 +33,15 @@ pub unsafe fn syscall1(n: usize, args: &[usize]) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall2(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
     asm!(
         "syscall",
-        inlateout("rax") n as usize => ret,
+        inlateout("rax") n => ret,
         in("rdi") args[0],
         in("rsi") args[1],
         out("rcx") _,
@@ -42,12 +51,15 @@ pub unsafe fn syscall2(n: usize, args: &[usize]) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall3(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
     asm!(
         "syscall",
-        inlateout("rax") n as usize => ret,
+        inlateout("rax") n => ret,
         in("rdi") args[0],
         in("rsi") args[1],
         in("rdx") args[2],
@@ -58,12 +70,15 @@ pub unsafe fn syscall3(n: usize, args: &[usize]) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall4(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
     asm!(
         "syscall",
-        inlateout("rax") n as usize => ret,
+        inlateout("rax") n => ret,
         in("rdi") args[0],
         in("rsi") args[1],
         in("rdx") args[2],
@@ -75,12 +90,15 @@ pub unsafe fn syscall4(n: usize, args: &[usize]) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall5(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
     asm!(
         "syscall",
-        inlateout("rax") n as usize => ret,
+        inlateout("rax") n => ret,
         in("rdi") args[0],
         in("rsi") args[1],
         in("rdx") args[2],
@@ -93,12 +111,15 @@ pub unsafe fn syscall5(n: usize, args: &[usize]) -> usize {
     ret
 }
 
+/// # Safety
+/// `n` must be a valid system call and `args` must hold the arguments it
+/// expects, pointers among them must be valid for what the kernel does with them.
 #[inline]
 pub unsafe fn syscall6(n: usize, args: &[usize]) -> usize {
     let mut ret: usize;
     asm!(
         "syscall",
-        inlateout("rax") n as usize => ret,
+        inlateout("rax") n => ret,
         in("rdi") args[0],
         in("rsi") args[1],
         in("rdx") args[2],
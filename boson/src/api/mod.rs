@@ -3,6 +3,7 @@ extern crate rand;
 use crate::compiler::errors::CompileError;
 use crate::compiler::loader::BytecodeLoader;
 use crate::compiler::loader::BytecodeWriter;
+use crate::compiler::spans::SourceLocation;
 use crate::compiler::BytecodeCompiler;
 use crate::compiler::BytecodeDecompiler;
 use crate::compiler::CompiledBytecode;
@@ -14,6 +15,7 @@ use crate::vm::errors::VMError;
 use crate::vm::BosonVM;
 use std::env::Vars;
 use std::fmt;
+use std::fs;
 use std::rc::Rc;
 
 use rand::rngs::SmallRng;
@@ -23,6 +25,12 @@ pub mod arch_syscalls;
 pub mod native;
 pub mod packing;
 
+// platform functions with longer signatures:
+pub type ExecFn = fn(args: &[Rc<Object>]) -> Result<(i32, Vec<u8>), String>;
+pub type FreadFn =
+    fn(path: String, start: Option<u64>, n_b: Option<u64>) -> Result<(Vec<u8>, u64), String>;
+pub type SyscallFn = fn(Vec<Rc<Object>>) -> Result<Rc<Object>, String>;
+
 #[derive(Debug)]
 pub enum PlatformKind {
     Native,
@@ -32,7 +40,7 @@ pub enum PlatformKind {
 pub struct Platform {
     pub platform_type: PlatformKind,
     pub print: fn(fmt_string: &String),
-    pub exec: fn(args: &Vec<Rc<Object>>) -> Result<(i32, Vec<u8>), String>,
+    pub exec: ExecFn,
     pub get_args: fn() -> Vec<Rc<Object>>,
     pub get_env: fn(name: &String) -> Result<String, String>,
     pub get_envs: fn() -> Vars,
@@ -41,17 +49,16 @@ pub struct Platform {
     pub sleep: fn(duration_ms: &f64),
     pub sys_shell: fn() -> String,
     pub get_stdlib_path: fn() -> String,
-    pub fread:
-        fn(path: String, start: Option<u64>, n_b: Option<u64>) -> Result<(Vec<u8>, u64), String>,
-    pub fwrite: fn(path: String, data: &Vec<u8>) -> Result<u64, String>,
-    pub fappend: fn(path: String, data: &Vec<u8>) -> Result<u64, String>,
+    pub fread: FreadFn,
+    pub fwrite: fn(path: String, data: &[u8]) -> Result<u64, String>,
+    pub fappend: fn(path: String, data: &[u8]) -> Result<u64, String>,
     pub finfo: fn(path: String) -> Result<Rc<Object>, String>,
     pub stdin_read: fn() -> Result<Vec<u8>, String>,
-    pub stdout_write: fn(data: &Vec<u8>) -> Result<usize, String>,
+    pub stdout_write: fn(data: &[u8]) -> Result<usize, String>,
     pub read_line: fn(display: Option<String>) -> Result<String, String>,
     pub rand_generator: SmallRng,
     pub get_supported_syscalls: fn() -> Rc<Object>,
-    pub syscall: fn(Vec<Rc<Object>>) -> Result<Rc<Object>, String>,
+    pub syscall: SyscallFn,
 }
 
 impl fmt::Debug for Platform {
@@ -104,9 +111,11 @@ impl BosonLang {
     }
 
     pub fn new_from_file(file: String) -> BosonLang {
+        let mut compiler = BytecodeCompiler::new();
+        compiler.set_source_file(&file);
+
         let lexer = LexerAPI::new_from_file(file);
         let parser = Parser::new_from_lexer(lexer);
-        let compiler = BytecodeCompiler::new();
 
         return BosonLang {
             parser: parser,
@@ -157,14 +166,44 @@ impl BosonLang {
             }
             ErrorKind::CompileError(c_error) => {
                 println!("Compiler Error:");
-                println!("{:?}: {}, at: {}", c_error.t, c_error.message, c_error.pos);
+                let message = format!("{:?}: {}", c_error.t, c_error.message);
+                if c_error.location.is_some() {
+                    println!(
+                        "{}",
+                        self.__render_location(c_error.location.as_ref().unwrap(), &message)
+                    );
+                } else {
+                    println!("{}, at: {}", message, c_error.pos);
+                }
             }
             ErrorKind::VMError(vm_error) => {
                 println!("Runtime Error:");
-                println!(
-                    "{:?}: {} at {}, Instruction: {:?}",
-                    vm_error.t, vm_error.message, vm_error.pos, vm_error.instruction
-                );
+                if !vm_error.trace.is_empty() {
+                    println!("Traceback (most recent call last):");
+                    for entry in vm_error.trace.iter().rev() {
+                        println!("  {}", entry);
+                        let source_line = match &entry.location {
+                            Some(location) => self.__get_source_line(location),
+                            None => None,
+                        };
+                        if source_line.is_some() {
+                            println!("    {}", source_line.unwrap().trim());
+                        }
+                    }
+                }
+
+                let message = format!("{:?}: {}", vm_error.t, vm_error.message);
+                if vm_error.location.is_some() {
+                    println!(
+                        "{}",
+                        self.__render_location(vm_error.location.as_ref().unwrap(), &message)
+                    );
+                } else {
+                    println!(
+                        "{} at {}, Instruction: {:?}",
+                        message, vm_error.pos, vm_error.instruction
+                    );
+                }
             }
         }
 
@@ -172,6 +211,22 @@ impl BosonLang {
         self.print_globals();
     }
 
+    fn __get_source_line(&self, location: &SourceLocation) -> Option<String> {
+        // errors raised from imported modules point into the module file:
+        if location.file == self.parser.lexer.file_name {
+            return self.parser.lexer.get_source_line(location.line);
+        }
+
+        return match fs::read(&location.file) {
+            Ok(buffer) => LexerAPI::new_from_buffer(buffer).get_source_line(location.line),
+            Err(_) => None,
+        };
+    }
+
+    fn __render_location(&self, location: &SourceLocation, message: &String) -> String {
+        return location.render(message, self.__get_source_line(location));
+    }
+
     pub fn update(&mut self, new_buffer: Vec<u8>) {
         self.parser.reset_errors();
         self.parser.lexer = LexerAPI::new_from_buffer(new_buffer);
@@ -21,7 +21,6 @@ use std::cell::RefCell;
 
 use hash::HashTable;
 use object::Object;
-use std::collections::HashMap;
 use std::thread;
 use std::time::Duration;
 
@@ -44,10 +43,10 @@ pub fn print(st: &String) {
     print!("{}", st);
 }
 
-pub fn exec(args: &Vec<Rc<Object>>) -> Result<(i32, Vec<u8>), String> {
+pub fn exec(args: &[Rc<Object>]) -> Result<(i32, Vec<u8>), String> {
     let mut command = Command::new(args[0].as_ref().describe());
-    for idx in 1..args.len() {
-        command.arg(args[idx].as_ref().describe());
+    for arg in &args[1..] {
+        command.arg(arg.as_ref().describe());
     }
 
     let result = command.output();
@@ -113,7 +112,7 @@ pub fn get_platform_info() -> Vec<String> {
 }
 
 pub fn sleep(duration_ms: &f64) {
-    let ns_time = (*duration_ms * 1000000 as f64).round() as u64;
+    let ns_time = (*duration_ms * 1000000_f64).round() as u64;
     thread::sleep(Duration::from_nanos(ns_time));
 }
 
@@ -187,8 +186,12 @@ pub fn fread(path: String, start: Option<u64>, n_b: Option<u64>) -> Result<(Vec<
 
 // Writes content and returns the new size of the file.
 // This function will always create a new file.
-pub fn fwrite(path: String, data: &Vec<u8>) -> Result<u64, String> {
-    let f_handle_res = fs::OpenOptions::new().create(true).write(true).open(&path);
+pub fn fwrite(path: String, data: &[u8]) -> Result<u64, String> {
+    let f_handle_res = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&path);
     if f_handle_res.is_err() {
         return Err(format!(
             "Cannot open {}, Reason: {:?}",
@@ -199,14 +202,14 @@ pub fn fwrite(path: String, data: &Vec<u8>) -> Result<u64, String> {
 
     // write:
     let mut f_handle = f_handle_res.unwrap();
-    let mut result = f_handle.write_all(&data);
+    let mut result = f_handle.write_all(data);
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     result = f_handle.flush();
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     return Ok(data.len() as u64);
@@ -214,8 +217,8 @@ pub fn fwrite(path: String, data: &Vec<u8>) -> Result<u64, String> {
 
 // Writes content and returns the new size of the file.
 // This function append to a file, if exists or creates a new file.
-pub fn fappend(path: String, data: &Vec<u8>) -> Result<u64, String> {
-    let f_handle_res = fs::OpenOptions::new().write(true).append(true).open(&path);
+pub fn fappend(path: String, data: &[u8]) -> Result<u64, String> {
+    let f_handle_res = fs::OpenOptions::new().append(true).open(&path);
     if f_handle_res.is_err() {
         return Err(format!(
             "Cannot open {}, Reason: {:?}",
@@ -226,14 +229,14 @@ pub fn fappend(path: String, data: &Vec<u8>) -> Result<u64, String> {
 
     // write:
     let mut f_handle = f_handle_res.unwrap();
-    let mut result = f_handle.write_all(&data);
+    let mut result = f_handle.write_all(data);
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     result = f_handle.flush();
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     return Ok(data.len() as u64);
@@ -248,48 +251,43 @@ pub fn finfo(path: String) -> Result<Rc<Object>, String> {
 
     // convert to hash table
     let metadata = m_result.unwrap();
-    let mut h_map = HashMap::new();
+    let mut h_table = HashTable::new("finfo".to_string());
 
-    h_map.insert(
-        Rc::new(Object::Str(format!("is_file"))),
+    h_table.set(
+        Rc::new(Object::Str("is_file".to_string())),
         Rc::new(Object::Bool(metadata.is_file())),
     );
-    h_map.insert(
-        Rc::new(Object::Str(format!("is_dir"))),
+    h_table.set(
+        Rc::new(Object::Str("is_dir".to_string())),
         Rc::new(Object::Bool(metadata.is_dir())),
     );
-    h_map.insert(
-        Rc::new(Object::Str(format!("size"))),
+    h_table.set(
+        Rc::new(Object::Str("size".to_string())),
         Rc::new(Object::Int(metadata.len() as i64)),
     );
-    h_map.insert(
-        Rc::new(Object::Str(format!("is_read_only"))),
+    h_table.set(
+        Rc::new(Object::Str("is_read_only".to_string())),
         Rc::new(Object::Bool(metadata.permissions().readonly())),
     );
 
-    if let Some(created) = metadata.created().ok() {
-        if let Some(time_f) = created.duration_since(SystemTime::UNIX_EPOCH).ok() {
-            h_map.insert(
-                Rc::new(Object::Str(format!("created_at"))),
+    if let Ok(created) = metadata.created() {
+        if let Ok(time_f) = created.duration_since(SystemTime::UNIX_EPOCH) {
+            h_table.set(
+                Rc::new(Object::Str("created_at".to_string())),
                 Rc::new(Object::Float(time_f.as_secs_f64())),
             );
         }
     }
 
-    if let Some(access) = metadata.accessed().ok() {
-        if let Some(time_f) = access.duration_since(SystemTime::UNIX_EPOCH).ok() {
-            h_map.insert(
-                Rc::new(Object::Str(format!("accessed_at"))),
+    if let Ok(access) = metadata.accessed() {
+        if let Ok(time_f) = access.duration_since(SystemTime::UNIX_EPOCH) {
+            h_table.set(
+                Rc::new(Object::Str("accessed_at".to_string())),
                 Rc::new(Object::Float(time_f.as_secs_f64())),
             );
         }
     }
 
-    let h_table = HashTable {
-        name: "finfo".to_string(),
-        entries: h_map,
-    };
-
     return Ok(Rc::new(Object::HashTable(RefCell::new(h_table))));
 }
 
@@ -298,7 +296,7 @@ pub fn stdin_read() -> Result<Vec<u8>, String> {
     let mut buffer = vec![];
     let result = io::stdin().read(&mut buffer);
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     return Ok(buffer);
@@ -313,7 +311,7 @@ pub fn read_line(display: Option<String>) -> Result<String, String> {
     let mut string_buffer = String::new();
     let result = io::stdin().read_line(&mut string_buffer);
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     string_buffer = string_buffer.replace("\n", "");
@@ -321,18 +319,18 @@ pub fn read_line(display: Option<String>) -> Result<String, String> {
 }
 
 // Write stdout output:
-pub fn stdout_write(data: &Vec<u8>) -> Result<usize, String> {
+pub fn stdout_write(data: &[u8]) -> Result<usize, String> {
     let stdout = io::stdout();
     let mut lock = stdout.lock();
-    let mut result = lock.write_all(&data);
+    let mut result = lock.write_all(data);
 
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     result = lock.flush();
     if result.is_err() {
-        return Err(format!("IO Error"));
+        return Err("IO Error".to_string());
     }
 
     return Ok(data.len());
@@ -362,19 +360,16 @@ pub fn get_stdlib_path() -> String {
 
 pub fn get_syscalls_as_map() -> Rc<Object> {
     let all_syscall_names = syscall_numbers::syscall_names();
-    let mut hash_map = HashTable {
-        name: "syscall_names".to_string(),
-        entries: HashMap::new(),
-    };
+    let mut hash_map = HashTable::new("syscall_names".to_string());
 
     for (idx, syscall_name) in all_syscall_names.iter().enumerate() {
-        if *syscall_name == "" {
-            hash_map.entries.insert(
+        if syscall_name.is_empty() {
+            hash_map.set(
                 Rc::new(Object::Str(format!("{}_{}", "SYSCALL", idx))),
                 Rc::new(Object::Int(idx as i64)),
             );
         } else {
-            hash_map.entries.insert(
+            hash_map.set(
                 Rc::new(Object::Str(syscall_name.to_string().to_uppercase())),
                 Rc::new(Object::Int(idx as i64)),
             );
@@ -17,7 +17,7 @@ fn to_encodable_type(obj: Rc<Object>, required_type: &str) -> Option<EncodeType>
             "int8" => return Some(EncodeType::Int8(*number as i8)),
             "int16" => return Some(EncodeType::Int16(*number as i16)),
             "int32" => return Some(EncodeType::Int32(*number as i32)),
-            "int64" => return Some(EncodeType::Int64(*number as i64)),
+            "int64" => return Some(EncodeType::Int64(*number)),
             "int128" => return Some(EncodeType::Int128(*number as i128)),
             _ => return None,
         },
@@ -31,7 +31,7 @@ fn to_encodable_type(obj: Rc<Object>, required_type: &str) -> Option<EncodeType>
             _ => return None,
         },
         Object::Byte(b) => match required_type {
-            "uint8" => return Some(EncodeType::Uint8(*b as u8)),
+            "uint8" => return Some(EncodeType::Uint8(*b)),
             "int8" => return Some(EncodeType::Int8(*b as i8)),
             _ => return None,
         },
@@ -86,5 +86,5 @@ pub fn encode_boson_types(
         )))));
     }
 
-    Err(format!("encoding failed for given types"))
+    Err("encoding failed for given types".to_string())
 }
@@ -24,8 +24,8 @@ pub fn main() {
 
     // run compiler:
     let ret = BosonLang::save_bytecode_from_file(f_name.clone());
-    if ret.is_some() {
-        println!("Wrote {} bytes.", ret.unwrap());
+    if let Some(n_bytes) = ret {
+        println!("Wrote {} bytes.", n_bytes);
         process::exit(0);
     }
 
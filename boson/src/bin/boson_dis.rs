@@ -23,14 +23,14 @@ pub fn main() {
 
     if f_name.ends_with(".b") {
         let result = BosonLang::disasm_bytecode(f_name.clone());
-        if result.is_some() {
-            println!("{}", result.unwrap());
+        if let Some(listing) = result {
+            println!("{}", listing);
         }
     } else {
         // run evaluator:
         let result = BosonLang::disasm_file(f_name.clone());
-        if result.is_some() {
-            println!("{}", result.unwrap());
+        if let Some(listing) = result {
+            println!("{}", listing);
         }
     }
 }
@@ -1,6 +1,8 @@
 use std::fmt;
 
-#[derive(Debug, Clone)]
+use crate::compiler::spans::SourceLocation;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum CompilerErrorKind {
     UnresolvedSymbol,
     ConstantAssignment,
@@ -12,6 +14,13 @@ pub enum CompilerErrorKind {
     BytecodeError,
     InvalidBreak,
     InvalidContinue,
+    InvalidRethrow,
+    ImportError,
+    CircularImport,
+    ImpureFunction,
+    InvalidYield,
+    InvalidMatch,
+    InvalidArguments,
 }
 
 #[derive(Debug, Clone)]
@@ -19,6 +28,7 @@ pub struct CompileError {
     pub t: CompilerErrorKind,
     pub message: String,
     pub pos: usize,
+    pub location: Option<SourceLocation>,
 }
 
 impl CompileError {
@@ -28,6 +38,7 @@ impl CompileError {
             message: message,
             t: t,
             pos: pos,
+            location: None,
         };
     }
 }
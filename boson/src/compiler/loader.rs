@@ -4,13 +4,17 @@ use byteorder::BigEndian;
 use byteorder::LittleEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use num_bigint::BigInt;
 
+use crate::compiler::spans::SpanTable;
 use crate::compiler::symtab::ConstantPool;
 use crate::compiler::CompiledBytecode;
 use crate::compiler::CompiledInstructions;
+use crate::types::buffer::Buffer;
 use crate::types::object::Object;
 use crate::types::subroutine::Subroutine;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
 use std::mem;
@@ -20,6 +24,8 @@ use std::slice;
 
 const USE_BIG_ENDIAN_REPR: bool = false;
 const MAGIC: &str = "000BOSON";
+// bumped on every change of the layout, older files are rejected:
+const BYTECODE_VERSION: u64 = 2;
 
 #[allow(dead_code)]
 #[repr(u8)]
@@ -31,6 +37,9 @@ pub enum TypeCode {
     FLOAT,
     BOOL,
     SUBROUTINE,
+    BIGINT,
+    BYTE,
+    BYTES,
 }
 
 pub struct ByteOps {}
@@ -38,6 +47,8 @@ pub struct ByteOps {}
 impl ByteOps {
     // takes a sized struct and returns the in-memory byte representation
     // zero-copy, see: https://stackoverflow.com/questions/28127165/how-to-convert-struct-to-u8
+    /// # Safety
+    /// `S` must have no padding bytes, they are not initialized.
     pub unsafe fn as_bytes<S: Sized>(s: &S) -> &[u8] {
         let byte_slice_repr =
             slice::from_raw_parts((s as *const S) as *const u8, mem::size_of::<S>());
@@ -46,6 +57,8 @@ impl ByteOps {
 
     // returns the typed representation of a slice of bytes
     // zero-copy, this just returns the typed reference, does not copy any data.
+    /// # Safety
+    /// Any bit pattern of the size of `T` must be a valid `T`.
     pub unsafe fn as_type<T: Sized>(buf: &[u8]) -> Option<T> {
         if buf.len() == mem::size_of::<T>() {
             let addr = &buf[0];
@@ -131,10 +144,7 @@ impl ByteOps {
         println!("generating magic");
         let result = unsafe { ByteOps::as_type::<u64>(MAGIC.as_bytes()) };
 
-        match result {
-            Some(u64_magic) => u64_magic,
-            None => 0,
-        }
+        return result.unwrap_or_default();
     }
 }
 
@@ -149,6 +159,7 @@ pub struct DataIndexItem {
 #[repr(C, packed)]
 pub struct Header {
     pub magic: u64,
+    pub version: u64,
     pub num_data: u64,
     pub num_sub: u64,
     pub data_end_idx: u64,
@@ -163,6 +174,10 @@ pub struct SubroutineIndexItem {
     pub code_idx: u64,
     pub const_idx: i32,
     pub is_local: bool,
+    pub is_generator: bool,
+    pub n_defaults: u64,
+    pub is_variadic: bool,
+    pub is_pure: bool,
 }
 
 // organization of bytecode file:
@@ -182,6 +197,12 @@ pub struct BytecodeWriter {
     pub bin_pool: Vec<u8>,
 }
 
+impl Default for BytecodeWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BytecodeWriter {
     pub fn new() -> BytecodeWriter {
         BytecodeWriter {
@@ -190,6 +211,7 @@ impl BytecodeWriter {
             subroutine_items: vec![],
             header: Header {
                 magic: ByteOps::generate_magic(),
+                version: BYTECODE_VERSION,
                 num_data: 0,
                 num_sub: 0,
                 data_end_idx: 0,
@@ -226,11 +248,18 @@ impl BytecodeWriter {
         n_p: usize,
         n_l: usize,
         is_local: bool,
+        is_generator: bool,
+        params: &[String],
+        n_defaults: usize,
+        is_variadic: bool,
+        is_pure: bool,
         code: &CompiledInstructions,
     ) -> u64 {
         // create a data-index for name:
-        let name_data_idx = self.new_data_idx(const_idx, TypeCode::SUBROUTINE, &name.as_bytes());
-        let code_idx = self.new_data_idx(const_idx, TypeCode::SUBROUTINE, &code);
+        let name_data_idx = self.new_data_idx(const_idx, TypeCode::SUBROUTINE, name.as_bytes());
+        let code_idx = self.new_data_idx(const_idx, TypeCode::SUBROUTINE, code);
+        // parameter names, separated by commas:
+        self.new_data_idx(const_idx, TypeCode::SUBROUTINE, params.join(",").as_bytes());
 
         let subroutine = SubroutineIndexItem {
             name_data_idx,
@@ -239,6 +268,10 @@ impl BytecodeWriter {
             code_idx,
             const_idx,
             is_local,
+            is_generator,
+            n_defaults: n_defaults as u64,
+            is_variadic,
+            is_pure,
         };
 
         // push to subroutine pool:
@@ -251,25 +284,37 @@ impl BytecodeWriter {
 
     fn encode_to_binary(&mut self, bytecode: &CompiledBytecode) -> Result<Vec<u8>, String> {
         // main function:
-        self.new_subroutine_idx(-1, "main".to_string(), 0, 0, false, &bytecode.instructions);
+        self.new_subroutine_idx(
+            -1,
+            "main".to_string(),
+            0,
+            0,
+            false,
+            false,
+            &[],
+            0,
+            false,
+            false,
+            &bytecode.instructions,
+        );
 
-        let mut current_count = 0;
         // now compile the constant pool:
-        for object in &bytecode.constant_pool.objects {
+        for (idx, object) in bytecode.constant_pool.objects.iter().enumerate() {
+            let current_count = idx as i32;
             match object.as_ref() {
                 Object::Bool(b) => {
                     let b_val = if *b { vec![1u8] } else { vec![0u8] };
-                    self.new_data_idx(current_count as i32, TypeCode::BOOL, &b_val);
+                    self.new_data_idx(current_count, TypeCode::BOOL, &b_val);
                 }
 
                 Object::Str(st) => {
                     let b_val = st.as_bytes();
-                    self.new_data_idx(current_count as i32, TypeCode::STR, &b_val);
+                    self.new_data_idx(current_count, TypeCode::STR, b_val);
                 }
 
                 Object::Char(ch) => {
                     let b_val = vec![*ch as u8];
-                    self.new_data_idx(current_count as i32, TypeCode::CHAR, &b_val);
+                    self.new_data_idx(current_count, TypeCode::CHAR, &b_val);
                 }
 
                 Object::Int(i) => {
@@ -278,7 +323,22 @@ impl BytecodeWriter {
                         return Err(format!("Failed to serialize int {}", i));
                     }
 
-                    self.new_data_idx(current_count as i32, TypeCode::INT, &b_res.unwrap());
+                    self.new_data_idx(current_count, TypeCode::INT, &b_res.unwrap());
+                }
+
+                Object::Byte(b) => {
+                    self.new_data_idx(current_count, TypeCode::BYTE, &[*b]);
+                }
+
+                Object::ByteBuffer(buffer) => {
+                    let b_val = buffer.borrow().data.clone();
+                    self.new_data_idx(current_count, TypeCode::BYTES, &b_val);
+                }
+
+                // big integers are stored as little endian two's complement bytes:
+                Object::BigInt(b) => {
+                    let b_val = b.to_signed_bytes_le();
+                    self.new_data_idx(current_count, TypeCode::BIGINT, &b_val);
                 }
 
                 Object::Float(f) => {
@@ -287,22 +347,27 @@ impl BytecodeWriter {
                         return Err(format!("Failed to serialize float {}", f));
                     }
 
-                    self.new_data_idx(current_count as i32, TypeCode::FLOAT, &b_res.unwrap());
+                    self.new_data_idx(current_count, TypeCode::FLOAT, &b_res.unwrap());
                 }
 
                 Object::Subroutine(sub) => {
                     self.new_subroutine_idx(
-                        current_count as i32,
+                        current_count,
                         sub.get_name().clone(),
                         sub.gen_n_parameters(),
                         sub.get_n_locals(),
                         sub.is_local_scope,
+                        sub.is_generator,
+                        &sub.parameter_names,
+                        sub.num_defaults,
+                        sub.is_variadic,
+                        sub.is_pure,
                         &sub.as_ref().bytecode,
                     );
                 }
 
                 Object::Noval => {
-                    self.new_data_idx(current_count as i32, TypeCode::NONE, &vec![0u8]);
+                    self.new_data_idx(current_count, TypeCode::NONE, &[0u8]);
                 }
 
                 _ => {
@@ -312,7 +377,6 @@ impl BytecodeWriter {
                     ));
                 }
             }
-            current_count += 1;
         }
 
         let mut data_idx_bin: Vec<u8> = vec![];
@@ -409,7 +473,7 @@ impl BytecodeLoader {
         for idx in 0..h.num_sub {
             let item_slice =
                 &sub_section[(idx as usize * item_size)..((idx + 1) as usize * item_size)];
-            let sub_item_res = unsafe { ByteOps::as_type::<SubroutineIndexItem>(&item_slice) };
+            let sub_item_res = unsafe { ByteOps::as_type::<SubroutineIndexItem>(item_slice) };
             if sub_item_res.is_none() {
                 return Err(format!(
                     "SubroutineIndexItem cannot be derived from {:?}",
@@ -434,7 +498,7 @@ impl BytecodeLoader {
         for idx in 0..h.num_data {
             let item_slice =
                 &data_section[(idx as usize * item_size)..((idx + 1) as usize * item_size)];
-            let data_item_res = unsafe { ByteOps::as_type::<DataIndexItem>(&item_slice) };
+            let data_item_res = unsafe { ByteOps::as_type::<DataIndexItem>(item_slice) };
             if data_item_res.is_none() {
                 return Err(format!(
                     "DataIndexItem cannot be derived from {:?}",
@@ -462,6 +526,12 @@ impl BytecodeLoader {
         }
 
         let bin = bin_read_res.unwrap();
+        if bin.len() < mem::size_of::<Header>() {
+            return Err(format!(
+                "Improper bytecode file {}, header is truncated.",
+                self.name
+            ));
+        }
 
         // verify magic:
         let v_res = self.__verify_magic(&bin[0..8]);
@@ -480,6 +550,14 @@ impl BytecodeLoader {
         }
 
         let header: Header = header_res.unwrap();
+        let version = header.version;
+        if version != BYTECODE_VERSION {
+            return Err(format!(
+                "Incompatible bytecode version {} in {}, expected version {}, recompile the source file.",
+                version, self.name, BYTECODE_VERSION
+            ));
+        }
+
         // make some checks:
         let has_aligned_subs = (header.sub_end_idx as usize - mem::size_of::<Header>())
             / mem::size_of::<SubroutineIndexItem>()
@@ -523,7 +601,11 @@ impl BytecodeLoader {
         let bin_pool = &self.bin[self.bin_pool_start..];
 
         let mut cp = vec![];
-        cp.resize(self.n_data_items - self.n_sub_items, Rc::new(Object::Noval));
+        // every subroutine has its name, code and parameter names as data items:
+        cp.resize(
+            self.n_data_items - 2 * self.n_sub_items,
+            Rc::new(Object::Noval),
+        );
         let mut instructions = vec![];
         // iterate over data pool:
         for (const_idx, data_item) in &self.data_table {
@@ -538,8 +620,7 @@ impl BytecodeLoader {
                 }
                 TypeCode::BOOL => {
                     let data = bin_pool[base_data_item.start as usize];
-                    cp[*const_idx as usize] =
-                        Rc::new(Object::Bool(if data != 0u8 { true } else { false }));
+                    cp[*const_idx as usize] = Rc::new(Object::Bool(data != 0u8));
                 }
                 TypeCode::STR => {
                     let str_slice =
@@ -554,17 +635,33 @@ impl BytecodeLoader {
                 TypeCode::INT => {
                     let b_slice =
                         &bin_pool[base_data_item.start as usize..base_data_item.end as usize];
-                    let result = ByteOps::get_as_i64(&b_slice);
+                    let result = ByteOps::get_as_i64(b_slice);
                     if result.is_err() {
                         return Err(result.unwrap_err());
                     }
 
                     cp[*const_idx as usize] = Rc::new(Object::Int(result.unwrap()));
                 }
+                TypeCode::BYTE => {
+                    let data = bin_pool[base_data_item.start as usize];
+                    cp[*const_idx as usize] = Rc::new(Object::Byte(data));
+                }
+                TypeCode::BYTES => {
+                    let b_slice =
+                        &bin_pool[base_data_item.start as usize..base_data_item.end as usize];
+                    let buffer = Buffer::from_u8(b_slice.to_vec(), "literal".to_string(), false);
+                    cp[*const_idx as usize] = Rc::new(Object::ByteBuffer(RefCell::new(buffer)));
+                }
+                TypeCode::BIGINT => {
+                    let b_slice =
+                        &bin_pool[base_data_item.start as usize..base_data_item.end as usize];
+                    cp[*const_idx as usize] =
+                        Rc::new(Object::BigInt(BigInt::from_signed_bytes_le(b_slice)));
+                }
                 TypeCode::FLOAT => {
                     let b_slice =
                         &bin_pool[base_data_item.start as usize..base_data_item.end as usize];
-                    let result = ByteOps::get_as_f64(&b_slice);
+                    let result = ByteOps::get_as_f64(b_slice);
                     if result.is_err() {
                         return Err(result.unwrap_err());
                     }
@@ -599,12 +696,36 @@ impl BytecodeLoader {
                         instructions = bytecode_vector;
                     } else {
                         // child function
+                        let params_item: &DataIndexItem = &data_item[2];
+                        let params_res = String::from_utf8(
+                            bin_pool[params_item.start as usize..params_item.end as usize].to_vec(),
+                        );
+                        if params_res.is_err() {
+                            return Err(format!(
+                                "Subroutine with index {} has invalid parameter names",
+                                const_idx
+                            ));
+                        }
+
+                        let params = params_res.unwrap();
+                        let parameter_names = if params.is_empty() {
+                            vec![]
+                        } else {
+                            params.split(',').map(|name| name.to_string()).collect()
+                        };
+
                         let subroutine_obj = Subroutine {
                             name: sub_name_res.unwrap(),
                             bytecode: bytecode_vector,
                             num_locals: subroutine_item.n_locals as usize,
                             num_parameters: subroutine_item.n_params as usize,
                             is_local_scope: subroutine_item.is_local,
+                            spans: SpanTable::new(),
+                            is_pure: subroutine_item.is_pure,
+                            is_generator: subroutine_item.is_generator,
+                            parameter_names: parameter_names,
+                            num_defaults: subroutine_item.n_defaults as usize,
+                            is_variadic: subroutine_item.is_variadic,
                         };
 
                         cp[*const_idx as usize] =
@@ -621,6 +742,7 @@ impl BytecodeLoader {
                 size: n_objs,
             },
             instructions,
+            spans: SpanTable::new(),
         });
     }
 }
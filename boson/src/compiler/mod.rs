@@ -1,17 +1,32 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 pub mod errors;
 pub mod loader;
+pub mod spans;
 pub mod symtab;
 
+use crate::api::native;
 use crate::isa;
+use crate::lexer::LexerAPI;
+use crate::lexer::Span;
 use crate::parser::ast;
 use crate::parser::exp;
+use crate::parser::Parser;
+use crate::types::buffer::Buffer;
+use crate::types::builtins::BuiltinKind;
 use crate::types::object::Object;
 use crate::types::subroutine::Subroutine;
 
 use isa::InstructionPacker;
 use isa::Operands;
+use spans::SourceLocation;
+use spans::SpanTable;
 use symtab::ConstantPool;
 
 pub type CompiledInstructions = Vec<u8>;
@@ -20,6 +35,7 @@ pub type CompiledInstructions = Vec<u8>;
 pub struct CompiledBytecode {
     pub constant_pool: ConstantPool,
     pub instructions: CompiledInstructions,
+    pub spans: SpanTable,
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +55,7 @@ struct ProgramScope {
     last: Option<OpCode>,
     previous: Option<OpCode>,
     size: usize,
+    spans: SpanTable,
 }
 
 impl ProgramScope {
@@ -48,6 +65,7 @@ impl ProgramScope {
             last: None,
             previous: None,
             size: 0,
+            spans: SpanTable::new(),
         };
     }
 
@@ -101,6 +119,15 @@ impl ProgramScope {
 
 type ProgramScopes = Vec<ProgramScope>;
 
+#[derive(PartialEq)]
+enum FunctionKind {
+    Named,
+    Lambda,
+    Method,
+    // init of a class, returns the instance it was called on.
+    Constructor,
+}
+
 pub struct BytecodeCompiler {
     pub constant_pool: ConstantPool,
     pub symbol_table: symtab::SymbolTable,
@@ -109,12 +136,57 @@ pub struct BytecodeCompiler {
     loop_ctls: Vec<LoopControl>,
     n_lambdas: usize,
     attr_ctls: Vec<usize>,
+    n_exc_handlers: usize,
+    n_try_blocks: usize,
+    // finally blocks of the try statements being compiled, innermost last.
+    finally_ctls: Vec<FinallyControl>,
+    catch_idents: Vec<ast::IdentifierType>,
+    modules: HashMap<String, Vec<(String, usize)>>,
+    import_stack: Vec<PathBuf>,
+    source_name: String,
+    returns_instance: bool,
+    // name of the pure function being compiled, and all the pure functions seen so far.
+    pure_fn: Option<String>,
+    pure_functions: HashSet<String>,
+    // functions, classes and constants holding lambdas that may not be pure.
+    impure_callables: HashSet<String>,
+    // set once a yield is compiled in the current function, it becomes a generator.
+    is_generator: bool,
 }
 
 struct LoopControl {
     loop_start_pos: usize,
     pos_after_loop: usize,
     break_pos: Vec<usize>,
+    n_exc_handlers: usize,
+    n_finally_ctls: usize,
+}
+
+/*
+    break, continue and return inside a try statement with a finally block
+    store the action in a local and jump to the finally block, which carries
+    the action out once it completes.
+*/
+struct FinallyControl {
+    // handlers active outside the try statement:
+    n_exc_handlers: usize,
+    action_sym: Rc<symtab::Symbol>,
+    // the value of a pending return:
+    value_sym: Rc<symtab::Symbol>,
+    jump_pos: Vec<usize>,
+    // the actions used in the try statement:
+    actions: Vec<usize>,
+}
+
+const FINALLY_BREAK: usize = 1;
+const FINALLY_CONTINUE: usize = 2;
+const FINALLY_RETURN: usize = 3;
+const FINALLY_RETURN_VALUE: usize = 4;
+
+impl Default for BytecodeCompiler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BytecodeCompiler {
@@ -132,6 +204,18 @@ impl BytecodeCompiler {
             loop_ctls: vec![],
             n_lambdas: 0,
             attr_ctls: vec![],
+            n_exc_handlers: 0,
+            n_try_blocks: 0,
+            finally_ctls: vec![],
+            catch_idents: vec![],
+            modules: HashMap::new(),
+            import_stack: vec![],
+            source_name: String::from("<input>"),
+            returns_instance: false,
+            pure_fn: None,
+            pure_functions: HashSet::new(),
+            impure_callables: HashSet::new(),
+            is_generator: false,
         };
     }
 
@@ -149,9 +233,37 @@ impl BytecodeCompiler {
             loop_ctls: vec![],
             n_lambdas: 0,
             attr_ctls: vec![],
+            n_exc_handlers: 0,
+            n_try_blocks: 0,
+            finally_ctls: vec![],
+            catch_idents: vec![],
+            modules: HashMap::new(),
+            import_stack: vec![],
+            source_name: String::from("<input>"),
+            returns_instance: false,
+            pure_fn: None,
+            pure_functions: HashSet::new(),
+            impure_callables: HashSet::new(),
+            is_generator: false,
         };
     }
 
+    pub fn set_source_file(&mut self, file: &String) {
+        let path = fs::canonicalize(file).unwrap_or(PathBuf::from(file));
+        self.import_stack.clear();
+        self.import_stack.push(path);
+        self.source_name = file.clone();
+    }
+
+    fn current_file(&self) -> String {
+        // the root script keeps the name it was opened with:
+        if self.import_stack.len() <= 1 {
+            return self.source_name.clone();
+        }
+
+        return format!("{}", self.import_stack.last().unwrap().display());
+    }
+
     #[allow(dead_code)]
     fn register_constant(&mut self, obj: Object) -> usize {
         self.constant_pool.set_object(Rc::new(obj))
@@ -175,7 +287,7 @@ impl BytecodeCompiler {
     }
 
     #[allow(dead_code)]
-    fn exit_scope(&mut self) -> Result<CompiledInstructions, errors::CompileError> {
+    fn exit_scope(&mut self) -> Result<(CompiledInstructions, SpanTable), errors::CompileError> {
         if self.symbol_table.parent.is_none() {
             return Err(errors::CompileError::new(
                 "Compiler Error, invalid top-level scope".to_string(),
@@ -187,10 +299,11 @@ impl BytecodeCompiler {
         self.symbol_table = self.symbol_table.parent.as_ref().unwrap().as_ref().clone();
 
         let instructions = self.scopes[self.scope_index].get_instructions().clone();
+        let spans = self.scopes[self.scope_index].spans.clone();
         self.scope_index -= 1;
         self.scopes.pop();
 
-        return Ok(instructions);
+        return Ok((instructions, spans));
     }
 
     fn compile_literal(&mut self, literal: &ast::LiteralKind) -> Option<errors::CompileError> {
@@ -201,30 +314,43 @@ impl BytecodeCompiler {
                 self.save(isa::InstructionKind::IConstant, &vec![idx]);
             }
             ast::LiteralKind::Float(f) => {
-                let idx = self.register_constant(Object::Float(f.clone()));
+                let idx = self.register_constant(Object::Float(*f));
 
                 self.save(isa::InstructionKind::IConstant, &vec![idx]);
             }
             ast::LiteralKind::Int(i) => {
-                let idx = self.register_constant(Object::Int(i.clone()));
+                let idx = self.register_constant(Object::Int(*i));
+                self.save(isa::InstructionKind::IConstant, &vec![idx]);
+            }
+            ast::LiteralKind::BigInt(b) => {
+                let idx = self.register_constant(Object::BigInt(b.clone()));
                 self.save(isa::InstructionKind::IConstant, &vec![idx]);
             }
             ast::LiteralKind::Char(c) => {
-                let idx = self.register_constant(Object::Char(c.clone()));
+                let idx = self.register_constant(Object::Char(*c));
+                self.save(isa::InstructionKind::IConstant, &vec![idx]);
+            }
+            ast::LiteralKind::Byte(b) => {
+                let idx = self.register_constant(Object::Byte(*b));
+                self.save(isa::InstructionKind::IConstant, &vec![idx]);
+            }
+            ast::LiteralKind::Bytes(bytes) => {
+                let buffer = Buffer::from_u8(bytes.clone(), "literal".to_string(), false);
+                let idx = self.register_constant(Object::ByteBuffer(RefCell::new(buffer)));
                 self.save(isa::InstructionKind::IConstant, &vec![idx]);
             }
             ast::LiteralKind::Bool(b) => {
-                let idx = self.register_constant(Object::Bool(b.clone()));
+                let idx = self.register_constant(Object::Bool(*b));
                 self.save(isa::InstructionKind::IConstant, &vec![idx]);
             }
             ast::LiteralKind::Array(arr) => {
-                let error = self.compile_array(&arr);
+                let error = self.compile_array(arr);
                 if error.is_some() {
                     return error;
                 }
             }
             ast::LiteralKind::HashTable(ht) => {
-                let error = self.compile_hash(&ht);
+                let error = self.compile_hash(ht);
                 if error.is_some() {
                     return error;
                 }
@@ -250,9 +376,44 @@ impl BytecodeCompiler {
         return None;
     }
 
+    // pushes start, stop and step, a missing part is pushed as none.
+    fn compile_slice_parts(&mut self, node: &ast::SliceType) -> Option<errors::CompileError> {
+        for part in [&node.start, &node.stop, &node.step].iter() {
+            match part {
+                Some(expr) => {
+                    let error = self.compile_expression(expr);
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+                None => {
+                    let reg_constant = self.register_constant(Object::Noval);
+                    self.save(isa::InstructionKind::IConstant, &vec![reg_constant]);
+                }
+            }
+        }
+
+        return None;
+    }
+
+    fn compile_slice(&mut self, node: &ast::SliceType) -> Option<errors::CompileError> {
+        let mut error = self.compile_expression(&node.expression_left);
+        if error.is_some() {
+            return error;
+        }
+
+        error = self.compile_slice_parts(node);
+        if error.is_some() {
+            return error;
+        }
+
+        self.save(isa::InstructionKind::ISlice, &vec![]);
+        return None;
+    }
+
     fn compile_assert_statement(&mut self, node: &ast::AssertType) -> Option<errors::CompileError> {
         let assert_expr = &node.target_expr;
-        let mut error = self.compile_expression(&assert_expr);
+        let mut error = self.compile_expression(assert_expr);
         if error.is_some() {
             return error;
         }
@@ -284,7 +445,7 @@ impl BytecodeCompiler {
     fn compile_if_statement(&mut self, node: &ast::IfElseType) -> Option<errors::CompileError> {
         let if_expr = &node.condition;
         // compile the if expr:
-        let mut error = self.compile_expression(&if_expr);
+        let mut error = self.compile_expression(if_expr);
         if error.is_some() {
             return error;
         }
@@ -334,7 +495,7 @@ impl BytecodeCompiler {
         }
 
         // compile the else block
-        error = self.compile_block_statement(&node.alternate_block.as_ref().unwrap());
+        error = self.compile_block_statement(node.alternate_block.as_ref().unwrap());
         if error.is_some() {
             return error;
         }
@@ -354,202 +515,1074 @@ impl BytecodeCompiler {
         return None;
     }
 
-    fn compile_return_stmt(&mut self, node: &ast::ReturnType) -> Option<errors::CompileError> {
-        if node.expression.is_some() {
-            // compile the return expression
-            let exp = &node.expression.as_ref().unwrap();
-            let error = self.compile_expression(exp);
+    /*
+        Pure functions depend only on their arguments, so their results can be
+        memoized. Anything that touches the outside world is rejected while one
+        is being compiled, this includes functions nested in it.
+    */
+    fn check_pure(&self, action: String) -> Option<errors::CompileError> {
+        if self.pure_fn.is_none() {
+            return None;
+        }
 
-            if error.is_some() {
-                return error;
-            }
+        return Some(errors::CompileError::new(
+            format!(
+                "Pure function {} cannot {}",
+                self.pure_fn.as_ref().unwrap(),
+                action
+            ),
+            errors::CompilerErrorKind::ImpureFunction,
+            0,
+        ));
+    }
 
-            self.save(isa::InstructionKind::IRetVal, &vec![]);
-            return None;
-        } else {
-            self.save(isa::InstructionKind::IRet, &vec![]);
-            return None;
+    // the variable an expression like a.b[0].c starts from:
+    fn root_identifier(expr: &ast::ExpressionKind) -> Option<&ast::IdentifierType> {
+        match expr {
+            ast::ExpressionKind::Identifier(id) => return Some(id),
+            ast::ExpressionKind::Attribute(attr) => {
+                return BytecodeCompiler::root_identifier(&attr.parent)
+            }
+            ast::ExpressionKind::Index(idx) => {
+                return BytecodeCompiler::root_identifier(&idx.expression_left)
+            }
+            ast::ExpressionKind::Slice(slice) => {
+                return BytecodeCompiler::root_identifier(&slice.expression_left)
+            }
+            _ => return None,
         }
     }
 
-    fn compile_function(
-        &mut self,
-        node: &ast::FunctionType,
-        is_lambda: bool,
-    ) -> Option<errors::CompileError> {
-        // check function name:
-        let resolve_result = self.symbol_table.resolve_symbol(&node.name);
-        if resolve_result.is_some() {
-            return Some(errors::CompileError::new(
-                format!("Name {} already defined", &node.name),
-                errors::CompilerErrorKind::SymbolAlreadyExist,
-                0,
-            ));
+    // true if the expression starts from a global or a captured variable:
+    fn is_outer_value(&mut self, expr: &ast::ExpressionKind) -> bool {
+        let root = BytecodeCompiler::root_identifier(expr);
+        if root.is_none() {
+            return false;
         }
 
-        // store
-        let mut sym_res: Option<Rc<symtab::Symbol>> = None;
-        if !is_lambda {
-            sym_res = Some(self.symbol_table.insert_new_symbol(&node.name, true));
+        let symbol = self.symbol_table.resolve_symbol(&root.unwrap().name);
+        if symbol.is_none() {
+            return false;
         }
 
-        // enter the scope:
-        self.enter_scope();
+        match symbol.unwrap().scope {
+            symtab::ScopeKind::Global | symtab::ScopeKind::Free => return true,
+            _ => return false,
+        }
+    }
 
-        let args = &node.parameters;
-        let error: Option<errors::CompileError>;
+    fn check_pure_call(&mut self, node: &ast::CallType) -> Option<errors::CompileError> {
+        if self.pure_fn.is_none() {
+            return None;
+        }
 
-        for arg in args {
-            match arg {
-                ast::ExpressionKind::Identifier(id) => {
-                    self.symbol_table.insert_new_symbol(&id.name, false);
-                    //self.save(isa::InstructionKind::ILoadLocal, &vec![sym.pos]);
+        if node.is_thread || node.is_async {
+            return self.check_pure("start threads".to_string());
+        }
+
+        match node.function.as_ref() {
+            ast::ExpressionKind::Identifier(id) => {
+                let symbol = self.symbol_table.resolve_symbol(&id.name);
+                if symbol.is_some() {
+                    let symbol = symbol.unwrap();
+                    let is_pure = match symbol.scope {
+                        symtab::ScopeKind::Builtin => BuiltinKind::get_by_name(&id.name)
+                            .is_some_and(|builtin| builtin.is_pure()),
+                        _ => symbol.is_const && self.pure_functions.contains(&id.name),
+                    };
+
+                    if is_pure {
+                        return None;
+                    }
                 }
-                _ => {
-                    return Some(errors::CompileError::new(
-                        "Function parameter is a non identifier".to_string(),
-                        errors::CompilerErrorKind::InvalidOperand,
-                        0,
-                    ))
+
+                return self.check_pure(format!("call {}, it is not a pure function", id.name));
+            }
+            ast::ExpressionKind::Attribute(attr) => {
+                // methods of local values are allowed:
+                if self.is_outer_value(&attr.parent) {
+                    return self.check_pure(format!(
+                        "call {}, it belongs to a variable outside the function",
+                        attr.child_attrs.join(".")
+                    ));
                 }
+
+                return None;
+            }
+            _ => {
+                return self.check_pure("call functions that are not known to be pure".to_string());
             }
         }
+    }
 
-        let func_block = &node.body;
-        error = self.compile_block_statement(func_block);
-        if error.is_some() {
-            return error;
+    /*
+        A function passed as a value, like [x].map(println), runs just like a
+        call, so only pure builtins and pure functions can be used this way.
+    */
+    fn check_pure_value(&self, symbol: &symtab::Symbol) -> Option<errors::CompileError> {
+        if self.pure_fn.is_none() {
+            return None;
         }
 
-        // check if there is a return statement at last:
-        let n_statements = func_block.statements.len();
-        let last_stmt = &func_block.statements[n_statements - 1];
+        let is_impure = match symbol.scope {
+            symtab::ScopeKind::Builtin => {
+                BuiltinKind::get_by_name(&symbol.name).is_none_or(|builtin| !builtin.is_pure())
+            }
+            symtab::ScopeKind::Global | symtab::ScopeKind::Free => {
+                self.impure_callables.contains(&symbol.name)
+                    && !self.pure_functions.contains(&symbol.name)
+            }
+            _ => false,
+        };
 
-        if !is_lambda {
-            match last_stmt {
-                ast::StatementKind::Return(_) => {}
-                _ => {
-                    // append a return void statement
-                    self.save(isa::InstructionKind::IRet, &vec![]);
+        if is_impure {
+            return self.check_pure(format!("use {}, it is not a pure function", symbol.name));
+        }
+
+        return None;
+    }
+
+    // true if a constant is given a value that may be an impure function:
+    fn is_impure_callable(&self, expr: &ast::ExpressionKind) -> bool {
+        match expr {
+            ast::ExpressionKind::Lambda(_) => return true,
+            ast::ExpressionKind::Identifier(id) => {
+                if self.impure_callables.contains(&id.name) {
+                    return true;
                 }
+
+                return BuiltinKind::get_by_name(&id.name)
+                    .is_some_and(|builtin| !builtin.is_pure());
             }
-        } else {
-            self.save(isa::InstructionKind::IRetVal, &vec![]);
+            _ => return false,
         }
+    }
 
-        let free_symbols = self.symbol_table.get_free_symbols();
-        let n_locals = self.symbol_table.n_items;
+    fn compile_return_stmt(&mut self, node: &ast::ReturnType) -> Option<errors::CompileError> {
+        if self.returns_instance {
+            if node.expression.is_some() {
+                return Some(errors::CompileError::new(
+                    "init of a class cannot return a value".to_string(),
+                    errors::CompilerErrorKind::InvalidOperand,
+                    0,
+                ));
+            }
 
-        let compiled_result = self.exit_scope();
-        if compiled_result.is_err() {
-            return Some(compiled_result.unwrap_err());
+            // self is the first parameter:
+            self.save(isa::InstructionKind::ILoadLocal, &vec![0]);
+            self.compile_return(true);
+            return None;
         }
 
-        let compiled_func = compiled_result.unwrap();
+        if node.expression.is_some() {
+            // compile the return expression
+            let exp = &node.expression.as_ref().unwrap();
+            let error = self.compile_expression(exp);
 
-        for sym in &free_symbols {
-            match sym.scope {
-                symtab::ScopeKind::Local => {
-                    self.save(isa::InstructionKind::ILoadLocal, &vec![sym.pos]);
-                }
-                symtab::ScopeKind::Free => {
-                    self.save(isa::InstructionKind::ILoadGlobal, &vec![sym.pos]);
-                }
-                _ => {}
+            if error.is_some() {
+                return error;
             }
+
+            self.compile_return(true);
+            return None;
+        } else {
+            self.compile_return(false);
+            return None;
         }
+    }
 
-        let compiled_func_type = Subroutine {
-            name: node.name.clone(),
-            bytecode: compiled_func,
-            num_locals: n_locals,
-            num_parameters: args.len(),
-            is_local_scope: false,
-        };
+    // returns the value on top of the stack, after the finally blocks run:
+    fn compile_return(&mut self, has_value: bool) {
+        if self.finally_ctls.is_empty() {
+            if has_value {
+                self.save(isa::InstructionKind::IRetVal, &vec![]);
+            } else {
+                self.save(isa::InstructionKind::IRet, &vec![]);
+            }
 
-        let func_object = Object::Subroutine(Rc::new(compiled_func_type));
+            return;
+        }
 
-        // register the sub-routine:
-        let func_idx = self.register_constant(func_object);
-        // create a closure instruction:
-        self.save(
-            isa::InstructionKind::IClosure,
-            &vec![func_idx, free_symbols.len()],
-        );
+        if has_value {
+            let value_sym = Rc::clone(&self.finally_ctls[self.finally_ctls.len() - 1].value_sym);
+            self.compile_store_symbol(&value_sym);
+            self.compile_finally_jump(FINALLY_RETURN_VALUE);
+        } else {
+            self.compile_finally_jump(FINALLY_RETURN);
+        }
+    }
 
-        if sym_res.is_some() {
-            let sym = sym_res.unwrap();
-            match sym.scope {
-                symtab::ScopeKind::Global => {
-                    self.save(isa::InstructionKind::IStoreGlobal, &vec![sym.pos]);
-                }
-                symtab::ScopeKind::Local => {
-                    self.save(isa::InstructionKind::IStoreLocal, &vec![sym.pos]);
-                }
-                _ => {}
+    /*
+        yield makes the function it is in a generator, calling a generator
+        function returns an iterator and runs the body lazily, every yield
+        hands out one element.
+    */
+    fn compile_yield_stmt(&mut self, node: &ast::YieldType) -> Option<errors::CompileError> {
+        if self.scope_index == 0 {
+            return Some(errors::CompileError::new(
+                "yield encountered outside function".to_string(),
+                errors::CompilerErrorKind::InvalidYield,
+                0,
+            ));
+        }
+
+        if self.returns_instance {
+            return Some(errors::CompileError::new(
+                "init of a class cannot yield".to_string(),
+                errors::CompilerErrorKind::InvalidYield,
+                0,
+            ));
+        }
+
+        if node.expression.is_some() {
+            let error = self.compile_expression(node.expression.as_ref().unwrap());
+            if error.is_some() {
+                return error;
             }
+        } else {
+            let no_val = self.register_constant(Object::Noval);
+            self.save(isa::InstructionKind::IConstant, &vec![no_val]);
         }
 
+        self.is_generator = true;
+        self.save(isa::InstructionKind::IYield, &vec![]);
         return None;
     }
 
-    fn compile_lambda(&mut self, node: &ast::LambdaExpType) -> Option<errors::CompileError> {
-        //convert lambda to function, find better method soon
-        let func_type = ast::FunctionType {
-            name: format!("lambda_{}", self.n_lambdas),
-            parameters: node.parameters.clone(),
-            body: ast::BlockStatement {
-                statements: vec![ast::StatementKind::Expression(
-                    node.expression.as_ref().clone(),
-                )],
-                pos: vec![],
-            },
-            return_type: None,
-        };
-
-        // compile the function:
-        let error = self.compile_function(&func_type, true);
-        if error.is_some() {
-            return error;
+    // loads the value a pattern is tested against, the subject indexed along the path.
+    fn load_match_value(
+        &mut self,
+        subject: &symtab::Symbol,
+        path: &[ast::ExpressionKind],
+    ) -> Option<errors::CompileError> {
+        self.load_hidden_symbol(subject);
+        for key in path.iter() {
+            let error = self.compile_expression(key);
+            if error.is_some() {
+                return error;
+            }
+            self.save(isa::InstructionKind::IGetIndex, &vec![]);
         }
 
-        self.n_lambdas += 1;
         return None;
     }
 
-    fn register_loop_var(&mut self, node: &ast::ExpressionKind) -> Option<errors::CompileError> {
-        let registered_element: Rc<symtab::Symbol>;
+    // stores the value on the stack in a variable of the arm, made by compile_match.
+    fn bind_pattern_name(&mut self, name: &String) {
+        let symbol = self.symbol_table.get_symbol(name).unwrap();
+        self.compile_store_symbol(&symbol);
+    }
 
-        match node {
-            ast::ExpressionKind::Identifier(id) => {
-                // check if it's constant:
-                let resolved_sym = self.symbol_table.resolve_symbol(&id.name);
-                if resolved_sym.is_some() && resolved_sym.unwrap().is_const {
-                    return Some(errors::CompileError::new(
-                        format!("Cannot assign to constant {}", id.name),
-                        errors::CompilerErrorKind::ConstantAssignment,
-                        0,
-                    ));
+    // the names a pattern binds, each one once:
+    fn collect_pattern_names(pattern: &ast::PatternKind, names: &mut Vec<String>) {
+        match pattern {
+            ast::PatternKind::Binding(name) if !names.contains(name) => {
+                names.push(name.clone());
+            }
+            ast::PatternKind::Array(elements) => {
+                for element in elements.iter() {
+                    BytecodeCompiler::collect_pattern_names(element, names);
+                }
+            }
+            ast::PatternKind::ArrayRest(elements, rest) => {
+                for element in elements.iter() {
+                    BytecodeCompiler::collect_pattern_names(element, names);
                 }
 
-                registered_element = self.symbol_table.insert_new_symbol(&id.name, false)
+                if rest != "_" && !names.contains(rest) {
+                    names.push(rest.clone());
+                }
             }
-            _ => {
-                return Some(errors::CompileError::new(
-                    "Invalid expression, loop target must be an identifier".to_string(),
-                    errors::CompilerErrorKind::InvalidOperand,
-                    0,
-                ))
+            ast::PatternKind::HashTable(pairs) => {
+                for (_, value) in pairs.iter() {
+                    BytecodeCompiler::collect_pattern_names(value, names);
+                }
             }
+            ast::PatternKind::Or(alternatives) => {
+                for alternative in alternatives.iter() {
+                    BytecodeCompiler::collect_pattern_names(alternative, names);
+                }
+            }
+            _ => {}
         }
+    }
 
-        // load the iter variable:
-        match registered_element.scope {
-            symtab::ScopeKind::Global => {
-                self.save(
-                    isa::InstructionKind::IStoreGlobal,
-                    &vec![registered_element.pos],
-                );
+    fn hoist_patterns_keys(
+        &mut self,
+        patterns: &[ast::PatternKind],
+    ) -> Result<Vec<ast::PatternKind>, errors::CompileError> {
+        let mut hoisted = vec![];
+        for pattern in patterns.iter() {
+            let result = self.hoist_pattern_keys(pattern);
+            if result.is_err() {
+                return Err(result.unwrap_err());
+            }
+            hoisted.push(result.unwrap());
+        }
+
+        return Ok(hoisted);
+    }
+
+    /*
+        Keys of hash patterns given as variables are read into hidden
+        variables before the tests, the variables of the arm could hide them.
+    */
+    fn hoist_pattern_keys(
+        &mut self,
+        pattern: &ast::PatternKind,
+    ) -> Result<ast::PatternKind, errors::CompileError> {
+        match pattern {
+            ast::PatternKind::Array(elements) => {
+                let result = self.hoist_patterns_keys(elements);
+                if result.is_err() {
+                    return Err(result.unwrap_err());
+                }
+
+                return Ok(ast::PatternKind::Array(result.unwrap()));
+            }
+            ast::PatternKind::ArrayRest(elements, rest) => {
+                let result = self.hoist_patterns_keys(elements);
+                if result.is_err() {
+                    return Err(result.unwrap_err());
+                }
+
+                return Ok(ast::PatternKind::ArrayRest(result.unwrap(), rest.clone()));
+            }
+            ast::PatternKind::Or(alternatives) => {
+                let result = self.hoist_patterns_keys(alternatives);
+                if result.is_err() {
+                    return Err(result.unwrap_err());
+                }
+
+                return Ok(ast::PatternKind::Or(result.unwrap()));
+            }
+            ast::PatternKind::HashTable(pairs) => {
+                let mut hoisted = vec![];
+                for (key, value) in pairs.iter() {
+                    let mut hoisted_key = key.clone();
+                    if let ast::ExpressionKind::Identifier(id) = key {
+                        let error = self.compile_identifier(id, false);
+                        if error.is_some() {
+                            return Err(error.unwrap());
+                        }
+
+                        let key_sym = self.store_hidden_symbol("key");
+                        hoisted_key = ast::ExpressionKind::Identifier(ast::IdentifierType {
+                            name: key_sym.name.clone(),
+                            t: None,
+                        });
+                    }
+
+                    let result = self.hoist_pattern_keys(value);
+                    if result.is_err() {
+                        return Err(result.unwrap_err());
+                    }
+                    hoisted.push((hoisted_key, result.unwrap()));
+                }
+
+                return Ok(ast::PatternKind::HashTable(hoisted));
+            }
+            _ => return Ok(pattern.clone()),
+        }
+    }
+
+    /*
+        Compiles the tests of a pattern, every test pushes true or false and
+        is followed by an INotJump to the next arm, collected in fail_jumps.
+        Bindings are assigned as soon as the tests before them pass.
+    */
+    fn compile_pattern(
+        &mut self,
+        pattern: &ast::PatternKind,
+        subject: &symtab::Symbol,
+        path: &mut Vec<ast::ExpressionKind>,
+        fail_jumps: &mut Vec<usize>,
+    ) -> Option<errors::CompileError> {
+        match pattern {
+            ast::PatternKind::Wildcard => {}
+            ast::PatternKind::Binding(name) => {
+                let error = self.load_match_value(subject, path);
+                if error.is_some() {
+                    return error;
+                }
+
+                self.bind_pattern_name(name);
+            }
+            ast::PatternKind::Literal(literal) => {
+                let mut error = self.load_match_value(subject, path);
+                if error.is_some() {
+                    return error;
+                }
+
+                error = self.compile_expression(literal);
+                if error.is_some() {
+                    return error;
+                }
+
+                self.save(isa::InstructionKind::IMatchValue, &vec![]);
+                fail_jumps.push(self.save(isa::InstructionKind::INotJump, &vec![0]));
+            }
+            ast::PatternKind::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let mut error = self.load_match_value(subject, path);
+                if error.is_some() {
+                    return error;
+                }
+
+                error = self.compile_expression(start);
+                if error.is_some() {
+                    return error;
+                }
+
+                error = self.compile_expression(end);
+                if error.is_some() {
+                    return error;
+                }
+
+                self.save(
+                    isa::InstructionKind::IMatchRange,
+                    &vec![*inclusive as usize],
+                );
+                fail_jumps.push(self.save(isa::InstructionKind::INotJump, &vec![0]));
+            }
+            ast::PatternKind::Type(name) => {
+                let error = self.load_match_value(subject, path);
+                if error.is_some() {
+                    return error;
+                }
+
+                let type_name = self.register_constant(Object::Str(name.clone()));
+                self.save(isa::InstructionKind::IConstant, &vec![type_name]);
+                self.save(isa::InstructionKind::IMatchType, &vec![]);
+                fail_jumps.push(self.save(isa::InstructionKind::INotJump, &vec![0]));
+            }
+            ast::PatternKind::Array(elements) => {
+                let error = self.load_match_value(subject, path);
+                if error.is_some() {
+                    return error;
+                }
+
+                self.save(isa::InstructionKind::IMatchArray, &vec![elements.len()]);
+                fail_jumps.push(self.save(isa::InstructionKind::INotJump, &vec![0]));
+
+                for (idx, element) in elements.iter().enumerate() {
+                    path.push(ast::ExpressionKind::Literal(ast::LiteralKind::Int(
+                        idx as i64,
+                    )));
+                    let error = self.compile_pattern(element, subject, path, fail_jumps);
+                    path.pop();
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+            }
+            ast::PatternKind::ArrayRest(elements, rest) => {
+                let mut error = self.load_match_value(subject, path);
+                if error.is_some() {
+                    return error;
+                }
+
+                self.save(isa::InstructionKind::IMatchArrayRest, &vec![elements.len()]);
+                fail_jumps.push(self.save(isa::InstructionKind::INotJump, &vec![0]));
+
+                for (idx, element) in elements.iter().enumerate() {
+                    path.push(ast::ExpressionKind::Literal(ast::LiteralKind::Int(
+                        idx as i64,
+                    )));
+                    error = self.compile_pattern(element, subject, path, fail_jumps);
+                    path.pop();
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+
+                if rest == "_" {
+                    return None;
+                }
+
+                // rest is the slice after the elements:
+                error = self.load_match_value(subject, path);
+                if error.is_some() {
+                    return error;
+                }
+
+                let start = self.register_constant(Object::Int(elements.len() as i64));
+                self.save(isa::InstructionKind::IConstant, &vec![start]);
+                let no_val = self.register_constant(Object::Noval);
+                self.save(isa::InstructionKind::IConstant, &vec![no_val]);
+                self.save(isa::InstructionKind::IConstant, &vec![no_val]);
+                self.save(isa::InstructionKind::ISlice, &vec![]);
+                self.bind_pattern_name(rest);
+            }
+            ast::PatternKind::HashTable(pairs) => {
+                let error = self.compile_pattern(
+                    &ast::PatternKind::Type("hashmap".to_string()),
+                    subject,
+                    path,
+                    fail_jumps,
+                );
+                if error.is_some() {
+                    return error;
+                }
+
+                for (key, value) in pairs.iter() {
+                    let mut error = self.load_match_value(subject, path);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    error = self.compile_expression(key);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    self.save(isa::InstructionKind::IMatchKey, &vec![]);
+                    fail_jumps.push(self.save(isa::InstructionKind::INotJump, &vec![0]));
+
+                    path.push(key.clone());
+                    error = self.compile_pattern(value, subject, path, fail_jumps);
+                    path.pop();
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+            }
+            ast::PatternKind::Or(alternatives) => {
+                // a failing alternative tries the next one, the last one fails the pattern:
+                let mut success_jumps = vec![];
+                for (idx, alternative) in alternatives.iter().enumerate() {
+                    if idx == alternatives.len() - 1 {
+                        let error = self.compile_pattern(alternative, subject, path, fail_jumps);
+                        if error.is_some() {
+                            return error;
+                        }
+                        break;
+                    }
+
+                    let mut alternative_fails = vec![];
+                    let mut error =
+                        self.compile_pattern(alternative, subject, path, &mut alternative_fails);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    success_jumps.push(self.save(isa::InstructionKind::IJump, &vec![0]));
+                    let next_alternative = self.save(isa::InstructionKind::INoOp, &vec![]);
+                    error = self.patch_jumps(
+                        isa::InstructionKind::INotJump,
+                        &alternative_fails,
+                        next_alternative,
+                    );
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+
+                let matched_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+                let error =
+                    self.patch_jumps(isa::InstructionKind::IJump, &success_jumps, matched_pos);
+                if error.is_some() {
+                    return error;
+                }
+            }
+        }
+
+        return None;
+    }
+
+    fn patch_jumps(
+        &mut self,
+        inst: isa::InstructionKind,
+        jumps: &[usize],
+        target: usize,
+    ) -> Option<errors::CompileError> {
+        for pos in jumps.iter() {
+            let error = self.replace_instruction_operands(
+                self.scope_index,
+                inst.clone(),
+                &vec![target],
+                pos,
+            );
+            if error.is_some() {
+                return error;
+            }
+        }
+
+        return None;
+    }
+
+    // an arm that matches every value: _ or a name, without a guard.
+    fn is_default_arm(arm: &ast::MatchArm) -> bool {
+        if arm.guard.is_some() {
+            return false;
+        }
+
+        match arm.pattern {
+            ast::PatternKind::Wildcard | ast::PatternKind::Binding(_) => return true,
+            _ => return false,
+        }
+    }
+
+    /*
+        match is compiled to the tests of each arm in order, a failing test
+        jumps to the next arm and a matching arm jumps past the others
+        after its body. The subject is kept in a hidden variable. Used as
+        an expression, every arm pushes its value, so a default arm is
+        needed for the values no arm matches.
+    */
+    fn compile_match(
+        &mut self,
+        node: &ast::MatchType,
+        is_expression: bool,
+    ) -> Option<errors::CompileError> {
+        if is_expression && !node.arms.iter().any(BytecodeCompiler::is_default_arm) {
+            return Some(errors::CompileError::new(
+                "match used as an expression needs a default arm, add _ => ...".to_string(),
+                errors::CompilerErrorKind::InvalidMatch,
+                0,
+            ));
+        }
+
+        let mut error = self.compile_expression(&node.subject);
+        if error.is_some() {
+            return error;
+        }
+
+        let subject = self.store_hidden_symbol("match");
+
+        let mut end_jumps = vec![];
+        for arm in node.arms.iter() {
+            let pattern = self.hoist_pattern_keys(&arm.pattern);
+            if pattern.is_err() {
+                return Some(pattern.unwrap_err());
+            }
+
+            // the names bound by the pattern are new variables of the arm:
+            let mut names = vec![];
+            BytecodeCompiler::collect_pattern_names(&arm.pattern, &mut names);
+            let mut outer_symbols = vec![];
+            for name in names.iter() {
+                outer_symbols.push(self.symbol_table.get_symbol(name));
+                self.symbol_table.insert_new_symbol(name, false);
+            }
+
+            let mut fail_jumps = vec![];
+            error = self.compile_pattern(&pattern.unwrap(), &subject, &mut vec![], &mut fail_jumps);
+            if error.is_some() {
+                return error;
+            }
+
+            if arm.guard.is_some() {
+                error = self.compile_expression(arm.guard.as_ref().unwrap());
+                if error.is_some() {
+                    return error;
+                }
+                fail_jumps.push(self.save(isa::InstructionKind::INotJump, &vec![0]));
+            }
+
+            error = match &arm.body {
+                ast::MatchArmBody::Expression(expr) => self.compile_expression(expr),
+                ast::MatchArmBody::Block(block) => {
+                    if is_expression {
+                        return Some(errors::CompileError::new(
+                            "arms of a match used as an expression must be expressions, not blocks or statements"
+                                .to_string(),
+                            errors::CompilerErrorKind::InvalidMatch,
+                            0,
+                        ));
+                    }
+                    self.compile_block_statement(block)
+                }
+            };
+            if error.is_some() {
+                return error;
+            }
+
+            for (name, symbol) in names.iter().zip(outer_symbols) {
+                self.symbol_table.restore_symbol(name, symbol);
+            }
+
+            end_jumps.push(self.save(isa::InstructionKind::IJump, &vec![0]));
+            let next_arm = self.save(isa::InstructionKind::INoOp, &vec![]);
+            error = self.patch_jumps(isa::InstructionKind::INotJump, &fail_jumps, next_arm);
+            if error.is_some() {
+                return error;
+            }
+        }
+
+        let end_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+        return self.patch_jumps(isa::InstructionKind::IJump, &end_jumps, end_pos);
+    }
+
+    fn compile_function(
+        &mut self,
+        node: &ast::FunctionType,
+        kind: FunctionKind,
+    ) -> Option<errors::CompileError> {
+        // methods live in their class, not in the enclosing scope:
+        let is_method = kind == FunctionKind::Method || kind == FunctionKind::Constructor;
+
+        // check function name:
+        if !is_method {
+            let resolve_result = self.symbol_table.resolve_symbol(&node.name);
+            if resolve_result.is_some() {
+                return Some(errors::CompileError::new(
+                    format!("Name {} already defined", &node.name),
+                    errors::CompilerErrorKind::SymbolAlreadyExist,
+                    0,
+                ));
+            }
+        }
+
+        // store
+        let mut sym_res: Option<Rc<symtab::Symbol>> = None;
+        if kind == FunctionKind::Named {
+            sym_res = Some(self.symbol_table.insert_new_symbol(&node.name, true));
+        }
+
+        // enter the scope, exception handlers are local to a frame:
+        self.enter_scope();
+        let n_exc_handlers = self.n_exc_handlers;
+        self.n_exc_handlers = 0;
+        let finally_ctls = std::mem::take(&mut self.finally_ctls);
+        // rethrow refers to the catch blocks of this function only:
+        let catch_idents = std::mem::take(&mut self.catch_idents);
+        let returns_instance = self.returns_instance;
+        self.returns_instance = kind == FunctionKind::Constructor;
+        let pure_fn = self.pure_fn.clone();
+        if node.is_pure {
+            // pure functions can call themselves:
+            self.pure_functions.insert(node.name.clone());
+            self.pure_fn = Some(node.name.clone());
+        } else if kind == FunctionKind::Named && pure_fn.is_none() {
+            self.impure_callables.insert(node.name.clone());
+        }
+        let is_generator = self.is_generator;
+        self.is_generator = false;
+
+        let args = &node.parameters;
+
+        // parameters are names, name = default or ...name as the last one:
+        let mut parameter_names = vec![];
+        let mut defaults = vec![];
+        let mut is_variadic = false;
+        for arg in args {
+            if is_variadic {
+                return Some(errors::CompileError::new(
+                    format!(
+                        "Variadic parameter ...{} must be the last parameter",
+                        parameter_names.last().unwrap()
+                    ),
+                    errors::CompilerErrorKind::InvalidArguments,
+                    0,
+                ));
+            }
+
+            let name = match arg {
+                ast::ExpressionKind::Identifier(id) => {
+                    if !defaults.is_empty() {
+                        return Some(errors::CompileError::new(
+                            format!(
+                                "Parameter {} without a default follows a parameter with a default",
+                                id.name
+                            ),
+                            errors::CompilerErrorKind::InvalidArguments,
+                            0,
+                        ));
+                    }
+                    id.name.clone()
+                }
+                ast::ExpressionKind::Infix(infix) if infix.infix == exp::InfixExpKind::Equal => {
+                    match infix.expression_left.as_ref() {
+                        ast::ExpressionKind::Identifier(id) => {
+                            defaults.push(infix.expression_right.as_ref().clone());
+                            id.name.clone()
+                        }
+                        _ => {
+                            return Some(errors::CompileError::new(
+                                "Function parameter is a non identifier".to_string(),
+                                errors::CompilerErrorKind::InvalidOperand,
+                                0,
+                            ))
+                        }
+                    }
+                }
+                ast::ExpressionKind::Prefix(prefix)
+                    if prefix.prefix == exp::PrefixExpKind::Spread =>
+                {
+                    match prefix.expression.as_ref() {
+                        ast::ExpressionKind::Identifier(id) => {
+                            is_variadic = true;
+                            id.name.clone()
+                        }
+                        _ => {
+                            return Some(errors::CompileError::new(
+                                "Function parameter is a non identifier".to_string(),
+                                errors::CompilerErrorKind::InvalidOperand,
+                                0,
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    return Some(errors::CompileError::new(
+                        "Function parameter is a non identifier".to_string(),
+                        errors::CompilerErrorKind::InvalidOperand,
+                        0,
+                    ))
+                }
+            };
+
+            self.symbol_table.insert_new_symbol(&name, false);
+            parameter_names.push(name);
+        }
+
+        let func_block = &node.body;
+        let error: Option<errors::CompileError> = self.compile_block_statement(func_block);
+        if error.is_some() {
+            return error;
+        }
+
+        // check if there is a return statement at last:
+        let ends_with_return = matches!(
+            func_block.statements.last(),
+            Some(ast::StatementKind::Return(_))
+        );
+
+        if !ends_with_return {
+            if kind == FunctionKind::Constructor {
+                // init returns the instance:
+                self.save(isa::InstructionKind::ILoadLocal, &vec![0]);
+                self.save(isa::InstructionKind::IRetVal, &vec![]);
+            } else {
+                // append a return void statement
+                self.save(isa::InstructionKind::IRet, &vec![]);
+            }
+        }
+
+        let free_symbols = self.symbol_table.get_free_symbols();
+        let n_locals = self.symbol_table.n_items;
+        self.n_exc_handlers = n_exc_handlers;
+        self.finally_ctls = finally_ctls;
+        self.catch_idents = catch_idents;
+        self.returns_instance = returns_instance;
+        self.pure_fn = pure_fn;
+        let compiled_generator = self.is_generator;
+        self.is_generator = is_generator;
+
+        let compiled_result = self.exit_scope();
+        if compiled_result.is_err() {
+            return Some(compiled_result.unwrap_err());
+        }
+
+        let (compiled_func, spans) = compiled_result.unwrap();
+
+        // defaults are evaluated once, where the function is defined, and
+        // IClosure keeps them in the closure:
+        for default in defaults.iter() {
+            let error = self.compile_expression(default);
+            if error.is_some() {
+                return error;
+            }
+        }
+
+        // the closure shares the captured variables with this scope:
+        for sym in &free_symbols {
+            match sym.scope {
+                symtab::ScopeKind::Local => {
+                    self.save(isa::InstructionKind::ICaptureLocal, &vec![sym.pos]);
+                }
+                symtab::ScopeKind::Free => {
+                    self.save(isa::InstructionKind::ICaptureFree, &vec![sym.pos]);
+                }
+                _ => {}
+            }
+        }
+
+        let compiled_func_type = Subroutine {
+            name: node.name.clone(),
+            bytecode: compiled_func,
+            num_locals: n_locals,
+            num_parameters: args.len(),
+            is_local_scope: false,
+            spans: spans,
+            is_pure: node.is_pure,
+            is_generator: compiled_generator,
+            parameter_names: parameter_names,
+            num_defaults: defaults.len(),
+            is_variadic: is_variadic,
+        };
+
+        let func_object = Object::Subroutine(Rc::new(compiled_func_type));
+
+        // register the sub-routine:
+        let func_idx = self.register_constant(func_object);
+        // create a closure instruction:
+        self.save(
+            isa::InstructionKind::IClosure,
+            &vec![func_idx, free_symbols.len()],
+        );
+
+        if sym_res.is_some() {
+            let sym = sym_res.unwrap();
+            match sym.scope {
+                symtab::ScopeKind::Global => {
+                    self.save(isa::InstructionKind::IStoreGlobal, &vec![sym.pos]);
+                }
+                symtab::ScopeKind::Local => {
+                    self.save(isa::InstructionKind::IStoreLocal, &vec![sym.pos]);
+                }
+                _ => {}
+            }
+        }
+
+        return None;
+    }
+
+    /*
+        Pushes the class name, the field names and a name, closure pair
+        for every method, IClass builds the class out of them.
+    */
+    fn compile_class(&mut self, node: &ast::ClassType) -> Option<errors::CompileError> {
+        let resolve_result = self.symbol_table.resolve_symbol(&node.name);
+        if resolve_result.is_some() {
+            return Some(errors::CompileError::new(
+                format!("Name {} already defined", &node.name),
+                errors::CompilerErrorKind::SymbolAlreadyExist,
+                0,
+            ));
+        }
+
+        // methods can refer to their own class:
+        let symbol = self.symbol_table.insert_new_symbol(&node.name, true);
+        // creating an instance runs init:
+        if self.pure_fn.is_none() {
+            self.impure_callables.insert(node.name.clone());
+        }
+
+        let name_idx = self.register_constant(Object::Str(node.name.clone()));
+        self.save(isa::InstructionKind::IConstant, &vec![name_idx]);
+
+        for field in &node.fields {
+            let idx = self.register_constant(Object::Str(field.clone()));
+            self.save(isa::InstructionKind::IConstant, &vec![idx]);
+        }
+
+        for method in &node.methods {
+            let idx = self.register_constant(Object::Str(method.name.clone()));
+            self.save(isa::InstructionKind::IConstant, &vec![idx]);
+
+            let kind = if method.name == "init" {
+                FunctionKind::Constructor
+            } else {
+                FunctionKind::Method
+            };
+
+            // tracebacks show the method as Class.method:
+            let mut method_type = method.clone();
+            method_type.name = format!("{}.{}", node.name, method.name);
+            let error = self.compile_function(&method_type, kind);
+            if error.is_some() {
+                return error;
+            }
+        }
+
+        self.save(
+            isa::InstructionKind::IClass,
+            &vec![node.fields.len(), node.methods.len() * 2],
+        );
+        self.compile_store_symbol(&symbol);
+
+        return None;
+    }
+
+    fn compile_lambda(&mut self, node: &ast::LambdaExpType) -> Option<errors::CompileError> {
+        //convert lambda to function, find better method soon
+        let func_type = ast::FunctionType {
+            name: format!("lambda_{}", self.n_lambdas),
+            parameters: node.parameters.clone(),
+            body: ast::BlockStatement {
+                statements: vec![ast::StatementKind::Return(ast::ReturnType {
+                    expression: Some(node.expression.as_ref().clone()),
+                })],
+                pos: vec![],
+            },
+            return_type: None,
+            is_pure: false,
+        };
+
+        // compile the function:
+        let error = self.compile_function(&func_type, FunctionKind::Lambda);
+        if error.is_some() {
+            return error;
+        }
+
+        self.n_lambdas += 1;
+        return None;
+    }
+
+    /*
+        Values the compiler keeps for itself, like the iterator of a loop,
+        live in hidden variables named after their position in the bytecode.
+        Statements of a loop body can leave their values on the data stack,
+        they are never mistaken for the iterator.
+    */
+    fn store_hidden_symbol(&mut self, prefix: &str) -> Rc<symtab::Symbol> {
+        let name = format!("__{}_{}", prefix, self.scopes[self.scope_index].get_size());
+        let symbol = self.symbol_table.insert_new_symbol(&name, false);
+        if symbol.scope == symtab::ScopeKind::Global {
+            self.save(isa::InstructionKind::IStoreGlobal, &vec![symbol.pos]);
+        } else {
+            self.save(isa::InstructionKind::IStoreLocal, &vec![symbol.pos]);
+        }
+
+        return symbol;
+    }
+
+    fn load_hidden_symbol(&mut self, symbol: &symtab::Symbol) -> usize {
+        if symbol.scope == symtab::ScopeKind::Global {
+            return self.save(isa::InstructionKind::ILoadGlobal, &vec![symbol.pos]);
+        }
+
+        return self.save(isa::InstructionKind::ILoadLocal, &vec![symbol.pos]);
+    }
+
+    fn register_loop_var(&mut self, node: &ast::ExpressionKind) -> Option<errors::CompileError> {
+        let registered_element: Rc<symtab::Symbol>;
+
+        match node {
+            ast::ExpressionKind::Identifier(id) => {
+                // check if it's constant:
+                let resolved_sym = self.symbol_table.resolve_symbol(&id.name);
+                if resolved_sym.is_some() && resolved_sym.unwrap().is_const {
+                    return Some(errors::CompileError::new(
+                        format!("Cannot assign to constant {}", id.name),
+                        errors::CompilerErrorKind::ConstantAssignment,
+                        0,
+                    ));
+                }
+
+                registered_element = self.symbol_table.insert_new_symbol(&id.name, false)
+            }
+            _ => {
+                return Some(errors::CompileError::new(
+                    "Invalid expression, loop target must be an identifier".to_string(),
+                    errors::CompilerErrorKind::InvalidOperand,
+                    0,
+                ))
+            }
+        }
+
+        // load the iter variable:
+        match registered_element.scope {
+            symtab::ScopeKind::Global => {
+                self.save(
+                    isa::InstructionKind::IStoreGlobal,
+                    &vec![registered_element.pos],
+                );
             }
             symtab::ScopeKind::Local => {
                 self.save(
@@ -567,16 +1600,18 @@ impl BytecodeCompiler {
         let iter_exp = &node.iter;
 
         // compile:
-        let mut error = self.compile_expression(&iter_exp);
+        let mut error = self.compile_expression(iter_exp);
         if error.is_some() {
             return error;
         }
 
         let current_pos = self.scopes[self.scope_index].get_size();
         let new_loop_ctl = LoopControl {
-            loop_start_pos: current_pos.clone(),
+            loop_start_pos: current_pos,
             pos_after_loop: 0,
             break_pos: vec![],
+            n_exc_handlers: self.n_exc_handlers,
+            n_finally_ctls: self.finally_ctls.len(),
         };
 
         self.loop_ctls.push(new_loop_ctl);
@@ -587,7 +1622,9 @@ impl BytecodeCompiler {
 
         // Perform iteration, replace the iterator end later
         self.save(isa::InstructionKind::IIter, &vec![]);
-        let loop_start = self.save(isa::InstructionKind::IIterNext, &vec![0]);
+        let iter_symbol = self.store_hidden_symbol("iter");
+        let loop_start = self.load_hidden_symbol(&iter_symbol);
+        let iter_next = self.save(isa::InstructionKind::IIterNext, &vec![0]);
         self.loop_ctls[current_loop_ctl].loop_start_pos = loop_start;
 
         // load the iter variable:
@@ -612,7 +1649,7 @@ impl BytecodeCompiler {
             self.scope_index,
             isa::InstructionKind::IIterNext,
             &vec![loop_end_pos],
-            &loop_start,
+            &iter_next,
         );
 
         if error.is_some() {
@@ -642,16 +1679,18 @@ impl BytecodeCompiler {
         let iter_exp = &node.iterator_exp;
 
         // compile:
-        let mut error = self.compile_expression(&iter_exp);
+        let mut error = self.compile_expression(iter_exp);
         if error.is_some() {
             return error;
         }
 
         let current_pos = self.scopes[self.scope_index].get_size();
         let new_loop_ctl = LoopControl {
-            loop_start_pos: current_pos.clone(),
+            loop_start_pos: current_pos,
             pos_after_loop: 0,
             break_pos: vec![],
+            n_exc_handlers: self.n_exc_handlers,
+            n_finally_ctls: self.finally_ctls.len(),
         };
 
         self.loop_ctls.push(new_loop_ctl);
@@ -662,7 +1701,9 @@ impl BytecodeCompiler {
         let index = &node.index;
 
         self.save(isa::InstructionKind::IIter, &vec![]);
-        let loop_start = self.save(isa::InstructionKind::IEnumNext, &vec![0]);
+        let iter_symbol = self.store_hidden_symbol("iter");
+        let loop_start = self.load_hidden_symbol(&iter_symbol);
+        let iter_next = self.save(isa::InstructionKind::IEnumNext, &vec![0]);
         self.loop_ctls[current_loop_ctl].loop_start_pos = loop_start;
 
         error = self.register_loop_var(index);
@@ -691,7 +1732,7 @@ impl BytecodeCompiler {
             self.scope_index,
             isa::InstructionKind::IEnumNext,
             &vec![loop_end_pos],
-            &loop_start,
+            &iter_next,
         );
 
         if error.is_some() {
@@ -743,6 +1784,24 @@ impl BytecodeCompiler {
             ));
         }
 
+        let error = self.check_pure_value(&resolved_symbol);
+        if error.is_some() {
+            return error;
+        }
+
+        // variables outside a pure function can change between calls:
+        if !resolved_symbol.is_const {
+            match resolved_symbol.scope {
+                symtab::ScopeKind::Global | symtab::ScopeKind::Free => {
+                    let error = self.check_pure(format!("read variable {}", resolved_symbol.name));
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+                _ => {}
+            }
+        }
+
         match resolved_symbol.scope {
             symtab::ScopeKind::Builtin => {
                 self.save(
@@ -767,11 +1826,16 @@ impl BytecodeCompiler {
         return None;
     }
 
+    /*
+        a++ is compiled as a = a + 1, the value of the expression is left on
+        the stack only when it is used, a++ as a statement leaves nothing.
+    */
     fn compile_incr_decr(
         &mut self,
         expr: &ast::PrefixType,
         is_pre: bool,
         is_decr: bool,
+        keep_value: bool,
     ) -> Option<errors::CompileError> {
         match expr.expression.as_ref() {
             ast::ExpressionKind::Identifier(id) => {
@@ -794,12 +1858,36 @@ impl BytecodeCompiler {
                     ));
                 }
 
-                // compile the identifier, with constant assignment
-                // check
-                let res = self.compile_identifier(&id, true);
+                // the old value is the result of a++:
+                if keep_value && !is_pre {
+                    let res = self.compile_identifier(id, true);
+                    if res.is_some() {
+                        return res;
+                    }
+                }
+
+                let res = self.compile_identifier(id, true);
+                if res.is_some() {
+                    return res;
+                }
+
+                let one = self.register_constant(Object::Int(1));
+                self.save(isa::InstructionKind::IConstant, &vec![one]);
+                if is_decr {
+                    self.save(isa::InstructionKind::ISub, &vec![]);
+                } else {
+                    self.save(isa::InstructionKind::IAdd, &vec![]);
+                }
+
+                let res = self.compile_item_assignment(id);
                 if res.is_some() {
                     return res;
                 }
+
+                // and the new value is the result of ++a:
+                if keep_value && is_pre {
+                    return self.compile_identifier(id, true);
+                }
             }
             _ => {
                 return Some(errors::CompileError::new(
@@ -810,17 +1898,37 @@ impl BytecodeCompiler {
             }
         }
 
-        if is_pre && is_decr {
-            self.save(isa::InstructionKind::IPreDecr, &vec![]);
-        } else if is_pre && !is_decr {
-            self.save(isa::InstructionKind::IPreIncr, &vec![]);
-        } else if !is_pre && is_decr {
-            self.save(isa::InstructionKind::IPostDecr, &vec![]);
-        } else {
-            self.save(isa::InstructionKind::IPostIncr, &vec![]);
+        return None;
+    }
+
+    // a statement like a++ is only compiled for its side effect:
+    fn compile_expression_stmt(
+        &mut self,
+        expr: &ast::ExpressionKind,
+    ) -> Option<errors::CompileError> {
+        match expr {
+            ast::ExpressionKind::Prefix(prefix) => match prefix.prefix {
+                exp::PrefixExpKind::PreIncrement => {
+                    return self.compile_incr_decr(prefix, true, false, false);
+                }
+                exp::PrefixExpKind::PreDecrement => {
+                    return self.compile_incr_decr(prefix, true, true, false);
+                }
+                _ => {}
+            },
+            ast::ExpressionKind::Suffix(suffix) => {
+                let transformed_prefix = ast::PrefixType {
+                    expression: suffix.expression.clone(),
+                    prefix: exp::PrefixExpKind::PreIncrement,
+                };
+
+                let is_decr = suffix.suffix == exp::SuffixExpKind::PostDecrement;
+                return self.compile_incr_decr(&transformed_prefix, false, is_decr, false);
+            }
+            _ => {}
         }
 
-        return None;
+        return self.compile_expression(expr);
     }
 
     fn compile_suffix_expression(
@@ -835,7 +1943,7 @@ impl BytecodeCompiler {
                     prefix: exp::PrefixExpKind::PreDecrement,
                 };
 
-                let res = self.compile_incr_decr(&transformed_prefix, false, true);
+                let res = self.compile_incr_decr(&transformed_prefix, false, true, true);
 
                 if res.is_some() {
                     return res;
@@ -848,7 +1956,7 @@ impl BytecodeCompiler {
                     prefix: exp::PrefixExpKind::PreIncrement,
                 };
 
-                let res = self.compile_incr_decr(&transformed_prefix, false, false);
+                let res = self.compile_incr_decr(&transformed_prefix, false, false, true);
 
                 if res.is_some() {
                     return res;
@@ -862,8 +1970,11 @@ impl BytecodeCompiler {
     fn compile_attribute_resolver(
         &mut self,
         resolver: &ast::AttributeResolver,
-        is_get: bool,
     ) -> Option<errors::CompileError> {
+        // take the call params before compiling the parent, the parent
+        // can have attributes of its own.
+        let attr_params = self.attr_ctls.pop();
+
         // compile parent:
         let expr_result = self.compile_expression(&resolver.parent);
         if expr_result.is_some() {
@@ -877,27 +1988,19 @@ impl BytecodeCompiler {
         }
 
         // check if the attribute is being called:
-        if self.attr_ctls.len() != 0 {
-            let attr_params = self.attr_ctls.pop().unwrap();
+        if attr_params.is_some() {
             self.save(
                 isa::InstructionKind::ICallAttr,
-                &vec![resolver.child_attrs.len(), attr_params],
+                &vec![resolver.child_attrs.len(), attr_params.unwrap()],
             );
 
             return None;
         }
 
-        if is_get {
-            self.save(
-                isa::InstructionKind::IGetAttr,
-                &vec![resolver.child_attrs.len()],
-            );
-        } else {
-            self.save(
-                isa::InstructionKind::ISetAttr,
-                &vec![resolver.child_attrs.len()],
-            );
-        }
+        self.save(
+            isa::InstructionKind::IGetAttr,
+            &vec![resolver.child_attrs.len()],
+        );
 
         return None;
     }
@@ -914,14 +2017,25 @@ impl BytecodeCompiler {
                 }
                 self.save(isa::InstructionKind::INeg, &vec![]);
             }
+            exp::PrefixExpKind::Minus => {
+                // -a is compiled as 0 - a:
+                let zero = self.register_constant(Object::Int(0));
+                self.save(isa::InstructionKind::IConstant, &vec![zero]);
+
+                let res = self.compile_expression(&expr.expression);
+                if res.is_some() {
+                    return res;
+                }
+                self.save(isa::InstructionKind::ISub, &vec![]);
+            }
             exp::PrefixExpKind::PreIncrement => {
-                let res = self.compile_incr_decr(&expr, true, false);
+                let res = self.compile_incr_decr(expr, true, false, true);
                 if res.is_some() {
                     return res;
                 }
             }
             exp::PrefixExpKind::PreDecrement => {
-                let res = self.compile_incr_decr(&expr, true, true);
+                let res = self.compile_incr_decr(expr, true, true, true);
                 if res.is_some() {
                     return res;
                 }
@@ -933,6 +2047,13 @@ impl BytecodeCompiler {
                 }
                 self.save(isa::InstructionKind::ILNot, &vec![]);
             }
+            exp::PrefixExpKind::Spread => {
+                return Some(errors::CompileError::new(
+                    "Spread ... is only allowed in the arguments of a call".to_string(),
+                    errors::CompilerErrorKind::InvalidArguments,
+                    0,
+                ));
+            }
         }
 
         return None;
@@ -963,6 +2084,11 @@ impl BytecodeCompiler {
         // the symbol is resolved without any errors, store it back:
         match resolved_symbol.scope {
             symtab::ScopeKind::Global => {
+                let error = self.check_pure(format!("assign global variable {}", id.name));
+                if error.is_some() {
+                    return error;
+                }
+
                 self.save(
                     isa::InstructionKind::IStoreGlobal,
                     &vec![resolved_symbol.pos],
@@ -974,6 +2100,14 @@ impl BytecodeCompiler {
                     &vec![resolved_symbol.pos],
                 );
             }
+            symtab::ScopeKind::Free => {
+                let error = self.check_pure(format!("assign captured variable {}", id.name));
+                if error.is_some() {
+                    return error;
+                }
+
+                self.save(isa::InstructionKind::IStoreFree, &vec![resolved_symbol.pos]);
+            }
             _ => {
                 return Some(errors::CompileError::new(
                     format!("Invalid assignment {}", id.name),
@@ -987,6 +2121,37 @@ impl BytecodeCompiler {
     }
 
     fn compile_infix_expression(&mut self, expr: &ast::InfixType) -> Option<errors::CompileError> {
+        // a += b is compiled as a = a + b:
+        let compound_op = match expr.infix {
+            exp::InfixExpKind::PlusEq => Some(exp::InfixExpKind::Plus),
+            exp::InfixExpKind::MinusEq => Some(exp::InfixExpKind::Minus),
+            exp::InfixExpKind::MulEq => Some(exp::InfixExpKind::Mul),
+            exp::InfixExpKind::DivEq => Some(exp::InfixExpKind::Div),
+            exp::InfixExpKind::ModEq => Some(exp::InfixExpKind::Mod),
+            exp::InfixExpKind::AndEq => Some(exp::InfixExpKind::And),
+            exp::InfixExpKind::OrEq => Some(exp::InfixExpKind::Or),
+            exp::InfixExpKind::XorEq => Some(exp::InfixExpKind::Xor),
+            exp::InfixExpKind::ShiftLeftEq => Some(exp::InfixExpKind::ShiftLeft),
+            exp::InfixExpKind::ShiftRightEq => Some(exp::InfixExpKind::ShiftRight),
+            exp::InfixExpKind::PowEq => Some(exp::InfixExpKind::Pow),
+            exp::InfixExpKind::FloorDivEq => Some(exp::InfixExpKind::FloorDiv),
+            _ => None,
+        };
+
+        if compound_op.is_some() {
+            let assignment = ast::InfixType {
+                infix: exp::InfixExpKind::Equal,
+                expression_left: expr.expression_left.clone(),
+                expression_right: Box::new(ast::ExpressionKind::Infix(ast::InfixType {
+                    infix: compound_op.unwrap(),
+                    expression_left: expr.expression_left.clone(),
+                    expression_right: expr.expression_right.clone(),
+                })),
+            };
+
+            return self.compile_infix_expression(&assignment);
+        }
+
         // parse the expression:
         if expr.infix != exp::InfixExpKind::Equal {
             let mut res = self.compile_expression(&expr.expression_left);
@@ -1005,73 +2170,104 @@ impl BytecodeCompiler {
             let left = &expr.expression_left;
             match left.as_ref() {
                 ast::ExpressionKind::Identifier(id) => {
-                    let error = self.compile_item_assignment(&id);
+                    let error = self.compile_item_assignment(id);
                     if error.is_some() {
                         return error;
                     }
                 }
                 ast::ExpressionKind::Index(idx_type) => {
-                    // get the index variable name:
-                    let expr_left = &idx_type.expression_left;
-                    // compile the left expression:
-                    match expr_left.as_ref() {
-                        ast::ExpressionKind::Identifier(id) => {
-                            // compile the identifier:
-                            let mut error = self.compile_identifier(&id, false);
-                            if error.is_some() {
-                                return error;
-                            }
-
-                            // compile the index expression:
-                            error = self.compile_expression(&idx_type.index);
-                            if error.is_some() {
-                                return error;
-                            }
-
-                            // since both are loaded on to the stack, call ISetIndex:
-                            self.save(isa::InstructionKind::ISetIndex, &vec![]);
-
-                            error = self.compile_item_assignment(&id);
-                            if error.is_some() {
-                                return error;
-                            }
+                    if self.is_outer_value(&idx_type.expression_left) {
+                        let error = self.check_pure(
+                            "assign an index of a variable outside the function".to_string(),
+                        );
+                        if error.is_some() {
+                            return error;
                         }
-                        _ => {
-                            return Some(errors::CompileError::new(
-                                "Assignment not possible".to_string(),
-                                errors::CompilerErrorKind::InvalidOperand,
-                                0,
-                            ));
+                    }
+
+                    // ISetIndex changes the object in place, so it is not stored back:
+                    let mut error = self.compile_expression(&idx_type.expression_left);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    error = self.compile_expression(&idx_type.index);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    self.save(isa::InstructionKind::ISetIndex, &vec![]);
+                }
+                ast::ExpressionKind::Slice(slice_type) => {
+                    if self.is_outer_value(&slice_type.expression_left) {
+                        let error = self.check_pure(
+                            "assign a slice of a variable outside the function".to_string(),
+                        );
+                        if error.is_some() {
+                            return error;
                         }
                     }
+
+                    // xs[a:b] = ys replaces the elements of the slice in place, like ISetIndex:
+                    let mut error = self.compile_expression(&slice_type.expression_left);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    error = self.compile_slice_parts(slice_type);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    self.save(isa::InstructionKind::ISetSlice, &vec![]);
                 }
-                ast::ExpressionKind::Attribute(attr) => {
-                    let parent_attr = attr.parent.clone();
-                    // compile the resolver:
-                    let expr_result = self.compile_attribute_resolver(&attr, false);
-                    if expr_result.is_some() {
-                        return expr_result;
+                ast::ExpressionKind::Literal(ast::LiteralKind::Array(_)) => {
+                    // [a, b] = [b, a], the right side is evaluated before any store:
+                    let target = BytecodeCompiler::get_unpack_target(left);
+                    if target.is_err() {
+                        return Some(errors::CompileError::new(
+                            target.unwrap_err(),
+                            errors::CompilerErrorKind::InvalidAssignment,
+                            0,
+                        ));
                     }
 
-                    // create load instruction:
-                    match parent_attr.as_ref() {
-                        ast::ExpressionKind::Identifier(id) => {
-                            let assign_result = self.compile_item_assignment(&id);
-                            if assign_result.is_some() {
-                                return assign_result;
-                            }
-                        }
-                        _ => {
-                            return Some(errors::CompileError::new(
-                                format!(
-                                    "Failed to resolve attribute assignment for type {:?}",
-                                    parent_attr
-                                ),
-                                errors::CompilerErrorKind::InvalidOperand,
-                                0,
-                            ));
+                    let error = self.compile_unpack(&target.unwrap(), false, false);
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+                ast::ExpressionKind::Attribute(attr) => {
+                    if self.is_outer_value(&attr.parent) {
+                        let error = self.check_pure(format!(
+                            "assign {}, it belongs to a variable outside the function",
+                            attr.child_attrs.join(".")
+                        ));
+                        if error.is_some() {
+                            return error;
                         }
                     }
+
+                    // a.b.c = v is compiled as a.b["c"] = v:
+                    let n_attrs = attr.child_attrs.len();
+                    let target = if n_attrs > 1 {
+                        ast::ExpressionKind::Attribute(ast::AttributeResolver {
+                            parent: attr.parent.clone(),
+                            child_attrs: attr.child_attrs[..n_attrs - 1].to_vec(),
+                        })
+                    } else {
+                        attr.parent.as_ref().clone()
+                    };
+
+                    let error = self.compile_expression(&target);
+                    if error.is_some() {
+                        return error;
+                    }
+
+                    let idx =
+                        self.register_constant(Object::Str(attr.child_attrs[n_attrs - 1].clone()));
+                    self.save(isa::InstructionKind::IConstant, &vec![idx]);
+                    self.save(isa::InstructionKind::ISetIndex, &vec![]);
                 }
                 _ => {
                     return Some(errors::CompileError::new(
@@ -1108,6 +2304,21 @@ impl BytecodeCompiler {
             exp::InfixExpKind::Or => {
                 self.save(isa::InstructionKind::IOr, &vec![]);
             }
+            exp::InfixExpKind::Xor => {
+                self.save(isa::InstructionKind::IXor, &vec![]);
+            }
+            exp::InfixExpKind::ShiftLeft => {
+                self.save(isa::InstructionKind::IShl, &vec![]);
+            }
+            exp::InfixExpKind::ShiftRight => {
+                self.save(isa::InstructionKind::IShr, &vec![]);
+            }
+            exp::InfixExpKind::Pow => {
+                self.save(isa::InstructionKind::IPow, &vec![]);
+            }
+            exp::InfixExpKind::FloorDiv => {
+                self.save(isa::InstructionKind::IFloorDiv, &vec![]);
+            }
             exp::InfixExpKind::LesserThan => {
                 self.save(isa::InstructionKind::ILLt, &vec![]);
             }
@@ -1139,6 +2350,11 @@ impl BytecodeCompiler {
     }
 
     fn compile_shell_expr(&mut self, node: &ast::ShellType) -> Option<errors::CompileError> {
+        let pure_error = self.check_pure("run shell commands".to_string());
+        if pure_error.is_some() {
+            return pure_error;
+        }
+
         // compile the expression
         let error = self.compile_expression(&node.shell);
         if error.is_some() {
@@ -1165,54 +2381,257 @@ impl BytecodeCompiler {
                 self.save(isa::InstructionKind::IConstant, &vec![reg_constant]);
             }
             ast::ExpressionKind::Literal(lt) => {
-                let result = self.compile_literal(&lt);
+                let result = self.compile_literal(lt);
                 return result;
             }
             ast::ExpressionKind::Identifier(id) => {
-                let result = self.compile_identifier(&id, false);
+                let result = self.compile_identifier(id, false);
                 return result;
             }
             ast::ExpressionKind::Infix(expr) => {
-                let result = self.compile_infix_expression(&expr);
+                let result = self.compile_infix_expression(expr);
                 return result;
             }
             ast::ExpressionKind::Prefix(expr) => {
-                let result = self.compile_prefix_expression(&expr);
+                let result = self.compile_prefix_expression(expr);
                 return result;
             }
             ast::ExpressionKind::Suffix(expr) => {
-                let result = self.compile_suffix_expression(&expr);
+                let result = self.compile_suffix_expression(expr);
                 return result;
             }
             ast::ExpressionKind::Call(ct) => {
-                let result = self.compile_call(&ct);
+                let result = self.compile_call(ct);
+                return result;
+            }
+            ast::ExpressionKind::Shell(sh) => {
+                let result = self.compile_shell_expr(sh);
+                return result;
+            }
+            ast::ExpressionKind::Lambda(lm) => {
+                let result = self.compile_lambda(lm);
+                return result;
+            }
+            ast::ExpressionKind::Index(idx) => {
+                let result = self.compile_index(idx);
+                return result;
+            }
+            ast::ExpressionKind::Slice(slice) => {
+                let result = self.compile_slice(slice);
+                return result;
+            }
+            ast::ExpressionKind::Match(node) => {
+                let result = self.compile_match(node, true);
+                return result;
+            }
+            ast::ExpressionKind::Attribute(attr) => {
+                let result = self.compile_attribute_resolver(attr);
                 return result;
             }
-            ast::ExpressionKind::Shell(sh) => {
-                let result = self.compile_shell_expr(&sh);
-                return result;
+            _ => return None,
+        }
+        return None;
+    }
+
+    fn compile_const_declr(&mut self, stmt: &ast::ConstType) -> Option<errors::CompileError> {
+        let var_name = &stmt.identifier.name;
+        // resolve the name:
+        let resolve_result = self.symbol_table.resolve_symbol(var_name);
+        if resolve_result.is_some() {
+            return Some(errors::CompileError::new(
+                format!("{} already declared", var_name),
+                errors::CompilerErrorKind::SymbolAlreadyExist,
+                0,
+            ));
+        }
+
+        let registered_symbol = self.symbol_table.insert_new_symbol(var_name, true);
+        if self.pure_fn.is_none()
+            && stmt.expression.is_some()
+            && self.is_impure_callable(stmt.expression.as_ref().unwrap())
+        {
+            self.impure_callables.insert(var_name.clone());
+        }
+
+        if stmt.expression.is_some() {
+            let error = self.compile_expression(stmt.expression.as_ref().unwrap());
+            if error.is_some() {
+                return error;
+            }
+        }
+
+        match registered_symbol.scope {
+            symtab::ScopeKind::Global => {
+                self.save(
+                    isa::InstructionKind::IStoreGlobal,
+                    &vec![registered_symbol.pos],
+                );
+            }
+            symtab::ScopeKind::Local => {
+                self.save(
+                    isa::InstructionKind::IStoreLocal,
+                    &vec![registered_symbol.pos],
+                );
+            }
+            symtab::ScopeKind::Builtin => {
+                return Some(errors::CompileError::new(
+                    format!("Cannot assign {} as built-in.", var_name),
+                    errors::CompilerErrorKind::BuiltinAssignment,
+                    0,
+                ));
+            }
+            _ => {}
+        }
+
+        return None;
+    }
+
+    /*
+        Stores the value on top of the stack into the targets of a destructuring
+        declaration, or of an assignment when is_declaration is false.
+        IUnpackArray and IUnpackHash replace the value by its parts with the
+        first part on top, so the targets are stored from left to right.
+    */
+    fn compile_unpack(
+        &mut self,
+        target: &ast::UnpackKind,
+        is_declaration: bool,
+        is_const: bool,
+    ) -> Option<errors::CompileError> {
+        match target {
+            ast::UnpackKind::Name(name) => {
+                if name == "_" {
+                    self.store_hidden_symbol("unpack");
+                    return None;
+                }
+
+                if !is_declaration {
+                    return self.compile_item_assignment(&ast::IdentifierType {
+                        name: name.clone(),
+                        t: None,
+                    });
+                }
+
+                if self.symbol_table.resolve_symbol(name).is_some() {
+                    return Some(errors::CompileError::new(
+                        format!("{} already declared", name),
+                        errors::CompilerErrorKind::SymbolAlreadyExist,
+                        0,
+                    ));
+                }
+
+                let symbol = self.symbol_table.insert_new_symbol(name, is_const);
+                if symbol.scope == symtab::ScopeKind::Global {
+                    self.save(isa::InstructionKind::IStoreGlobal, &vec![symbol.pos]);
+                } else {
+                    self.save(isa::InstructionKind::IStoreLocal, &vec![symbol.pos]);
+                }
+            }
+            ast::UnpackKind::Array(elements) => {
+                self.save(isa::InstructionKind::IUnpackArray, &vec![elements.len()]);
+                for element in elements.iter() {
+                    let error = self.compile_unpack(element, is_declaration, is_const);
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+            }
+            ast::UnpackKind::HashTable(pairs) => {
+                for (key, _) in pairs.iter() {
+                    let idx = self.register_constant(Object::Str(key.clone()));
+                    self.save(isa::InstructionKind::IConstant, &vec![idx]);
+                }
+
+                self.save(isa::InstructionKind::IUnpackHash, &vec![pairs.len()]);
+                for (_, element) in pairs.iter() {
+                    let error = self.compile_unpack(element, is_declaration, is_const);
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+            }
+        }
+
+        return None;
+    }
+
+    fn compile_destructure(&mut self, stmt: &ast::DestructureType) -> Option<errors::CompileError> {
+        let error = self.compile_expression(&stmt.expression);
+        if error.is_some() {
+            return error;
+        }
+
+        return self.compile_unpack(&stmt.target, true, stmt.is_const);
+    }
+
+    // the targets of [a, b] = ..., names or nested arrays of names.
+    fn get_unpack_target(expr: &ast::ExpressionKind) -> Result<ast::UnpackKind, String> {
+        match expr {
+            ast::ExpressionKind::Identifier(id) => {
+                return Ok(ast::UnpackKind::Name(id.name.clone()))
+            }
+            ast::ExpressionKind::Literal(ast::LiteralKind::Array(arr)) => {
+                let mut elements = vec![];
+                for element in arr.array_values.iter() {
+                    let target = BytecodeCompiler::get_unpack_target(element);
+                    if target.is_err() {
+                        return Err(target.unwrap_err());
+                    }
+                    elements.push(target.unwrap());
+                }
+
+                return Ok(ast::UnpackKind::Array(elements));
+            }
+            _ => {
+                return Err(
+                    "Only names and arrays of names can be assigned by destructuring".to_string(),
+                )
             }
-            ast::ExpressionKind::Lambda(lm) => {
-                let result = self.compile_lambda(&lm);
-                return result;
+        }
+    }
+
+    fn compile_array(&mut self, arr: &ast::ArrayType) -> Option<errors::CompileError> {
+        let elements = &arr.array_values;
+
+        let mut error: Option<errors::CompileError>;
+        for expr in elements {
+            error = self.compile_expression(expr);
+            if error.is_some() {
+                return error;
             }
-            ast::ExpressionKind::Index(idx) => {
-                let result = self.compile_index(&idx);
-                return result;
+        }
+
+        // save the array:
+        self.save(isa::InstructionKind::IArray, &vec![arr.length]);
+        return None;
+    }
+
+    fn compile_hash(&mut self, ht: &ast::HashTableType) -> Option<errors::CompileError> {
+        let pairs = &ht.pairs;
+
+        let mut error: Option<errors::CompileError>;
+        for (key_expr, value_expr) in pairs {
+            error = self.compile_expression(key_expr);
+            if error.is_some() {
+                return error;
             }
-            ast::ExpressionKind::Attribute(attr) => {
-                let result = self.compile_attribute_resolver(&attr, true);
-                return result;
+
+            error = self.compile_expression(value_expr);
+            if error.is_some() {
+                return error;
             }
-            _ => return None,
         }
+
+        // set hash:
+        let scope_length = pairs.len() * 2;
+        self.save(isa::InstructionKind::IHash, &vec![scope_length]);
         return None;
     }
 
-    fn compile_const_declr(&mut self, stmt: &ast::ConstType) -> Option<errors::CompileError> {
+    fn compile_variable_declr(&mut self, stmt: &ast::LetType) -> Option<errors::CompileError> {
         let var_name = &stmt.identifier.name;
         // resolve the name:
-        let resolve_result = self.symbol_table.resolve_symbol(&var_name);
+        let resolve_result = self.symbol_table.resolve_symbol(var_name);
         if resolve_result.is_some() {
             return Some(errors::CompileError::new(
                 format!("{} already declared", var_name),
@@ -1221,10 +2640,17 @@ impl BytecodeCompiler {
             ));
         }
 
-        let registered_symbol = self.symbol_table.insert_new_symbol(&var_name, true);
+        let registered_symbol = self.symbol_table.insert_new_symbol(var_name, false);
 
         if stmt.expression.is_some() {
-            self.compile_expression(stmt.expression.as_ref().unwrap());
+            let error = self.compile_expression(stmt.expression.as_ref().unwrap());
+            if error.is_some() {
+                return error;
+            }
+        } else {
+            // register a noval:
+            let no_val = self.register_constant(Object::Noval);
+            self.save(isa::InstructionKind::IConstant, &vec![no_val]);
         }
 
         match registered_symbol.scope {
@@ -1253,228 +2679,607 @@ impl BytecodeCompiler {
         return None;
     }
 
-    fn compile_array(&mut self, arr: &ast::ArrayType) -> Option<errors::CompileError> {
-        let elements = &arr.array_values;
+    fn replace_instruction_operands(
+        &mut self,
+        scope_idx: usize,
+        inst: isa::InstructionKind,
+        operands: &Operands,
+        pos: &usize,
+    ) -> Option<errors::CompileError> {
+        if self.scopes.len() <= scope_idx {
+            return Some(errors::CompileError::new(
+                "Reached out of scope while replacing opcode".to_string(),
+                errors::CompilerErrorKind::BytecodeError,
+                0,
+            ));
+        }
 
-        let mut error: Option<errors::CompileError>;
-        for idx in 0..elements.len() {
-            let expr = elements[idx].clone();
-            error = self.compile_expression(&expr);
+        let opcodes_length = self.scopes[scope_idx].get_size();
+        if opcodes_length <= *pos {
+            return Some(errors::CompileError::new(
+                "Reached out of buffer while replacing opcode".to_string(),
+                errors::CompilerErrorKind::BytecodeError,
+                0,
+            ));
+        }
+
+        // substitute:
+        let compiled_opcode = InstructionPacker::encode_instruction(inst, operands);
+
+        let end = *pos + compiled_opcode.len();
+        self.scopes[scope_idx].instructions[*pos..end].copy_from_slice(&compiled_opcode);
+
+        return None;
+    }
+
+    fn compile_call(&mut self, node: &ast::CallType) -> Option<errors::CompileError> {
+        let pure_error = self.check_pure_call(node);
+        if pure_error.is_some() {
+            return pure_error;
+        }
+
+        let args = &node.arguments;
+        let is_spread_call = args.iter().any(|arg| {
+            BytecodeCompiler::get_keyword_argument(arg).is_some()
+                || BytecodeCompiler::get_spread_argument(arg).is_some()
+        });
+
+        if is_spread_call {
+            return self.compile_spread_call(node);
+        }
+
+        // compile all arguments:
+        for expr in args {
+            // compile the expression:
+            let error = self.compile_expression(expr);
             if error.is_some() {
                 return error;
             }
         }
 
-        // save the array:
-        self.save(isa::InstructionKind::IArray, &vec![arr.length]);
+        // resolve function name:
+
+        let fn_expr = &node.function;
+
+        // check if it's a attribute call:
+        let mut is_attr = false;
+        if let ast::ExpressionKind::Attribute(_) = fn_expr.as_ref() {
+            self.attr_ctls.push(args.len());
+            is_attr = true;
+        }
+
+        let error = self.compile_expression(fn_expr);
+        if error.is_some() {
+            return error;
+        }
+
+        if is_attr {
+            return None;
+        }
+
+        // place the call instruction:
+        if node.is_thread {
+            self.save(isa::InstructionKind::ICallThread, &vec![args.len()]);
+        } else if node.is_async {
+            self.save(isa::InstructionKind::ICallAsync, &vec![args.len()]);
+        } else {
+            self.save(isa::InstructionKind::ICall, &vec![args.len()]);
+        }
+
         return None;
     }
 
-    fn compile_hash(&mut self, ht: &ast::HashTableType) -> Option<errors::CompileError> {
-        let pairs = &ht.pairs;
+    // name = value in the arguments of a call:
+    fn get_keyword_argument(arg: &ast::ExpressionKind) -> Option<(&String, &ast::ExpressionKind)> {
+        if let ast::ExpressionKind::Infix(infix) = arg {
+            if let ast::ExpressionKind::Identifier(id) = infix.expression_left.as_ref() {
+                if infix.infix == exp::InfixExpKind::Equal {
+                    return Some((&id.name, infix.expression_right.as_ref()));
+                }
+            }
+        }
 
-        let mut error: Option<errors::CompileError>;
-        for idx in 0..pairs.len() {
-            let pair = pairs[idx].clone();
-            let (key_expr, value_expr) = pair;
+        return None;
+    }
+
+    // ...xs in the arguments of a call:
+    fn get_spread_argument(arg: &ast::ExpressionKind) -> Option<&ast::ExpressionKind> {
+        if let ast::ExpressionKind::Prefix(prefix) = arg {
+            if prefix.prefix == exp::PrefixExpKind::Spread {
+                return Some(prefix.expression.as_ref());
+            }
+        }
+
+        return None;
+    }
+
+    /*
+        A call with spread or keyword arguments pushes its positional
+        arguments as arrays, runs of plain arguments and the spread ones,
+        followed by a hash table of the keyword arguments. ICallSpread
+        joins them and calls the function like ICall or ICallAttr.
+    */
+    fn compile_spread_call(&mut self, node: &ast::CallType) -> Option<errors::CompileError> {
+        if node.is_thread || node.is_async {
+            return Some(errors::CompileError::new(
+                "Spread and keyword arguments are not supported in thread and async calls"
+                    .to_string(),
+                errors::CompilerErrorKind::InvalidArguments,
+                0,
+            ));
+        }
+
+        let mut n_chunks = 0;
+        let mut n_plain = 0;
+        let mut keywords: Vec<(&String, &ast::ExpressionKind)> = vec![];
+
+        for arg in node.arguments.iter() {
+            let keyword = BytecodeCompiler::get_keyword_argument(arg);
+            if keyword.is_some() {
+                let (name, value) = keyword.unwrap();
+                if keywords.iter().any(|(other, _)| *other == name) {
+                    return Some(errors::CompileError::new(
+                        format!("Keyword argument {} repeated", name),
+                        errors::CompilerErrorKind::InvalidArguments,
+                        0,
+                    ));
+                }
+
+                keywords.push((name, value));
+                continue;
+            }
+
+            if !keywords.is_empty() {
+                return Some(errors::CompileError::new(
+                    "Positional argument follows keyword arguments".to_string(),
+                    errors::CompilerErrorKind::InvalidArguments,
+                    0,
+                ));
+            }
+
+            let spread = BytecodeCompiler::get_spread_argument(arg);
+            if spread.is_none() {
+                let error = self.compile_expression(arg);
+                if error.is_some() {
+                    return error;
+                }
+
+                n_plain += 1;
+                continue;
+            }
+
+            if n_plain > 0 {
+                self.save(isa::InstructionKind::IArray, &vec![n_plain]);
+                n_chunks += 1;
+                n_plain = 0;
+            }
 
-            error = self.compile_expression(&key_expr);
+            let error = self.compile_expression(spread.unwrap());
             if error.is_some() {
                 return error;
             }
+            n_chunks += 1;
+        }
+
+        if n_plain > 0 {
+            self.save(isa::InstructionKind::IArray, &vec![n_plain]);
+            n_chunks += 1;
+        }
 
-            error = self.compile_expression(&value_expr);
+        for (name, value) in keywords.iter() {
+            let name_idx = self.register_constant(Object::Str(name.to_string()));
+            self.save(isa::InstructionKind::IConstant, &vec![name_idx]);
+
+            let error = self.compile_expression(value);
             if error.is_some() {
                 return error;
             }
         }
+        self.save(isa::InstructionKind::IHash, &vec![keywords.len() * 2]);
 
-        // set hash:
-        let scope_length = pairs.len() * 2;
-        self.save(isa::InstructionKind::IHash, &vec![scope_length]);
+        // a.b(...) pushes the parent and the attributes like ICallAttr:
+        let mut n_attrs = 0;
+        match node.function.as_ref() {
+            ast::ExpressionKind::Attribute(resolver) => {
+                let error = self.compile_expression(&resolver.parent);
+                if error.is_some() {
+                    return error;
+                }
+
+                for attr in &resolver.child_attrs {
+                    let idx = self.register_constant(Object::Str(attr.clone()));
+                    self.save(isa::InstructionKind::IConstant, &vec![idx]);
+                }
+                n_attrs = resolver.child_attrs.len();
+            }
+            _ => {
+                let error = self.compile_expression(&node.function);
+                if error.is_some() {
+                    return error;
+                }
+            }
+        }
+
+        self.save(isa::InstructionKind::ICallSpread, &vec![n_chunks, n_attrs]);
         return None;
     }
 
-    fn compile_variable_declr(&mut self, stmt: &ast::LetType) -> Option<errors::CompileError> {
-        let var_name = &stmt.identifier.name;
-        // resolve the name:
-        let resolve_result = self.symbol_table.resolve_symbol(&var_name);
-        if resolve_result.is_some() {
+    fn compile_break_stmt(&mut self) -> Option<errors::CompileError> {
+        let n_loop_ctls = self.loop_ctls.len();
+        if n_loop_ctls == 0 {
             return Some(errors::CompileError::new(
-                format!("{} already declared", var_name),
-                errors::CompilerErrorKind::SymbolAlreadyExist,
+                "break encountered outside loop".to_string(),
+                errors::CompilerErrorKind::InvalidBreak,
+                0,
+            ));
+        }
+
+        if self.finally_ctls.len() > self.loop_ctls[n_loop_ctls - 1].n_finally_ctls {
+            self.compile_finally_jump(FINALLY_BREAK);
+            return None;
+        }
+
+        self.pop_loop_exc_handlers();
+        self.save(isa::InstructionKind::IBlockEnd, &vec![]);
+        let break_pos = self.save(isa::InstructionKind::IJump, &vec![0]);
+
+        self.loop_ctls[n_loop_ctls - 1].break_pos.push(break_pos);
+        return None;
+    }
+
+    fn compile_continue_stmt(&mut self) -> Option<errors::CompileError> {
+        let n_loop_ctls = self.loop_ctls.len();
+        if n_loop_ctls == 0 {
+            return Some(errors::CompileError::new(
+                "continue encountered outside loop".to_string(),
+                errors::CompilerErrorKind::InvalidContinue,
                 0,
             ));
         }
 
-        let registered_symbol = self.symbol_table.insert_new_symbol(&var_name, false);
+        let jump_pos = self.loop_ctls[n_loop_ctls - 1].loop_start_pos;
+
+        if self.finally_ctls.len() > self.loop_ctls[n_loop_ctls - 1].n_finally_ctls {
+            self.compile_finally_jump(FINALLY_CONTINUE);
+            return None;
+        }
+
+        self.pop_loop_exc_handlers();
+        self.save(isa::InstructionKind::IBlockEnd, &vec![]);
+        self.save(isa::InstructionKind::IJump, &vec![jump_pos]);
+
+        return None;
+    }
+
+    // break and continue jump out of the try blocks opened inside the loop,
+    // their handlers should not outlive the jump.
+    fn pop_loop_exc_handlers(&mut self) {
+        let n_loop_ctls = self.loop_ctls.len();
+        let n_loop_handlers = self.loop_ctls[n_loop_ctls - 1].n_exc_handlers;
+        for _ in n_loop_handlers..self.n_exc_handlers {
+            self.save(isa::InstructionKind::IPopExcHandle, &vec![]);
+        }
+    }
+
+    // leaves the innermost try statement through its finally block:
+    fn compile_finally_jump(&mut self, action: usize) {
+        let ctl_idx = self.finally_ctls.len() - 1;
+        for _ in self.finally_ctls[ctl_idx].n_exc_handlers..self.n_exc_handlers {
+            self.save(isa::InstructionKind::IPopExcHandle, &vec![]);
+        }
+
+        let action_idx = self.register_constant(Object::Int(action as i64));
+        self.save(isa::InstructionKind::IConstant, &vec![action_idx]);
+        let action_sym = Rc::clone(&self.finally_ctls[ctl_idx].action_sym);
+        self.compile_store_symbol(&action_sym);
+
+        let jump_pos = self.save(isa::InstructionKind::IJump, &vec![0]);
+        let finally_ctl = &mut self.finally_ctls[ctl_idx];
+        finally_ctl.jump_pos.push(jump_pos);
+        if !finally_ctl.actions.contains(&action) {
+            finally_ctl.actions.push(action);
+        }
+    }
+
+    // carries out the actions stored by compile_finally_jump:
+    fn compile_finally_actions(
+        &mut self,
+        finally_ctl: &FinallyControl,
+    ) -> Option<errors::CompileError> {
+        for action in &finally_ctl.actions {
+            self.compile_load_symbol(&finally_ctl.action_sym);
+            let action_idx = self.register_constant(Object::Int(*action as i64));
+            self.save(isa::InstructionKind::IConstant, &vec![action_idx]);
+            self.save(isa::InstructionKind::ILEq, &vec![]);
+            let not_jmp_pos = self.save(isa::InstructionKind::INotJump, &vec![0]);
+
+            let error = match *action {
+                FINALLY_BREAK => self.compile_break_stmt(),
+                FINALLY_CONTINUE => self.compile_continue_stmt(),
+                FINALLY_RETURN => {
+                    self.compile_return(false);
+                    None
+                }
+                _ => {
+                    self.compile_load_symbol(&finally_ctl.value_sym);
+                    self.compile_return(true);
+                    None
+                }
+            };
+
+            if error.is_some() {
+                return error;
+            }
+
+            let next_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+            let error = self.replace_instruction_operands(
+                self.scope_index,
+                isa::InstructionKind::INotJump,
+                &vec![next_pos],
+                &not_jmp_pos,
+            );
+
+            if error.is_some() {
+                return error;
+            }
+        }
+
+        return None;
+    }
 
-        if stmt.expression.is_some() {
-            self.compile_expression(stmt.expression.as_ref().unwrap());
-        } else {
-            // register a noval:
-            let no_val = self.register_constant(Object::Noval);
-            self.save(isa::InstructionKind::IConstant, &vec![no_val]);
+    fn compile_store_symbol(&mut self, sym: &Rc<symtab::Symbol>) {
+        match sym.scope {
+            symtab::ScopeKind::Global => {
+                self.save(isa::InstructionKind::IStoreGlobal, &vec![sym.pos]);
+            }
+            symtab::ScopeKind::Local => {
+                self.save(isa::InstructionKind::IStoreLocal, &vec![sym.pos]);
+            }
+            _ => {}
         }
+    }
 
-        match registered_symbol.scope {
+    fn compile_load_symbol(&mut self, sym: &Rc<symtab::Symbol>) {
+        match sym.scope {
             symtab::ScopeKind::Global => {
-                self.save(
-                    isa::InstructionKind::IStoreGlobal,
-                    &vec![registered_symbol.pos],
-                );
+                self.save(isa::InstructionKind::ILoadGlobal, &vec![sym.pos]);
             }
             symtab::ScopeKind::Local => {
-                self.save(
-                    isa::InstructionKind::IStoreLocal,
-                    &vec![registered_symbol.pos],
-                );
-            }
-            symtab::ScopeKind::Builtin => {
-                return Some(errors::CompileError::new(
-                    format!("Cannot assign {} as built-in.", var_name),
-                    errors::CompilerErrorKind::BuiltinAssignment,
-                    0,
-                ));
+                self.save(isa::InstructionKind::ILoadLocal, &vec![sym.pos]);
             }
             _ => {}
         }
-
-        return None;
     }
 
-    fn replace_instruction_operands(
-        &mut self,
-        scope_idx: usize,
-        inst: isa::InstructionKind,
-        operands: &Operands,
-        pos: &usize,
-    ) -> Option<errors::CompileError> {
-        if self.scopes.len() <= scope_idx {
-            return Some(errors::CompileError::new(
-                "Reached out of scope while replacing opcode".to_string(),
-                errors::CompilerErrorKind::BytecodeError,
-                0,
-            ));
+    fn compile_throw_stmt(&mut self, node: &ast::ThrowType) -> Option<errors::CompileError> {
+        let error = self.compile_expression(&node.expression);
+        if error.is_some() {
+            return error;
         }
 
-        let opcodes_length = self.scopes[scope_idx].get_size();
-        if opcodes_length <= *pos {
+        self.save(isa::InstructionKind::IRaise, &vec![]);
+        return None;
+    }
+
+    fn compile_rethrow_stmt(&mut self) -> Option<errors::CompileError> {
+        if self.catch_idents.is_empty() {
             return Some(errors::CompileError::new(
-                "Reached out of buffer while replacing opcode".to_string(),
-                errors::CompilerErrorKind::BytecodeError,
+                "rethrow encountered outside catch block".to_string(),
+                errors::CompilerErrorKind::InvalidRethrow,
                 0,
             ));
         }
 
-        // substitute:
-        let compiled_opcode = InstructionPacker::encode_instruction(inst, &operands);
-
-        for idx in 0..compiled_opcode.len() {
-            self.scopes[scope_idx].instructions[*pos + idx] = compiled_opcode[idx];
+        // load the caught exception and raise it again:
+        let catch_ident = self.catch_idents[self.catch_idents.len() - 1].clone();
+        let error = self.compile_identifier(&catch_ident, false);
+        if error.is_some() {
+            return error;
         }
 
+        self.save(isa::InstructionKind::IRaise, &vec![]);
         return None;
     }
 
-    fn compile_call(&mut self, node: &ast::CallType) -> Option<errors::CompileError> {
-        let args = &node.arguments;
-
-        // compile all arguments:
-        for idx in 0..args.len() {
-            let expr = &args[idx];
-
-            // compile the expression:
-            let error = self.compile_expression(expr);
-            if error.is_some() {
-                return error;
+    fn compile_try_statement(&mut self, node: &ast::TryCatchType) -> Option<errors::CompileError> {
+        let catch_ident = match node.exception_ident.as_ref() {
+            ast::ExpressionKind::Identifier(id) => id.clone(),
+            _ => {
+                return Some(errors::CompileError::new(
+                    "Invalid expression, catch target must be an identifier".to_string(),
+                    errors::CompilerErrorKind::InvalidOperand,
+                    0,
+                ))
             }
+        };
+
+        // break, continue and return run the finally block before leaving:
+        let has_finally = node.final_block.is_some();
+        let try_idx = self.n_try_blocks;
+        if has_finally {
+            self.n_try_blocks += 1;
+            let action_name = format!("__finally_action_{}", try_idx);
+            let action_sym = self.symbol_table.insert_new_symbol(&action_name, false);
+            let value_name = format!("__finally_value_{}", try_idx);
+            let value_sym = self.symbol_table.insert_new_symbol(&value_name, false);
+
+            let no_action = self.register_constant(Object::Int(0));
+            self.save(isa::InstructionKind::IConstant, &vec![no_action]);
+            self.compile_store_symbol(&action_sym);
+
+            self.finally_ctls.push(FinallyControl {
+                n_exc_handlers: self.n_exc_handlers,
+                action_sym: action_sym,
+                value_sym: value_sym,
+                jump_pos: vec![],
+                actions: vec![],
+            });
+        }
+
+        // try block, the handler jumps to the catch block:
+        let try_handler_pos = self.save(isa::InstructionKind::IPushExcHandle, &vec![0]);
+        self.n_exc_handlers += 1;
+
+        let mut error = self.compile_block_statement(&node.try_block);
+        if error.is_some() {
+            return error;
         }
 
-        // resolve function name:
+        self.save(isa::InstructionKind::IPopExcHandle, &vec![]);
+        self.n_exc_handlers -= 1;
+        let after_try_pos = self.save(isa::InstructionKind::IJump, &vec![0]);
 
-        let fn_expr = &node.function;
+        // catch block, the VM leaves the exception on top of the stack:
+        let catch_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+        error = self.replace_instruction_operands(
+            self.scope_index,
+            isa::InstructionKind::IPushExcHandle,
+            &vec![catch_pos],
+            &try_handler_pos,
+        );
 
-        // check if it's a attribute call:
-        let mut is_attr = false;
-        match fn_expr.as_ref() {
-            ast::ExpressionKind::Attribute(_) => {
-                self.attr_ctls.push(args.len());
-                is_attr = true;
-            }
-            _ => {}
+        if error.is_some() {
+            return error;
         }
 
-        let error = self.compile_expression(fn_expr);
+        // exceptions raised inside catch block should still run the finally block:
+        let mut catch_handler_pos = 0;
+        if has_finally {
+            catch_handler_pos = self.save(isa::InstructionKind::IPushExcHandle, &vec![0]);
+            self.n_exc_handlers += 1;
+        }
+
+        // the exception is stored like a loop variable, constants cannot hold it:
+        let resolved_sym = self.symbol_table.resolve_symbol(&catch_ident.name);
+        if resolved_sym.is_some() && resolved_sym.unwrap().is_const {
+            return Some(errors::CompileError::new(
+                format!("Cannot assign to constant {}", catch_ident.name),
+                errors::CompilerErrorKind::ConstantAssignment,
+                0,
+            ));
+        }
+
+        let catch_sym = self
+            .symbol_table
+            .insert_new_symbol(&catch_ident.name, false);
+        self.compile_store_symbol(&catch_sym);
+
+        self.catch_idents.push(catch_ident);
+        error = self.compile_block_statement(&node.catch_block);
+        self.catch_idents.pop();
         if error.is_some() {
             return error;
         }
 
-        if is_attr {
-            return None;
+        if !has_finally {
+            let end_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+            return self.replace_instruction_operands(
+                self.scope_index,
+                isa::InstructionKind::IJump,
+                &vec![end_pos],
+                &after_try_pos,
+            );
         }
 
-        // place the call instruction:
-        if node.is_thread {
-            self.save(isa::InstructionKind::ICallThread, &vec![args.len()]);
-        } else if node.is_async {
-            self.save(isa::InstructionKind::ICallAsync, &vec![args.len()]);
-        } else {
-            self.save(isa::InstructionKind::ICall, &vec![args.len()]);
+        self.save(isa::InstructionKind::IPopExcHandle, &vec![]);
+        self.n_exc_handlers -= 1;
+        let finally_ctl = self.finally_ctls.pop().unwrap();
+        let after_catch_pos = self.save(isa::InstructionKind::IJump, &vec![0]);
+
+        // holds the exception raised in the catch block till finally completes:
+        let pending_name = format!("__exc_pending_{}", try_idx);
+        let pending_sym = self.symbol_table.insert_new_symbol(&pending_name, false);
+
+        let pending_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+        error = self.replace_instruction_operands(
+            self.scope_index,
+            isa::InstructionKind::IPushExcHandle,
+            &vec![pending_pos],
+            &catch_handler_pos,
+        );
+
+        if error.is_some() {
+            return error;
         }
 
-        return None;
-    }
+        self.compile_store_symbol(&pending_sym);
+        let to_finally_pos = self.save(isa::InstructionKind::IJump, &vec![0]);
 
-    fn compile_break_stmt(&mut self) -> Option<errors::CompileError> {
-        let n_loop_ctls = self.loop_ctls.len();
-        if n_loop_ctls == 0 {
-            return Some(errors::CompileError::new(
-                "break encountered outside loop".to_string(),
-                errors::CompilerErrorKind::InvalidBreak,
-                0,
-            ));
+        // try and catch blocks completed normally, nothing is pending:
+        let no_pending_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+        let mut jumps = vec![after_try_pos, after_catch_pos];
+        jumps.extend(finally_ctl.jump_pos.iter().cloned());
+        for jmp_pos in jumps {
+            error = self.replace_instruction_operands(
+                self.scope_index,
+                isa::InstructionKind::IJump,
+                &vec![no_pending_pos],
+                &jmp_pos,
+            );
+
+            if error.is_some() {
+                return error;
+            }
         }
 
-        self.save(isa::InstructionKind::IBlockEnd, &vec![]);
-        let break_pos = self.save(isa::InstructionKind::IJump, &vec![0]);
+        let no_val = self.register_constant(Object::Noval);
+        self.save(isa::InstructionKind::IConstant, &vec![no_val]);
+        self.compile_store_symbol(&pending_sym);
 
-        self.loop_ctls[n_loop_ctls - 1].break_pos.push(break_pos);
-        return None;
-    }
+        let finally_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+        error = self.replace_instruction_operands(
+            self.scope_index,
+            isa::InstructionKind::IJump,
+            &vec![finally_pos],
+            &to_finally_pos,
+        );
 
-    fn compile_continue_stmt(&mut self) -> Option<errors::CompileError> {
-        let n_loop_ctls = self.loop_ctls.len();
-        if n_loop_ctls == 0 {
-            return Some(errors::CompileError::new(
-                "continue encountered outside loop".to_string(),
-                errors::CompilerErrorKind::InvalidContinue,
-                0,
-            ));
+        if error.is_some() {
+            return error;
         }
 
-        let jump_pos = self.loop_ctls[n_loop_ctls - 1].loop_start_pos;
+        error = self.compile_block_statement(node.final_block.as_ref().unwrap());
+        if error.is_some() {
+            return error;
+        }
 
-        self.save(isa::InstructionKind::IBlockEnd, &vec![]);
-        self.save(isa::InstructionKind::IJump, &vec![jump_pos]);
+        // raise the pending exception again, if any:
+        self.compile_load_symbol(&pending_sym);
+        let not_jmp_pos = self.save(isa::InstructionKind::INotJump, &vec![0]);
+        self.compile_load_symbol(&pending_sym);
+        self.save(isa::InstructionKind::IRaise, &vec![]);
 
-        return None;
+        let end_pos = self.save(isa::InstructionKind::INoOp, &vec![]);
+        error = self.replace_instruction_operands(
+            self.scope_index,
+            isa::InstructionKind::INotJump,
+            &vec![end_pos],
+            &not_jmp_pos,
+        );
+
+        if error.is_some() {
+            return error;
+        }
+
+        return self.compile_finally_actions(&finally_ctl);
     }
 
     fn compile_while_loop(&mut self, node: &ast::WhileLoopType) -> Option<errors::CompileError> {
         let while_expr = &node.target_expr;
         let current_pos = self.scopes[self.scope_index].get_size();
         let new_loop_ctl = LoopControl {
-            loop_start_pos: current_pos.clone(),
+            loop_start_pos: current_pos,
             pos_after_loop: 0,
             break_pos: vec![],
+            n_exc_handlers: self.n_exc_handlers,
+            n_finally_ctls: self.finally_ctls.len(),
         };
 
         self.loop_ctls.push(new_loop_ctl);
         let current_loop_ctl = self.loop_ctls.len() - 1;
 
         // compile the loop expression:
-        let expr_error = self.compile_expression(&while_expr);
+        let expr_error = self.compile_expression(while_expr);
         if expr_error.is_some() {
             return expr_error;
         }
@@ -1524,20 +3329,198 @@ impl BytecodeCompiler {
         return None;
     }
 
+    fn resolve_module_path(&self, path: &String) -> Result<PathBuf, errors::CompileError> {
+        let mut module_path = if let Some(std_path) = path.strip_prefix("std::") {
+            Path::new(&native::get_stdlib_path()).join(std_path)
+        } else if Path::new(path).is_absolute() || self.import_stack.is_empty() {
+            PathBuf::from(path)
+        } else {
+            // relative imports are resolved from the importing file
+            let importer = self.import_stack.last().unwrap();
+            let parent = importer
+                .parent()
+                .map_or(PathBuf::new(), |p| p.to_path_buf());
+            parent.join(path)
+        };
+
+        if module_path.extension().is_none() {
+            module_path.set_extension("np");
+        }
+
+        let canonical_result = fs::canonicalize(&module_path);
+        if canonical_result.is_err() {
+            return Err(errors::CompileError::new(
+                format!("Module {} not found at {}", path, module_path.display()),
+                errors::CompilerErrorKind::ImportError,
+                0,
+            ));
+        }
+
+        return Ok(canonical_result.unwrap());
+    }
+
+    fn compile_module_file(
+        &mut self,
+        module_path: &PathBuf,
+    ) -> Result<Vec<(String, usize)>, errors::CompileError> {
+        let read_result = fs::read(module_path);
+        if read_result.is_err() {
+            return Err(errors::CompileError::new(
+                format!("Failed to read module {}", module_path.display()),
+                errors::CompilerErrorKind::ImportError,
+                0,
+            ));
+        }
+
+        let mut lexer = LexerAPI::new_from_buffer(read_result.unwrap());
+        lexer.file_name = format!("{}", module_path.display());
+
+        let mut parser = Parser::new_from_lexer(lexer);
+        let parsed_result = parser.parse();
+        if parsed_result.is_err() {
+            let messages = parser.get_formatted_errors();
+            return Err(errors::CompileError::new(
+                format!(
+                    "Failed to parse module {}:\n{}",
+                    module_path.display(),
+                    messages.join("\n")
+                ),
+                errors::CompilerErrorKind::ImportError,
+                0,
+            ));
+        }
+
+        let program = parsed_result.unwrap();
+
+        // modules get their own global namespace, but share the global
+        // pool with the importer, so allocation continues from n_items.
+        let mut module_symtab = symtab::SymbolTable::create_new_root();
+        module_symtab.insert_builtins();
+        module_symtab.n_items = self.symbol_table.n_items;
+
+        let importer_symtab = std::mem::replace(&mut self.symbol_table, module_symtab);
+        self.import_stack.push(module_path.clone());
+
+        let mut error = None;
+        for (idx, stmt) in program.statements.iter().enumerate() {
+            error = self.compile_statement_at(stmt, program.pos.get(idx));
+            if error.is_some() {
+                break;
+            }
+        }
+
+        self.import_stack.pop();
+        let module_symtab = std::mem::replace(&mut self.symbol_table, importer_symtab);
+        self.symbol_table.n_items = module_symtab.n_items;
+
+        if error.is_some() {
+            return Err(error.unwrap());
+        }
+
+        // export all the top-level names, except the hidden ones:
+        let mut exports: Vec<(String, usize)> = module_symtab
+            .symbols
+            .values()
+            .filter(|sym| sym.scope == symtab::ScopeKind::Global && !sym.name.starts_with("__"))
+            .map(|sym| (sym.name.clone(), sym.pos))
+            .collect();
+        exports.sort();
+
+        return Ok(exports);
+    }
+
+    fn compile_use_statement(&mut self, node: &ast::UseType) -> Option<errors::CompileError> {
+        if self.scope_index != 0 || self.symbol_table.level != 0 {
+            return Some(errors::CompileError::new(
+                format!("use {} is allowed only at the top-level", node.path),
+                errors::CompilerErrorKind::InvalidScope,
+                0,
+            ));
+        }
+
+        let alias = &node.alias.name;
+        if self.symbol_table.resolve_symbol(alias).is_some() {
+            return Some(errors::CompileError::new(
+                format!("{} already declared", alias),
+                errors::CompilerErrorKind::SymbolAlreadyExist,
+                0,
+            ));
+        }
+
+        let path_result = self.resolve_module_path(&node.path);
+        if path_result.is_err() {
+            return Some(path_result.unwrap_err());
+        }
+
+        let module_path = path_result.unwrap();
+        if self.import_stack.contains(&module_path) {
+            return Some(errors::CompileError::new(
+                format!("Circular import of module {}", module_path.display()),
+                errors::CompilerErrorKind::CircularImport,
+                0,
+            ));
+        }
+
+        let cache_key = module_path.to_string_lossy().to_string();
+
+        // every module is compiled and executed only once, later imports
+        // re-use the globals created by the first one.
+        if !self.modules.contains_key(&cache_key) {
+            let compile_result = self.compile_module_file(&module_path);
+            if compile_result.is_err() {
+                return Some(compile_result.unwrap_err());
+            }
+
+            self.modules
+                .insert(cache_key.clone(), compile_result.unwrap());
+        }
+
+        let exports = self.modules.get(&cache_key).unwrap().clone();
+        let module_name = module_path
+            .file_stem()
+            .map_or(alias.clone(), |stem| stem.to_string_lossy().to_string());
+
+        let name_idx = self.register_constant(Object::Str(module_name));
+        self.save(isa::InstructionKind::IConstant, &vec![name_idx]);
+        let path_idx = self.register_constant(Object::Str(cache_key));
+        self.save(isa::InstructionKind::IConstant, &vec![path_idx]);
+
+        for (name, pos) in &exports {
+            let idx = self.register_constant(Object::Str(name.clone()));
+            self.save(isa::InstructionKind::IConstant, &vec![idx]);
+            self.save(isa::InstructionKind::ILoadGlobal, &vec![*pos]);
+        }
+
+        self.save(isa::InstructionKind::IModule, &vec![exports.len() * 2]);
+
+        let symbol = self.symbol_table.insert_new_symbol(alias, true);
+        self.compile_store_symbol(&symbol);
+
+        return None;
+    }
+
     fn compile_statement(&mut self, stmt: &ast::StatementKind) -> Option<errors::CompileError> {
         let error = match stmt {
-            ast::StatementKind::Expression(node) => self.compile_expression(&node),
-            ast::StatementKind::Var(node) => self.compile_variable_declr(&node),
-            ast::StatementKind::Const(node) => self.compile_const_declr(&node),
-            ast::StatementKind::While(node) => self.compile_while_loop(&node),
+            ast::StatementKind::Expression(node) => self.compile_expression_stmt(node),
+            ast::StatementKind::Var(node) => self.compile_variable_declr(node),
+            ast::StatementKind::Const(node) => self.compile_const_declr(node),
+            ast::StatementKind::Destructure(node) => self.compile_destructure(node),
+            ast::StatementKind::While(node) => self.compile_while_loop(node),
             ast::StatementKind::Break => self.compile_break_stmt(),
             ast::StatementKind::Continue => self.compile_continue_stmt(),
-            ast::StatementKind::If(node) => self.compile_if_statement(&node),
-            ast::StatementKind::Assert(node) => self.compile_assert_statement(&node),
-            ast::StatementKind::For(node) => self.compile_for_loop(&node),
-            ast::StatementKind::Function(node) => self.compile_function(&node, false),
-            ast::StatementKind::Return(node) => self.compile_return_stmt(&node),
-            ast::StatementKind::ForEach(node) => self.compile_feach_stmt(&node),
+            ast::StatementKind::If(node) => self.compile_if_statement(node),
+            ast::StatementKind::Assert(node) => self.compile_assert_statement(node),
+            ast::StatementKind::For(node) => self.compile_for_loop(node),
+            ast::StatementKind::Function(node) => self.compile_function(node, FunctionKind::Named),
+            ast::StatementKind::Class(node) => self.compile_class(node),
+            ast::StatementKind::Return(node) => self.compile_return_stmt(node),
+            ast::StatementKind::Yield(node) => self.compile_yield_stmt(node),
+            ast::StatementKind::Match(node) => self.compile_match(node, false),
+            ast::StatementKind::ForEach(node) => self.compile_feach_stmt(node),
+            ast::StatementKind::TryCatch(node) => self.compile_try_statement(node),
+            ast::StatementKind::Throw(node) => self.compile_throw_stmt(node),
+            ast::StatementKind::Rethrow => self.compile_rethrow_stmt(),
+            ast::StatementKind::Use(node) => self.compile_use_statement(node),
             _ => {
                 return Some(errors::CompileError::new(
                     "Not yet implemented".to_string(),
@@ -1561,8 +3544,8 @@ impl BytecodeCompiler {
     ) -> Option<errors::CompileError> {
         self.save(isa::InstructionKind::IBlockStart, &vec![]);
 
-        for stmt in &node.statements {
-            let error = self.compile_statement(&stmt);
+        for (idx, stmt) in node.statements.iter().enumerate() {
+            let error = self.compile_statement_at(stmt, node.pos.get(idx));
             if error.is_some() {
                 return error;
             }
@@ -1573,10 +3556,39 @@ impl BytecodeCompiler {
         return None;
     }
 
+    fn compile_statement_at(
+        &mut self,
+        stmt: &ast::StatementKind,
+        span: Option<&Span>,
+    ) -> Option<errors::CompileError> {
+        if span.is_none() {
+            return self.compile_statement(stmt);
+        }
+
+        // instructions from here on belong to this statement:
+        let span = *span.unwrap();
+        let offset = self.scopes[self.scope_index].get_size();
+        let file = self.current_file();
+        self.scopes[self.scope_index].spans.add(offset, &file, span);
+
+        let error = self.compile_statement(stmt);
+        if error.is_some() {
+            // the innermost statement already set the location:
+            let mut unwrapped_error = error.unwrap();
+            if unwrapped_error.location.is_none() {
+                unwrapped_error.location = Some(SourceLocation::new(file, span));
+            }
+            return Some(unwrapped_error);
+        }
+
+        return None;
+    }
+
     fn get_bytecode(&self) -> CompiledBytecode {
         return CompiledBytecode {
             constant_pool: self.constant_pool.clone(),
             instructions: self.scopes[self.scope_index].get_instructions().clone(),
+            spans: self.scopes[self.scope_index].spans.clone(),
         };
     }
 
@@ -1585,8 +3597,8 @@ impl BytecodeCompiler {
         program_ast: &ast::Program,
     ) -> Result<CompiledBytecode, errors::CompileError> {
         let statements = &program_ast.statements;
-        for stmt in statements {
-            let error = self.compile_statement(&stmt);
+        for (idx, stmt) in statements.iter().enumerate() {
+            let error = self.compile_statement_at(stmt, program_ast.pos.get(idx));
             if error.is_some() {
                 let unwrapped_error = error.unwrap();
                 return Err(unwrapped_error);
@@ -1598,6 +3610,8 @@ impl BytecodeCompiler {
 
     pub fn clear_previous(&mut self) {
         self.scopes[self.scope_index].instructions.clear();
+        self.scopes[self.scope_index].size = 0;
+        self.scopes[self.scope_index].spans = SpanTable::new();
     }
 }
 
@@ -1645,12 +3659,12 @@ impl BytecodeDecompiler {
                     decoded_string.push_str("Subroutine Start:\n");
                     decoded_string.push_str(&repr);
                     decoded_string.push_str("Subroutine End\n");
-                    idx = idx + 1;
+                    idx += 1;
                 }
                 _ => {
                     let repr = item.describe();
                     decoded_string.push_str(&format!("{:0>8x} {}\n", idx, repr));
-                    idx = idx + 1;
+                    idx += 1;
                 }
             }
         }
@@ -1663,11 +3677,11 @@ impl BytecodeDecompiler {
 
         decoded_string.push_str("Instructions: \n");
 
-        decoded_string.push_str(&BytecodeDecompiler::disassemble_instructions(&bytecode));
+        decoded_string.push_str(&BytecodeDecompiler::disassemble_instructions(bytecode));
 
         decoded_string.push_str("\nConstants: \n");
 
-        decoded_string.push_str(&BytecodeDecompiler::disassemble_constants(&bytecode));
+        decoded_string.push_str(&BytecodeDecompiler::disassemble_constants(bytecode));
 
         return decoded_string;
     }
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use crate::lexer::Span;
+
+/*
+    Source position of a statement, errors are reported at the statement
+    they were raised from.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl SourceLocation {
+    pub fn new(file: String, span: Span) -> SourceLocation {
+        return SourceLocation {
+            file: file,
+            line: span.line,
+            col: span.col,
+        };
+    }
+
+    /*
+        Renders the error message followed by the source line and a caret
+        under the column, like:
+            main.np:3:9: TypeError: Operation Div is not applicable
+                var x = 1 / "a";
+                        ^
+    */
+    pub fn render(&self, message: &String, source_line: Option<String>) -> String {
+        let mut rendered = format!("{}: {}", self, message);
+        if source_line.is_none() {
+            return rendered;
+        }
+
+        let line = source_line.unwrap();
+
+        // tabs are kept so that the caret lines up with the source line:
+        let padding: String = line
+            .chars()
+            .take(self.col.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        rendered.push_str(&format!("\n    {}\n    {}^", line, padding));
+        return rendered;
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.col)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct SpanEntry {
+    pub offset: usize,
+    pub file: usize,
+    pub span: Span,
+}
+
+/*
+    Maps bytecode offsets to the source position of the statement they were
+    compiled from. Entries are sorted by offset, an instruction belongs to the
+    last entry at or before it. Statements of imported modules are compiled
+    into the importer, so a table can refer to more than one file.
+*/
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct SpanTable {
+    pub files: Vec<String>,
+    pub entries: Vec<SpanEntry>,
+}
+
+impl Default for SpanTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpanTable {
+    pub fn new() -> SpanTable {
+        return SpanTable {
+            files: vec![],
+            entries: vec![],
+        };
+    }
+
+    pub fn add(&mut self, offset: usize, file: &String, span: Span) {
+        let file_idx = match self.files.iter().position(|f| f == file) {
+            Some(idx) => idx,
+            None => {
+                self.files.push(file.clone());
+                self.files.len() - 1
+            }
+        };
+
+        let entry = SpanEntry {
+            offset: offset,
+            file: file_idx,
+            span: span,
+        };
+
+        // a statement that emitted no instructions is replaced by the next one.
+        if self.entries.last().map(|last| last.offset) == Some(offset) {
+            self.entries.pop();
+        }
+
+        self.entries.push(entry);
+    }
+
+    pub fn lookup(&self, offset: usize) -> Option<SourceLocation> {
+        let n_before = self.entries.partition_point(|entry| entry.offset <= offset);
+        if n_before == 0 {
+            return None;
+        }
+
+        let entry = &self.entries[n_before - 1];
+        return Some(SourceLocation::new(
+            self.files[entry.file].clone(),
+            entry.span,
+        ));
+    }
+}
@@ -117,46 +117,36 @@ impl SymbolTable {
         return ref_counted_symbol;
     }
 
-    pub fn resolve_symbol(&mut self, name: &str) -> Option<Rc<Symbol>> {
-        let result = self.__resolve(name);
-
-        if result.is_some() {
-            let (unwrapped_sym, is_parent) = result.unwrap();
-
-            if !is_parent {
-                return Some(Rc::clone(&unwrapped_sym));
-            }
-
-            match unwrapped_sym.scope {
-                ScopeKind::Global | ScopeKind::Builtin => return Some(Rc::clone(&unwrapped_sym)),
-                ScopeKind::Local | ScopeKind::Free => {
-                    let free = self.insert_free_symbol(&unwrapped_sym);
-                    return Some(free);
-                }
-            }
+    // puts back a symbol hidden by insert_new_symbol, or removes the name:
+    pub fn restore_symbol(&mut self, name: &str, symbol: Option<Rc<Symbol>>) {
+        if symbol.is_some() {
+            self.symbols.insert(name.to_string(), symbol.unwrap());
+        } else {
+            self.symbols.remove(name);
         }
-
-        return None;
     }
 
-    fn __resolve(&self, name: &str) -> Option<(Rc<Symbol>, bool)> {
-        let sym_key = name.to_string();
-        let mut current_symtab = self;
-
-        let mut is_parent_level = false;
-
-        let mut symbol_res = current_symtab.get_symbol(&sym_key);
-        while symbol_res.is_none() && current_symtab.level != 0 {
-            current_symtab = current_symtab.parent.as_ref().unwrap();
-            symbol_res = current_symtab.get_symbol(&sym_key);
-            is_parent_level = true;
+    pub fn resolve_symbol(&mut self, name: &str) -> Option<Rc<Symbol>> {
+        let symbol = self.get_symbol(&name.to_string());
+        if symbol.is_some() || self.level == 0 {
+            return symbol;
         }
 
-        if symbol_res.is_some() {
-            return Some((symbol_res.unwrap(), is_parent_level));
+        // a variable of an outer function is captured by every function in
+        // between, each of them passes the cell on to the next level:
+        let parent_symbol = self.parent.as_mut().unwrap().resolve_symbol(name);
+        if parent_symbol.is_none() {
+            return None;
         }
 
-        return None;
+        let unwrapped_sym = parent_symbol.unwrap();
+        match unwrapped_sym.scope {
+            ScopeKind::Global | ScopeKind::Builtin => return Some(unwrapped_sym),
+            ScopeKind::Local | ScopeKind::Free => {
+                let free = self.insert_free_symbol(&unwrapped_sym);
+                return Some(free);
+            }
+        }
     }
 
     pub fn get_free_symbols(&self) -> Vec<Rc<Symbol>> {
@@ -171,6 +161,12 @@ pub struct ConstantPool {
     pub size: usize,
 }
 
+impl Default for ConstantPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ConstantPool {
     pub fn new() -> ConstantPool {
         return ConstantPool {
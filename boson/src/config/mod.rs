@@ -14,3 +14,24 @@ pub const USE_STATIC_DATA_STACK: bool = true;
 // Enabl-concurrency features, if disabled
 // multi-threading code will run sequentially and join/async will throw errors.
 pub const ENABLE_CONCURRENCY: bool = true;
+
+// enable-jit
+// Subroutines called more than JIT_CALL_THRESHOLD times are compiled
+// to native code, can also be disabled at runtime with BOSON_JIT=0.
+pub const ENABLE_JIT: bool = true;
+pub const JIT_CALL_THRESHOLD: usize = 100;
+
+// max-string-size
+// Largest string in bytes built by repeat(), the padding methods and
+// format(), bigger results raise an error instead of exhausting the memory.
+pub const MAX_STRING_SIZE: usize = 1 << 28;
+
+// max-sandbox-depth
+// Callbacks and generators run on a sandbox VM nested on the native stack,
+// deeper nesting raises CallStackOverflow before the native stack runs out.
+pub const MAX_SANDBOX_DEPTH: usize = 64;
+
+// max-int-bits
+// Largest result in bits of ** and << on integers, bigger results raise an
+// OverflowError instead of exhausting the memory or blocking the VM.
+pub const MAX_INT_BITS: u64 = 1 << 20;
@@ -130,6 +130,49 @@ pub enum InstructionKind {
     ISetAttr,
     IGetAttr,
     ICallAttr,
+
+    // module namespace
+    IModule,
+
+    // user type
+    IClass,
+
+    // suspends a generator
+    IYield,
+
+    // slicing
+    ISlice,
+    ISetSlice,
+
+    // pattern tests of match, they push true or false
+    IMatchValue,
+    IMatchRange,
+    IMatchType,
+    IMatchArray,
+    IMatchKey,
+
+    // destructuring, replace a value by its parts
+    IUnpackArray,
+    IUnpackHash,
+
+    // call with spread or keyword arguments
+    ICallSpread,
+
+    // captured variables, IClosure takes the cells pushed by the
+    // capture instructions
+    IStoreFree,
+    ICaptureLocal,
+    ICaptureFree,
+
+    // bitwise xor, shifts, power and floor division
+    IXor,
+    IShl,
+    IShr,
+    IPow,
+    IFloorDiv,
+
+    // array pattern with ...rest, matches arrays at least that long
+    IMatchArrayRest,
 }
 
 pub type Operands = Vec<usize>;
@@ -263,6 +306,28 @@ impl InstructionKind {
             InstructionKind::ISetAttr => "ISetAttr".to_string(),
             InstructionKind::IGetAttr => "IGetAttr".to_string(),
             InstructionKind::ICallAttr => "ICallAttr".to_string(),
+            InstructionKind::IModule => "IModule".to_string(),
+            InstructionKind::IClass => "IClass".to_string(),
+            InstructionKind::IYield => "IYield".to_string(),
+            InstructionKind::ISlice => "ISlice".to_string(),
+            InstructionKind::ISetSlice => "ISetSlice".to_string(),
+            InstructionKind::IMatchValue => "IMatchValue".to_string(),
+            InstructionKind::IMatchRange => "IMatchRange".to_string(),
+            InstructionKind::IMatchType => "IMatchType".to_string(),
+            InstructionKind::IMatchArray => "IMatchArray".to_string(),
+            InstructionKind::IMatchKey => "IMatchKey".to_string(),
+            InstructionKind::IUnpackArray => "IUnpackArray".to_string(),
+            InstructionKind::IUnpackHash => "IUnpackHash".to_string(),
+            InstructionKind::ICallSpread => "ICallSpread".to_string(),
+            InstructionKind::IStoreFree => "IStoreFree".to_string(),
+            InstructionKind::ICaptureLocal => "ICaptureLocal".to_string(),
+            InstructionKind::ICaptureFree => "ICaptureFree".to_string(),
+            InstructionKind::IXor => "IXor".to_string(),
+            InstructionKind::IShl => "IShl".to_string(),
+            InstructionKind::IShr => "IShr".to_string(),
+            InstructionKind::IPow => "IPow".to_string(),
+            InstructionKind::IFloorDiv => "IFloorDiv".to_string(),
+            InstructionKind::IMatchArrayRest => "IMatchArrayRest".to_string(),
             _ => "invalid".to_string(),
         }
     }
@@ -283,7 +348,14 @@ impl InstructionKind {
             | InstructionKind::ICall
             | InstructionKind::ICallThread
             | InstructionKind::ILoadFree
+            | InstructionKind::IStoreFree
+            | InstructionKind::ICaptureFree
             | InstructionKind::IPushExcHandle
+            | InstructionKind::IModule
+            | InstructionKind::IMatchArray
+            | InstructionKind::IMatchArrayRest
+            | InstructionKind::IUnpackArray
+            | InstructionKind::IUnpackHash
             | InstructionKind::ICallAsync => vec![2],
             InstructionKind::ISetAttr => vec![2],
             InstructionKind::IGetAttr => vec![2],
@@ -295,6 +367,11 @@ impl InstructionKind {
             | InstructionKind::IAnd
             | InstructionKind::IMod
             | InstructionKind::IOr
+            | InstructionKind::IXor
+            | InstructionKind::IShl
+            | InstructionKind::IShr
+            | InstructionKind::IPow
+            | InstructionKind::IFloorDiv
             | InstructionKind::INeg
             | InstructionKind::IPreDecr
             | InstructionKind::IPreIncr
@@ -316,16 +393,28 @@ impl InstructionKind {
             | InstructionKind::IBlockStart
             | InstructionKind::IRetVal
             | InstructionKind::IRet
+            | InstructionKind::IYield
             | InstructionKind::IGetIndex
             | InstructionKind::ISetIndex
+            | InstructionKind::ISlice
+            | InstructionKind::ISetSlice
+            | InstructionKind::IMatchValue
+            | InstructionKind::IMatchType
+            | InstructionKind::IMatchKey
             | InstructionKind::IRaise
             | InstructionKind::IShellRaw
             | InstructionKind::IShell
             | InstructionKind::IPopExcHandle => vec![],
 
-            InstructionKind::IClosure | InstructionKind::ICallAttr => vec![2, 2],
+            InstructionKind::IClosure
+            | InstructionKind::ICallAttr
+            | InstructionKind::IClass
+            | InstructionKind::ICallSpread => vec![2, 2],
 
-            InstructionKind::IStoreLocal | InstructionKind::ILoadLocal => vec![1],
+            InstructionKind::IStoreLocal
+            | InstructionKind::ILoadLocal
+            | InstructionKind::ICaptureLocal
+            | InstructionKind::IMatchRange => vec![1],
 
             _ => vec![],
         }
@@ -333,7 +422,7 @@ impl InstructionKind {
 
     #[allow(dead_code)]
     pub fn disasm_instruction(&self, operands: &Operands) -> String {
-        let op_strings: Vec<String> = operands.into_iter().map(|op| format!("{:x}", op)).collect();
+        let op_strings: Vec<String> = operands.iter().map(|op| format!("{:x}", op)).collect();
         let op_formatted = op_strings.join(", ");
         let opcode = self.as_string();
 
@@ -3,20 +3,24 @@ use std::io::Read;
 use std::io::Write;
 use std::vec::Vec;
 
+use num_bigint::BigInt;
+
 pub const EOF_BYTE: u8 = 0x00;
 
 #[allow(dead_code)]
-pub const SYMBOLS: &'static [&'static str] = &[
+pub const SYMBOLS: &[&str] = &[
     "invalid", "+", "-", "*", "/", "(", ")", "<", ">", "<=", ">=", ";", ",", "%", "!", "=", "==",
     "!=", "{", "}", "&", "|", "~", "&&", "||", "+=", "-=", "++", "--", "*=", "/=", "%=", "[", "]",
-    "=>", ":", ".", "$",
+    "=>", ":", ".", "::", "$", "..", "..=", "...", "^", "^=", "<<", "<<=", ">>", ">>=", "**",
+    "**=", "//", "//=",
 ];
 
 #[allow(dead_code)]
-pub const KEYWORDS: &'static [&'static str] = &[
+pub const KEYWORDS: &[&str] = &[
     "invalid", "if", "else", "while", "for", "break", "continue", "const", "var", "none", "func",
     "return", "try", "catch", "finally", "rethrow", "throw", "as", "true", "false", "foreach",
-    "in", "use", "pure", "lambda", "assert", "thread", "async",
+    "in", "use", "pure", "lambda", "assert", "thread", "async", "class", "struct", "yield",
+    "match",
 ];
 
 #[allow(dead_code)]
@@ -63,6 +67,19 @@ pub enum SymbolKind {
     SDot = 38,
     SResolve = 39,
     SDollar = 40,
+    SRange = 41,
+    SRangeIncl = 42,
+    SEllipsis = 43,
+    SXor = 44,
+    SXorEq = 45,
+    SShl = 46,
+    SShlEq = 47,
+    SShr = 48,
+    SShrEq = 49,
+    SPow = 50,
+    SPowEq = 51,
+    SFloorDiv = 52,
+    SFloorDivEq = 53,
 }
 
 #[allow(dead_code)]
@@ -96,6 +113,10 @@ pub enum KeywordKind {
     KAssert = 25,
     KThread = 26,
     KAsync = 27,
+    KClass = 28,
+    KStruct = 29,
+    KYield = 30,
+    KMatch = 31,
 }
 
 #[allow(dead_code)]
@@ -106,15 +127,41 @@ pub enum TokenKind {
     EOF,
 
     Integer(i64),
+    // an int literal beyond the i64 range.
+    BigInteger(BigInt),
     Str(String),
+    // f"..." literal, split into its text and its replacement fields.
+    FStr(Vec<FStrPart>),
     Float(f64),
     Char(char),
+    // b'A' and b"raw" literals.
+    Byte(u8),
+    Bytes(Vec<u8>),
 
     Identifier(String),
     Operator(SymbolKind),
     Keyword(KeywordKind),
 
     Unknown(String),
+    // source text that could not be read as a token, with the reason.
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FStrPart {
+    Text(String),
+    // source of the expression and the format specifier after the colon.
+    Field(String, Option<String>),
+}
+
+/*
+    line and column of a token in the source, both start from 1,
+    columns are counted in characters.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
 }
 
 /*
@@ -124,6 +171,7 @@ pub enum TokenKind {
 pub struct LexedToken {
     pub token: TokenKind,
     pub pos: usize,
+    pub span: Span,
 }
 
 /*
@@ -156,7 +204,7 @@ impl ChunkedBuffer {
         }
     }
 
-    #[allow(dead_code)]
+    #[allow(dead_code, clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<LexerBuffer> {
         if self.is_end_reached {
             return None;
@@ -189,6 +237,8 @@ pub struct ProgramBuffer {
     pub current_pos: usize,
     pub next_pos: usize,
     pub buffer_size: usize,
+    // line and column of current_pos:
+    pub span: Span,
 }
 
 impl ProgramBuffer {
@@ -200,6 +250,7 @@ impl ProgramBuffer {
             current_pos: 0,
             next_pos: 0,
             buffer_size: l,
+            span: Span { line: 1, col: 1 },
         }
     }
 
@@ -211,8 +262,8 @@ impl ProgramBuffer {
         let f_len = file_metadata.len() as usize;
         let mut buffer = vec![0; f_len];
 
-        (&mut file_handle)
-            .read(&mut buffer)
+        file_handle
+            .read_exact(&mut buffer)
             .expect("Failed to read file into buffer");
 
         ProgramBuffer {
@@ -220,6 +271,7 @@ impl ProgramBuffer {
             current_pos: 0,
             next_pos: 0,
             buffer_size: f_len,
+            span: Span { line: 1, col: 1 },
         }
     }
 
@@ -238,29 +290,66 @@ impl ProgramBuffer {
             return 0x00;
         } else {
             let current_char = self.buffer[self.next_pos];
+
+            // UTF-8 continuation bytes belong to the previous column:
+            if self.next_pos > 0 {
+                if self.buffer[self.current_pos] == b'\n' {
+                    self.span.line += 1;
+                    self.span.col = 1;
+                } else if current_char & 0xc0 != 0x80 {
+                    self.span.col += 1;
+                }
+            }
+
             self.current_pos = self.next_pos;
-            self.next_pos = self.next_pos + 1;
+            self.next_pos += 1;
             return current_char;
         }
     }
 
-    pub fn get_as_i64(&mut self, start: usize, end: usize) -> i64 {
-        let sub_buffer = self.buffer[start..end].to_vec();
-        String::from_utf8(sub_buffer).unwrap().parse().unwrap()
-    }
-
-    pub fn get_as_f64(&mut self, start: usize, end: usize) -> f64 {
-        let sub_buffer = self.buffer[start..end].to_vec();
-        String::from_utf8(sub_buffer).unwrap().parse().unwrap()
+    // the byte at the given distance from the current position.
+    pub fn peek_at(&self, offset: usize) -> u8 {
+        if self.current_pos + offset >= self.buffer_size {
+            return 0x00;
+        } else {
+            return self.buffer[self.current_pos + offset];
+        }
     }
 
     pub fn get_as_string(&mut self, start: usize, end: usize) -> String {
-        let sub_buffer = self.buffer[start..end].to_vec();
-        String::from_utf8(sub_buffer).unwrap()
+        String::from_utf8_lossy(&self.buffer[start..end]).to_string()
     }
 
-    pub fn get_as_char(&mut self, pos: usize) -> char {
-        self.buffer[pos] as char
+    /*
+        Decodes the UTF-8 character that starts at the given position,
+        returns the character and the number of bytes it takes.
+    */
+    pub fn decode_char(&self, pos: usize) -> Result<(char, usize), String> {
+        let first = self.buffer[pos];
+        let width = match first {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => 0,
+        };
+
+        if width == 0 || pos + width > self.buffer_size {
+            return Err(format!(
+                "Invalid UTF-8 sequence starting with byte 0x{:02x}",
+                first
+            ));
+        }
+
+        match std::str::from_utf8(&self.buffer[pos..pos + width]) {
+            Ok(decoded) => return Ok((decoded.chars().next().unwrap(), width)),
+            Err(_) => {
+                return Err(format!(
+                    "Invalid UTF-8 sequence starting with byte 0x{:02x}",
+                    first
+                ))
+            }
+        }
     }
 }
 
@@ -273,6 +362,9 @@ pub struct StreamingLexer {
 pub struct ProgramLexer {
     pub buffer: ProgramBuffer,
     pub current_char: u8,
+    // start of the last token read by next_token:
+    token_pos: usize,
+    token_span: Span,
 }
 
 impl ProgramLexer {
@@ -281,6 +373,8 @@ impl ProgramLexer {
         let mut lexer = ProgramLexer {
             buffer: ProgramBuffer::new_from_file(file_name),
             current_char: 0,
+            token_pos: 0,
+            token_span: Span::default(),
         };
 
         lexer.append_eof_newline();
@@ -294,6 +388,8 @@ impl ProgramLexer {
         let mut lexer = ProgramLexer {
             buffer: ProgramBuffer::new_from_buffer(buffer),
             current_char: 0,
+            token_pos: 0,
+            token_span: Span::default(),
         };
 
         // read the first character from program buffer and return.
@@ -310,30 +406,151 @@ impl ProgramLexer {
         return self.buffer.peek_next();
     }
 
+    fn skip_bytes(&mut self, n_bytes: usize) {
+        for _ in 0..n_bytes {
+            self.read_next();
+        }
+    }
+
+    // decodes the character under the cursor, non ASCII characters take more than one byte.
+    fn decode_current(&mut self) -> Result<(char, usize), String> {
+        return self.buffer.decode_char(self.buffer.current_pos);
+    }
+
+    /*
+        Reads an int or a float literal. Ints can also be written in hex,
+        binary and octal with 0x, 0b and 0o, floats can have an exponent
+        like 1.5e-3 and the digits of both can be grouped with underscores,
+        like 1_000_000.
+    */
     fn find_number_literal(&mut self) -> TokenKind {
-        let start_pos = self.buffer.current_pos;
+        let radix = match (self.current_char, self.look_next_byte()) {
+            (b'0', b'x') | (b'0', b'X') => 16,
+            (b'0', b'b') | (b'0', b'B') => 2,
+            (b'0', b'o') | (b'0', b'O') => 8,
+            _ => 10,
+        };
 
+        if radix != 10 {
+            self.skip_bytes(2);
+            return self.find_radix_literal(radix);
+        }
+
+        let mut digits = String::new();
         let mut is_float = false;
+        let mut has_exponent = false;
+        let mut error: Option<String> = None;
 
         loop {
+            let next_byte = self.look_next_byte();
             match self.current_char {
-                b'0'..=b'9' | b'.' => {
-                    if self.current_char == b'.' {
-                        is_float = true;
+                b'0'..=b'9' => digits.push(self.current_char as char),
+                b'_' if next_byte.is_ascii_digit() => {}
+                b'_' => {
+                    error = error.or(Some(String::from(
+                        "Underscore in number literal must be followed by a digit",
+                    )));
+                }
+                // 1..5 is a range of ints:
+                b'.' if !is_float && next_byte != b'.' => {
+                    is_float = true;
+                    digits.push('.');
+                }
+                b'e' | b'E' if !has_exponent && self.exponent_follows() => {
+                    is_float = true;
+                    has_exponent = true;
+                    digits.push('e');
+                    if next_byte == b'+' || next_byte == b'-' {
+                        digits.push(next_byte as char);
+                        self.read_next();
                     }
-                    self.read_next();
                 }
                 _ => {
                     break;
                 }
             }
+            self.read_next();
+        }
+
+        if error.is_some() {
+            return TokenKind::Error(error.unwrap());
         }
 
         if is_float {
-            return TokenKind::Float(self.buffer.get_as_f64(start_pos, self.buffer.current_pos));
-        } else {
-            return TokenKind::Integer(self.buffer.get_as_i64(start_pos, self.buffer.current_pos));
+            return TokenKind::Float(digits.parse().unwrap());
+        }
+
+        return ProgramLexer::new_int_token(&digits, 10);
+    }
+
+    // the current character is an e that starts an exponent, like in 2e10 or 2e-3:
+    fn exponent_follows(&self) -> bool {
+        match self.buffer.peek_at(1) {
+            b'0'..=b'9' => return true,
+            b'+' | b'-' => return self.buffer.peek_at(2).is_ascii_digit(),
+            _ => return false,
+        }
+    }
+
+    // digits of a 0x, 0b or 0o literal, the prefix is already read.
+    fn find_radix_literal(&mut self, radix: u32) -> TokenKind {
+        let mut digits = String::new();
+        let mut error: Option<String> = None;
+
+        loop {
+            let ch = self.current_char as char;
+            let next_ch = self.look_next_byte() as char;
+            match ch {
+                '_' if next_ch.is_digit(radix) => {}
+                '_' => {
+                    error = error.or(Some(String::from(
+                        "Underscore in number literal must be followed by a digit",
+                    )));
+                }
+                _ if ch.is_digit(radix) => digits.push(ch),
+                // like the 2 of 0b102:
+                _ if ch.is_ascii_alphanumeric() => {
+                    error = error.or(Some(format!(
+                        "Invalid digit {} in base {} literal",
+                        ch, radix
+                    )));
+                }
+                _ => {
+                    break;
+                }
+            }
+            self.read_next();
+        }
+
+        if error.is_some() {
+            return TokenKind::Error(error.unwrap());
+        }
+
+        if digits.is_empty() {
+            return TokenKind::Error(format!("Base {} literal has no digits", radix));
+        }
+
+        return ProgramLexer::new_int_token(&digits, radix);
+    }
+
+    // ints beyond the i64 range are big integers:
+    fn new_int_token(digits: &str, radix: u32) -> TokenKind {
+        let parsed = i64::from_str_radix(digits, radix);
+        if parsed.is_err() {
+            let big = BigInt::parse_bytes(digits.as_bytes(), radix);
+            return TokenKind::BigInteger(big.unwrap());
         }
+        return TokenKind::Integer(parsed.unwrap());
+    }
+
+    // an operator followed by = is its compound assignment, like <<=:
+    fn find_compound(&mut self, op: SymbolKind, compound: SymbolKind) -> TokenKind {
+        if self.look_next_byte() == b'=' {
+            self.read_next();
+            return TokenKind::Operator(compound);
+        }
+
+        return TokenKind::Operator(op);
     }
 
     fn find_keyword_or_identifier(&mut self) -> TokenKind {
@@ -345,6 +562,13 @@ impl ProgramLexer {
                     self.read_next();
                     continue;
                 }
+                0x80..=0xff => match self.decode_current() {
+                    Ok((ch, width)) if ch.is_alphanumeric() => {
+                        self.skip_bytes(width);
+                        continue;
+                    }
+                    _ => break,
+                },
                 _ => {
                     break;
                 }
@@ -385,50 +609,262 @@ impl ProgramLexer {
             "assert" => TokenKind::Keyword(KeywordKind::KAssert),
             "thread" => TokenKind::Keyword(KeywordKind::KThread),
             "async" => TokenKind::Keyword(KeywordKind::KAsync),
+            "class" => TokenKind::Keyword(KeywordKind::KClass),
+            "struct" => TokenKind::Keyword(KeywordKind::KStruct),
+            "yield" => TokenKind::Keyword(KeywordKind::KYield),
+            "match" => TokenKind::Keyword(KeywordKind::KMatch),
             _ => TokenKind::Identifier(id_string),
         };
 
         return token;
     }
 
+    /*
+        Reads an escape sequence, the current character is the backslash.
+        Returns None for sequences that are not escapes, they are kept as
+        they are (like \d in a regex).
+    */
+    fn find_escape_sequence(&mut self) -> Result<Option<char>, String> {
+        let escaped = match self.look_next_byte() {
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'0' => '\0',
+            b'\\' => '\\',
+            b'"' => '"',
+            b'\'' => '\'',
+            b'u' => {
+                self.read_next();
+                return self.find_unicode_escape().map(Some);
+            }
+            _ => {
+                self.read_next();
+                return Ok(None);
+            }
+        };
+
+        self.skip_bytes(2);
+        return Ok(Some(escaped));
+    }
+
+    // reads \u{XXXX}, the current character is the u.
+    fn find_unicode_escape(&mut self) -> Result<char, String> {
+        if self.look_next_byte() != b'{' {
+            self.read_next();
+            return Err(String::from("Expected { after \\u in unicode escape"));
+        }
+
+        self.skip_bytes(2);
+
+        let mut digits = String::new();
+        while self.current_char.is_ascii_hexdigit() {
+            digits.push(self.current_char as char);
+            self.read_next();
+        }
+
+        if self.current_char != b'}' {
+            return Err(format!(
+                "Unterminated unicode escape \\u{{{}, expected }}",
+                digits
+            ));
+        }
+
+        self.read_next();
+
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(format!(
+                "Unicode escape \\u{{{}}} must have 1 to 6 hex digits",
+                digits
+            ));
+        }
+
+        let code_point = u32::from_str_radix(&digits, 16).unwrap();
+        match std::char::from_u32(code_point) {
+            Some(ch) => return Ok(ch),
+            None => {
+                return Err(format!(
+                    "Unicode escape \\u{{{}}} is not a valid character",
+                    digits
+                ))
+            }
+        }
+    }
+
     fn find_string_literal(&mut self) -> TokenKind {
         self.read_next();
 
-        let mut prev_char = self.current_char;
-        let start_pos = self.buffer.current_pos;
+        // on errors, the literal is still read till the end so that
+        // the lexer can continue after it.
+        let mut string_literal = String::new();
+        let mut error: Option<String> = None;
+
         loop {
             match self.current_char {
+                b'"' => break,
+                // If we have reached the end of the file, then the string literal is invalid.
+                EOF_BYTE => return TokenKind::Error(String::from("Unterminated string literal")),
+                b'\\' => match self.find_escape_sequence() {
+                    Ok(Some(ch)) => string_literal.push(ch),
+                    Ok(None) => string_literal.push('\\'),
+                    Err(reason) => {
+                        error = error.or(Some(reason));
+                    }
+                },
+                _ => match self.decode_current() {
+                    Ok((ch, width)) => {
+                        string_literal.push(ch);
+                        self.skip_bytes(width);
+                    }
+                    Err(reason) => {
+                        error = error.or(Some(reason));
+                        self.read_next();
+                    }
+                },
+            }
+        }
+
+        self.read_next();
+
+        if error.is_some() {
+            return TokenKind::Error(error.unwrap());
+        }
+
+        return TokenKind::Str(string_literal);
+    }
+
+    /*
+        Reads the source of a replacement field in an f-string, the current
+        character is the one after the opening brace. A colon outside of
+        brackets and nested strings starts the format specifier.
+    */
+    fn find_format_field(&mut self) -> Result<FStrPart, String> {
+        let mut source = vec![];
+        let mut spec: Option<Vec<u8>> = None;
+        let mut depth = 0;
+
+        loop {
+            let ch = self.current_char;
+            match ch {
+                EOF_BYTE => return Err(String::from("Unterminated f-string field, expected }")),
+                b'}' if depth == 0 => break,
+                _ if spec.is_some() => spec.as_mut().unwrap().push(ch),
+                b':' if depth == 0 => spec = Some(vec![]),
                 b'"' => {
-                    if prev_char == b'\\' {
-                        prev_char = self.current_char;
+                    // nested strings are copied as they are:
+                    source.push(ch);
+                    self.read_next();
+                    while self.current_char != b'"' {
+                        if self.current_char == EOF_BYTE {
+                            return Err(String::from("Unterminated string in f-string field"));
+                        }
+                        if self.current_char == b'\\' {
+                            source.push(self.current_char);
+                            self.read_next();
+                        }
+                        source.push(self.current_char);
                         self.read_next();
-                        continue;
                     }
+                    source.push(ch);
+                }
+                b'(' | b'[' | b'{' => {
+                    depth += 1;
+                    source.push(ch);
+                }
+                b')' | b']' | b'}' => {
+                    depth -= 1;
+                    source.push(ch);
+                }
+                _ => source.push(ch),
+            }
 
-                    break;
+            self.read_next();
+        }
+
+        let source = String::from_utf8_lossy(&source).trim().to_string();
+        if source.is_empty() {
+            return Err(String::from("Empty expression in f-string field"));
+        }
+
+        let spec = spec.map(|spec| String::from_utf8_lossy(&spec).to_string());
+        return Ok(FStrPart::Field(source, spec));
+    }
+
+    fn find_format_string_literal(&mut self) -> TokenKind {
+        // skip the f and the opening quote:
+        self.skip_bytes(2);
+
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut error: Option<String> = None;
+
+        loop {
+            let next_byte = self.look_next_byte();
+            match self.current_char {
+                b'"' => break,
+                EOF_BYTE => return TokenKind::Error(String::from("Unterminated f-string literal")),
+                b'{' if next_byte == b'{' => {
+                    text.push('{');
+                    self.skip_bytes(2);
                 }
-                // If we have reached the end of the file, then the string literal is invalid.
-                EOF_BYTE => return TokenKind::Invalid,
+                b'}' if next_byte == b'}' => {
+                    text.push('}');
+                    self.skip_bytes(2);
+                }
+                b'{' => {
+                    self.read_next();
+                    if !text.is_empty() {
+                        parts.push(FStrPart::Text(text));
+                        text = String::new();
+                    }
 
-                _ => {
-                    prev_char = self.current_char;
+                    match self.find_format_field() {
+                        Ok(field) => parts.push(field),
+                        Err(reason) => {
+                            if self.current_char == EOF_BYTE {
+                                return TokenKind::Error(reason);
+                            }
+                            error = error.or(Some(reason));
+                        }
+                    }
                     self.read_next();
-                    continue;
                 }
+                b'}' => {
+                    error = error.or(Some(String::from(
+                        "Single } in f-string, use }} for a brace",
+                    )));
+                    self.read_next();
+                }
+                b'\\' => match self.find_escape_sequence() {
+                    Ok(Some(ch)) => text.push(ch),
+                    Ok(None) => text.push('\\'),
+                    Err(reason) => {
+                        error = error.or(Some(reason));
+                    }
+                },
+                _ => match self.decode_current() {
+                    Ok((ch, width)) => {
+                        text.push(ch);
+                        self.skip_bytes(width);
+                    }
+                    Err(reason) => {
+                        error = error.or(Some(reason));
+                        self.read_next();
+                    }
+                },
             }
         }
 
-        let mut string_literal = self
-            .buffer
-            .get_as_string(start_pos, self.buffer.current_pos);
+        self.read_next();
 
-        // proput erly format string literals
-        string_literal = string_literal.replace("\\n", "\n");
-        string_literal = string_literal.replace("\\r", "\r");
+        if error.is_some() {
+            return TokenKind::Error(error.unwrap());
+        }
 
-        self.read_next();
+        if !text.is_empty() {
+            parts.push(FStrPart::Text(text));
+        }
 
-        return TokenKind::Str(string_literal);
+        return TokenKind::FStr(parts);
     }
 
     fn append_eof_newline(&mut self) {
@@ -438,20 +874,185 @@ impl ProgramLexer {
         }
     }
 
+    // leaves the cursor on the closing quote.
     fn find_char_literal(&mut self) -> TokenKind {
         self.read_next();
-        let ch_read = self.buffer.get_as_char(self.buffer.current_pos);
+
+        let ch_read = match self.current_char {
+            b'\'' | b'\n' | EOF_BYTE => {
+                return TokenKind::Error(String::from("Empty character literal"));
+            }
+            b'\\' => match self.find_escape_sequence() {
+                Ok(Some(ch)) => Ok(ch),
+                Ok(None) => Err(format!(
+                    "Unknown escape sequence \\{} in character literal",
+                    self.current_char as char
+                )),
+                Err(reason) => Err(reason),
+            },
+            _ => match self.decode_current() {
+                Ok((ch, width)) => {
+                    self.skip_bytes(width);
+                    Ok(ch)
+                }
+                Err(reason) => {
+                    self.read_next();
+                    Err(reason)
+                }
+            },
+        };
+
+        if self.current_char != b'\'' {
+            // skip the rest of the literal:
+            while self.current_char != b'\'' && self.current_char != b'\n' {
+                if self.current_char == EOF_BYTE {
+                    break;
+                }
+                self.read_next();
+            }
+
+            if ch_read.is_ok() {
+                return TokenKind::Error(String::from(
+                    "Character literal must contain a single character",
+                ));
+            }
+        }
+
+        match ch_read {
+            Ok(ch) => return TokenKind::Char(ch),
+            Err(reason) => return TokenKind::Error(reason),
+        }
+    }
+
+    // reads \xNN in a byte literal, the current character is the backslash.
+    fn find_hex_escape(&mut self) -> Result<u8, String> {
+        self.skip_bytes(2);
+
+        let mut digits = String::new();
+        while digits.len() < 2 && self.current_char.is_ascii_hexdigit() {
+            digits.push(self.current_char as char);
+            self.read_next();
+        }
+
+        if digits.len() != 2 {
+            return Err(format!("Hex escape \\x{} must have 2 hex digits", digits));
+        }
+
+        return Ok(u8::from_str_radix(&digits, 16).unwrap());
+    }
+
+    // b'A', leaves the cursor on the closing quote.
+    fn find_byte_literal(&mut self) -> TokenKind {
+        self.skip_bytes(2);
+
+        let next_byte = self.look_next_byte();
+        let byte_read = match self.current_char {
+            b'\'' | b'\n' | EOF_BYTE => {
+                return TokenKind::Error(String::from("Empty byte literal"));
+            }
+            b'\\' if next_byte == b'x' => self.find_hex_escape(),
+            b'\\' => match self.find_escape_sequence() {
+                Ok(Some(ch)) if ch.is_ascii() => Ok(ch as u8),
+                Ok(Some(_)) => Err(String::from(
+                    "Escape in byte literal must be an ASCII character",
+                )),
+                Ok(None) => Err(format!(
+                    "Unknown escape sequence \\{} in byte literal",
+                    self.current_char as char
+                )),
+                Err(reason) => Err(reason),
+            },
+            ch if ch.is_ascii() => {
+                self.read_next();
+                Ok(ch)
+            }
+            _ => {
+                match self.decode_current() {
+                    Ok((_, width)) => self.skip_bytes(width),
+                    Err(_) => self.read_next(),
+                }
+                Err(String::from(
+                    "Byte literal must be an ASCII character, use \\x for other bytes",
+                ))
+            }
+        };
+
+        if self.current_char != b'\'' {
+            // skip the rest of the literal:
+            while self.current_char != b'\'' && self.current_char != b'\n' {
+                if self.current_char == EOF_BYTE {
+                    break;
+                }
+                self.read_next();
+            }
+
+            if byte_read.is_ok() {
+                return TokenKind::Error(String::from("Byte literal must contain a single byte"));
+            }
+        }
+
+        match byte_read {
+            Ok(byte) => return TokenKind::Byte(byte),
+            Err(reason) => return TokenKind::Error(reason),
+        }
+    }
+
+    /*
+        Reads b"...", text is kept as its UTF-8 bytes and \xNN escapes
+        write any byte.
+    */
+    fn find_bytes_literal(&mut self) -> TokenKind {
+        self.skip_bytes(2);
+
+        let mut bytes = vec![];
+        let mut error: Option<String> = None;
+
+        loop {
+            let next_byte = self.look_next_byte();
+            match self.current_char {
+                b'"' => break,
+                EOF_BYTE => {
+                    return TokenKind::Error(String::from("Unterminated byte string literal"))
+                }
+                b'\\' if next_byte == b'x' => match self.find_hex_escape() {
+                    Ok(byte) => bytes.push(byte),
+                    Err(reason) => {
+                        error = error.or(Some(reason));
+                    }
+                },
+                b'\\' => match self.find_escape_sequence() {
+                    Ok(Some(ch)) => {
+                        let mut encoded = [0u8; 4];
+                        bytes.extend_from_slice(ch.encode_utf8(&mut encoded).as_bytes());
+                    }
+                    Ok(None) => bytes.push(b'\\'),
+                    Err(reason) => {
+                        error = error.or(Some(reason));
+                    }
+                },
+                byte => {
+                    bytes.push(byte);
+                    self.read_next();
+                }
+            }
+        }
+
         self.read_next();
-        return TokenKind::Char(ch_read);
+
+        if error.is_some() {
+            return TokenKind::Error(error.unwrap());
+        }
+
+        return TokenKind::Bytes(bytes);
     }
 
     pub fn next_lexed_token(&mut self) -> LexedToken {
-        let current_pos = self.buffer.current_pos;
         let token = self.next_token();
 
         LexedToken {
             token: token,
-            pos: current_pos,
+            pos: self.token_pos,
+            span: self.token_span,
         }
     }
 
@@ -461,15 +1062,8 @@ impl ProgramLexer {
 
         let mut incr_next_char = true;
 
-        loop {
-            match self.current_char {
-                b' ' | b'\t' => {
-                    self.read_next();
-                }
-                _ => {
-                    break;
-                }
-            }
+        while let b' ' | b'\t' = self.current_char {
+            self.read_next();
         }
 
         // handle comment:
@@ -487,11 +1081,15 @@ impl ProgramLexer {
             }
         }
 
+        self.token_pos = self.buffer.current_pos;
+        self.token_span = self.buffer.span;
+
         let token = match self.current_char {
             // basic arithmetic and comparision operator:
             b'+' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'+' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SIncr)
@@ -501,13 +1099,13 @@ impl ProgramLexer {
                         TokenKind::Operator(SymbolKind::SPlusEq)
                     }
                     _ => TokenKind::Operator(SymbolKind::SPlus),
-                };
-                combined_token
+                }
             }
 
             b'-' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'-' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SDecr)
@@ -517,75 +1115,93 @@ impl ProgramLexer {
                         TokenKind::Operator(SymbolKind::SMinusEq)
                     }
                     _ => TokenKind::Operator(SymbolKind::SMinus),
-                };
-                combined_token
+                }
             }
 
             b'*' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'=' => {
                         self.read_next();
-                        TokenKind::Operator(SymbolKind::SMinusEq)
+                        TokenKind::Operator(SymbolKind::SMulEq)
+                    }
+                    b'*' => {
+                        self.read_next();
+                        self.find_compound(SymbolKind::SPow, SymbolKind::SPowEq)
                     }
                     _ => TokenKind::Operator(SymbolKind::SMul),
-                };
-                combined_token
+                }
             }
 
             b'/' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'=' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SDivEq)
                     }
+                    b'/' => {
+                        self.read_next();
+                        self.find_compound(SymbolKind::SFloorDiv, SymbolKind::SFloorDivEq)
+                    }
                     _ => TokenKind::Operator(SymbolKind::SDiv),
-                };
-                combined_token
+                }
             }
 
+            b'^' => self.find_compound(SymbolKind::SXor, SymbolKind::SXorEq),
+
             b'%' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'=' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SModEq)
                     }
                     _ => TokenKind::Operator(SymbolKind::SMod),
-                };
-                combined_token
+                }
             }
 
             // comparision operators
             b'<' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'=' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SLte)
                     }
+                    b'<' => {
+                        self.read_next();
+                        self.find_compound(SymbolKind::SShl, SymbolKind::SShlEq)
+                    }
                     _ => TokenKind::Operator(SymbolKind::SLt),
-                };
-                combined_token
+                }
             }
 
             b'>' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'=' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SGte)
                     }
+                    b'>' => {
+                        self.read_next();
+                        self.find_compound(SymbolKind::SShr, SymbolKind::SShrEq)
+                    }
                     _ => TokenKind::Operator(SymbolKind::SGt),
-                };
-                combined_token
+                }
             }
 
             // Equality operators:
             b'=' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'=' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SEeq)
@@ -595,26 +1211,26 @@ impl ProgramLexer {
                         TokenKind::Operator(SymbolKind::SImpl)
                     }
                     _ => TokenKind::Operator(SymbolKind::SEq),
-                };
-                combined_token
+                }
             }
 
             b'!' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'=' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SNe)
                     }
                     _ => TokenKind::Operator(SymbolKind::SExcl),
-                };
-                combined_token
+                }
             }
 
             // logical operators
             b'&' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'&' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SLAnd)
@@ -624,13 +1240,13 @@ impl ProgramLexer {
                         TokenKind::Operator(SymbolKind::SAndEq)
                     }
                     _ => TokenKind::Operator(SymbolKind::SAnd),
-                };
-                combined_token
+                }
             }
 
             b'|' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b'|' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SLOr)
@@ -640,8 +1256,7 @@ impl ProgramLexer {
                         TokenKind::Operator(SymbolKind::SOrEq)
                     }
                     _ => TokenKind::Operator(SymbolKind::SOr),
-                };
-                combined_token
+                }
             }
 
             b'~' => TokenKind::Operator(SymbolKind::SNeg),
@@ -668,21 +1283,49 @@ impl ProgramLexer {
 
             b':' => {
                 let next_char = self.look_next_byte();
-                let combined_token = match next_char {
+
+                match next_char {
                     b':' => {
                         self.read_next();
                         TokenKind::Operator(SymbolKind::SResolve)
                     }
                     _ => TokenKind::Operator(SymbolKind::SColon),
-                };
-                combined_token
+                }
             }
 
-            b'.' => TokenKind::Operator(SymbolKind::SDot),
+            b'.' => {
+                let next_char = self.look_next_byte();
+                match next_char {
+                    b'.' => {
+                        self.read_next();
+                        match self.look_next_byte() {
+                            b'=' => {
+                                self.read_next();
+                                TokenKind::Operator(SymbolKind::SRangeIncl)
+                            }
+                            b'.' => {
+                                self.read_next();
+                                TokenKind::Operator(SymbolKind::SEllipsis)
+                            }
+                            _ => TokenKind::Operator(SymbolKind::SRange),
+                        }
+                    }
+                    _ => TokenKind::Operator(SymbolKind::SDot),
+                }
+            }
 
             b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
                 incr_next_char = false;
-                self.find_keyword_or_identifier()
+                if self.current_char == b'f' && self.look_next_byte() == b'"' {
+                    self.find_format_string_literal()
+                } else if self.current_char == b'b' && self.look_next_byte() == b'"' {
+                    self.find_bytes_literal()
+                } else if self.current_char == b'b' && self.look_next_byte() == b'\'' {
+                    incr_next_char = true;
+                    self.find_byte_literal()
+                } else {
+                    self.find_keyword_or_identifier()
+                }
             }
 
             b'0'..=b'9' => {
@@ -712,7 +1355,24 @@ impl ProgramLexer {
             }
 
             EOF_BYTE => TokenKind::EOF,
-            _ => TokenKind::Invalid,
+
+            // non ASCII characters are allowed in identifiers.
+            0x80..=0xff => match self.decode_current() {
+                Ok((ch, _)) if ch.is_alphabetic() => {
+                    incr_next_char = false;
+                    self.find_keyword_or_identifier()
+                }
+                Ok((ch, width)) => {
+                    self.skip_bytes(width - 1);
+                    TokenKind::Error(format!("Unexpected character '{}'", ch))
+                }
+                Err(reason) => TokenKind::Error(reason),
+            },
+
+            _ => TokenKind::Error(format!(
+                "Unexpected character '{}'",
+                self.current_char as char
+            )),
         };
 
         if incr_next_char {
@@ -730,7 +1390,7 @@ impl ProgramLexer {
         let mut l_token: LexedToken;
         loop {
             l_token = self.next_lexed_token();
-            (&mut f_handle)
+            f_handle
                 .write_fmt(format_args!("{:?} at {}\n", l_token.token, l_token.pos))
                 .expect("Failed to write token into the file");
 
@@ -741,6 +1401,7 @@ impl ProgramLexer {
     }
     pub fn reset(&mut self) {
         self.buffer.current_pos = 0;
+        self.buffer.span = Span { line: 1, col: 1 };
     }
 }
 
@@ -748,17 +1409,20 @@ pub struct LexerAPI {
     pub lexer: ProgramLexer,
     pub current_token: LexedToken,
     pub next_token: LexedToken,
+    // used in diagnostics, <input> if the source is not a file.
+    pub file_name: String,
 }
 
 impl LexerAPI {
     pub fn new_from_file(file_name: String) -> LexerAPI {
-        let mut lexer = ProgramLexer::new_from_file(file_name);
+        let mut lexer = ProgramLexer::new_from_file(file_name.clone());
         let tok1 = lexer.next_lexed_token().clone();
         let tok2 = lexer.next_lexed_token().clone();
         LexerAPI {
             lexer: lexer,
             current_token: tok1,
             next_token: tok2,
+            file_name: file_name,
         }
     }
 
@@ -770,6 +1434,7 @@ impl LexerAPI {
             lexer: lexer,
             current_token: tok1,
             next_token: tok2,
+            file_name: String::from("<input>"),
         }
     }
 
@@ -802,6 +1467,25 @@ impl LexerAPI {
         sym1 == (&sym2)
     }
 
+    // returns the given line of the source, lines start from 1.
+    pub fn get_source_line(&self, line: usize) -> Option<String> {
+        if line == 0 {
+            return None;
+        }
+
+        return self
+            .lexer
+            .buffer
+            .buffer
+            .split(|b| *b == b'\n')
+            .nth(line - 1)
+            .map(|l| {
+                String::from_utf8_lossy(l)
+                    .trim_end_matches('\r')
+                    .to_string()
+            });
+    }
+
     pub fn get_line_by_pos(&mut self, pos: usize) -> (String, usize, usize) {
         let mut back_iter = pos;
         let mut front_iter = pos;
@@ -1,3 +1,13 @@
+// explicit returns, is_err()/is_none() checks before unwrapping and VM state
+// passed as arguments are the style of this code base:
+#![allow(
+    clippy::needless_return,
+    clippy::unnecessary_unwrap,
+    clippy::question_mark,
+    clippy::redundant_field_names,
+    clippy::too_many_arguments
+)]
+
 pub mod api;
 pub mod compiler;
 pub mod config;
@@ -1,3 +1,5 @@
+use num_bigint::BigInt;
+
 use crate::lexer::*;
 use crate::parser::exp::InfixExpKind;
 use crate::parser::exp::PrefixExpKind;
@@ -21,6 +23,14 @@ pub struct FunctionType {
     pub parameters: Vec<ExpressionKind>,
     pub body: BlockStatement,
     pub return_type: Option<String>,
+    pub is_pure: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClassType {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: Vec<FunctionType>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -52,6 +62,12 @@ pub struct IdentifierType {
     pub t: Option<String>,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct UseType {
+    pub path: String,
+    pub alias: IdentifierType,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct LetType {
     pub identifier: IdentifierType,
@@ -70,6 +86,15 @@ pub struct IndexType {
     pub index: Box<ExpressionKind>,
 }
 
+// xs[start:stop:step], the missing parts are None.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SliceType {
+    pub expression_left: Box<ExpressionKind>,
+    pub start: Option<Box<ExpressionKind>>,
+    pub stop: Option<Box<ExpressionKind>>,
+    pub step: Option<Box<ExpressionKind>>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct LambdaExpType {
     pub parameters: Vec<ExpressionKind>,
@@ -106,6 +131,72 @@ pub struct ReturnType {
     pub expression: Option<ExpressionKind>,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct YieldType {
+    pub expression: Option<ExpressionKind>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PatternKind {
+    // _, matches anything
+    Wildcard,
+    // a name, matches anything and assigns it to the name
+    Binding(String),
+    Literal(ExpressionKind),
+    // 1..5 leaves out the end, 1..=5 includes it
+    Range {
+        start: ExpressionKind,
+        end: ExpressionKind,
+        inclusive: bool,
+    },
+    // int, string, ... match the values of that type
+    Type(String),
+    // [a, b] matches arrays of exactly that length
+    Array(Vec<PatternKind>),
+    // [a, ...rest] matches arrays at least that long, rest gets the other elements
+    ArrayRest(Vec<PatternKind>, String),
+    // {"key": p} matches hash tables that have the keys, a key can be a variable
+    HashTable(Vec<(ExpressionKind, PatternKind)>),
+    // p1 | p2
+    Or(Vec<PatternKind>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MatchArmBody {
+    Expression(ExpressionKind),
+    Block(BlockStatement),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchArm {
+    pub pattern: PatternKind,
+    pub guard: Option<ExpressionKind>,
+    pub body: MatchArmBody,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchType {
+    pub subject: Box<ExpressionKind>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum UnpackKind {
+    // a name takes the whole value, _ drops it
+    Name(String),
+    // [a, b] takes the elements of an array of exactly that length
+    Array(Vec<UnpackKind>),
+    // {name, age: years} takes the keys of a hash table or the fields of an instance
+    HashTable(Vec<(String, UnpackKind)>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DestructureType {
+    pub target: UnpackKind,
+    pub expression: ExpressionKind,
+    pub is_const: bool,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ThrowType {
     pub expression: Box<ExpressionKind>,
@@ -146,8 +237,12 @@ pub struct InfixType {
 #[derive(Debug, PartialEq, Clone)]
 pub enum LiteralKind {
     Int(i64),
+    // an int literal beyond the i64 range:
+    BigInt(BigInt),
     Float(f64),
     Char(char),
+    Byte(u8),
+    Bytes(Vec<u8>),
     Str(String),
     Bool(bool),
     Array(ArrayType),
@@ -166,6 +261,7 @@ pub enum ExpressionKind {
     Identifier(IdentifierType),
     Literal(LiteralKind),
     Index(IndexType),
+    Slice(SliceType),
     Call(CallType),
     Infix(InfixType),
     Prefix(PrefixType),
@@ -174,6 +270,7 @@ pub enum ExpressionKind {
     Boolean(bool),
     Shell(ShellType),
     Attribute(AttributeResolver),
+    Match(MatchType),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -183,11 +280,17 @@ pub enum StatementKind {
     Continue,
     Var(LetType),
     Const(ConstType),
+    Destructure(DestructureType),
     Return(ReturnType),
+    Yield(YieldType),
+    Match(MatchType),
     Throw(ThrowType),
+    Rethrow,
+    Use(UseType),
     Expression(ExpressionKind),
     TryCatch(TryCatchType),
     Function(FunctionType),
+    Class(ClassType),
     For(ForLoopType),
     While(WhileLoopType),
     Assert(AssertType),
@@ -198,7 +301,7 @@ pub enum StatementKind {
 #[derive(Debug, PartialEq, Clone)]
 pub struct BlockStatement {
     pub statements: Vec<StatementKind>,
-    pub pos: Vec<usize>,
+    pub pos: Vec<Span>,
 }
 
 pub type Program = BlockStatement;
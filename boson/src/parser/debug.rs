@@ -4,6 +4,7 @@ use std::fmt;
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParserErrorKind {
     UnexpectedToken,
+    InvalidToken,
 }
 
 #[derive(Debug, Clone)]
@@ -11,6 +12,7 @@ pub struct ParserError {
     pub message: String,
     pub t: ParserErrorKind,
     pub pos: usize,
+    pub span: Span,
     pub error_token: TokenKind,
 }
 
@@ -20,6 +22,7 @@ impl ParserError {
             message: msg,
             t: t,
             pos: token.pos,
+            span: token.span,
             error_token: token.token,
         }
     }
@@ -29,8 +32,8 @@ impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Type={:?} Position={} Reason={} Error Token={:?}",
-            self.t, self.pos, self.message, self.error_token
+            "Type={:?} Position={}:{} Reason={} Error Token={:?}",
+            self.t, self.span.line, self.span.col, self.message, self.error_token
         )
     }
 }
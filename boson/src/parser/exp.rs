@@ -7,6 +7,9 @@ pub enum PrefixExpKind {
     PreDecrement,
     Not,
     Neg,
+    Minus,
+    // ...xs, spreads an array into the arguments of a call
+    Spread,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -40,6 +43,16 @@ pub enum InfixExpKind {
     AndEq,
     OrEq,
     ModEq,
+    Xor,
+    ShiftLeft,
+    ShiftRight,
+    Pow,
+    FloorDiv,
+    XorEq,
+    ShiftLeftEq,
+    ShiftRightEq,
+    PowEq,
+    FloorDivEq,
 }
 
 #[derive(PartialEq, PartialOrd, Debug, Clone)]
@@ -49,12 +62,15 @@ pub enum ExpOrder {
     LogicalOr,
     LogicalAnd,
     BitwiseOr,
+    BitwiseXor,
     BitwiseAnd,
     Equality,
     LessGreater,
+    Shift,
     AddSub,
     DivMulMod,
     Unary,
+    Power,
     IncrDecr,
     Call,
     Index,
@@ -71,12 +87,18 @@ pub fn get_eval_order(sym: &SymbolKind) -> ExpOrder {
         | SymbolKind::SMinusEq
         | SymbolKind::SMulEq
         | SymbolKind::SDivEq
-        | SymbolKind::SModEq => return ExpOrder::Equals,
+        | SymbolKind::SModEq
+        | SymbolKind::SXorEq
+        | SymbolKind::SShlEq
+        | SymbolKind::SShrEq
+        | SymbolKind::SPowEq
+        | SymbolKind::SFloorDivEq => return ExpOrder::Equals,
 
         SymbolKind::SLOr => return ExpOrder::LogicalOr,
         SymbolKind::SLAnd => return ExpOrder::LogicalAnd,
 
         SymbolKind::SOr => return ExpOrder::BitwiseOr,
+        SymbolKind::SXor => return ExpOrder::BitwiseXor,
         SymbolKind::SAnd => return ExpOrder::BitwiseAnd,
 
         SymbolKind::SEeq | SymbolKind::SNe => return ExpOrder::Equality,
@@ -85,9 +107,16 @@ pub fn get_eval_order(sym: &SymbolKind) -> ExpOrder {
             return ExpOrder::LessGreater
         }
 
+        SymbolKind::SShl | SymbolKind::SShr => return ExpOrder::Shift,
+
         SymbolKind::SPlus | SymbolKind::SMinus => return ExpOrder::AddSub,
 
-        SymbolKind::SMul | SymbolKind::SDiv | SymbolKind::SMod => return ExpOrder::DivMulMod,
+        SymbolKind::SMul | SymbolKind::SDiv | SymbolKind::SMod | SymbolKind::SFloorDiv => {
+            return ExpOrder::DivMulMod
+        }
+
+        // binds tighter than -, -2 ** 2 is -4:
+        SymbolKind::SPow => return ExpOrder::Power,
 
         SymbolKind::SNeg | SymbolKind::SExcl => return ExpOrder::Unary,
 
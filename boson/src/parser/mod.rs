@@ -2,6 +2,10 @@ pub mod ast;
 pub mod debug;
 pub mod exp;
 
+use num_traits::ToPrimitive;
+
+use crate::compiler::spans::SourceLocation;
+use crate::lexer::FStrPart;
 use crate::lexer::KeywordKind;
 use crate::lexer::LexerAPI;
 use crate::lexer::SymbolKind;
@@ -16,6 +20,27 @@ use exp::ExpOrder;
 use exp::InfixExpKind;
 use exp::SuffixExpKind;
 
+// names that match values by type in a pattern, the names given by type_of().
+const PATTERN_TYPES: &[&str] = &[
+    "int",
+    "float",
+    "string",
+    "char",
+    "bool",
+    "raw",
+    "array",
+    "bytes",
+    "hashmap",
+    "iter",
+    "exception",
+    "func",
+    "module",
+    "channel",
+    "mutex",
+    "atomic",
+    "class",
+];
+
 pub struct Parser {
     pub lexer: LexerAPI,
     pub errors: ParserErrors,
@@ -68,10 +93,12 @@ impl Parser {
         self.lexer.iterate();
 
         // parse the first statement:
+        let span = self.lexer.get_current_token().span;
         match self.parse_statement() {
             Ok(stmt) => {
                 if !self.is_empty_statement(&stmt) {
                     block_statement.statements.push(stmt);
+                    block_statement.pos.push(span);
                 }
             }
             Err(error) => return Err(error),
@@ -86,10 +113,12 @@ impl Parser {
                 continue;
             }
 
+            let span = self.lexer.get_current_token().span;
             match self.parse_statement() {
                 Ok(stmt) => {
                     if !self.is_empty_statement(&stmt) {
                         block_statement.statements.push(stmt);
+                        block_statement.pos.push(span);
                     }
                     self.lexer.iterate();
                 }
@@ -196,6 +225,7 @@ impl Parser {
                                                 parameters: expr_list,
                                                 body: block,
                                                 return_type: None,
+                                                is_pure: false,
                                             },
                                         ));
                                     }
@@ -215,6 +245,125 @@ impl Parser {
         }
     }
 
+    fn parse_pure_function_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
+        if !self.next_keyword_is(KeywordKind::KFunc) {
+            return Err(self.new_invalid_token_err(String::from("Expected func after pure")));
+        }
+
+        self.lexer.iterate();
+        let func_result = self.parse_function_statement();
+        if func_result.is_err() {
+            return func_result;
+        }
+
+        match func_result.unwrap() {
+            ast::StatementKind::Function(mut func) => {
+                func.is_pure = true;
+                return Ok(ast::StatementKind::Function(func));
+            }
+            stmt => return Ok(stmt),
+        }
+    }
+
+    /*
+        class Point {
+            x, y;
+            func move(self, dx, dy) { ... }
+        }
+        Fields are comma separated names ending with a ;, methods are
+        regular functions taking the instance as their first parameter.
+    */
+    fn parse_class_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
+        self.lexer.iterate();
+
+        let id_result = self.get_identifier();
+        if id_result.is_err() {
+            return Err(
+                self.new_invalid_token_err(String::from("Expected identifier after class keyword"))
+            );
+        }
+
+        let name = id_result.unwrap();
+        if !self.next_symbol_is(SymbolKind::SLBrace) {
+            return Err(self.new_invalid_token_err(String::from("Expected { after class name")));
+        }
+
+        self.lexer.iterate();
+        self.lexer.iterate();
+
+        let mut fields: Vec<String> = vec![];
+        let mut methods: Vec<ast::FunctionType> = vec![];
+
+        while !self.current_symbol_is(SymbolKind::SRBrace) {
+            let current_token = self.lexer.get_current_token();
+            match current_token.token {
+                TokenKind::Operator(SymbolKind::SSemiColon) | TokenKind::Empty => {}
+                TokenKind::Identifier(field) => {
+                    if fields.contains(&field) {
+                        return Err(self.new_invalid_token_err(format!(
+                            "Duplicate field {} in class {}",
+                            field, name
+                        )));
+                    }
+
+                    fields.push(field);
+                    if !self.next_symbol_is(SymbolKind::SComma)
+                        && !self.next_symbol_is(SymbolKind::SSemiColon)
+                    {
+                        return Err(self.new_invalid_token_err(String::from(
+                            "Expected , or ; after field name",
+                        )));
+                    }
+
+                    self.lexer.iterate();
+                }
+                TokenKind::Keyword(KeywordKind::KFunc) => {
+                    let func_result = self.parse_function_statement();
+                    if func_result.is_err() {
+                        return Err(func_result.unwrap_err());
+                    }
+
+                    if let ast::StatementKind::Function(method) = func_result.unwrap() {
+                        if method.parameters.is_empty() {
+                            return Err(self.new_invalid_token_err(format!(
+                                "Method {} of class {} must take the instance as its first parameter",
+                                method.name, name
+                            )));
+                        }
+
+                        if methods.iter().any(|m| m.name == method.name) {
+                            return Err(self.new_invalid_token_err(format!(
+                                "Duplicate method {} in class {}",
+                                method.name, name
+                            )));
+                        }
+
+                        methods.push(method);
+                    }
+                }
+                TokenKind::EOF => {
+                    return Err(self.new_invalid_token_err(format!(
+                        "Expected }} at the end of class {}",
+                        name
+                    )));
+                }
+                _ => {
+                    return Err(self.new_invalid_token_err(String::from(
+                        "Expected a field name or a method in class body",
+                    )));
+                }
+            }
+
+            self.lexer.iterate();
+        }
+
+        return Ok(ast::StatementKind::Class(ast::ClassType {
+            name: name,
+            fields: fields,
+            methods: methods,
+        }));
+    }
+
     fn parse_if_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
         self.lexer.iterate();
 
@@ -289,6 +438,19 @@ impl Parser {
     }
 
     fn new_invalid_token_err(&mut self, msg: String) -> ParserError {
+        // the parser fails at, or right before, a token the lexer could not
+        // read, the reason given by the lexer is the actual error.
+        let (current_token, next_token) = self.lexer.get_tokens();
+        for token in [current_token, next_token].iter() {
+            if let TokenKind::Error(reason) = &token.token {
+                return ParserError::new(
+                    ParserErrorKind::InvalidToken,
+                    reason.clone(),
+                    token.clone(),
+                );
+            }
+        }
+
         ParserError::new(
             ParserErrorKind::UnexpectedToken,
             msg,
@@ -296,9 +458,130 @@ impl Parser {
         )
     }
 
+    // target of a destructuring declaration, the current token is its start.
+    fn parse_unpack_target(&mut self) -> Result<ast::UnpackKind, ParserError> {
+        let current_token = self.lexer.get_current_token();
+        match current_token.token {
+            TokenKind::Identifier(name) => return Ok(ast::UnpackKind::Name(name)),
+            TokenKind::Operator(SymbolKind::SLBox) => {
+                let mut elements = vec![];
+                self.skip_empty_tokens();
+
+                while !self.next_symbol_is(SymbolKind::SRBox) {
+                    self.lexer.iterate();
+                    let target = self.parse_unpack_target();
+                    if target.is_err() {
+                        return Err(target.unwrap_err());
+                    }
+                    elements.push(target.unwrap());
+
+                    self.skip_empty_tokens();
+                    if !self.next_symbol_is(SymbolKind::SComma) {
+                        break;
+                    }
+                    self.lexer.iterate();
+                    self.skip_empty_tokens();
+                }
+
+                if !self.next_symbol_is(SymbolKind::SRBox) {
+                    return Err(self.new_invalid_token_err(String::from(
+                        "Expected ] after the names to destructure",
+                    )));
+                }
+
+                self.lexer.iterate();
+                return Ok(ast::UnpackKind::Array(elements));
+            }
+            TokenKind::Operator(SymbolKind::SLBrace) => {
+                let mut pairs = vec![];
+                self.skip_empty_tokens();
+
+                while !self.next_symbol_is(SymbolKind::SRBrace) {
+                    self.lexer.iterate();
+                    let key = match self.lexer.get_current_token().token {
+                        TokenKind::Identifier(name) => name,
+                        TokenKind::Str(name) => name,
+                        _ => {
+                            return Err(self.new_invalid_token_err(String::from(
+                                "Expected a key name to destructure",
+                            )))
+                        }
+                    };
+
+                    // {name} is short for {name: name}:
+                    let target = if self.next_symbol_is(SymbolKind::SColon) {
+                        self.lexer.iterate();
+                        self.lexer.iterate();
+                        self.parse_unpack_target()
+                    } else {
+                        Ok(ast::UnpackKind::Name(key.clone()))
+                    };
+
+                    if target.is_err() {
+                        return Err(target.unwrap_err());
+                    }
+                    pairs.push((key, target.unwrap()));
+
+                    self.skip_empty_tokens();
+                    if !self.next_symbol_is(SymbolKind::SComma) {
+                        break;
+                    }
+                    self.lexer.iterate();
+                    self.skip_empty_tokens();
+                }
+
+                if !self.next_symbol_is(SymbolKind::SRBrace) {
+                    return Err(self.new_invalid_token_err(String::from(
+                        "Expected } after the keys to destructure",
+                    )));
+                }
+
+                self.lexer.iterate();
+                return Ok(ast::UnpackKind::HashTable(pairs));
+            }
+            _ => {
+                return Err(self
+                    .new_invalid_token_err(String::from("Expected a name, [ or { to destructure")))
+            }
+        }
+    }
+
+    fn parse_destructure(&mut self, is_const: bool) -> Result<ast::StatementKind, ParserError> {
+        let target = self.parse_unpack_target();
+        if target.is_err() {
+            return Err(target.unwrap_err());
+        }
+
+        if !self.next_symbol_is(SymbolKind::SEq) {
+            return Err(self
+                .new_invalid_token_err(String::from("Expected = after the names to destructure")));
+        }
+
+        self.lexer.iterate();
+        self.lexer.iterate();
+        let expression = self.parse_expression(ExpOrder::Zero);
+        if expression.is_err() {
+            return Err(expression.unwrap_err());
+        }
+
+        return Ok(ast::StatementKind::Destructure(ast::DestructureType {
+            target: target.unwrap(),
+            expression: expression.unwrap(),
+            is_const: is_const,
+        }));
+    }
+
     fn parse_var_or_const(&mut self, is_const: bool) -> Result<ast::StatementKind, ParserError> {
         self.lexer.iterate();
 
+        // var [a, b] = ... and var {a, b} = ... destructure the value:
+        match self.lexer.get_current_token().token {
+            TokenKind::Operator(SymbolKind::SLBox) | TokenKind::Operator(SymbolKind::SLBrace) => {
+                return self.parse_destructure(is_const);
+            }
+            _ => {}
+        }
+
         let id_name = self.get_identifier();
         let stmt_result = match id_name {
             Err(error) => Err(error),
@@ -332,7 +615,7 @@ impl Parser {
                         _ => Err(self.new_invalid_token_err(String::from("Expected ="))),
                     };
 
-                    let let_const_stmt = match expr_result {
+                    match expr_result {
                         Ok(expr) => {
                             if is_const {
                                 Ok(ast::StatementKind::Const(ast::ConstType {
@@ -347,9 +630,7 @@ impl Parser {
                             }
                         }
                         Err(error) => Err(error),
-                    };
-
-                    let_const_stmt
+                    }
                 }
             }
         };
@@ -359,33 +640,30 @@ impl Parser {
 
     fn next_keyword_is(&mut self, compare: KeywordKind) -> bool {
         let next_token = self.lexer.get_next_token();
-        let result = match next_token.token {
+
+        match next_token.token {
             TokenKind::Keyword(kw) => self.lexer.keywords_are_equal(&kw, compare),
             _ => false,
-        };
-
-        result
+        }
     }
 
     fn next_symbol_is(&mut self, compare: SymbolKind) -> bool {
         let next_token = self.lexer.get_next_token();
-        let result = match next_token.token {
+
+        match next_token.token {
             TokenKind::Operator(op) => self.lexer.symbols_are_equal(&op, compare),
             _ => false,
-        };
-
-        result
+        }
     }
 
     #[allow(dead_code)]
     fn current_symbol_is(&mut self, compare: SymbolKind) -> bool {
         let current_token = self.lexer.get_current_token();
-        let result = match current_token.token {
+
+        match current_token.token {
             TokenKind::Operator(op) => self.lexer.symbols_are_equal(&op, compare),
             _ => false,
-        };
-
-        result
+        }
     }
 
     fn parse_list_expr(&mut self) -> Result<Vec<ast::ExpressionKind>, ParserError> {
@@ -531,6 +809,393 @@ impl Parser {
         }
     }
 
+    fn skip_empty_tokens(&mut self) {
+        while self.lexer.get_next_token().token == TokenKind::Empty {
+            self.lexer.iterate();
+        }
+    }
+
+    // literal of a pattern, the current token is its start.
+    fn parse_pattern_literal(&mut self) -> Result<ast::ExpressionKind, ParserError> {
+        let current_token = self.lexer.get_current_token();
+        match current_token.token {
+            TokenKind::Integer(_) | TokenKind::BigInteger(_) => {
+                return self.parse_integer_expression()
+            }
+            TokenKind::Float(_) => return self.parse_floating_expression(),
+            TokenKind::Char(_) => return self.parse_char_expression(),
+            TokenKind::Byte(_) => return self.parse_byte_expression(),
+            TokenKind::Str(_) => return self.parse_string_expression(),
+            TokenKind::Keyword(KeywordKind::KTrue) => {
+                return Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Bool(true)))
+            }
+            TokenKind::Keyword(KeywordKind::KFalse) => {
+                return Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Bool(false)))
+            }
+            TokenKind::Keyword(KeywordKind::KNone) => return Ok(ast::ExpressionKind::Noval),
+            TokenKind::Operator(SymbolKind::SMinus) => {
+                self.lexer.iterate();
+                match self.lexer.get_current_token().token {
+                    TokenKind::Integer(num) => {
+                        return Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Int(-num)))
+                    }
+                    // -9223372036854775808 is the smallest int:
+                    TokenKind::BigInteger(num) => {
+                        let literal = match (-&num).to_i64() {
+                            Some(int) => ast::LiteralKind::Int(int),
+                            None => ast::LiteralKind::BigInt(-num),
+                        };
+                        return Ok(ast::ExpressionKind::Literal(literal));
+                    }
+                    TokenKind::Float(num) => {
+                        return Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Float(-num)))
+                    }
+                    _ => {
+                        return Err(
+                            self.new_invalid_token_err(String::from("Expected a number after -"))
+                        )
+                    }
+                }
+            }
+            _ => return Err(self.new_invalid_token_err(String::from("Invalid pattern"))),
+        }
+    }
+
+    fn parse_array_pattern(&mut self) -> Result<ast::PatternKind, ParserError> {
+        let mut elements = vec![];
+        let mut rest = None;
+        self.skip_empty_tokens();
+
+        while !self.next_symbol_is(SymbolKind::SRBox) {
+            self.lexer.iterate();
+            // ...name takes the remaining elements, it has to be the last one:
+            if self.current_symbol_is(SymbolKind::SEllipsis) {
+                self.lexer.iterate();
+                match self.lexer.get_current_token().token {
+                    TokenKind::Identifier(name) => rest = Some(name),
+                    _ => {
+                        return Err(self.new_invalid_token_err(String::from(
+                            "Expected a name after ... in an array pattern",
+                        )))
+                    }
+                }
+
+                self.skip_empty_tokens();
+                if !self.next_symbol_is(SymbolKind::SRBox) {
+                    return Err(self.new_invalid_token_err(String::from(
+                        "...rest must be the last element of an array pattern",
+                    )));
+                }
+                break;
+            }
+
+            let pattern = self.parse_pattern();
+            if pattern.is_err() {
+                return Err(pattern.unwrap_err());
+            }
+            elements.push(pattern.unwrap());
+
+            self.skip_empty_tokens();
+            if !self.next_symbol_is(SymbolKind::SComma) {
+                break;
+            }
+            self.lexer.iterate();
+            self.skip_empty_tokens();
+        }
+
+        if !self.next_symbol_is(SymbolKind::SRBox) {
+            return Err(self.new_invalid_token_err(String::from("Expected ] after array pattern")));
+        }
+
+        self.lexer.iterate();
+        if rest.is_some() {
+            return Ok(ast::PatternKind::ArrayRest(elements, rest.unwrap()));
+        }
+
+        return Ok(ast::PatternKind::Array(elements));
+    }
+
+    fn parse_hash_pattern(&mut self) -> Result<ast::PatternKind, ParserError> {
+        let mut pairs = vec![];
+        self.skip_empty_tokens();
+
+        while !self.next_symbol_is(SymbolKind::SRBrace) {
+            self.lexer.iterate();
+            // a name as the key is the value of that variable:
+            let key = match self.lexer.get_current_token().token {
+                TokenKind::Identifier(name) => {
+                    Ok(ast::ExpressionKind::Identifier(ast::IdentifierType {
+                        name: name,
+                        t: None,
+                    }))
+                }
+                _ => self.parse_pattern_literal(),
+            };
+            if key.is_err() {
+                return Err(key.unwrap_err());
+            }
+
+            if !self.next_symbol_is(SymbolKind::SColon) {
+                return Err(self.new_invalid_token_err(String::from(
+                    "Expected : after the key of a hash pattern",
+                )));
+            }
+
+            self.lexer.iterate();
+            self.lexer.iterate();
+            let pattern = self.parse_pattern();
+            if pattern.is_err() {
+                return Err(pattern.unwrap_err());
+            }
+            pairs.push((key.unwrap(), pattern.unwrap()));
+
+            self.skip_empty_tokens();
+            if !self.next_symbol_is(SymbolKind::SComma) {
+                break;
+            }
+            self.lexer.iterate();
+            self.skip_empty_tokens();
+        }
+
+        if !self.next_symbol_is(SymbolKind::SRBrace) {
+            return Err(self.new_invalid_token_err(String::from("Expected } after hash pattern")));
+        }
+
+        self.lexer.iterate();
+        return Ok(ast::PatternKind::HashTable(pairs));
+    }
+
+    fn parse_single_pattern(&mut self) -> Result<ast::PatternKind, ParserError> {
+        let current_token = self.lexer.get_current_token();
+        match current_token.token {
+            TokenKind::Operator(SymbolKind::SLBox) => return self.parse_array_pattern(),
+            TokenKind::Operator(SymbolKind::SLBrace) => return self.parse_hash_pattern(),
+            TokenKind::Identifier(name) => {
+                if name == "_" {
+                    return Ok(ast::PatternKind::Wildcard);
+                }
+
+                if PATTERN_TYPES.contains(&name.as_str()) {
+                    return Ok(ast::PatternKind::Type(name));
+                }
+
+                return Ok(ast::PatternKind::Binding(name));
+            }
+            _ => {}
+        }
+
+        let literal = self.parse_pattern_literal();
+        if literal.is_err() {
+            return Err(literal.unwrap_err());
+        }
+
+        let inclusive = self.next_symbol_is(SymbolKind::SRangeIncl);
+        if !inclusive && !self.next_symbol_is(SymbolKind::SRange) {
+            return Ok(ast::PatternKind::Literal(literal.unwrap()));
+        }
+
+        self.lexer.iterate();
+        self.lexer.iterate();
+        let end = self.parse_pattern_literal();
+        if end.is_err() {
+            return Err(end.unwrap_err());
+        }
+
+        return Ok(ast::PatternKind::Range {
+            start: literal.unwrap(),
+            end: end.unwrap(),
+            inclusive: inclusive,
+        });
+    }
+
+    // p1 | p2 | ..., the current token is the start of the pattern.
+    fn parse_pattern(&mut self) -> Result<ast::PatternKind, ParserError> {
+        let mut alternatives = vec![];
+        loop {
+            let pattern = self.parse_single_pattern();
+            if pattern.is_err() {
+                return Err(pattern.unwrap_err());
+            }
+            alternatives.push(pattern.unwrap());
+
+            if !self.next_symbol_is(SymbolKind::SOr) {
+                break;
+            }
+
+            self.lexer.iterate();
+            self.lexer.iterate();
+        }
+
+        if alternatives.len() == 1 {
+            return Ok(alternatives.pop().unwrap());
+        }
+
+        return Ok(ast::PatternKind::Or(alternatives));
+    }
+
+    fn parse_match_arm(&mut self) -> Result<ast::MatchArm, ParserError> {
+        let pattern = self.parse_pattern();
+        if pattern.is_err() {
+            return Err(pattern.unwrap_err());
+        }
+
+        let mut guard = None;
+        if self.next_keyword_is(KeywordKind::KIf) {
+            self.lexer.iterate();
+            self.lexer.iterate();
+            let guard_result = self.parse_expression(ExpOrder::Zero);
+            if guard_result.is_err() {
+                return Err(guard_result.unwrap_err());
+            }
+            guard = Some(guard_result.unwrap());
+        }
+
+        if !self.next_symbol_is(SymbolKind::SImpl) {
+            return Err(self.new_invalid_token_err(String::from("Expected => after the pattern")));
+        }
+
+        self.lexer.iterate();
+        let body = if self.next_symbol_is(SymbolKind::SLBrace) {
+            let block = self.parse_block_statement();
+            if block.is_err() {
+                return Err(block.unwrap_err());
+            }
+            ast::MatchArmBody::Block(block.unwrap())
+        } else if self.next_is_arm_statement() {
+            // return, throw, break, continue and rethrow form a block of one statement:
+            self.lexer.iterate();
+            let span = self.lexer.get_current_token().span;
+            let stmt = self.parse_arm_statement();
+            if stmt.is_err() {
+                return Err(stmt.unwrap_err());
+            }
+            ast::MatchArmBody::Block(ast::BlockStatement {
+                statements: vec![stmt.unwrap()],
+                pos: vec![span],
+            })
+        } else {
+            self.lexer.iterate();
+            let exp_result = self.parse_expression(ExpOrder::Zero);
+            if exp_result.is_err() {
+                return Err(exp_result.unwrap_err());
+            }
+            ast::MatchArmBody::Expression(exp_result.unwrap())
+        };
+
+        return Ok(ast::MatchArm {
+            pattern: pattern.unwrap(),
+            guard: guard,
+            body: body,
+        });
+    }
+
+    fn next_is_arm_statement(&mut self) -> bool {
+        return self.next_keyword_is(KeywordKind::KReturn)
+            || self.next_keyword_is(KeywordKind::KThrow)
+            || self.next_keyword_is(KeywordKind::KBreak)
+            || self.next_keyword_is(KeywordKind::KContinue)
+            || self.next_keyword_is(KeywordKind::KRethrow);
+    }
+
+    // the statement ends with the arm, the , or ; after it is not needed:
+    fn parse_arm_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
+        let arm_ends = self.next_symbol_is(SymbolKind::SComma)
+            || self.next_symbol_is(SymbolKind::SSemiColon)
+            || self.next_symbol_is(SymbolKind::SRBrace)
+            || self.lexer.get_next_token().token == TokenKind::Empty;
+
+        match self.lexer.get_current_token().token {
+            TokenKind::Keyword(KeywordKind::KBreak) => return Ok(ast::StatementKind::Break),
+            TokenKind::Keyword(KeywordKind::KContinue) => return Ok(ast::StatementKind::Continue),
+            TokenKind::Keyword(KeywordKind::KRethrow) => return Ok(ast::StatementKind::Rethrow),
+            TokenKind::Keyword(KeywordKind::KReturn) if arm_ends => {
+                return Ok(ast::StatementKind::Return(ast::ReturnType {
+                    expression: None,
+                }))
+            }
+            _ => return self.parse_statement(),
+        }
+    }
+
+    // match value { pattern [if guard] => body, ... }, the current token is match.
+    fn parse_match(&mut self) -> Result<ast::MatchType, ParserError> {
+        self.lexer.iterate();
+        let subject = self.parse_expression(ExpOrder::Zero);
+        if subject.is_err() {
+            return Err(subject.unwrap_err());
+        }
+
+        if !self.next_symbol_is(SymbolKind::SLBrace) {
+            return Err(self.new_invalid_token_err(String::from("Expected { after match value")));
+        }
+
+        self.lexer.iterate();
+        self.skip_empty_tokens();
+
+        let mut arms = vec![];
+        while !self.next_symbol_is(SymbolKind::SRBrace) {
+            self.lexer.iterate();
+            let arm = self.parse_match_arm();
+            if arm.is_err() {
+                return Err(arm.unwrap_err());
+            }
+            arms.push(arm.unwrap());
+
+            if self.next_symbol_is(SymbolKind::SComma)
+                || self.next_symbol_is(SymbolKind::SSemiColon)
+            {
+                self.lexer.iterate();
+            }
+            self.skip_empty_tokens();
+
+            if self.lexer.get_next_token().token == TokenKind::EOF {
+                return Err(self.new_invalid_token_err(String::from("Expected } after match arms")));
+            }
+        }
+
+        self.lexer.iterate();
+        return Ok(ast::MatchType {
+            subject: Box::new(subject.unwrap()),
+            arms: arms,
+        });
+    }
+
+    fn parse_match_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
+        let match_result = self.parse_match();
+        if match_result.is_err() {
+            return Err(match_result.unwrap_err());
+        }
+
+        return Ok(ast::StatementKind::Match(match_result.unwrap()));
+    }
+
+    fn parse_match_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
+        let match_result = self.parse_match();
+        if match_result.is_err() {
+            return Err(match_result.unwrap_err());
+        }
+
+        return Ok(ast::ExpressionKind::Match(match_result.unwrap()));
+    }
+
+    fn parse_yield_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
+        if self.is_terminated() {
+            return Ok(ast::StatementKind::Yield(ast::YieldType {
+                expression: None,
+            }));
+        }
+
+        self.lexer.iterate();
+        match self.parse_expression(ExpOrder::Zero) {
+            Ok(expr) => {
+                return Ok(ast::StatementKind::Yield(ast::YieldType {
+                    expression: Some(expr),
+                }))
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
     fn parse_hash_literal(&mut self) -> Result<ast::ExpressionKind, ParserError> {
         let mut h_pairs = vec![];
 
@@ -606,14 +1271,14 @@ impl Parser {
 
     fn parse_integer_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
         let current_token = self.lexer.get_current_token();
-        let int_literal = match current_token.token {
-            TokenKind::Integer(num) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Int(
-                num.clone(),
-            ))),
-            _ => Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
-        };
 
-        int_literal
+        match current_token.token {
+            TokenKind::Integer(num) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Int(num))),
+            TokenKind::BigInteger(num) => Ok(ast::ExpressionKind::Literal(
+                ast::LiteralKind::BigInt(num.clone()),
+            )),
+            _ => Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
+        }
     }
 
     fn parse_prefix_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
@@ -625,13 +1290,21 @@ impl Parser {
                     SymbolKind::SIncr => exp::PrefixExpKind::PreIncrement,
                     SymbolKind::SDecr => exp::PrefixExpKind::PreDecrement,
                     SymbolKind::SNeg => exp::PrefixExpKind::Neg,
+                    SymbolKind::SMinus => exp::PrefixExpKind::Minus,
+                    SymbolKind::SEllipsis => exp::PrefixExpKind::Spread,
                     _ => {
                         return Err(self.new_invalid_token_err(format!("Invalid prefix {:?}", sym)))
                     }
                 };
 
+                // -a + b negates a only:
+                let order = match matched_prefix {
+                    exp::PrefixExpKind::Minus => ExpOrder::Unary,
+                    _ => ExpOrder::Zero,
+                };
+
                 self.lexer.iterate();
-                let exp_result = self.parse_expression(ExpOrder::Zero);
+                let exp_result = self.parse_expression(order);
                 if exp_result.is_err() {
                     return Err(exp_result.unwrap_err());
                 }
@@ -647,38 +1320,133 @@ impl Parser {
 
     fn parse_floating_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
         let current_token = self.lexer.get_current_token();
-        let float_literal = match current_token.token {
-            TokenKind::Float(num) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Float(
-                num.clone(),
-            ))),
-            _ => Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
-        };
 
-        float_literal
+        match current_token.token {
+            TokenKind::Float(num) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Float(num))),
+            _ => Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
+        }
     }
 
     fn parse_char_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
         let current_token = self.lexer.get_current_token();
-        let char_literal = match current_token.token {
-            TokenKind::Char(ch) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Char(
-                ch.clone(),
-            ))),
+
+        match current_token.token {
+            TokenKind::Char(ch) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Char(ch))),
             _ => Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
-        };
+        }
+    }
+
+    fn parse_byte_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
+        let current_token = self.lexer.get_current_token();
 
-        char_literal
+        match current_token.token {
+            TokenKind::Byte(byte) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Byte(byte))),
+            TokenKind::Bytes(bytes) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Bytes(
+                bytes.clone(),
+            ))),
+            _ => Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
+        }
     }
 
     fn parse_string_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
         let current_token = self.lexer.get_current_token();
-        let string_literal = match current_token.token {
+
+        match current_token.token {
             TokenKind::Str(str) => Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Str(
                 str.clone().replace("\\\\", "\\"),
             ))),
             _ => Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
+        }
+    }
+
+    // parses the expression of an f-string field with a parser of its own.
+    fn parse_format_field(&mut self, source: &String) -> Result<ast::ExpressionKind, ParserError> {
+        let lexer = LexerAPI::new_from_buffer(source.as_bytes().to_vec());
+        let mut field_parser = Parser::new_from_lexer(lexer);
+
+        let exp_result = field_parser.parse_expression(ExpOrder::Zero);
+        if exp_result.is_err() {
+            let reason = exp_result.unwrap_err().message;
+            return Err(self.new_invalid_token_err(format!(
+                "Invalid expression {} in f-string: {}",
+                source, reason
+            )));
+        }
+
+        if field_parser.lexer.get_next_token().token != TokenKind::EOF {
+            return Err(
+                self.new_invalid_token_err(format!("Invalid expression {} in f-string", source))
+            );
+        }
+
+        return Ok(exp_result.unwrap());
+    }
+
+    fn new_builtin_call(name: &str, arguments: Vec<ast::ExpressionKind>) -> ast::ExpressionKind {
+        return ast::ExpressionKind::Call(ast::CallType {
+            function: Box::new(ast::ExpressionKind::Identifier(ast::IdentifierType {
+                name: name.to_string(),
+                t: None,
+            })),
+            arguments: arguments,
+            is_thread: false,
+            is_async: false,
+        });
+    }
+
+    /*
+        f"{name} is {age:>3}" is parsed as
+        string(name) + " is " + format("{:>3}", age).
+    */
+    fn parse_format_string_expression(&mut self) -> Result<ast::ExpressionKind, ParserError> {
+        let parts = match self.lexer.get_current_token().token {
+            TokenKind::FStr(parts) => parts,
+            _ => return Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
         };
 
-        string_literal
+        let mut concatenated: Option<ast::ExpressionKind> = None;
+        for part in parts.iter() {
+            let part_exp = match part {
+                FStrPart::Text(text) => {
+                    ast::ExpressionKind::Literal(ast::LiteralKind::Str(text.clone()))
+                }
+                FStrPart::Field(source, spec) => {
+                    let field_result = self.parse_format_field(source);
+                    if field_result.is_err() {
+                        return Err(field_result.unwrap_err());
+                    }
+
+                    match spec {
+                        Some(spec) => Parser::new_builtin_call(
+                            "format",
+                            vec![
+                                ast::ExpressionKind::Literal(ast::LiteralKind::Str(format!(
+                                    "{{:{}}}",
+                                    spec
+                                ))),
+                                field_result.unwrap(),
+                            ],
+                        ),
+                        None => Parser::new_builtin_call("string", vec![field_result.unwrap()]),
+                    }
+                }
+            };
+
+            concatenated = match concatenated {
+                None => Some(part_exp),
+                Some(left) => Some(ast::ExpressionKind::Infix(ast::InfixType {
+                    infix: InfixExpKind::Plus,
+                    expression_left: Box::new(left),
+                    expression_right: Box::new(part_exp),
+                })),
+            };
+        }
+
+        return Ok(
+            concatenated.unwrap_or(ast::ExpressionKind::Literal(ast::LiteralKind::Str(
+                String::new(),
+            ))),
+        );
     }
 
     fn parse_for_each_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
@@ -743,47 +1511,53 @@ impl Parser {
 
     fn has_infix(&mut self) -> bool {
         let next_tok = self.lexer.get_next_token();
-        match next_tok.token {
-            TokenKind::Operator(op) => match op {
+        return matches!(
+            next_tok.token,
+            TokenKind::Operator(
                 SymbolKind::SPlus
-                | SymbolKind::SMinus
-                | SymbolKind::SMul
-                | SymbolKind::SDiv
-                | SymbolKind::SMod
-                | SymbolKind::SAnd
-                | SymbolKind::SOr
-                | SymbolKind::SEq
-                | SymbolKind::SEeq
-                | SymbolKind::SNe
-                | SymbolKind::SGte
-                | SymbolKind::SGt
-                | SymbolKind::SLte
-                | SymbolKind::SLt
-                | SymbolKind::SPlusEq
-                | SymbolKind::SMinusEq
-                | SymbolKind::SMulEq
-                | SymbolKind::SDivEq
-                | SymbolKind::SModEq
-                | SymbolKind::SAndEq
-                | SymbolKind::SOrEq
-                | SymbolKind::SLOr
-                | SymbolKind::SLAnd => return true,
-                _ => return false,
-            },
-            _ => return false,
-        }
+                    | SymbolKind::SMinus
+                    | SymbolKind::SMul
+                    | SymbolKind::SDiv
+                    | SymbolKind::SMod
+                    | SymbolKind::SAnd
+                    | SymbolKind::SOr
+                    | SymbolKind::SEq
+                    | SymbolKind::SEeq
+                    | SymbolKind::SNe
+                    | SymbolKind::SGte
+                    | SymbolKind::SGt
+                    | SymbolKind::SLte
+                    | SymbolKind::SLt
+                    | SymbolKind::SPlusEq
+                    | SymbolKind::SMinusEq
+                    | SymbolKind::SMulEq
+                    | SymbolKind::SDivEq
+                    | SymbolKind::SModEq
+                    | SymbolKind::SAndEq
+                    | SymbolKind::SOrEq
+                    | SymbolKind::SLOr
+                    | SymbolKind::SLAnd
+                    | SymbolKind::SXor
+                    | SymbolKind::SShl
+                    | SymbolKind::SShr
+                    | SymbolKind::SPow
+                    | SymbolKind::SFloorDiv
+                    | SymbolKind::SXorEq
+                    | SymbolKind::SShlEq
+                    | SymbolKind::SShrEq
+                    | SymbolKind::SPowEq
+                    | SymbolKind::SFloorDivEq
+            )
+        );
     }
 
     #[allow(dead_code)]
     fn has_suffix(&mut self) -> bool {
         let next_tok = self.lexer.get_next_token();
-        match next_tok.token {
-            TokenKind::Operator(op) => match op {
-                SymbolKind::SIncr | SymbolKind::SDecr => return true,
-                _ => return false,
-            },
-            _ => return false,
-        }
+        return matches!(
+            next_tok.token,
+            TokenKind::Operator(SymbolKind::SIncr | SymbolKind::SDecr)
+        );
     }
 
     fn parse_async_expr(&mut self) -> Result<ast::ExpressionKind, ParserError> {
@@ -840,10 +1614,24 @@ impl Parser {
                     SymbolKind::SOrEq => InfixExpKind::OrEq,
                     SymbolKind::SLOr => InfixExpKind::LogicalOr,
                     SymbolKind::SLAnd => InfixExpKind::LogicalAnd,
+                    SymbolKind::SXor => InfixExpKind::Xor,
+                    SymbolKind::SShl => InfixExpKind::ShiftLeft,
+                    SymbolKind::SShr => InfixExpKind::ShiftRight,
+                    SymbolKind::SPow => InfixExpKind::Pow,
+                    SymbolKind::SFloorDiv => InfixExpKind::FloorDiv,
+                    SymbolKind::SXorEq => InfixExpKind::XorEq,
+                    SymbolKind::SShlEq => InfixExpKind::ShiftLeftEq,
+                    SymbolKind::SShrEq => InfixExpKind::ShiftRightEq,
+                    SymbolKind::SPowEq => InfixExpKind::PowEq,
+                    SymbolKind::SFloorDivEq => InfixExpKind::FloorDivEq,
                     _ => return Err(self.new_invalid_token_err(String::from("Invalid operator"))),
                 };
 
-                current_precedence = get_eval_order(&op);
+                current_precedence = match op {
+                    // 2 ** 3 ** 2 is 2 ** 9, the right side takes the next ** too:
+                    SymbolKind::SPow => ExpOrder::Unary,
+                    _ => get_eval_order(&op),
+                };
                 matched_op_kind
             }
             _ => {
@@ -876,15 +1664,11 @@ impl Parser {
     ) -> Result<ast::ExpressionKind, ParserError> {
         let current_token = self.lexer.get_current_token();
         let matched_suffix = match current_token.token {
-            TokenKind::Operator(op) => {
-                let matched_symbol = match op {
-                    SymbolKind::SIncr => SuffixExpKind::PostIncrement,
-                    SymbolKind::SDecr => SuffixExpKind::PostDecrement,
-                    _ => return Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
-                };
-
-                matched_symbol
-            }
+            TokenKind::Operator(op) => match op {
+                SymbolKind::SIncr => SuffixExpKind::PostIncrement,
+                SymbolKind::SDecr => SuffixExpKind::PostDecrement,
+                _ => return Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
+            },
             _ => return Err(self.new_invalid_token_err(String::from("Invalid syntax"))),
         };
 
@@ -1005,6 +1789,30 @@ impl Parser {
         }));
     }
 
+    // attributes of an expression other than an identifier,
+    // like "abc".upper() or s.trim().upper()
+    fn parse_attribute_expression(
+        &mut self,
+        parent_exp: ast::ExpressionKind,
+    ) -> Result<ast::ExpressionKind, ParserError> {
+        let mut attrs = vec![];
+
+        while self.next_symbol_is(SymbolKind::SDot) {
+            self.lexer.iterate();
+            self.lexer.iterate();
+            let id_res = self.get_identifier();
+            if id_res.is_err() {
+                return Err(id_res.unwrap_err());
+            }
+            attrs.push(id_res.unwrap());
+        }
+
+        return Ok(ast::ExpressionKind::Attribute(ast::AttributeResolver {
+            parent: Box::new(parent_exp),
+            child_attrs: attrs,
+        }));
+    }
+
     fn parse_expression(&mut self, pre: ExpOrder) -> Result<ast::ExpressionKind, ParserError> {
         // when an expression is called, the current_token should be at the starting
         // the next_token should point to the next token after start.
@@ -1013,13 +1821,16 @@ impl Parser {
         let mut matched_prefix = match current_token.token {
             // literals:
             TokenKind::Identifier(_) => self.parse_dot_expression(),
-            TokenKind::Integer(_) => self.parse_integer_expression(),
+            TokenKind::Integer(_) | TokenKind::BigInteger(_) => self.parse_integer_expression(),
             TokenKind::Float(_) => self.parse_floating_expression(),
             TokenKind::Char(_) => self.parse_char_expression(),
+            TokenKind::Byte(_) | TokenKind::Bytes(_) => self.parse_byte_expression(),
             TokenKind::Str(_) => self.parse_string_expression(),
+            TokenKind::FStr(_) => self.parse_format_string_expression(),
             TokenKind::Keyword(kw) => {
                 // all expressions that start with a keyword:
-                let kw_exp_result = match kw {
+
+                match kw {
                     KeywordKind::KTrue => {
                         Ok(ast::ExpressionKind::Literal(ast::LiteralKind::Bool(true)))
                     }
@@ -1030,28 +1841,25 @@ impl Parser {
                     KeywordKind::KNone => Ok(ast::ExpressionKind::Noval),
                     KeywordKind::KThread => self.parse_thread_exp(),
                     KeywordKind::KAsync => self.parse_async_expr(),
+                    KeywordKind::KMatch => self.parse_match_expression(),
                     _ => Err(self
                         .new_invalid_token_err(String::from("Functionality not yet implemented"))),
-                };
-
-                kw_exp_result
+                }
             }
             // starts with a symbol:
-            TokenKind::Operator(op) => {
-                let op_expr_result = match op {
-                    SymbolKind::SLBox => self.parse_array_expression(),
-                    SymbolKind::SLBrace => self.parse_hash_literal(),
-                    SymbolKind::SIncr
-                    | SymbolKind::SDecr
-                    | SymbolKind::SNeg
-                    | SymbolKind::SExcl => self.parse_prefix_expression(),
-                    SymbolKind::SLParen => self.parse_sub_expression(),
-                    SymbolKind::SDollar => self.parse_shell_expression(),
-                    _ => Err(self.new_invalid_token_err(String::from("Invalid symbol"))),
-                };
-
-                op_expr_result
-            }
+            TokenKind::Operator(op) => match op {
+                SymbolKind::SLBox => self.parse_array_expression(),
+                SymbolKind::SLBrace => self.parse_hash_literal(),
+                SymbolKind::SIncr
+                | SymbolKind::SDecr
+                | SymbolKind::SNeg
+                | SymbolKind::SMinus
+                | SymbolKind::SEllipsis
+                | SymbolKind::SExcl => self.parse_prefix_expression(),
+                SymbolKind::SLParen => self.parse_sub_expression(),
+                SymbolKind::SDollar => self.parse_shell_expression(),
+                _ => Err(self.new_invalid_token_err(String::from("Invalid symbol"))),
+            },
             _ => Err(self.new_invalid_token_err(String::from("Invalid token"))),
         };
 
@@ -1077,6 +1885,8 @@ impl Parser {
             } else if self.next_symbol_is(SymbolKind::SLBox) {
                 self.lexer.iterate();
                 matched_prefix = self.parse_index_expression(matched_prefix.unwrap());
+            } else if self.next_symbol_is(SymbolKind::SDot) {
+                matched_prefix = self.parse_attribute_expression(matched_prefix.unwrap());
             } else {
                 break;
             }
@@ -1085,14 +1895,76 @@ impl Parser {
         return matched_prefix;
     }
 
+    fn next_is_slice_colon(&mut self) -> bool {
+        return self.next_symbol_is(SymbolKind::SColon)
+            || self.next_symbol_is(SymbolKind::SResolve);
+    }
+
+    // parses one part of a slice, None when the part is left out.
+    fn parse_slice_part(&mut self) -> Result<Option<Box<ast::ExpressionKind>>, ParserError> {
+        if self.next_is_slice_colon() || self.next_symbol_is(SymbolKind::SRBox) {
+            return Ok(None);
+        }
+
+        self.lexer.iterate();
+        let exp_result = self.parse_expression(ExpOrder::Zero);
+        if exp_result.is_err() {
+            return Err(exp_result.unwrap_err());
+        }
+
+        return Ok(Some(Box::new(exp_result.unwrap())));
+    }
+
     fn parse_index_expression(
         &mut self,
         prefix_exp: ast::ExpressionKind,
     ) -> Result<ast::ExpressionKind, ParserError> {
+        let start_result = self.parse_slice_part();
+        if start_result.is_err() {
+            return Err(start_result.unwrap_err());
+        }
+
+        let start = start_result.unwrap();
+        if start.is_some() && self.next_symbol_is(SymbolKind::SRBox) {
+            self.lexer.iterate();
+            let index_type = ast::IndexType {
+                expression_left: Box::new(prefix_exp),
+                index: start.unwrap(),
+            };
+
+            return Ok(ast::ExpressionKind::Index(index_type));
+        }
+
+        if !self.next_is_slice_colon() {
+            return Err(self.new_invalid_token_err(String::from("Invalid syntax")));
+        }
+
+        // xs[a::c] is lexed with a single :: token, the stop is left out.
+        let mut has_step = self.next_symbol_is(SymbolKind::SResolve);
         self.lexer.iterate();
-        let exp_result = self.parse_expression(ExpOrder::Zero);
-        if exp_result.is_err() {
-            return Err(exp_result.unwrap_err());
+
+        let mut stop = None;
+        if !has_step {
+            let stop_result = self.parse_slice_part();
+            if stop_result.is_err() {
+                return Err(stop_result.unwrap_err());
+            }
+
+            stop = stop_result.unwrap();
+            if self.next_symbol_is(SymbolKind::SColon) {
+                has_step = true;
+                self.lexer.iterate();
+            }
+        }
+
+        let mut step = None;
+        if has_step {
+            let step_result = self.parse_slice_part();
+            if step_result.is_err() {
+                return Err(step_result.unwrap_err());
+            }
+
+            step = step_result.unwrap();
         }
 
         if !self.next_symbol_is(SymbolKind::SRBox) {
@@ -1100,12 +1972,14 @@ impl Parser {
         }
 
         self.lexer.iterate();
-        let index_type = ast::IndexType {
+        let slice_type = ast::SliceType {
             expression_left: Box::new(prefix_exp),
-            index: Box::new(exp_result.unwrap()),
+            start: start,
+            stop: stop,
+            step: step,
         };
 
-        return Ok(ast::ExpressionKind::Index(index_type));
+        return Ok(ast::ExpressionKind::Slice(slice_type));
     }
 
     fn parse_call_expression(
@@ -1241,6 +2115,41 @@ impl Parser {
         }));
     }
 
+    fn parse_use_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
+        self.lexer.iterate();
+
+        // module path:
+        let current_token = self.lexer.get_current_token();
+        let path = match current_token.token {
+            TokenKind::Str(st) => st,
+            _ => {
+                return Err(
+                    self.new_invalid_token_err(String::from("Expected module path after use"))
+                )
+            }
+        };
+
+        if !self.next_keyword_is(KeywordKind::KAs) {
+            return Err(self.new_invalid_token_err(String::from("Expected as after module path")));
+        }
+
+        self.lexer.iterate();
+        self.lexer.iterate();
+
+        let alias_result = self.get_identifier();
+        if alias_result.is_err() {
+            return Err(alias_result.unwrap_err());
+        }
+
+        return Ok(ast::StatementKind::Use(ast::UseType {
+            path: path,
+            alias: ast::IdentifierType {
+                name: alias_result.unwrap(),
+                t: None,
+            },
+        }));
+    }
+
     fn parse_expression_statement(&mut self) -> Result<ast::StatementKind, ParserError> {
         let parsed_exp = self.parse_expression(ExpOrder::Zero);
         if parsed_exp.is_err() {
@@ -1316,6 +2225,14 @@ impl Parser {
                 return self.parse_return_statement();
             }
 
+            TokenKind::Keyword(KeywordKind::KYield) => {
+                return self.parse_yield_statement();
+            }
+
+            TokenKind::Keyword(KeywordKind::KMatch) => {
+                return self.parse_match_statement();
+            }
+
             TokenKind::Keyword(KeywordKind::KFunc) => {
                 if self.is_terminated() {
                     return Err(self.new_invalid_token_err(String::from("Invalid syntax")));
@@ -1323,6 +2240,23 @@ impl Parser {
                     return self.parse_function_statement();
                 }
             }
+
+            TokenKind::Keyword(KeywordKind::KPure) => {
+                if self.is_terminated() {
+                    return Err(self.new_invalid_token_err(String::from("Invalid syntax")));
+                } else {
+                    return self.parse_pure_function_statement();
+                }
+            }
+
+            TokenKind::Keyword(KeywordKind::KClass) | TokenKind::Keyword(KeywordKind::KStruct) => {
+                if self.is_terminated() {
+                    return Err(self.new_invalid_token_err(String::from("Invalid syntax")));
+                } else {
+                    return self.parse_class_statement();
+                }
+            }
+
             TokenKind::Keyword(KeywordKind::KFor) => {
                 if self.is_terminated() {
                     return Err(self.new_invalid_token_err(String::from("Invalid syntax")));
@@ -1339,6 +2273,24 @@ impl Parser {
                 }
             }
 
+            TokenKind::Keyword(KeywordKind::KRethrow) => {
+                if self.is_terminated() {
+                    return Ok(ast::StatementKind::Rethrow);
+                } else {
+                    return Err(
+                        self.new_invalid_token_err(String::from("Expected ; after rethrow."))
+                    );
+                }
+            }
+
+            TokenKind::Keyword(KeywordKind::KUse) => {
+                if self.is_terminated() {
+                    return Err(self.new_invalid_token_err(String::from("Invalid syntax")));
+                } else {
+                    return self.parse_use_statement();
+                }
+            }
+
             TokenKind::Keyword(KeywordKind::KTry) => {
                 if self.is_terminated() {
                     return Err(self.new_invalid_token_err(String::from("Invalid syntax")));
@@ -1386,17 +2338,28 @@ impl Parser {
                 continue;
             }
 
+            let span = current_token.span;
             let stmt_result = self.parse_statement();
             match stmt_result {
-                Ok(stmt) => program.statements.push(stmt),
-                Err(error) => self.errors.push(error),
+                Ok(stmt) => {
+                    program.statements.push(stmt);
+                    program.pos.push(span);
+                }
+                Err(error) => {
+                    // the same invalid token can fail two statements in a row.
+                    let is_repeated = error.t == ParserErrorKind::InvalidToken
+                        && self.errors.last().map(|last| last.pos) == Some(error.pos);
+                    if !is_repeated {
+                        self.errors.push(error);
+                    }
+                }
             }
 
             self.lexer.iterate();
             current_token = self.lexer.get_current_token();
         }
 
-        if self.errors.len() > 0 {
+        if !self.errors.is_empty() {
             return Err(&self.errors);
         }
 
@@ -1407,13 +2370,14 @@ impl Parser {
         let mut error_strings = vec![];
 
         for err in &self.errors {
-            let (err_line, b_pos, _) = self.lexer.get_line_by_pos(err.pos);
-            let pointed_line = format!("{:0pos$}", "^^^", pos = (err.pos - b_pos));
+            let location = SourceLocation::new(self.lexer.file_name.clone(), err.span);
+            let message = format!(
+                "Parsing Error: {}, Token: {:?}",
+                err.message, err.error_token
+            );
 
-            error_strings.push(format!(
-                "Parsing Error at position: {}, Token: {:?}\n\t{}\n\t{}\n{}",
-                err.pos, err.error_token, err_line, pointed_line, err.message
-            ))
+            error_strings
+                .push(location.render(&message, self.lexer.get_source_line(err.span.line)));
         }
 
         return error_strings;
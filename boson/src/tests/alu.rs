@@ -0,0 +1,192 @@
+use crate::tests::eval_global;
+use crate::tests::with_large_stack;
+use crate::types::object::Object;
+use crate::vm::alu::Arithmetic;
+use crate::vm::alu::Bitwise;
+use crate::vm::alu::Comparision;
+use crate::vm::errors::ISAErrorKind;
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+
+fn int(value: i64) -> Rc<Object> {
+    return Rc::new(Object::Int(value));
+}
+
+fn big(value: BigInt) -> Rc<Object> {
+    return Rc::new(Object::BigInt(value));
+}
+
+#[test]
+pub fn overflow_promotion() {
+    // results that do not fit 64 bits become big integers:
+    let result = Arithmetic::add(&int(i64::MAX), &int(1));
+    assert_eq!(result.unwrap(), big(BigInt::from(i64::MAX) + 1));
+
+    let result = Arithmetic::sub(&int(i64::MIN), &int(1));
+    assert_eq!(result.unwrap(), big(BigInt::from(i64::MIN) - 1));
+
+    let result = Arithmetic::mul(&int(i64::MAX), &int(2));
+    assert_eq!(result.unwrap(), big(BigInt::from(i64::MAX) * 2));
+
+    let result = Arithmetic::pow(&int(2), &int(64));
+    assert_eq!(result.unwrap(), big(BigInt::from(1u64 << 63) * 2));
+
+    let result = Bitwise::shl(&int(1), &int(70));
+    assert_eq!(result.unwrap(), big(BigInt::from(1) << 70));
+
+    // i64::MIN / -1 is the only int quotient that overflows:
+    let result = Arithmetic::floor_div(&int(i64::MIN), &int(-1));
+    assert_eq!(result.unwrap(), big(-BigInt::from(i64::MIN)));
+
+    let result = Arithmetic::modulus(&int(i64::MIN), &int(-1));
+    assert_eq!(result.unwrap(), int(0));
+}
+
+#[test]
+pub fn bigint_demotion() {
+    // results that fit again are plain ints:
+    let above_max = big(BigInt::from(i64::MAX) + 1);
+    let result = Arithmetic::sub(&above_max, &int(1));
+    assert_eq!(result.unwrap(), int(i64::MAX));
+
+    let result = Arithmetic::modulus(&above_max, &int(10));
+    assert_eq!(result.unwrap(), int(8));
+
+    // mixed with a float the result is a float:
+    let result = Arithmetic::add(&above_max, &Rc::new(Object::Float(0.5)));
+    assert_eq!(
+        result.unwrap(),
+        Rc::new(Object::Float(9223372036854775808.0))
+    );
+}
+
+#[test]
+pub fn bigint_comparison() {
+    let above_max = big(BigInt::from(i64::MAX) + 1);
+    assert_eq!(
+        Comparision::order(&above_max, &int(i64::MAX)),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        Comparision::order(&int(i64::MIN), &big(BigInt::from(i64::MIN) - 1)),
+        Some(Ordering::Greater)
+    );
+    assert!(Comparision::equals(&big(BigInt::from(5)), &Object::Int(5)));
+}
+
+#[test]
+pub fn arithmetic_errors() {
+    let result = Arithmetic::div(&int(1), &int(0));
+    assert_eq!(result.unwrap_err().t, ISAErrorKind::DivideByZeroError);
+
+    let result = Arithmetic::modulus(&int(1), &int(0));
+    assert!(result.is_err());
+
+    let result = Arithmetic::modulus(&big(BigInt::from(i64::MAX) + 1), &int(0));
+    assert!(result.is_err());
+
+    let result = Arithmetic::add(&int(1), &Rc::new(Object::Bool(true)));
+    assert!(result.is_err());
+}
+
+#[test]
+pub fn cyclic_equality() {
+    // two separate arrays which contain themselves:
+    let source = r#"
+        var a = [1]; a.push(a);
+        var b = [1]; b.push(b);
+        var c = [2]; c.push(c);
+        var h = {"x": 1}; h["self"] = h;
+        var g = {"x": 1}; g["self"] = g;
+        var result = [a == b, a == c, a < c, a <= b, h == g];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([true, false, true, true, true])"
+    );
+}
+
+#[test]
+pub fn numeric_keys() {
+    // numbers equal by value are the same key, the first key is kept:
+    let source = r#"
+        var h = {1: "a"};
+        h[1.0] = "b";
+        var result = [h[1], h.contains(1.0), len(h.keys()), h.keys()[0]];
+        h.delete(1.0);
+        var remaining = len(h.keys());
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(result.unwrap().describe(), "Array([b, true, 1, 1])");
+    let remaining = eval_global(source, "remaining");
+    assert_eq!(remaining.unwrap(), int(0));
+}
+
+#[test]
+pub fn recursive_comparator_overflow() {
+    with_large_stack(|| {
+        let source = r#"
+            func f(x) { var a = [2, 1]; sort(a, lambda p, q => f(p)); return a; }
+            var kind = "";
+            try { f(1); } catch e { kind = e.kind; }
+        "#;
+        let result = eval_global(source, "kind");
+        assert_eq!(
+            result.unwrap(),
+            Rc::new(Object::Str("CallStackOverflow".to_string()))
+        );
+    });
+}
+
+#[test]
+pub fn int_bit_limit() {
+    // results larger than MAX_INT_BITS raise an error instead of being computed:
+    let result = Bitwise::shl(&int(1), &int(99999999999));
+    assert_eq!(result.unwrap_err().t, ISAErrorKind::OverflowError);
+
+    let result = Arithmetic::pow(&int(3), &int(u32::MAX as i64));
+    assert_eq!(result.unwrap_err().t, ISAErrorKind::OverflowError);
+
+    let result = Arithmetic::pow(&big(BigInt::from(2).pow(100)), &int(100000));
+    assert_eq!(result.unwrap_err().t, ISAErrorKind::OverflowError);
+
+    // results below the limit, and powers and shifts of 0, 1 and -1:
+    let result = Bitwise::shl(&int(1), &int(1000));
+    assert_eq!(result.unwrap(), big(BigInt::from(1) << 1000));
+    let result = Arithmetic::pow(&int(2), &int(1000));
+    assert_eq!(result.unwrap(), big(BigInt::from(2).pow(1000)));
+    let result = Arithmetic::pow(&int(-1), &int(u32::MAX as i64));
+    assert_eq!(result.unwrap(), int(-1));
+    let result = Bitwise::shl(&int(0), &int(99999999999));
+    assert_eq!(result.unwrap(), int(0));
+}
+
+#[test]
+pub fn floor_modulus() {
+    // % floors like //, the remainder has the sign of the divisor:
+    let cases = vec![
+        (-7, 2, -4, 1),
+        (7, -2, -4, -1),
+        (-7, -2, 3, -1),
+        (7, 2, 3, 1),
+    ];
+    for (lval, rval, quotient, remainder) in cases {
+        let result = Arithmetic::floor_div(&int(lval), &int(rval));
+        assert_eq!(result.unwrap(), int(quotient));
+        let result = Arithmetic::modulus(&int(lval), &int(rval));
+        assert_eq!(result.unwrap(), int(remainder));
+    }
+
+    let result = Arithmetic::modulus(&int(i64::MIN), &int(-1));
+    assert_eq!(result.unwrap(), int(0));
+
+    let result = Arithmetic::modulus(&Rc::new(Object::Float(-7.5)), &int(2));
+    assert_eq!(result.unwrap(), Rc::new(Object::Float(0.5)));
+
+    let result = Arithmetic::modulus(&big(-(BigInt::from(1) << 70usize)), &int(3));
+    assert_eq!(result.unwrap(), int(2));
+}
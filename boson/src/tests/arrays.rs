@@ -0,0 +1,50 @@
+use crate::tests::eval_global;
+use crate::tests::with_large_stack;
+use crate::types::object::Object;
+
+use std::rc::Rc;
+
+#[test]
+pub fn callbacks() {
+    let source = r#"
+        var doubled = [1, 2, 3].map(lambda x => x * 2);
+        var odd = [1, 2, 3, 4, 5].filter(lambda x => x % 2 == 1);
+        var total = [1, 2, 3, 4].reduce(lambda acc, x => acc + x, 0);
+        var result = [doubled, odd, total];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([2, 4, 6]), Array([1, 3, 5]), 10])"
+    );
+}
+
+#[test]
+pub fn callback_recursion_overflow() {
+    with_large_stack(|| {
+        // callbacks which recurse through the array methods raise a catchable
+        // error once they are nested deeper than MAX_SANDBOX_DEPTH:
+        let sources = vec![
+            "func f(x) { return [x].map(f); }",
+            "func f(x) { return [x].filter(f); }",
+            "func f(x) { return [1, 2].reduce(lambda a, b => f(a), 0); }",
+            "func f(x) { return [1, 2].sort_by(lambda a, b => f(a)); }",
+        ];
+
+        for definition in sources {
+            let source = format!(
+                r#"
+                {}
+                var kind = "";
+                try {{ f(1); }} catch e {{ kind = e.kind; }}
+                "#,
+                definition
+            );
+            let result = eval_global(&source, "kind");
+            assert_eq!(
+                result.unwrap(),
+                Rc::new(Object::Str("CallStackOverflow".to_string()))
+            );
+        }
+    });
+}
@@ -0,0 +1,95 @@
+use crate::tests::eval_global;
+
+#[test]
+pub fn shared_in_place() {
+    // index, slice and attribute assignments and the modifying methods all
+    // change the object every alias refers to:
+    let source = r#"
+        var h = {"k": 1};
+        var g = h;
+        h.k = 2;
+        h["n"] = 3;
+
+        var a = [1, 2, 3];
+        var b = a;
+        a[0] = 9;
+        a[1:2] = [7, 7];
+        a.push(4);
+
+        func set_first(xs) { xs[0] = 0; }
+        var c = [5];
+        set_first(c);
+
+        var result = [g, b, c, a.slice(0) == a];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([HashTable({k: 2, n: 3}), Array([9, 7, 7, 3, 4]), Array([0]), true])"
+    );
+
+    // a copy is not changed:
+    let source = r#"
+        var a = [1, 2];
+        var copy = a.slice(0);
+        a[0] = 5;
+        var h = {"k": 1};
+        var h_copy = {}.merge(h);
+        h.k = 2;
+        var result = [copy, h_copy];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([1, 2]), HashTable({k: 1})])"
+    );
+}
+
+#[test]
+pub fn nested_targets() {
+    let source = r#"
+        const config = {"server": {"host": "localhost"}};
+        config.server.port = 8080;
+        config["server"]["port"] += 1;
+
+        var grid = [[1, 2], [3, 4]];
+        grid[1][0] = 0;
+
+        class P {
+            items;
+            func init(self) { self.items = []; }
+            func add(self, x) { self.items.push(x); self.items[0] = x; }
+        }
+        var p = P();
+        var q = p;
+        p.add(1);
+        p.add(2);
+        p["items"] = p.items;
+
+        var result = [config.server.port, grid, q.items];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([8081, Array([Array([1, 2]), Array([0, 4])]), Array([2, 2])])"
+    );
+}
+
+#[test]
+pub fn assignment_errors() {
+    let source = r#"
+        class P { x; func init(self) { self.x = 0; } }
+        var p = P();
+        var h = {};
+        var results = [];
+        try { p.y = 1; } catch e { results.push(e.kind); }
+        try { h[h] = 1; } catch e { results.push(e.kind); }
+        try { var n = 5; n[0] = 1; } catch e { results.push(e.kind); }
+        try { var m = 5; m.k = 1; } catch e { results.push(e.kind); }
+    "#;
+    let result = eval_global(source, "results");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([AttributeError, IndexError, IndexError, AttributeError])"
+    );
+}
@@ -0,0 +1,81 @@
+use crate::tests::eval_global;
+use crate::tests::parser_errors;
+
+const POINT: &str = r#"
+    class Point {
+        x, y;
+
+        func init(self, x, y) {
+            self.x = x;
+            self.y = y;
+        }
+
+        func move(self, dx, dy) {
+            self.x += dx;
+            self.y += dy;
+        }
+
+        func sum(self) {
+            return self.x + self.y;
+        }
+    }
+
+    struct Pair { first, second; }
+"#;
+
+#[test]
+pub fn instances() {
+    let source = format!(
+        r#"{}
+        const p = Point(1, 2);
+        var alias = p;
+        p.move(1, 2);
+        Point.move(p, 1, 1);
+
+        var result = [
+            alias,
+            p.x,
+            p.sum(),
+            type_of(p),
+            type_of(Point),
+            Pair("a", 1),
+            attrs(p).contains("move")
+        ];
+        "#,
+        POINT
+    );
+    let result = eval_global(&source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Point(x=3, y=5), 3, 8, Point, class, Pair(first=a, second=1), true])"
+    );
+}
+
+#[test]
+pub fn class_errors() {
+    let source = format!(
+        r#"{}
+        const p = Point(1, 2);
+        var errors = [];
+        try {{ p.z = 1; }} catch e {{ errors.push(e.message); }}
+        try {{ p.missing(); }} catch e {{ errors.push(e.message); }}
+        try {{ var value = p.missing; }} catch e {{ errors.push(e.message); }}
+        try {{ Point(1); }} catch e {{ errors.push(e.kind); }}
+        try {{ Pair(1); }} catch e {{ errors.push(e.message); }}
+        "#,
+        POINT
+    );
+    let result = eval_global(&source, "errors");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Point has no field z, Point has no attribute missing, \
+         Point has no attribute missing, FunctionArgumentsError, \
+         Pair expects 2 arguments, given 1])"
+    );
+
+    let errors = parser_errors("class A { x, x; }".as_bytes());
+    assert!(errors[0].contains("Duplicate field x in class A"));
+
+    let errors = parser_errors("class A { x; func m() {} }".as_bytes());
+    assert!(errors[0].contains("Method m of class A must take the instance as its first parameter"));
+}
@@ -0,0 +1,111 @@
+use crate::compiler::errors::CompilerErrorKind;
+use crate::tests::compile_error;
+use crate::tests::eval_global;
+
+#[test]
+pub fn shared_captures() {
+    let source = r#"
+        func make_counter() {
+            var count = 0;
+            func incr() { count++; return count; }
+            func reset() { count = 0; }
+            func get() { return count; }
+            return [incr, reset, get];
+        }
+        const counter = make_counter();
+        counter[0]();
+        counter[0]();
+        var third = counter[0]();
+        counter[1]();
+        var after_reset = counter[0]();
+        var seen = counter[2]();
+
+        func make_accumulator(start) {
+            var total = start;
+            func add(x) { total += x; return total; }
+            return add;
+        }
+        const acc = make_accumulator(10);
+        acc(5);
+        var accumulated = acc(5);
+
+        func outer() {
+            var depth = 0;
+            func middle() {
+                func inner() { depth++; ++depth; depth--; }
+                inner();
+                inner();
+            }
+            middle();
+            return depth;
+        }
+
+        # the closure reads the variable, not its value when it was created:
+        func late_write() {
+            var v = 1;
+            func get() { return v; }
+            v = 5;
+            return get();
+        }
+
+        var result = [third, after_reset, seen, accumulated, outer(), late_write()];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(result.unwrap().describe(), "Array([3, 1, 1, 20, 2, 5])");
+}
+
+#[test]
+pub fn loop_and_thread_captures() {
+    let source = r#"
+        # every iteration captures the same variable:
+        func loop_capture() {
+            var getters = [];
+            for i in range(0, 3) => {
+                func get() { return i; }
+                getters.push(get);
+            }
+            return getters.map(lambda f => f());
+        }
+
+        # a thread works on a copy of the captured variables:
+        func threaded() {
+            var n = 1;
+            func bump() { n += 10; return n; }
+            const t = thread bump();
+            return [wait(t), n];
+        }
+
+        var result = [loop_capture(), threaded()];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([2, 2, 2]), Array([11, 1])])"
+    );
+}
+
+#[test]
+pub fn capture_errors() {
+    let invalid = vec![
+        (
+            "func f() { const k = 1; func g() { k = 2; } return g; }",
+            CompilerErrorKind::InvalidAssignment,
+        ),
+        (
+            "func f() { const k = 1; func g() { k++; } return g; }",
+            CompilerErrorKind::InvalidAssignment,
+        ),
+        (
+            "func f() { var k = 1; pure func g() { k = 2; } return g; }",
+            CompilerErrorKind::ImpureFunction,
+        ),
+        (
+            "func f() { var k = 1; pure func g() { return k; } return g; }",
+            CompilerErrorKind::ImpureFunction,
+        ),
+    ];
+
+    for (source, kind) in invalid {
+        assert_eq!(compile_error(source), Some(kind), "{}", source);
+    }
+}
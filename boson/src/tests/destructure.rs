@@ -0,0 +1,67 @@
+use crate::compiler::errors::CompilerErrorKind;
+use crate::tests::compile_error;
+use crate::tests::eval_global;
+use crate::tests::parser_errors;
+
+#[test]
+pub fn destructuring() {
+    let source = r#"
+        const person = {"name": "ann", "age": 31, "langs": ["rust", "go"]};
+        var {name, age: years, langs: [first, _]} = person;
+
+        func divmod(a, b) {
+            return [int(a / b), a % b];
+        }
+        var [q, r] = divmod(17, 5);
+        [q, r] = [r, q];
+
+        class P { x, y; }
+        var {x, y: py} = P(1, 2);
+        var [[a1, a2], b1] = [[1, 2], 3];
+        const [code, out] = $ "echo hi";
+
+        var result = [name, years, first, q, r, x, py, a1, a2, b1, code, out];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([ann, 31, rust, 2, 3, 1, 2, 1, 2, 3, 0, hi])"
+    );
+}
+
+#[test]
+pub fn destructure_errors() {
+    let source = r#"
+        class P { x, y; }
+        var errors = [];
+        try { var [a, b] = [1, 2, 3]; } catch e { errors.push(e.message); }
+        try { var [c, d] = 5; } catch e { errors.push(e.message); }
+        try { var {missing} = {"a": 1}; } catch e { errors.push(e.message); }
+        try { var {w} = 5; } catch e { errors.push(e.message); }
+        try { var {nope} = P(1, 2); } catch e { errors.push(e.kind); }
+    "#;
+    let result = eval_global(source, "errors");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Cannot destructure Array of 3 elements into 2 names, \
+         Cannot destructure int into 2 names, expected Array, \
+         Cannot destructure HashTable, key missing not found, \
+         Cannot destructure int by keys, expected HashTable or an instance, IndexError])"
+    );
+
+    assert_eq!(
+        compile_error("var [a, a] = [1, 2];"),
+        Some(CompilerErrorKind::SymbolAlreadyExist)
+    );
+    assert_eq!(
+        compile_error("const k = 1; [k] = [2];"),
+        Some(CompilerErrorKind::InvalidAssignment)
+    );
+    assert_eq!(
+        compile_error("[u] = [1];"),
+        Some(CompilerErrorKind::InvalidAssignment)
+    );
+
+    let errors = parser_errors("var [1] = [1];".as_bytes());
+    assert!(errors[0].contains("Expected a name, [ or { to destructure"));
+}
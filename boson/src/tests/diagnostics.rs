@@ -0,0 +1,67 @@
+use crate::compiler::spans::SourceLocation;
+use crate::compiler::BytecodeCompiler;
+use crate::lexer::LexerAPI;
+use crate::parser::Parser;
+use crate::tests::parser_errors;
+use crate::tests::runtime_error;
+use crate::vm::errors::VMErrorKind;
+
+fn location(line: usize, col: usize) -> Option<SourceLocation> {
+    return Some(SourceLocation {
+        file: "<input>".to_string(),
+        line: line,
+        col: col,
+    });
+}
+
+#[test]
+pub fn parser_error_caret() {
+    let errors = parser_errors("var a = 1;\nvar c = (1 + ;\n".as_bytes());
+    assert_eq!(
+        errors[0],
+        "<input>:2:14: Parsing Error: Invalid symbol, Token: Operator(SSemiColon)\n    \
+         var c = (1 + ;\n                 ^"
+    );
+
+    // columns count characters, not bytes:
+    let errors = parser_errors("var größe = (1 + ;".as_bytes());
+    assert_eq!(
+        errors[0],
+        "<input>:1:18: Parsing Error: Invalid symbol, Token: Operator(SSemiColon)\n    \
+         var größe = (1 + ;\n                     ^"
+    );
+}
+
+#[test]
+pub fn compile_error_location() {
+    let source = "var a = 1;\n  a = b + 1;\n";
+    let lexer = LexerAPI::new_from_buffer(source.as_bytes().to_vec());
+    let mut parser = Parser::new_from_lexer(lexer);
+    let program = parser.parse().unwrap();
+
+    let mut compiler = BytecodeCompiler::new();
+    let error = compiler.compile(&program).unwrap_err();
+    assert_eq!(error.location, location(2, 3));
+}
+
+#[test]
+pub fn runtime_error_location() {
+    let source = "var a = 1;\nfunc f(x) {\n\treturn x / \"a\";\n}\nvar b = f(a);\n";
+    let error = runtime_error(source);
+    assert_eq!(error.t, VMErrorKind::TypeError);
+    assert_eq!(error.location, location(3, 2));
+
+    // tabs are kept in front of the caret:
+    let rendered = error.location.unwrap().render(
+        &"TypeError: failed".to_string(),
+        Some("\treturn x / \"a\";".to_string()),
+    );
+    assert_eq!(
+        rendered,
+        "<input>:3:2: TypeError: failed\n    \treturn x / \"a\";\n    \t^"
+    );
+
+    // errors raised by builtins point to the statement that called them:
+    let error = runtime_error("var a = 1;\n\nvar b = int(\"x\");\n");
+    assert_eq!(error.location, location(3, 1));
+}
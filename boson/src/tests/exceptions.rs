@@ -0,0 +1,126 @@
+use crate::compiler::errors::CompilerErrorKind;
+use crate::tests::compile_error;
+use crate::tests::eval_global;
+use crate::types::object::Object;
+
+use std::rc::Rc;
+
+fn assert_global(source: &str, name: &str, expected: Object) {
+    let result = eval_global(source, name);
+    assert!(result.is_some());
+    assert_eq!(result.unwrap(), Rc::new(expected));
+}
+
+#[test]
+pub fn finally_after_catch() {
+    // the finally block runs after the try block and after the catch block:
+    let source = r#"
+        var log = "";
+        try { log = log + "t"; } catch e { log = log + "c"; } finally { log = log + "f"; }
+        try { throw "x"; } catch e { log = log + "c"; } finally { log = log + "f"; }
+    "#;
+    assert_global(source, "log", Object::Str("tfcf".to_string()));
+
+    // runtime errors are caught with their kind:
+    let source = r#"
+        var kind = "";
+        try { var z = 1 / 0; } catch e { kind = e.kind; }
+    "#;
+    assert_global(source, "kind", Object::Str("DivideByZeroError".to_string()));
+}
+
+#[test]
+pub fn finally_unwinding() {
+    // an exception raised in the catch block is raised again after finally:
+    let source = r#"
+        var log = "";
+        try {
+            try { throw "a"; } catch e { throw "b"; } finally { log = log + "f"; }
+        } catch e {
+            log = log + e.message;
+        }
+    "#;
+    assert_global(source, "log", Object::Str("fb".to_string()));
+
+    // an exception raised in a called function unwinds its frames:
+    let source = r#"
+        var log = "";
+        func inner() { try { throw "deep"; } catch e { rethrow; } finally { log = log + "i"; } }
+        func outer() { inner(); log = log + "never"; }
+        try { outer(); } catch e { log = log + e.message; }
+    "#;
+    assert_global(source, "log", Object::Str("ideep".to_string()));
+}
+
+#[test]
+pub fn finally_on_jumps() {
+    // break and continue run the finally block of the loop body:
+    let source = r#"
+        var log = "";
+        for i in range(0, 4) => {
+            try {
+                if (i == 1) { continue; }
+                if (i == 2) { break; }
+                log = log + string(i);
+            } catch e {
+            } finally {
+                log = log + "f";
+            }
+        }
+    "#;
+    assert_global(source, "log", Object::Str("0fff".to_string()));
+
+    // return runs every finally block it leaves and keeps its value:
+    let source = r#"
+        var log = "";
+        func f() {
+            try {
+                try { return 1; } catch e { } finally { log = log + "a"; }
+            } catch e { } finally { log = log + "b"; }
+        }
+        var result = f();
+    "#;
+    assert_global(source, "log", Object::Str("ab".to_string()));
+    assert_global(source, "result", Object::Int(1));
+
+    // a return inside the finally block replaces the value:
+    let source = r#"
+        func f() { try { return 1; } catch e { } finally { return 2; } }
+        var result = f();
+    "#;
+    assert_global(source, "result", Object::Int(2));
+}
+
+#[test]
+pub fn catch_bindings() {
+    // a constant cannot hold the exception:
+    let source = r#"
+        const e = 5;
+        try { throw 1; } catch e {}
+        e = 3;
+    "#;
+    assert_eq!(
+        compile_error(source),
+        Some(CompilerErrorKind::ConstantAssignment)
+    );
+
+    // rethrow inside a function refers to the catch blocks of that function:
+    let source = r#"
+        try { throw "outer"; } catch e {
+            func f() { rethrow; }
+        }
+    "#;
+    assert_eq!(
+        compile_error(source),
+        Some(CompilerErrorKind::InvalidRethrow)
+    );
+
+    let source = r#"
+        var message = "";
+        try { throw "outer"; } catch e {
+            func f() { try { throw "inner"; } catch x { rethrow; } }
+            try { f(); } catch y { message = y.message; }
+        }
+    "#;
+    assert_global(source, "message", Object::Str("inner".to_string()));
+}
@@ -0,0 +1,80 @@
+use crate::tests::eval_global;
+use crate::tests::parser_errors;
+
+#[test]
+pub fn f_strings() {
+    let source = r#"
+        const name = "boson";
+        const version = 1.5;
+        var result = [
+            f"{name} v{version}",
+            f"[{name:>8}] [{version:.3f}]",
+            f"{{braces}} {len(name) * 2}",
+            f"{[1, 2].map(lambda x => x * 10)}"
+        ];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([boson v1.5, [   boson] [1.500], {braces} 10, Array([10, 20])])"
+    );
+}
+
+#[test]
+pub fn format_specifiers() {
+    let source = r#"
+        var result = [
+            format("{} is {:#x}", 255, 255),
+            format("{1}-{0}", "a", "b"),
+            format("{:*^9}|{:+08.2f}", "mid", 3.14159),
+            format("{:b} {:o} {:X} {:c}", 5, 8, 255, 65),
+            format("{:e} {:.1%}", 1234.5, 0.256),
+            format("{:.2s}|{:<5}|", "hello", 1),
+            format("{: d}|{:05d}", 5, -42)
+        ];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([255 is 0xff, b-a, ***mid***|+0003.14, 101 10 FF A, 1.234500e+03 25.6%, \
+         he|1    |,  5|-0042])"
+    );
+}
+
+#[test]
+pub fn format_errors() {
+    let source = r#"
+        var errors = [];
+        try { format("{:d}", "x"); } catch e { errors.push(e.message); }
+        try { format("{} {}", 1); } catch e { errors.push(e.message); }
+        try { format("{5}", 1); } catch e { errors.push(e.message); }
+        try { format("{", 1); } catch e { errors.push(e.message); }
+        try { format("{:q}", 1); } catch e { errors.push(e.message); }
+        try { format(1); } catch e { errors.push(e.message); }
+        try { format("{:999999999999}", 1); } catch e { errors.push(e.kind); }
+        var word = "x";
+        try { var s = f"{word:d}"; } catch e { errors.push(e.kind); }
+    "#;
+    let result = eval_global(source, "errors");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Unknown format type 'd' for string, \
+         format() has no argument for field 1, given 1 arguments, \
+         format() has no argument for field 5, given 1 arguments, \
+         Unterminated { in format template, Unknown format type 'q' for int, \
+         format() expects a string template, got int, \
+         BuiltinFunctionError, BuiltinFunctionError])"
+    );
+
+    let invalid = vec![
+        ("var x = f\"{}\";", "Empty expression in f-string field"),
+        (
+            "var x = f\"}\";",
+            "Single } in f-string, use }} for a brace",
+        ),
+    ];
+    for (source, message) in invalid {
+        let errors = parser_errors(source.as_bytes());
+        assert!(errors[0].contains(message), "{:?}", errors);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::tests::eval_global;
+use crate::tests::with_large_stack;
+use crate::types::object::Object;
+
+use std::rc::Rc;
+
+#[test]
+pub fn nested_generators_overflow() {
+    with_large_stack(|| {
+        // generators nested deeper than MAX_SANDBOX_DEPTH raise a catchable error:
+        let source = r#"
+            func gen(n) {
+                if (n < 300) { for x in gen(n + 1) => { yield x; } }
+                yield n;
+            }
+            var kind = "";
+            try { for v in gen(0) => {} } catch e { kind = e.kind; }
+        "#;
+        let result = eval_global(source, "kind");
+        assert_eq!(
+            result.unwrap(),
+            Rc::new(Object::Str("CallStackOverflow".to_string()))
+        );
+
+        // shallow nesting still works:
+        let source = r#"
+            func gen(n) {
+                if (n < 20) { for x in gen(n + 1) => { yield x; } }
+                yield n;
+            }
+            var total = 0;
+            for v in gen(0) => { total = total + v; }
+        "#;
+        let result = eval_global(source, "total");
+        assert_eq!(result.unwrap(), Rc::new(Object::Int(210)));
+    });
+}
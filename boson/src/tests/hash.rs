@@ -0,0 +1,84 @@
+use crate::tests::eval_global;
+
+#[test]
+pub fn hash_methods() {
+    let source = r#"
+        var stock = {"apples": 3, "pears": 0};
+        stock["plums"] = 7;
+        var result = [
+            stock.get("kiwis", 0),
+            stock.get("apples"),
+            stock.contains("plums"),
+            stock.delete("pears"),
+            stock.delete("pears"),
+            stock.keys(),
+            stock.items()
+        ];
+
+        stock.update({"apples": 5, "kiwis": 2});
+        result.push(stock.pop("plums"));
+        result.push(stock.pop("plums", 0));
+        result.push(stock.setdefault("figs", 1));
+        result.push(stock.setdefault("figs", 9));
+        result.push(stock.values());
+        result.push({"a": 1, "b": 2}.merge({"b": 3, "c": 4}));
+
+        stock.clear();
+        result.push(len(stock));
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([0, 3, true, true, false, Array([apples, plums]), \
+         Array([Array([apples, 3]), Array([plums, 7])]), 7, 0, 1, 1, Array([5, 2, 1]), \
+         HashTable({a: 1, b: 3, c: 4}), 0])"
+    );
+
+    // a missing key without a default is undefined:
+    let source = r#"
+        var missing = {"a": 1}.get("b");
+    "#;
+    let result = eval_global(source, "missing");
+    assert_eq!(result.unwrap().describe(), "undef");
+}
+
+#[test]
+pub fn insertion_order() {
+    // iteration, keys and printing follow the order keys were first inserted:
+    let source = r#"
+        var order = [];
+        for key in {"z": 1, "a": 2, "m": 3} => {
+            order.push(key);
+        }
+
+        var table = {"a": 1, "b": 2, "c": 3};
+        table.delete("a");
+        table["a"] = 4;
+        table["b"] = 5;
+
+        var result = [order, table.keys(), table];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([z, a, m]), Array([b, c, a]), HashTable({b: 5, c: 3, a: 4})])"
+    );
+}
+
+#[test]
+pub fn hash_method_errors() {
+    let source = r#"
+        var table = {};
+        var errors = [];
+        try { table.pop("missing"); } catch e { errors.push(e.message); }
+        try { table.merge(1); } catch e { errors.push(e.message); }
+        try { table.get(); } catch e { errors.push(e.message); }
+        try { table.missing(); } catch e { errors.push(e.kind); }
+    "#;
+    let result = eval_global(source, "errors");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([pop() key missing not found., merge() expects hashmap as argument, got int, \
+         get() takes 1 to 2 arguments, provided 0., AttributeError])"
+    );
+}
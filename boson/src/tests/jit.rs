@@ -0,0 +1,238 @@
+use crate::api::BosonLang;
+use crate::compiler::BytecodeCompiler;
+use crate::config::ENABLE_JIT;
+use crate::config::JIT_CALL_THRESHOLD;
+use crate::lexer::LexerAPI;
+use crate::parser::Parser;
+use crate::types::object::Object;
+use crate::types::subroutine::Subroutine;
+use crate::vm::jit::BosonJIT;
+use crate::vm::jit::JitType;
+use crate::vm::BosonVM;
+
+use std::env;
+use std::rc::Rc;
+
+// runs a program with the JIT switched on or off, the compiler knows
+// where its globals are:
+fn run(source: &str, jit_enabled: bool) -> (BytecodeCompiler, BosonVM) {
+    let lexer = LexerAPI::new_from_buffer(source.as_bytes().to_vec());
+    let mut parser = Parser::new_from_lexer(lexer);
+    let program = parser.parse();
+    assert!(program.is_ok());
+
+    let mut compiler = BytecodeCompiler::new();
+    let bytecode = compiler.compile(&program.unwrap());
+    assert!(bytecode.is_ok());
+
+    let mut vm = BosonVM::new(&bytecode.unwrap());
+    vm.jit = BosonJIT::new_enabled(jit_enabled);
+
+    let mut platform = BosonLang::prepare_native_platform();
+    let result = vm.eval_bytecode(&mut platform, true, false);
+    assert!(result.is_ok());
+
+    return (compiler, vm);
+}
+
+fn get_global(compiler: &mut BytecodeCompiler, vm: &BosonVM, name: &str) -> Rc<Object> {
+    let symbol = compiler.symbol_table.resolve_symbol(name).unwrap();
+    return vm.globals.get(symbol.pos).unwrap();
+}
+
+fn get_subroutine(compiler: &mut BytecodeCompiler, vm: &BosonVM, name: &str) -> Rc<Subroutine> {
+    match get_global(compiler, vm, name).as_ref() {
+        Object::ClosureContext(closure) => return closure.compiled_fn.clone(),
+        Object::Subroutine(subroutine) => return subroutine.clone(),
+        other => panic!("{} is not a function: {}", name, other.describe()),
+    }
+}
+
+fn ints(values: &[i64]) -> Vec<Rc<Object>> {
+    return values.iter().map(|v| Rc::new(Object::Int(*v))).collect();
+}
+
+const FUNCTIONS: &str = r#"
+    func add(a, b) { return a + b; }
+    func div(a, b) { return a / b; }
+    func rem(a, b) { return a % b; }
+    func less(a, b) { return a < b; }
+"#;
+
+#[test]
+pub fn call_threshold() {
+    let (mut compiler, vm) = run(FUNCTIONS, false);
+    let add = get_subroutine(&mut compiler, &vm, "add");
+
+    // the interpreter runs the first JIT_CALL_THRESHOLD - 1 calls:
+    let mut jit = BosonJIT::new_enabled(true);
+    for _ in 1..JIT_CALL_THRESHOLD {
+        assert_eq!(jit.execute(&add, &ints(&[1, 2]), &vm.constants), None);
+    }
+
+    assert_eq!(jit.get_native_variants(&add).len(), 0);
+    assert_eq!(
+        jit.execute(&add, &ints(&[1, 2]), &vm.constants),
+        Some(Rc::new(Object::Int(3)))
+    );
+    assert_eq!(
+        jit.get_native_variants(&add),
+        vec![vec![JitType::Int, JitType::Int]]
+    );
+
+    // a disabled JIT never compiles:
+    let mut disabled = BosonJIT::new_enabled(false);
+    for _ in 0..2 * JIT_CALL_THRESHOLD {
+        assert_eq!(disabled.execute(&add, &ints(&[1, 2]), &vm.constants), None);
+    }
+    assert_eq!(disabled.get_native_variants(&add).len(), 0);
+
+    // BOSON_JIT=0 switches it off at runtime:
+    env::set_var("BOSON_JIT", "0");
+    assert!(!BosonJIT::new().is_enabled());
+    env::remove_var("BOSON_JIT");
+    assert_eq!(BosonJIT::new().is_enabled(), ENABLE_JIT);
+}
+
+#[test]
+pub fn type_guards() {
+    let (mut compiler, vm) = run(FUNCTIONS, false);
+    let add = get_subroutine(&mut compiler, &vm, "add");
+    let less = get_subroutine(&mut compiler, &vm, "less");
+
+    let mut jit = BosonJIT::new_enabled(true);
+    for _ in 0..JIT_CALL_THRESHOLD {
+        jit.execute(&add, &ints(&[1, 2]), &vm.constants);
+        jit.execute(&less, &ints(&[1, 2]), &vm.constants);
+    }
+
+    // every combination of argument types gets its own native version:
+    let floats = vec![Rc::new(Object::Float(1.5)), Rc::new(Object::Float(2.5))];
+    assert_eq!(
+        jit.execute(&add, &floats, &vm.constants),
+        Some(Rc::new(Object::Float(4.0)))
+    );
+
+    let mixed = vec![Rc::new(Object::Int(1)), Rc::new(Object::Float(0.5))];
+    assert_eq!(
+        jit.execute(&add, &mixed, &vm.constants),
+        Some(Rc::new(Object::Float(1.5)))
+    );
+    assert_eq!(
+        jit.execute(&less, &mixed, &vm.constants),
+        Some(Rc::new(Object::Bool(false)))
+    );
+
+    // types the native code cannot handle stay in the interpreter:
+    let bools = vec![Rc::new(Object::Bool(true)), Rc::new(Object::Bool(false))];
+    assert_eq!(jit.execute(&add, &bools, &vm.constants), None);
+
+    let strings = vec![
+        Rc::new(Object::Str("a".to_string())),
+        Rc::new(Object::Str("b".to_string())),
+    ];
+    assert_eq!(jit.execute(&add, &strings, &vm.constants), None);
+
+    assert_eq!(
+        jit.get_native_variants(&add),
+        vec![
+            vec![JitType::Int, JitType::Int],
+            vec![JitType::Float, JitType::Float],
+            vec![JitType::Int, JitType::Float],
+        ]
+    );
+    assert_eq!(
+        jit.get_native_variants(&less),
+        vec![
+            vec![JitType::Int, JitType::Int],
+            vec![JitType::Int, JitType::Float],
+        ]
+    );
+}
+
+#[test]
+pub fn deopt() {
+    let (mut compiler, vm) = run(FUNCTIONS, false);
+    let add = get_subroutine(&mut compiler, &vm, "add");
+    let div = get_subroutine(&mut compiler, &vm, "div");
+    let rem = get_subroutine(&mut compiler, &vm, "rem");
+
+    let mut jit = BosonJIT::new_enabled(true);
+    for _ in 0..JIT_CALL_THRESHOLD {
+        jit.execute(&add, &ints(&[1, 2]), &vm.constants);
+        jit.execute(&div, &ints(&[1, 2]), &vm.constants);
+        jit.execute(&rem, &ints(&[1, 2]), &vm.constants);
+    }
+
+    // overflow and division by zero go back to the interpreter:
+    assert_eq!(
+        jit.execute(&add, &ints(&[i64::MAX, 1]), &vm.constants),
+        None
+    );
+    assert_eq!(jit.execute(&div, &ints(&[1, 0]), &vm.constants), None);
+    assert_eq!(jit.execute(&rem, &ints(&[1, 0]), &vm.constants), None);
+
+    // the native version is kept for the next calls:
+    assert_eq!(
+        jit.execute(&add, &ints(&[i64::MAX - 1, 1]), &vm.constants),
+        Some(Rc::new(Object::Int(i64::MAX)))
+    );
+    assert_eq!(
+        jit.execute(&div, &ints(&[1, 4]), &vm.constants),
+        Some(Rc::new(Object::Float(0.25)))
+    );
+    assert_eq!(
+        jit.execute(&rem, &ints(&[-7, 2]), &vm.constants),
+        Some(Rc::new(Object::Int(1)))
+    );
+    assert_eq!(
+        jit.execute(&rem, &ints(&[i64::MIN, -1]), &vm.constants),
+        Some(Rc::new(Object::Int(0)))
+    );
+}
+
+#[test]
+pub fn same_results() {
+    let source = format!(
+        r#"
+        {}
+        func mix(a, b) {{
+            if (a > b) {{
+                return a * b - 1;
+            }}
+            return a - b;
+        }}
+
+        var total = 0;
+        var float_total = 0.0;
+        var compared = 0;
+        for i in range(0, 300) => {{
+            total = total + add(i, i * 2) + rem(0 - i, 7) + mix(i, 150);
+            float_total = float_total + div(i, 3) + add(i * 0.5, 1);
+            if (less(i, 100.5)) {{
+                compared = compared + 1;
+            }}
+        }}
+
+        var overflow = add(9223372036854775807, 1);
+        var error = "";
+        try {{ div(1, 0); }} catch e {{ error = e.kind; }}
+        var result = [total, float_total, compared, overflow, error, rem(-7, 2), mix(7, 3)];
+        "#,
+        FUNCTIONS
+    );
+
+    let (mut interpreted_compiler, interpreted_vm) = run(&source, false);
+    let (mut jit_compiler, jit_vm) = run(&source, true);
+
+    let interpreted = get_global(&mut interpreted_compiler, &interpreted_vm, "result");
+    let jitted = get_global(&mut jit_compiler, &jit_vm, "result");
+    assert_eq!(interpreted.describe(), jitted.describe());
+
+    // the JIT run went through native code:
+    let add = get_subroutine(&mut jit_compiler, &jit_vm, "add");
+    assert_eq!(jit_vm.jit.get_native_variants(&add).len(), 2);
+
+    let add = get_subroutine(&mut interpreted_compiler, &interpreted_vm, "add");
+    assert_eq!(interpreted_vm.jit.get_native_variants(&add).len(), 0);
+}
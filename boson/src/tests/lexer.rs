@@ -0,0 +1,82 @@
+use crate::lexer::ProgramLexer;
+use crate::lexer::SymbolKind;
+use crate::lexer::TokenKind;
+
+use num_bigint::BigInt;
+
+// the tokens of the source, spaces left out:
+fn lex(source: &str) -> Vec<TokenKind> {
+    let mut lexer = ProgramLexer::new_from_buffer(source.as_bytes().to_vec());
+    let mut tokens = vec![];
+    loop {
+        let token = lexer.next_token();
+        match token {
+            TokenKind::EOF => break,
+            TokenKind::Empty => continue,
+            _ => tokens.push(token),
+        }
+    }
+
+    return tokens;
+}
+
+#[test]
+pub fn int_literals() {
+    assert_eq!(
+        lex("42 0x2A 0X2a 0b101010 0o52 1_000_000 0xFF_FF"),
+        vec![
+            TokenKind::Integer(42),
+            TokenKind::Integer(42),
+            TokenKind::Integer(42),
+            TokenKind::Integer(42),
+            TokenKind::Integer(42),
+            TokenKind::Integer(1000000),
+            TokenKind::Integer(65535),
+        ]
+    );
+
+    // ints beyond the i64 range are big integers:
+    assert_eq!(
+        lex("9223372036854775808 0xFFFFFFFFFFFFFFFF"),
+        vec![
+            TokenKind::BigInteger(BigInt::from(9223372036854775808u64)),
+            TokenKind::BigInteger(BigInt::from(u64::MAX)),
+        ]
+    );
+}
+
+#[test]
+pub fn float_literals() {
+    assert_eq!(
+        lex("1.5 2e3 2E-3 1_0.2_5 6.02e+23"),
+        vec![
+            TokenKind::Float(1.5),
+            TokenKind::Float(2000.0),
+            TokenKind::Float(0.002),
+            TokenKind::Float(10.25),
+            TokenKind::Float(6.02e23),
+        ]
+    );
+
+    // a range of ints is not a float:
+    assert_eq!(
+        lex("1..5"),
+        vec![
+            TokenKind::Integer(1),
+            TokenKind::Operator(SymbolKind::SRange),
+            TokenKind::Integer(5),
+        ]
+    );
+}
+
+#[test]
+pub fn invalid_literals() {
+    let invalid = vec!["1__0", "10_", "0b102", "0x", "0o8"];
+    for source in invalid {
+        let tokens = lex(source);
+        let has_error = tokens
+            .iter()
+            .any(|token| matches!(token, TokenKind::Error(_)));
+        assert!(has_error, "{} should not lex", source);
+    }
+}
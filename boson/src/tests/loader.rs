@@ -0,0 +1,144 @@
+use crate::api::BosonLang;
+use crate::compiler::loader::BytecodeLoader;
+use crate::compiler::loader::BytecodeWriter;
+use crate::compiler::BytecodeCompiler;
+use crate::compiler::CompiledBytecode;
+use crate::lexer::LexerAPI;
+use crate::parser::Parser;
+use crate::types::object::Object;
+use crate::types::subroutine::Subroutine;
+use crate::vm::BosonVM;
+
+use std::env;
+use std::fs;
+use std::process;
+use std::rc::Rc;
+
+fn compile_source(source: &str) -> (BytecodeCompiler, CompiledBytecode) {
+    let lexer = LexerAPI::new_from_buffer(source.as_bytes().to_vec());
+    let mut parser = Parser::new_from_lexer(lexer);
+    let program = parser.parse();
+    assert!(program.is_ok());
+
+    let mut compiler = BytecodeCompiler::new();
+    let bytecode = compiler.compile(&program.unwrap());
+    assert!(bytecode.is_ok());
+    return (compiler, bytecode.unwrap());
+}
+
+// a bytecode file in the temporary directory, unique to this process:
+fn bytecode_path(name: &str) -> String {
+    let path = env::temp_dir().join(format!("boson_{}_{}.b", name, process::id()));
+    return path.to_str().unwrap().to_string();
+}
+
+fn save_and_load(bytecode: &CompiledBytecode, name: &str) -> CompiledBytecode {
+    let path = bytecode_path(name);
+    let saved = BytecodeWriter::new().save_bytecode(path.clone(), bytecode);
+    assert!(saved.is_ok());
+
+    let loaded = BytecodeLoader::new(path.clone()).load_bytecode();
+    let _ = fs::remove_file(&path);
+    assert!(loaded.is_ok());
+    return loaded.unwrap();
+}
+
+fn find_subroutine(bytecode: &CompiledBytecode, name: &str) -> Rc<Subroutine> {
+    for obj in bytecode.constant_pool.objects.iter() {
+        if let Object::Subroutine(sub) = obj.as_ref() {
+            if sub.name == name {
+                return sub.clone();
+            }
+        }
+    }
+
+    panic!("subroutine {} not found", name);
+}
+
+// runs loaded bytecode and returns a global variable, resolved by the compiler:
+fn eval_loaded(
+    compiler: &mut BytecodeCompiler,
+    bytecode: &CompiledBytecode,
+    name: &str,
+) -> Rc<Object> {
+    let mut vm = BosonVM::new(bytecode);
+    let mut platform = BosonLang::prepare_native_platform();
+    let result = vm.eval_bytecode(&mut platform, true, false);
+    assert!(result.is_ok());
+
+    let symbol = compiler.symbol_table.resolve_symbol(name).unwrap();
+    return vm.globals.get(symbol.pos).unwrap();
+}
+
+#[test]
+pub fn pure_flag_round_trip() {
+    let source = r#"
+        pure func square(x) { return x * x; }
+        func plain(x) { return x; }
+        var result = square(12) + square(12) + plain(1);
+    "#;
+    let (mut compiler, bytecode) = compile_source(source);
+    let loaded = save_and_load(&bytecode, "pure");
+
+    assert!(find_subroutine(&loaded, "square").is_pure);
+    assert!(!find_subroutine(&loaded, "plain").is_pure);
+    assert_eq!(
+        eval_loaded(&mut compiler, &loaded, "result"),
+        Rc::new(Object::Int(289))
+    );
+}
+
+#[test]
+pub fn parameters_round_trip() {
+    let source = r#"
+        func greet(name, greeting = "hi", ...rest) { return greeting + " " + name + string(len(rest)); }
+        var result = greet("a") + "," + greet("b", "yo", 1, 2) + "," + greet(greeting="hey", name="c");
+    "#;
+    let (mut compiler, bytecode) = compile_source(source);
+    let loaded = save_and_load(&bytecode, "parameters");
+
+    let greet = find_subroutine(&loaded, "greet");
+    assert_eq!(greet.num_parameters, 3);
+    assert_eq!(greet.num_defaults, 1);
+    assert!(greet.is_variadic);
+    assert_eq!(
+        greet.parameter_names,
+        vec![
+            "name".to_string(),
+            "greeting".to_string(),
+            "rest".to_string()
+        ]
+    );
+
+    assert_eq!(
+        eval_loaded(&mut compiler, &loaded, "result"),
+        Rc::new(Object::Str("hi a0,yo b2,hey c0".to_string()))
+    );
+}
+
+#[test]
+pub fn version_mismatch() {
+    let (_, bytecode) = compile_source("var x = 1;");
+    let path = bytecode_path("version");
+    let saved = BytecodeWriter::new().save_bytecode(path.clone(), &bytecode);
+    assert!(saved.is_ok());
+
+    // the version follows the magic number in the header:
+    let mut content = fs::read(&path).unwrap();
+    content[8..16].copy_from_slice(&999u64.to_ne_bytes());
+    fs::write(&path, &content).unwrap();
+
+    let loaded = BytecodeLoader::new(path.clone()).load_bytecode();
+    let _ = fs::remove_file(&path);
+    assert!(loaded.is_err());
+    assert!(loaded
+        .err()
+        .unwrap()
+        .starts_with("Incompatible bytecode version 999"));
+
+    // a file shorter than the header:
+    fs::write(&path, &content[0..4]).unwrap();
+    let loaded = BytecodeLoader::new(path.clone()).load_bytecode();
+    let _ = fs::remove_file(&path);
+    assert!(loaded.is_err());
+}
@@ -0,0 +1,93 @@
+use crate::compiler::errors::CompilerErrorKind;
+use crate::tests::compile_error;
+use crate::tests::eval_global;
+
+#[test]
+pub fn match_patterns() {
+    let source = r#"
+        func classify(value) {
+            return match value {
+                0 => "zero",
+                1 | 2 | 3 => "small",
+                4..10 => "below ten",
+                10..=100 => "up to a hundred",
+                string => "a string",
+                float => "a float",
+                [x, y] => f"a pair {x}, {y}",
+                [x, ...rest] => f"{x} and {len(rest)} more",
+                {"path": "/", "method": "GET"} => "index",
+                {"path": p} if len(p) > 3 => "long path",
+                _ => "something else"
+            };
+        }
+
+        var values = [
+            0, 2, 7, 10, 100, 101, "s", 1.5, [1, 2], [1, 2, 3],
+            {"path": "/", "method": "GET"}, {"path": "/abcd"}, {"path": "/a"}
+        ];
+        var result = values.map(classify);
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([zero, small, below ten, up to a hundred, up to a hundred, something else, \
+         a string, a float, a pair 1, 2, 1 and 2 more, index, long path, something else])"
+    );
+}
+
+#[test]
+pub fn match_statements() {
+    let source = r#"
+        var x = 5;
+        var hidden = match 3 { x => x * 2 };
+
+        var visited = [];
+        for i in range(0, 5) => {
+            match i {
+                1 => continue,
+                3 => break,
+                _ => { visited.push(i); }
+            }
+        }
+
+        # a statement without a matching arm does nothing:
+        var untouched = 0;
+        match 9 {
+            1 => { untouched = 1; }
+        }
+
+        func check(v) {
+            match v {
+                0 => throw "zero",
+                _ => return v
+            }
+        }
+        var message = "";
+        try { check(0); } catch e { message = e.message; }
+
+        var result = [hidden, x, visited, untouched, check(4), message];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([6, 5, Array([0, 2]), 0, 4, zero])"
+    );
+}
+
+#[test]
+pub fn match_errors() {
+    let invalid = vec![
+        "var y = match 1 { 1 => 2 };",
+        "var y = match 1 { 1 => 2, z if z > 1 => 3 };",
+        "var y = match 1 { 1 => { 2 }, _ => 3 };",
+    ];
+
+    for source in invalid {
+        assert_eq!(
+            compile_error(source),
+            Some(CompilerErrorKind::InvalidMatch),
+            "{}",
+            source
+        );
+    }
+}
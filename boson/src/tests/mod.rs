@@ -1 +1,109 @@
+pub mod alu;
+pub mod arrays;
+pub mod assignment;
+pub mod classes;
+pub mod closures;
+pub mod destructure;
+pub mod diagnostics;
+pub mod exceptions;
+pub mod format;
+pub mod generators;
+pub mod hash;
+pub mod jit;
+pub mod lexer;
+pub mod loader;
+pub mod matching;
+pub mod modules;
+pub mod sendable;
+pub mod slices;
+pub mod strings;
+pub mod sync;
+pub mod traceback;
 pub mod types;
+pub mod unicode;
+
+use crate::api::BosonLang;
+use crate::compiler::errors::CompilerErrorKind;
+use crate::compiler::BytecodeCompiler;
+use crate::lexer::LexerAPI;
+use crate::parser::Parser;
+use crate::types::object::Object;
+use crate::vm::errors::VMError;
+use crate::vm::BosonVM;
+
+use std::rc::Rc;
+use std::thread;
+
+// runs a program and returns the value of one of its global variables:
+pub fn eval_global(source: &str, name: &str) -> Option<Rc<Object>> {
+    let mut lang = BosonLang::new_from_buffer(source.as_bytes().to_vec());
+    let result = lang.eval_state();
+    if result.is_none() {
+        return None;
+    }
+
+    let symbol = lang.compiler.symbol_table.resolve_symbol(name);
+    if symbol.is_none() {
+        return None;
+    }
+
+    return lang.vm.as_ref().unwrap().globals.get(symbol.unwrap().pos);
+}
+
+// compiles a program which parses and returns the kind of its compile error:
+pub fn compile_error(source: &str) -> Option<CompilerErrorKind> {
+    let lexer = LexerAPI::new_from_buffer(source.as_bytes().to_vec());
+    let mut parser = Parser::new_from_lexer(lexer);
+    let program = parser.parse();
+    assert!(program.is_ok());
+
+    let mut compiler = BytecodeCompiler::new();
+    let result = compiler.compile(&program.unwrap());
+    if result.is_ok() {
+        return None;
+    }
+
+    return Some(result.unwrap_err().t);
+}
+
+// runs a program which fails and returns its uncaught runtime error:
+pub fn runtime_error(source: &str) -> VMError {
+    let lexer = LexerAPI::new_from_buffer(source.as_bytes().to_vec());
+    let mut parser = Parser::new_from_lexer(lexer);
+    let program = parser.parse();
+    assert!(program.is_ok());
+
+    let mut compiler = BytecodeCompiler::new();
+    let bytecode = compiler.compile(&program.unwrap());
+    assert!(bytecode.is_ok());
+
+    let mut vm = BosonVM::new(&bytecode.unwrap());
+    let mut platform = BosonLang::prepare_native_platform();
+    let result = vm.eval_bytecode(&mut platform, true, false);
+    assert!(result.is_err());
+
+    return result.unwrap_err();
+}
+
+// parses a program which does not parse and returns its rendered errors:
+pub fn parser_errors(source: &[u8]) -> Vec<String> {
+    let lexer = LexerAPI::new_from_buffer(source.to_vec());
+    let mut parser = Parser::new_from_lexer(lexer);
+    let program = parser.parse();
+    assert!(program.is_err());
+
+    return parser.get_formatted_errors();
+}
+
+/*
+    Runs the test on a thread with a bigger native stack, the default one of
+    the test threads is too small for MAX_SANDBOX_DEPTH nested sandbox VMs
+    in debug builds.
+*/
+pub fn with_large_stack<F: FnOnce() + Send + 'static>(test: F) {
+    let handle = thread::Builder::new()
+        .stack_size(64 << 20)
+        .spawn(test)
+        .unwrap();
+    handle.join().unwrap();
+}
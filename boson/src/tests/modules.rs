@@ -0,0 +1,116 @@
+use crate::compiler::errors::CompilerErrorKind;
+use crate::tests::compile_error;
+use crate::tests::eval_global;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// writes the module files of a test into a directory of its own:
+fn write_modules(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("boson_{}_{}", test, std::process::id()));
+    for (name, source) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, source).unwrap();
+    }
+
+    return dir;
+}
+
+#[test]
+pub fn imports() {
+    let dir = write_modules(
+        "imports",
+        &[
+            (
+                "lib/geometry.np",
+                r#"
+                use "shapes" as shapes;
+                const PI = 3;
+                var items = [];
+                func area(r) { return PI * r * r; }
+                func kind() { return shapes.CIRCLE; }
+                "#,
+            ),
+            ("lib/shapes.np", r#"const CIRCLE = "circle";"#),
+        ],
+    );
+
+    // the extension is optional, both names refer to the same module, which
+    // runs only once, so both see the same globals:
+    let source = format!(
+        r#"
+        use "{0}/lib/geometry" as geo;
+        use "{0}/lib/geometry.np" as again;
+        geo.items.push(1);
+        var result = [geo.area(2), geo.kind(), geo.__name__, again.items, type_of(geo)];
+        "#,
+        dir.display()
+    );
+    let result = eval_global(&source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([12, circle, geometry, Array([1]), module])"
+    );
+
+    // members are read only and missing ones raise an error:
+    let source = format!(
+        r#"
+        use "{}/lib/geometry" as geo;
+        var kinds = [];
+        try {{ geo.PI = 4; }} catch e {{ kinds.push(e.kind); }}
+        try {{ geo.missing(); }} catch e {{ kinds.push(e.kind); }}
+        try {{ var x = geo.nothing; }} catch e {{ kinds.push(e.kind); }}
+        var result = [kinds, geo.PI];
+        "#,
+        dir.display()
+    );
+    let result = eval_global(&source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([AttributeError, AttributeError, AttributeError]), 3])"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+pub fn import_errors() {
+    let dir = write_modules(
+        "import_errors",
+        &[
+            ("a.np", r#"use "b" as b;"#),
+            ("b.np", r#"use "a" as a;"#),
+            ("broken.np", "var = ;"),
+            ("ok.np", "const X = 1;"),
+        ],
+    );
+
+    let source = format!(r#"use "{}/a" as a;"#, dir.display());
+    assert_eq!(
+        compile_error(&source),
+        Some(CompilerErrorKind::CircularImport)
+    );
+
+    let source = format!(r#"use "{}/missing" as m;"#, dir.display());
+    assert_eq!(compile_error(&source), Some(CompilerErrorKind::ImportError));
+
+    let source = format!(r#"use "{}/broken" as m;"#, dir.display());
+    assert_eq!(compile_error(&source), Some(CompilerErrorKind::ImportError));
+
+    // only at the top-level, and the alias must be a new name:
+    let source = format!(r#"func f() {{ use "{}/ok" as m; }}"#, dir.display());
+    assert_eq!(
+        compile_error(&source),
+        Some(CompilerErrorKind::InvalidScope)
+    );
+
+    let source = format!(r#"var m = 1; use "{}/ok" as m;"#, dir.display());
+    assert_eq!(
+        compile_error(&source),
+        Some(CompilerErrorKind::SymbolAlreadyExist)
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
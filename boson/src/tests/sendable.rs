@@ -0,0 +1,156 @@
+use crate::types;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+
+use num_bigint::BigInt;
+use types::array::Array;
+use types::object::Object;
+use types::sendable::SendableContext;
+
+use crate::tests::eval_global;
+
+fn new_array(elements: Vec<Rc<Object>>) -> Rc<Object> {
+    return Rc::new(Object::Array(RefCell::new(Array {
+        name: "test".to_string(),
+        elements: elements,
+    })));
+}
+
+fn round_trip(obj: &Rc<Object>) -> Rc<Object> {
+    let mut context = SendableContext::new();
+    let encoded = context.encode(obj).unwrap();
+    return context.decoder().decode(&encoded);
+}
+
+#[test]
+pub fn scalar_round_trip() {
+    let objects = vec![
+        Object::Noval,
+        Object::Int(-42),
+        Object::BigInt(BigInt::from(i64::MAX) * 4),
+        Object::Bool(true),
+        Object::Char('ä'),
+        Object::Str("thread".to_string()),
+        Object::Float(2.5),
+        Object::Byte(255),
+    ];
+
+    for obj in objects {
+        let obj = Rc::new(obj);
+        assert_eq!(round_trip(&obj), obj);
+    }
+}
+
+#[test]
+pub fn container_round_trip() {
+    let mut table = types::hash::HashTable::new("test".to_string());
+    table.set(
+        Rc::new(Object::Str("list".to_string())),
+        new_array(vec![Rc::new(Object::Int(1)), Rc::new(Object::Int(2))]),
+    );
+    table.set(Rc::new(Object::Int(3)), Rc::new(Object::Float(0.5)));
+    let obj = Rc::new(Object::HashTable(RefCell::new(table)));
+
+    let decoded = round_trip(&obj);
+    assert_eq!(decoded, obj);
+    // the copy does not share anything with the original:
+    assert!(!Rc::ptr_eq(&decoded, &obj));
+}
+
+#[test]
+pub fn shared_containers() {
+    // an array referred twice is decoded once:
+    let inner = new_array(vec![Rc::new(Object::Int(7))]);
+    let outer = new_array(vec![inner.clone(), inner.clone()]);
+
+    let decoded = round_trip(&outer);
+    if let Object::Array(arr) = decoded.as_ref() {
+        let elements = &arr.borrow().elements;
+        assert!(Rc::ptr_eq(&elements[0], &elements[1]));
+        assert_eq!(elements[0], inner);
+    } else {
+        panic!("decoded object is not an array");
+    }
+
+    // an array that contains itself keeps the cycle:
+    let cyclic = new_array(vec![]);
+    if let Object::Array(arr) = cyclic.as_ref() {
+        arr.borrow_mut().elements.push(cyclic.clone());
+    }
+
+    let decoded = round_trip(&cyclic);
+    if let Object::Array(arr) = decoded.as_ref() {
+        let element = arr.borrow().elements[0].clone();
+        assert!(Rc::ptr_eq(&element, &decoded));
+        arr.borrow_mut().elements.clear();
+    } else {
+        panic!("decoded object is not an array");
+    }
+
+    if let Object::Array(arr) = cyclic.as_ref() {
+        arr.borrow_mut().elements.clear();
+    }
+}
+
+#[test]
+pub fn thread_round_trip() {
+    let obj = new_array(vec![
+        Rc::new(Object::Str("sent".to_string())),
+        Rc::new(Object::BigInt(BigInt::from(u64::MAX) * 3)),
+    ]);
+
+    let mut context = SendableContext::new();
+    let encoded = context.encode(&obj).unwrap();
+
+    // decode on another thread and send a copy back:
+    let handle = thread::spawn(move || {
+        let decoded = context.decoder().decode(&encoded);
+        let mut back_context = SendableContext::new();
+        let back_encoded = back_context.encode(&decoded).unwrap();
+        return (back_context, back_encoded);
+    });
+
+    let (back_context, back_encoded) = handle.join().unwrap();
+    assert_eq!(back_context.decoder().decode(&back_encoded), obj);
+}
+
+#[test]
+pub fn generators_not_sendable() {
+    // a generator passed to a thread raises an error instead of arriving empty:
+    let source = r#"
+        func gen() { yield 1; yield 2; }
+        func consume(it) { var n = 0; for x in it => { n = n + x; } return n; }
+        var message = "";
+        try { const th = thread consume(gen()); wait(th); } catch e { message = e.message; }
+    "#;
+    let result = eval_global(source, "message");
+    assert_eq!(
+        result.unwrap(),
+        Rc::new(Object::Str("generator is not sendable".to_string()))
+    );
+
+    // channels refuse them too:
+    let source = r#"
+        func gen() { yield 1; }
+        const ch = channel();
+        var message = "";
+        try { ch.send(gen()); } catch e { message = e.message; }
+    "#;
+    let result = eval_global(source, "message");
+    assert_eq!(
+        result.unwrap(),
+        Rc::new(Object::Str("generator is not sendable".to_string()))
+    );
+
+    // the iterator of a loop over a generator is not copied to the thread:
+    let source = r#"
+        func gen() { yield 1; yield 2; }
+        func add(a, b) { return a + b; }
+        var total = 0;
+        for v in gen() => { const th = thread add(total, v); total = wait(th); }
+    "#;
+    let result = eval_global(source, "total");
+    assert_eq!(result.unwrap(), Rc::new(Object::Int(3)));
+}
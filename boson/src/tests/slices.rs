@@ -0,0 +1,70 @@
+use crate::tests::eval_global;
+
+#[test]
+pub fn get_slices() {
+    let source = r#"
+        var xs = [0, 1, 2, 3, 4, 5];
+        var s = "héllo";
+        var b = b"abcdef";
+        var result = [
+            xs[1:4], xs[:-1], xs[::2], xs[::-1], xs[-2:], xs[4:1], xs[10:],
+            s[1:3], s[::-1], s[::2],
+            b[1:3], b[::-2]
+        ];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([1, 2, 3]), Array([0, 1, 2, 3, 4]), Array([0, 2, 4]), \
+         Array([5, 4, 3, 2, 1, 0]), Array([4, 5]), Array([]), Array([]), él, olléh, hlo, \
+         RawBuffer(size=2, elements=[98, 99]), RawBuffer(size=3, elements=[102, 100, 98])])"
+    );
+}
+
+#[test]
+pub fn set_slices() {
+    let source = r#"
+        var xs = [0, 1, 2, 3, 4, 5];
+        xs[1:3] = [10, 20, 30];
+        var grown = xs.slice(0);
+        xs[1:4] = [9];
+        var shrunk = xs.slice(0);
+        xs[::2] = [7, 7, 7];
+
+        var b = b"abcdef";
+        b[0:2] = b"zz";
+
+        var result = [grown, shrunk, xs, b];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([0, 10, 20, 30, 3, 4, 5]), Array([0, 9, 3, 4, 5]), \
+         Array([7, 9, 7, 4, 7]), RawBuffer(size=6, elements=[122, 122, 99, 100, 101, 102])])"
+    );
+}
+
+#[test]
+pub fn slice_errors() {
+    let source = r#"
+        var xs = [0, 1, 2, 3, 4, 5];
+        var s = "hello";
+        var n = 5;
+        var errors = [];
+        try { xs[::0]; } catch e { errors.push(e.message); }
+        try { xs[::2] = [1]; } catch e { errors.push(e.message); }
+        try { xs[1:2] = 5; } catch e { errors.push(e.message); }
+        try { s[0:1] = "x"; } catch e { errors.push(e.message); }
+        try { xs["a":2]; } catch e { errors.push(e.message); }
+        try { n[0:1]; } catch e { errors.push(e.kind); }
+    "#;
+    let result = eval_global(source, "errors");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Slice step cannot be zero, \
+         Cannot assign 1 elements to an extended slice of 3 elements, \
+         Cannot assign int to a slice of Array, expected Array, \
+         Object of type string does not support slice assignment, \
+         Slice start must be an integer, got string, IndexError])"
+    );
+}
@@ -0,0 +1,108 @@
+use crate::tests::eval_global;
+
+#[test]
+pub fn string_methods() {
+    let source = r#"
+        const line = "  name=boson, kind=lang  ";
+        var result = [
+            line.trim().split(", "),
+            "-".join(["a", 1, 'c']),
+            "hello".upper().pad_start(8, '.'),
+            "boson".find("so"),
+            "boson".length,
+            "a b".split(),
+            "a,b,c".split(",", 1),
+            "aaa".replace("a", "b", 2),
+            "abc".rfind("z"),
+            "abcabc".count("bc")
+        ];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([name=boson, kind=lang]), a-1-c, ...HELLO, 2, 5, Array([a, b]), \
+         Array([a, b,c]), bba, -1, 2])"
+    );
+
+    let source = r#"
+        var result = [
+            "ab".repeat(3),
+            "x".pad_end(3, '-'),
+            "x".center(5, '*'),
+            "hello world".capitalize(),
+            "a\nb".lines(),
+            "boson".starts_with("bo"),
+            "boson".ends_with("on"),
+            "  x ".trim_start() + "|",
+            "|" + " x  ".trim_end()
+        ];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([ababab, x--, **x**, Hello world, Array([a, b]), true, true, x |, | x])"
+    );
+}
+
+#[test]
+pub fn unicode_positions() {
+    // positions and lengths count characters, not bytes:
+    let source = r#"
+        var result = ["héllo".find("l"), "héllo".length, "ÄB".lower(), "héj".chars()];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([2, 5, äb, Array([h, é, j])])"
+    );
+}
+
+#[test]
+pub fn classification() {
+    let source = r#"
+        var result = [
+            "42".is_digit(),
+            "a1".is_alpha(),
+            "ab1".is_alnum(),
+            " \t".is_space(),
+            "AB".is_upper(),
+            "ab".is_lower(),
+            "".is_digit(),
+            attrs("text").contains("pad_start")
+        ];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([true, false, true, true, true, true, false, true])"
+    );
+}
+
+#[test]
+pub fn string_method_errors() {
+    let source = r#"
+        var errors = [];
+        try { "x".upper(1); } catch e { errors.push(e.message); }
+        try { "x".missing(); } catch e { errors.push(e.message); }
+        try { "x".repeat(-1); } catch e { errors.push(e.message); }
+        try { "x".pad_start(3, "ab"); } catch e { errors.push(e.message); }
+        try { "a".split(""); } catch e { errors.push(e.message); }
+        try { "a".find(1); } catch e { errors.push(e.message); }
+
+        var kind = "";
+        try { "x".repeat(999999999999); } catch e { kind = e.kind; }
+    "#;
+    let result = eval_global(source, "errors");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([upper() takes 0 argument(s), provided 1, \
+         Attribute missing not found for type string, \
+         repeat() count cannot be negative, got -1, \
+         pad_start() expects a single character as fill, got ab, \
+         split() separator cannot be empty, \
+         find() expects string as argument 1, got int])"
+    );
+
+    let result = eval_global(source, "kind");
+    assert_eq!(result.unwrap().describe(), "AttributeError");
+}
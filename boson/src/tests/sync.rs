@@ -0,0 +1,99 @@
+use crate::tests::eval_global;
+
+#[test]
+pub fn worker_threads() {
+    // two workers share the jobs channel, the counter and the mutex:
+    let source = r#"
+        const jobs = channel(2);
+        const results = channel();
+        const count = atomic(0);
+        const total = mutex(0);
+
+        func worker() {
+            var job = jobs.recv();
+            while (job != none) {
+                count.incr();
+                total.lock();
+                total.set(total.get() + job);
+                total.unlock();
+                results.send(job * 2);
+                job = jobs.recv();
+            }
+        }
+
+        const first = thread worker();
+        const second = thread worker();
+        for i in range(1, 11) => {
+            jobs.send(i);
+        }
+        jobs.close();
+        wait(first);
+        wait(second);
+
+        var doubled = 0;
+        var received = results.try_recv();
+        while (received != none) {
+            doubled = doubled + received;
+            received = results.try_recv();
+        }
+
+        var result = [count.get(), total.get(), doubled, jobs.is_closed(), results.len()];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(result.unwrap().describe(), "Array([10, 55, 110, true, 0])");
+}
+
+#[test]
+pub fn atomics_and_mutexes() {
+    let source = r#"
+        const counter = atomic(5);
+        var steps = [counter.add(3), counter.sub(1), counter.decr()];
+        steps.push(counter.cas(6, 10));
+        steps.push(counter.cas(1, 2));
+        steps.push(counter.get());
+
+        # stored values are copies:
+        var items = [1];
+        const shared = mutex(items);
+        items.push(2);
+        var locks = [shared.try_lock(), shared.is_locked(), shared.try_lock()];
+        shared.unlock();
+        locks.push(shared.is_locked());
+
+        var result = [steps, locks, shared.get()];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([8, 7, 6, true, false, 10]), Array([true, true, false, false]), Array([1])])"
+    );
+}
+
+#[test]
+pub fn sync_errors() {
+    let source = r#"
+        const jobs = channel();
+        const lock = mutex(0);
+        const counter = atomic(0);
+        jobs.close();
+
+        var errors = [];
+        try { jobs.send(1); } catch e { errors.push(e.kind); }
+        try { lock.unlock(); } catch e { errors.push(e.kind); }
+        try { counter.add("x"); } catch e { errors.push(e.kind); }
+        try { jobs.missing(); } catch e { errors.push(e.kind); }
+        try { channel(0); } catch e { errors.push(e.kind); }
+        try { atomic(1.5); } catch e { errors.push(e.kind); }
+
+        var drained = jobs.recv() == none;
+    "#;
+    let result = eval_global(source, "errors");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([AttributeError, AttributeError, AttributeError, AttributeError, \
+         BuiltinFunctionError, BuiltinFunctionError])"
+    );
+
+    let result = eval_global(source, "drained");
+    assert_eq!(result.unwrap().describe(), "true");
+}
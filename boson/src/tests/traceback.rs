@@ -0,0 +1,65 @@
+use crate::tests::eval_global;
+use crate::tests::runtime_error;
+use crate::vm::errors::VMErrorKind;
+
+const SOURCE: &str = "func inner(x) { return x / 0; }
+func outer(x) { return inner(x) + 1; }
+func again(x) {
+    try { outer(x); } catch e { rethrow; }
+}
+";
+
+#[test]
+pub fn uncaught_trace() {
+    let error = runtime_error(&format!("{}var result = outer(1);", SOURCE));
+    assert_eq!(error.t, VMErrorKind::DivideByZeroError);
+
+    // most recent call first, every entry points to its source line:
+    let frames: Vec<(String, usize)> = error
+        .trace
+        .iter()
+        .map(|entry| (entry.name.clone(), entry.location.as_ref().unwrap().line))
+        .collect();
+    assert_eq!(
+        frames,
+        vec![
+            ("inner".to_string(), 1),
+            ("outer".to_string(), 2),
+            ("main".to_string(), 6),
+        ]
+    );
+    assert!(error.trace[0]
+        .to_string()
+        .starts_with("<input>:1:17 in inner, offset "));
+}
+
+#[test]
+pub fn caught_trace() {
+    // e.trace lists the same frames, most recent call last:
+    let source = format!(
+        r#"{}
+        func names(trace) {{
+            return trace.map(lambda line => line.split(" in ")[1].split(",")[0]);
+        }}
+
+        var caught = [];
+        try {{ outer(1); }} catch e {{ caught = names(e.trace); }}
+
+        var rethrown = [];
+        try {{ again(2); }} catch e {{ rethrown = names(e.trace); }}
+
+        var thrown = [];
+        func raise() {{ throw "boom"; }}
+        try {{ raise(); }} catch e {{ thrown = [e.kind, e.message, names(e.trace)]; }}
+
+        var result = [caught, rethrown, thrown];
+        "#,
+        SOURCE
+    );
+    let result = eval_global(&source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([Array([main, outer, inner]), Array([main, again, outer, inner]), \
+         Array([UserException, boom, Array([main, raise])])])"
+    );
+}
@@ -1,7 +1,6 @@
 use crate::types;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::mem;
 use std::rc::Rc;
 
@@ -11,53 +10,47 @@ use types::object::Object;
 pub fn truthy() {
     // check the truthiness of objects:
     let bool_object_true = Object::Bool(true);
-    assert_eq!(bool_object_true.is_true(), true);
+    assert!(bool_object_true.is_true());
 
     let bool_object_false = Object::Bool(false);
-    assert_eq!(bool_object_false.is_true(), false);
+    assert!(!bool_object_false.is_true());
 
     let noval_object = Object::Noval;
-    assert_eq!(noval_object.is_true(), false);
+    assert!(!noval_object.is_true());
 
     let int_object = Object::Int(10);
-    assert_eq!(int_object.is_true(), true);
+    assert!(int_object.is_true());
 
     let int_object_zeroed = Object::Int(0);
-    assert_eq!(int_object_zeroed.is_true(), false);
+    assert!(!int_object_zeroed.is_true());
 
     let float_object = Object::Float(1.41);
-    assert_eq!(float_object.is_true(), true);
+    assert!(float_object.is_true());
 
     let string_object = Object::Str("Hi!".to_string());
-    assert_eq!(string_object.is_true(), true);
+    assert!(string_object.is_true());
 
     let empty_string = Object::Str("".to_string());
-    assert_eq!(empty_string.is_true(), false);
+    assert!(!empty_string.is_true());
     // array, hash table - filled and empty:
     let mut arr_obj = types::array::Array {
         elements: vec![Rc::new(Object::Int(10))],
         name: "test".to_string(),
     };
 
-    assert_eq!(Object::Array(RefCell::new(arr_obj.clone())).is_true(), true);
+    assert!(Object::Array(RefCell::new(arr_obj.clone())).is_true());
 
     arr_obj.elements = vec![];
-    assert_eq!(Object::Array(RefCell::new(arr_obj)).is_true(), false);
+    assert!(!Object::Array(RefCell::new(arr_obj)).is_true());
 
     // hash table:
-    let mut hash_table = types::hash::HashTable {
-        entries: HashMap::new(),
-        name: "test".to_string(),
-    };
+    let mut hash_table = types::hash::HashTable::new("test".to_string());
 
     hash_table.set(Rc::new(Object::Int(10)), Rc::new(Object::Int(20)));
-    assert_eq!(
-        Object::HashTable(RefCell::new(hash_table.clone())).is_true(),
-        true
-    );
+    assert!(Object::HashTable(RefCell::new(hash_table.clone())).is_true());
 
-    hash_table.entries = HashMap::new();
-    assert_eq!(Object::HashTable(RefCell::new(hash_table)).is_true(), false);
+    hash_table.clear();
+    assert!(!Object::HashTable(RefCell::new(hash_table)).is_true());
 }
 
 #[test]
@@ -68,106 +61,92 @@ pub fn indexing() {
         name: "test".to_string(),
     };
 
-    let mut arr_object = Object::Array(RefCell::new(array));
+    let arr_object = Object::Array(RefCell::new(array));
     let result = arr_object.get_indexed(&Rc::new(Object::Int(0)));
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     assert_eq!(*result.unwrap().as_ref(), Object::Int(10));
 
     // out of bounds:
     let result = arr_object.get_indexed(&Rc::new(Object::Int(1)));
-    assert_eq!(result.is_err(), true);
+    assert!(result.is_err());
 
     // set indexed:
     let result = arr_object.set_indexed(&Rc::new(Object::Int(0)), Rc::new(Object::Int(20)));
-    assert_eq!(result.is_none(), true);
+    assert!(result.is_none());
 
     // set indexed out of bounds:
     let result = arr_object.set_indexed(&Rc::new(Object::Int(1)), Rc::new(Object::Int(20)));
-    assert_eq!(result.is_some(), true);
+    assert!(result.is_some());
 
     // hash map set and get operations:
-    let hm = types::hash::HashTable {
-        entries: HashMap::new(),
-        name: "test".to_string(),
-    };
+    let hm = types::hash::HashTable::new("test".to_string());
 
-    let mut h_obj = Object::HashTable(RefCell::new(hm));
+    let h_obj = Object::HashTable(RefCell::new(hm));
 
     // set:
     let result = h_obj.set_indexed(
         &Rc::new(Object::Str("Age".to_string())),
         Rc::new(Object::Int(23)),
     );
-    assert_eq!(result.is_none(), true);
+    assert!(result.is_none());
 
     // get
     let result = h_obj.get_indexed(&Rc::new(Object::Str("Age".to_string())));
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     assert_eq!(*result.unwrap().as_ref(), Object::Int(23));
 
     // get key error
     let result = h_obj.get_indexed(&Rc::new(Object::Str("NotAge".to_string())));
-    assert_eq!(result.is_err(), true);
+    assert!(result.is_err());
 
     let string_obj = Object::Str("Prasanna".to_string());
     let result = string_obj.get_indexed(&Rc::new(Object::Int(3)));
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     assert_eq!(*result.unwrap().as_ref(), Object::Char('s'));
 
     // out of bounds:
     let result = string_obj.get_indexed(&Rc::new(Object::Int(30)));
-    assert_eq!(result.is_ok(), false);
+    assert!(result.is_err());
 
     // unsupported object
-    assert_eq!(
-        Object::Int(20)
-            .get_indexed(&Rc::new(Object::Int(30)))
-            .is_err(),
-        true
-    );
+    assert!(Object::Int(20)
+        .get_indexed(&Rc::new(Object::Int(30)))
+        .is_err());
 }
 
 #[test]
 pub fn object_equality() {
-    assert_eq!(Object::Int(20) == Object::Int(20), true);
+    assert!(Object::Int(20) == Object::Int(20));
 
-    assert_eq!(Object::Int(20) != Object::Int(25), true);
+    assert!(Object::Int(20) != Object::Int(25));
 
-    assert_eq!(Object::Float(3.144) == Object::Float(3.144), true);
+    assert!(Object::Float(3.144) == Object::Float(3.144));
 
-    assert_eq!(Object::Float(3.144) != Object::Float(3.140), true);
+    assert!(Object::Float(3.144) != Object::Float(3.145));
 
-    assert_eq!(
-        Object::Str(String::from("Hey String!")) == Object::Str(String::from("Hey String!")),
-        true
-    );
+    assert!(Object::Str(String::from("Hey String!")) == Object::Str(String::from("Hey String!")));
 
-    assert_eq!(
-        Object::Str(String::from("Hey String!")) != Object::Str(String::from("Heyy String!")),
-        true
-    );
+    assert!(Object::Str(String::from("Hey String!")) != Object::Str(String::from("Heyy String!")));
 
     // arrays:
-    assert_eq!(
+    assert!(
         Object::Array(RefCell::new(types::array::Array {
             elements: vec![Rc::new(Object::Int(10))],
             name: "test".to_string(),
         })) == Object::Array(RefCell::new(types::array::Array {
             elements: vec![Rc::new(Object::Int(10))],
             name: "test2".to_string(),
-        })),
-        true
+        }))
     );
 
-    assert_eq!(
+    assert!(
         Object::Array(RefCell::new(types::array::Array {
             elements: vec![Rc::new(Object::Int(10))],
             name: "test".to_string(),
         })) != Object::Array(RefCell::new(types::array::Array {
             elements: vec![Rc::new(Object::Int(10)), Rc::new(Object::Int(3))],
             name: "test".to_string(),
-        })),
-        true
+        }))
     );
 }
 
@@ -176,38 +155,36 @@ pub fn builtin_resolution() {
     // check an existing builtin:
     let existing_fn = String::from("println");
     let result = types::builtins::BuiltinKind::get_by_name(&existing_fn);
-    assert_eq!(result.is_some(), true);
+    assert!(result.is_some());
     assert_eq!(result.unwrap(), types::builtins::BuiltinKind::Println);
 
     let non_existing_fn = String::from("create_function");
     let result = types::builtins::BuiltinKind::get_by_name(&non_existing_fn);
-    assert_eq!(result.is_none(), true);
+    assert!(result.is_none());
 }
 
 #[test]
 pub fn buffer_endian_encoding() {
     let i64_number: i64 = 2584970882;
-    let buffer_truth = vec![0u8, 0u8, 0u8, 0u8, 154u8, 19u8, 134u8, 130u8];
+    let buffer_truth = [0u8, 0u8, 0u8, 0u8, 154u8, 19u8, 134u8, 130u8];
     // check encoding in bytes:
     // big endian:
     let buffer_res_big = types::buffer::Buffer::from_i64(&i64_number, false);
-    assert_eq!(buffer_res_big.is_ok(), true);
+    assert!(buffer_res_big.is_ok());
     let buffer = buffer_res_big.unwrap();
 
     assert_eq!(buffer.length, mem::size_of::<i64>());
     // check repr:
-    for idx in 0..buffer_truth.len() {
-        assert_eq!(buffer.data[idx], buffer_truth[idx]);
-    }
+    assert_eq!(buffer.data[..buffer_truth.len()], buffer_truth[..]);
 
     // check conversion back to integer:
     let back_conv = buffer.get_as_i64();
-    assert_eq!(back_conv.is_ok(), true);
+    assert!(back_conv.is_ok());
     assert_eq!(back_conv.unwrap(), i64_number);
 
     // little endian:
     let buffer_res_little = types::buffer::Buffer::from_i64(&i64_number, true);
-    assert_eq!(buffer_res_little.is_ok(), true);
+    assert!(buffer_res_little.is_ok());
     let buffer = buffer_res_little.unwrap();
 
     assert_eq!(buffer.length, mem::size_of::<i64>());
@@ -221,6 +198,6 @@ pub fn buffer_endian_encoding() {
 
     // check conversion back to integer:
     let back_conv = buffer.get_as_i64();
-    assert_eq!(back_conv.is_ok(), true);
+    assert!(back_conv.is_ok());
     assert_eq!(back_conv.unwrap(), i64_number);
 }
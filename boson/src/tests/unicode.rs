@@ -0,0 +1,60 @@
+use crate::tests::eval_global;
+use crate::tests::parser_errors;
+
+#[test]
+pub fn utf8_source() {
+    let source = r#"
+        const größe = "Grüße ✓";
+        var letters = [];
+        for c in "añ🚀" => {
+            letters.push(c);
+        }
+        var result = [größe.length, größe[2], letters, 'ü', type_of('ü')];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([7, ü, Array([a, ñ, 🚀]), ü, char])"
+    );
+}
+
+#[test]
+pub fn escapes() {
+    let source = r#"
+        var result = ["\u{1F680}", '\u{e9}', "a\tb", "\\", "\d+", '\'', "\u{48}\u{49}"];
+    "#;
+    let result = eval_global(source, "result");
+    assert_eq!(
+        result.unwrap().describe(),
+        "Array([🚀, é, a\tb, \\, \\d+, ', HI])"
+    );
+}
+
+#[test]
+pub fn invalid_source() {
+    let invalid: Vec<(&[u8], &str)> = vec![
+        (b"var x = \"\\u{110000}\";", "is not a valid character"),
+        (b"var x = \"\\u{}\";", "must have 1 to 6 hex digits"),
+        (b"var x = \"\\u{12\";", "Unterminated unicode escape"),
+        (b"var x = \"\\u12\";", "Expected { after \\u"),
+        (b"var x = 'ab';", "must contain a single character"),
+        (
+            b"var x = \"\xff\";",
+            "Invalid UTF-8 sequence starting with byte 0xff",
+        ),
+        (
+            b"var \xc3 = 1;",
+            "Invalid UTF-8 sequence starting with byte 0xc3",
+        ),
+    ];
+
+    for (source, message) in invalid {
+        let errors = parser_errors(source);
+        assert!(
+            errors[0].contains(message),
+            "{:?} does not contain {}",
+            errors,
+            message
+        );
+    }
+}
@@ -1,8 +1,20 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
+use crate::api::Platform;
+use crate::compiler::symtab::ConstantPool;
+use crate::types::object::AttributeResolver;
 use crate::types::object::Object;
+use crate::vm::alu::Comparision;
+use crate::vm::errors::VMError;
+use crate::vm::errors::VMErrorKind;
+use crate::vm::ffi::BosonFFI;
+use crate::vm::global::GlobalPool;
+use crate::vm::thread::BosonThreads;
+use crate::vm::BosonVM;
 
 #[derive(Clone, Debug, PartialOrd)]
 pub struct Array {
@@ -12,10 +24,7 @@ pub struct Array {
 
 impl Array {
     pub fn describe(&self) -> String {
-        let values: Vec<String> = (&self.elements)
-            .into_iter()
-            .map(|e| e.to_string())
-            .collect();
+        let values: Vec<String> = self.elements.iter().map(|e| e.to_string()).collect();
         return format!("Array([{}])", values.join(", "));
     }
 
@@ -88,3 +97,574 @@ impl fmt::Display for Array {
         write!(f, "{}", self.describe())
     }
 }
+
+/*
+    Methods of the array type, called as arr.push(1).
+    push, pop, insert, remove, extend, reverse and sort_by modify the array
+    in place, the other methods return a new object. map, filter, reduce
+    and sort_by call back into boson functions, the elements are copied out
+    before calling them so the callbacks are free to use the array.
+*/
+
+const METHODS: &[&str] = &[
+    "push", "pop", "insert", "remove", "extend", "slice", "reverse", "index_of", "contains", "map",
+    "filter", "reduce", "sort_by",
+];
+
+const HIGHER_ORDER_METHODS: &[&str] = &["map", "filter", "reduce", "sort_by"];
+
+const PROPERTIES: &[&str] = &["length"];
+
+pub fn attrs() -> Vec<Rc<Object>> {
+    return PROPERTIES
+        .iter()
+        .chain(METHODS.iter())
+        .map(|name| Rc::new(Object::Str(name.to_string())))
+        .collect();
+}
+
+pub fn resolve_get_attr(arr: &Array, keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+    let name = match keys[0].as_ref() {
+        Object::Str(name) => name,
+        _ => {
+            return Err(format!(
+                "Expected string attribute, got {}",
+                keys[0].get_type()
+            ))
+        }
+    };
+
+    let value = match name.as_ref() {
+        "length" => Rc::new(Object::Int(arr.elements.len() as i64)),
+        _ => {
+            if METHODS.contains(&name.as_str()) {
+                return Err(format!(
+                    "{} is a method of array, call it as {}()",
+                    name, name
+                ));
+            }
+
+            return Err(format!("Attribute {} not found for type array", name));
+        }
+    };
+
+    if keys.len() == 1 {
+        return Ok(value);
+    }
+
+    return value.resolve_get_attr(&keys[1..]);
+}
+
+fn new_array(method: &str, elements: Vec<Rc<Object>>) -> Rc<Object> {
+    return Rc::new(Object::Array(RefCell::new(Array {
+        name: format!("array_{}", method),
+        elements: elements,
+    })));
+}
+
+fn check_n_args(method: &str, args: &[Rc<Object>], min: usize, max: usize) -> Option<String> {
+    if args.len() < min || args.len() > max {
+        if min == max {
+            return Some(format!(
+                "{}() takes {} argument(s), provided {}",
+                method,
+                min,
+                args.len()
+            ));
+        }
+
+        if max == usize::MAX {
+            return Some(format!(
+                "{}() takes atleast {} argument(s), provided {}",
+                method,
+                min,
+                args.len()
+            ));
+        }
+
+        return Some(format!(
+            "{}() takes {} to {} arguments, provided {}",
+            method,
+            min,
+            max,
+            args.len()
+        ));
+    }
+
+    return None;
+}
+
+fn get_int_arg(method: &str, args: &[Rc<Object>], idx: usize) -> Result<i64, String> {
+    match args[idx].as_ref() {
+        Object::Int(i) => return Ok(*i),
+        _ => {
+            return Err(format!(
+                "{}() expects int as argument {}, got {}",
+                method,
+                idx + 1,
+                args[idx].get_type()
+            ))
+        }
+    }
+}
+
+// negative positions are counted from the end of the array.
+fn get_position(method: &str, pos: i64, length: usize, inclusive: bool) -> Result<usize, String> {
+    let resolved = if pos < 0 { pos + length as i64 } else { pos };
+    let limit = if inclusive {
+        length as i64
+    } else {
+        length as i64 - 1
+    };
+
+    if resolved < 0 || resolved > limit {
+        return Err(format!(
+            "{}() position {} out of range for array of length {}",
+            method, pos, length
+        ));
+    }
+
+    return Ok(resolved as usize);
+}
+
+// same as get_position, but clamps to the array bounds instead of failing.
+fn clamp_position(pos: i64, length: usize) -> usize {
+    let resolved = if pos < 0 { pos + length as i64 } else { pos };
+    return resolved.max(0).min(length as i64) as usize;
+}
+
+fn check_callable(method: &str, func: &Rc<Object>) -> Option<String> {
+    match func.as_ref() {
+        Object::ClosureContext(_) | Object::Builtins(_) => return None,
+        _ => {
+            return Some(format!(
+                "{}() expects a function as argument, got {}",
+                method,
+                func.get_type()
+            ))
+        }
+    }
+}
+
+// number of parameters the callback takes, builtins take the minimum.
+fn n_callback_params(func: &Rc<Object>, min: usize) -> usize {
+    match func.as_ref() {
+        Object::ClosureContext(ctx) => return ctx.compiled_fn.n_required(),
+        _ => return min,
+    }
+}
+
+fn call_function(
+    method: &str,
+    vm: &mut BosonVM,
+    func: &Rc<Object>,
+    args: Vec<Rc<Object>>,
+    platform: &mut Platform,
+) -> Result<Rc<Object>, VMError> {
+    let result = match func.as_ref() {
+        Object::ClosureContext(ctx) => {
+            if !ctx.compiled_fn.accepts_arguments(args.len()) {
+                return Err(VMError::new(
+                    format!(
+                        "{}() callback {} requires {} parameters, provided {}",
+                        method,
+                        ctx.compiled_fn.name,
+                        ctx.compiled_fn.describe_arity(0),
+                        args.len()
+                    ),
+                    VMErrorKind::FunctionArgumentsError,
+                    None,
+                    0,
+                ));
+            }
+
+            // errors raised by the callback are passed on as they are:
+            let call_result = vm.call_closure(ctx.clone(), args, platform);
+            if call_result.is_err() {
+                return Err(call_result.unwrap_err());
+            }
+
+            call_result.unwrap()
+        }
+        Object::Builtins(kind) => {
+            let call_result = kind.call(
+                args,
+                platform,
+                &mut vm.globals,
+                &mut vm.constants,
+                &mut vm.threads,
+                &mut vm.vm_ffi,
+            );
+            if call_result.is_err() {
+                return Err(call_result.unwrap_err());
+            }
+
+            call_result.unwrap()
+        }
+        _ => return Err(attribute_error(check_callable(method, func).unwrap())),
+    };
+
+    return Ok(result);
+}
+
+fn attribute_error(message: String) -> VMError {
+    return VMError::new(message, VMErrorKind::AttributeError, None, 0);
+}
+
+// a comparator returns a negative int (or true) when its first
+// argument goes before the second one.
+fn get_ordering(method: &str, result: &Rc<Object>) -> Result<Ordering, VMError> {
+    match result.as_ref() {
+        Object::Int(i) => return Ok(i.cmp(&0)),
+        Object::Bool(true) => return Ok(Ordering::Less),
+        Object::Bool(false) => return Ok(Ordering::Greater),
+        _ => {
+            return Err(VMError::new(
+                format!(
+                    "{}() comparator must return int or bool, got {}",
+                    method,
+                    result.get_type()
+                ),
+                VMErrorKind::TypeError,
+                None,
+                0,
+            ))
+        }
+    }
+}
+
+// sorts with a boson comparator, used by sort_by and the sort() builtin.
+pub fn sort_with_comparator(
+    method: &str,
+    vm: &mut BosonVM,
+    func: &Rc<Object>,
+    elements: Vec<Rc<Object>>,
+    platform: &mut Platform,
+) -> Result<Vec<Rc<Object>>, VMError> {
+    return merge_sort(elements, &mut |a, b| {
+        let call_result = call_function(method, vm, func, vec![a, b], platform);
+        if call_result.is_err() {
+            return Err(call_result.unwrap_err());
+        }
+
+        return get_ordering(method, &call_result.unwrap());
+    });
+}
+
+/*
+    Stable merge sort driven by a boson comparator, the comparator is only
+    asked whether the right element goes before the left one, so a comparator
+    that is not a consistent ordering gives an unspecified order instead of
+    a panic.
+*/
+fn merge_sort(
+    elements: Vec<Rc<Object>>,
+    compare: &mut dyn FnMut(Rc<Object>, Rc<Object>) -> Result<Ordering, VMError>,
+) -> Result<Vec<Rc<Object>>, VMError> {
+    if elements.len() <= 1 {
+        return Ok(elements);
+    }
+
+    let mut left_half = elements;
+    let right_half = left_half.split_off(left_half.len() / 2);
+
+    let left_res = merge_sort(left_half, compare);
+    if left_res.is_err() {
+        return left_res;
+    }
+
+    let right_res = merge_sort(right_half, compare);
+    if right_res.is_err() {
+        return right_res;
+    }
+
+    let (left, right) = (left_res.unwrap(), right_res.unwrap());
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let (mut l_idx, mut r_idx) = (0, 0);
+
+    while l_idx < left.len() && r_idx < right.len() {
+        let ordering = compare(right[r_idx].clone(), left[l_idx].clone());
+        if ordering.is_err() {
+            return Err(ordering.unwrap_err());
+        }
+
+        if ordering.unwrap() == Ordering::Less {
+            merged.push(right[r_idx].clone());
+            r_idx += 1;
+        } else {
+            merged.push(left[l_idx].clone());
+            l_idx += 1;
+        }
+    }
+
+    merged.extend_from_slice(&left[l_idx..]);
+    merged.extend_from_slice(&right[r_idx..]);
+    return Ok(merged);
+}
+
+/*
+    Errors of the array methods are AttributeError, the errors raised by the
+    callbacks of the higher order methods are passed on as they are.
+*/
+pub fn resolve_call_attr(
+    arr: &RefCell<Array>,
+    keys: &[Rc<Object>],
+    args: &[Rc<Object>],
+    platform: &mut Platform,
+    gp: &mut GlobalPool,
+    c: &mut ConstantPool,
+    th: &mut BosonThreads,
+    ffi: &mut BosonFFI,
+) -> Result<Rc<Object>, VMError> {
+    let method_res = get_method(keys, args);
+    if method_res.is_err() {
+        return Err(attribute_error(method_res.unwrap_err()));
+    }
+
+    let method = method_res.unwrap();
+    if HIGHER_ORDER_METHODS.contains(&method) {
+        return call_higher_order(arr, method, args, platform, gp, c, th, ffi);
+    }
+
+    let result = call_method(arr, method, args);
+    if result.is_err() {
+        return Err(attribute_error(result.unwrap_err()));
+    }
+
+    return Ok(result.unwrap());
+}
+
+// the method being called, after checking its arguments:
+fn get_method<'a>(keys: &'a [Rc<Object>], args: &[Rc<Object>]) -> Result<&'a str, String> {
+    if keys.len() != 1 {
+        return Err("Nested attributes are not supported for type array".to_string());
+    }
+
+    let method = match keys[0].as_ref() {
+        Object::Str(method) => method.as_str(),
+        _ => return Err(format!("invalid method type, found {}", keys[0].get_type())),
+    };
+
+    if PROPERTIES.contains(&method) {
+        return Err(format!(
+            "{} is a property of array, access it as {}",
+            method, method
+        ));
+    }
+
+    if !METHODS.contains(&method) {
+        return Err(format!("Attribute {} not found for type array", method));
+    }
+
+    let (min_args, max_args) = match method {
+        "push" => (1, usize::MAX),
+        "pop" => (0, 1),
+        "insert" => (2, 2),
+        "slice" | "reduce" => (1, 2),
+        "reverse" => (0, 0),
+        _ => (1, 1),
+    };
+
+    let args_error = check_n_args(method, args, min_args, max_args);
+    if args_error.is_some() {
+        return Err(args_error.unwrap());
+    }
+
+    if HIGHER_ORDER_METHODS.contains(&method) {
+        let callable_error = check_callable(method, &args[0]);
+        if callable_error.is_some() {
+            return Err(callable_error.unwrap());
+        }
+    }
+
+    return Ok(method);
+}
+
+fn call_method(
+    arr: &RefCell<Array>,
+    method: &str,
+    args: &[Rc<Object>],
+) -> Result<Rc<Object>, String> {
+    match method {
+        "push" => {
+            arr.borrow_mut().elements.extend(args.iter().cloned());
+            return Ok(Rc::new(Object::Noval));
+        }
+        "pop" | "remove" => {
+            let length = arr.borrow().elements.len();
+            if length == 0 {
+                return Err(format!("{}() called on an empty array", method));
+            }
+
+            let pos = if args.is_empty() {
+                length - 1
+            } else {
+                let pos_res = get_int_arg(method, args, 0);
+                if pos_res.is_err() {
+                    return Err(pos_res.unwrap_err());
+                }
+
+                let resolved = get_position(method, pos_res.unwrap(), length, false);
+                if resolved.is_err() {
+                    return Err(resolved.unwrap_err());
+                }
+                resolved.unwrap()
+            };
+
+            return Ok(arr.borrow_mut().elements.remove(pos));
+        }
+        "insert" => {
+            let pos_res = get_int_arg(method, args, 0);
+            if pos_res.is_err() {
+                return Err(pos_res.unwrap_err());
+            }
+
+            let length = arr.borrow().elements.len();
+            let resolved = get_position(method, pos_res.unwrap(), length, true);
+            if resolved.is_err() {
+                return Err(resolved.unwrap_err());
+            }
+
+            arr.borrow_mut()
+                .elements
+                .insert(resolved.unwrap(), args[1].clone());
+            return Ok(Rc::new(Object::Noval));
+        }
+        "extend" => {
+            // copied first, the array can be extended with itself.
+            let other = match args[0].as_ref() {
+                Object::Array(other) => other.borrow().elements.clone(),
+                _ => {
+                    return Err(format!(
+                        "extend() expects array as argument, got {}",
+                        args[0].get_type()
+                    ))
+                }
+            };
+
+            arr.borrow_mut().elements.extend(other);
+            return Ok(Rc::new(Object::Noval));
+        }
+        "slice" => {
+            let start_res = get_int_arg(method, args, 0);
+            if start_res.is_err() {
+                return Err(start_res.unwrap_err());
+            }
+
+            let array = arr.borrow();
+            let length = array.elements.len();
+            let end = if args.len() == 2 {
+                let end_res = get_int_arg(method, args, 1);
+                if end_res.is_err() {
+                    return Err(end_res.unwrap_err());
+                }
+                clamp_position(end_res.unwrap(), length)
+            } else {
+                length
+            };
+
+            let start = clamp_position(start_res.unwrap(), length);
+            if start >= end {
+                return Ok(new_array(method, vec![]));
+            }
+
+            return Ok(new_array(method, array.get_sliced(start, end)));
+        }
+        "reverse" => {
+            arr.borrow_mut().elements.reverse();
+            return Ok(Rc::new(Object::Noval));
+        }
+        "index_of" => {
+            let elements = arr.borrow().get_values();
+            let pos = elements
+                .iter()
+                .position(|e| Comparision::equals(e, &args[0]));
+            return Ok(Rc::new(Object::Int(pos.map_or(-1, |p| p as i64))));
+        }
+        "contains" => {
+            let elements = arr.borrow().get_values();
+            let found = elements.iter().any(|e| Comparision::equals(e, &args[0]));
+            return Ok(Rc::new(Object::Bool(found)));
+        }
+        _ => return Err(format!("Attribute {} not found for type array", method)),
+    }
+}
+
+fn call_higher_order(
+    arr: &RefCell<Array>,
+    method: &str,
+    args: &[Rc<Object>],
+    platform: &mut Platform,
+    gp: &mut GlobalPool,
+    c: &mut ConstantPool,
+    th: &mut BosonThreads,
+    ffi: &mut BosonFFI,
+) -> Result<Rc<Object>, VMError> {
+    let func = &args[0];
+    let elements = arr.borrow().get_values();
+
+    return BosonVM::with_sandbox(gp, c, th, ffi, |vm| match method {
+        "map" | "filter" => {
+            // the index is passed as the second argument if the callback takes it.
+            let with_index = n_callback_params(func, 1) == 2;
+            let mut result = vec![];
+
+            for (idx, element) in elements.into_iter().enumerate() {
+                let mut call_args = vec![element.clone()];
+                if with_index {
+                    call_args.push(Rc::new(Object::Int(idx as i64)));
+                }
+
+                let call_result = call_function(method, vm, func, call_args, platform);
+                if call_result.is_err() {
+                    return Err(call_result.unwrap_err());
+                }
+
+                let returned = call_result.unwrap();
+                if method == "map" {
+                    result.push(returned);
+                } else if returned.is_true() {
+                    result.push(element);
+                }
+            }
+
+            return Ok(new_array(method, result));
+        }
+        "reduce" => {
+            let mut iter = elements.into_iter();
+            let mut acc = if args.len() == 2 {
+                args[1].clone()
+            } else {
+                match iter.next() {
+                    Some(first) => first,
+                    None => {
+                        return Err(attribute_error(
+                            "reduce() of an empty array without an initial value".to_string(),
+                        ))
+                    }
+                }
+            };
+
+            for element in iter {
+                let call_result = call_function(method, vm, func, vec![acc, element], platform);
+                if call_result.is_err() {
+                    return Err(call_result.unwrap_err());
+                }
+                acc = call_result.unwrap();
+            }
+
+            return Ok(acc);
+        }
+        _ => {
+            // sort_by, the array is left untouched if the comparator fails.
+            let sorted = sort_with_comparator(method, vm, func, elements, platform);
+
+            if sorted.is_err() {
+                return Err(sorted.unwrap_err());
+            }
+
+            arr.borrow_mut().elements = sorted.unwrap();
+            return Ok(Rc::new(Object::Noval));
+        }
+    });
+}
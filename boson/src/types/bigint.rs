@@ -0,0 +1,70 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::types::object::Object;
+
+/*
+    Integers that do not fit an i64. Arithmetic on ints promotes to a
+    BigInt when the result overflows and every result that fits an i64
+    again is demoted back to an Int, so a BigInt value never lies in the
+    i64 range and an Int and a BigInt are never equal.
+*/
+
+pub fn from_bigint(value: BigInt) -> Rc<Object> {
+    match value.to_i64() {
+        Some(i) => return Rc::new(Object::Int(i)),
+        None => return Rc::new(Object::BigInt(value)),
+    }
+}
+
+pub fn as_bigint(obj: &Object) -> Option<BigInt> {
+    match obj {
+        Object::Int(i) => return Some(BigInt::from(*i)),
+        Object::BigInt(b) => return Some(b.clone()),
+        _ => return None,
+    }
+}
+
+pub fn to_f64(value: &BigInt) -> f64 {
+    return value.to_f64().unwrap_or(f64::NAN);
+}
+
+// decimal digits with an optional sign, of any length:
+pub fn parse(text: &str) -> Option<Rc<Object>> {
+    let digits = text.strip_prefix('+').unwrap_or(text);
+    let parsed = digits.parse::<BigInt>();
+    if parsed.is_err() {
+        return None;
+    }
+    return Some(from_bigint(parsed.unwrap()));
+}
+
+// both operands as big integers when at least one of them is a BigInt:
+pub fn big_operands(left: &Object, right: &Object) -> Option<(BigInt, BigInt)> {
+    match (left, right) {
+        (Object::BigInt(_), _) | (_, Object::BigInt(_)) => {}
+        _ => return None,
+    }
+
+    let lval = as_bigint(left);
+    let rval = as_bigint(right);
+    if lval.is_none() || rval.is_none() {
+        return None;
+    }
+    return Some((lval.unwrap(), rval.unwrap()));
+}
+
+// a BigInt mixed with a float is converted to a float:
+pub fn float_operands(left: &Rc<Object>, right: &Rc<Object>) -> Option<(Rc<Object>, Rc<Object>)> {
+    match (left.as_ref(), right.as_ref()) {
+        (Object::BigInt(lval), Object::Float(_)) => {
+            return Some((Rc::new(Object::Float(to_f64(lval))), right.clone()));
+        }
+        (Object::Float(_), Object::BigInt(rval)) => {
+            return Some((left.clone(), Rc::new(Object::Float(to_f64(rval)))));
+        }
+        _ => return None,
+    }
+}
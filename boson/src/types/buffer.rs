@@ -140,8 +140,7 @@ impl Buffer {
     }
 
     pub fn new_empty(size: usize, is_little_endian: bool) -> Buffer {
-        let mut inner = vec![];
-        inner.resize(size, 0);
+        let inner = vec![0; size];
 
         Self {
             data: inner,
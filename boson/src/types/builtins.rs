@@ -1,7 +1,7 @@
 extern crate rand;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cmp::Ordering;
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::path::Path;
@@ -10,21 +10,29 @@ use std::rc::Rc;
 
 use crate::api;
 
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
 use rand::Rng;
 
 use crate::api::{packing::encode_boson_types, BosonLang};
 use crate::compiler;
 use crate::config;
 use crate::types::array;
+use crate::types::bigint;
 use crate::types::buffer;
 use crate::types::dyn_module;
+use crate::types::format;
 use crate::types::hash;
 use crate::types::iter;
 use crate::types::object;
+use crate::types::sync;
 use crate::vm;
 
 use compiler::symtab::ConstantPool;
 use config::ENABLE_CONCURRENCY;
+use vm::alu::Comparision;
+use vm::errors::VMError;
+use vm::errors::VMErrorKind;
 use vm::ffi::BosonFFI;
 use vm::global::GlobalPool;
 use vm::thread::BosonThreads;
@@ -34,7 +42,11 @@ use vm::BosonVM;
 use api::Platform;
 use array::Array;
 use hash::HashTable;
+use object::AttributeResolver;
 use object::Object;
+use sync::AtomicCounter;
+use sync::BosonMutex;
+use sync::Channel;
 
 use super::buffer::Buffer;
 
@@ -93,16 +105,22 @@ pub enum BuiltinKind {
     EncodePacked,
     GetSyscalls,
     Syscall,
+    Channel,
+    Mutex,
+    Atomic,
+    Attrs,
+    Range,
+    Format,
     EndMark, // the end marker will tell the number of varinats in BuiltinKind, since
              // they are sequential.
 }
 
-fn repr_is_big_endian(args: &Vec<Rc<Object>>) -> bool {
-    if args.len() == 2 && args[1].get_type() == "bool" && args[1].is_true() {
-        true
-    } else {
-        false
-    }
+fn repr_is_big_endian(args: &[Rc<Object>]) -> bool {
+    args.len() == 2 && args[1].get_type() == "bool" && args[1].is_true()
+}
+
+fn builtin_error(message: String) -> VMError {
+    return VMError::new(message, VMErrorKind::BuiltinFunctionError, None, 0);
 }
 
 impl BuiltinKind {
@@ -167,10 +185,217 @@ impl BuiltinKind {
             BuiltinKind::EncodePacked => "encode_packed".to_string(),
             BuiltinKind::GetSyscalls => "get_syscalls".to_string(),
             BuiltinKind::Syscall => "syscall".to_string(),
+            BuiltinKind::Channel => "channel".to_string(),
+            BuiltinKind::Mutex => "mutex".to_string(),
+            BuiltinKind::Atomic => "atomic".to_string(),
+            BuiltinKind::Attrs => "attrs".to_string(),
+            BuiltinKind::Range => "range".to_string(),
+            BuiltinKind::Format => "format".to_string(),
             _ => "undef".to_string(),
         }
     }
 
+    /*
+        Calls the builtin from boson code. The builtins that run boson code
+        (generators stepped by next() or has_next() and sort() comparators)
+        pass the errors raised by it through as they are, so the exception
+        keeps its kind and trace. Other errors are BuiltinFunctionError.
+    */
+    pub fn call(
+        &self,
+        args: Vec<Rc<Object>>,
+        platform: &mut Platform,
+        gp: &mut GlobalPool,
+        c: &mut ConstantPool,
+        th: &mut BosonThreads,
+        ffi: &mut BosonFFI,
+    ) -> Result<Rc<Object>, VMError> {
+        match self {
+            BuiltinKind::HasNext => {
+                if args.len() != 1 {
+                    return Err(builtin_error(format!(
+                        "has_next() expects one argument, {} provided.",
+                        args.len()
+                    )));
+                }
+
+                let obj = args[0].as_ref();
+                match obj {
+                    Object::Iter(it) => {
+                        let iter_res = it.try_borrow_mut();
+                        if iter_res.is_err() {
+                            return Err(builtin_error(
+                                "has_next() called on a running generator".to_string(),
+                            ));
+                        }
+
+                        let has_next = iter_res.unwrap().has_next(platform, gp, c, th, ffi);
+                        if has_next.is_err() {
+                            return Err(has_next.unwrap_err());
+                        }
+
+                        return Ok(Rc::new(Object::Bool(has_next.unwrap())));
+                    }
+                    _ => {
+                        return Err(builtin_error(format!(
+                            "has_next() can be applied only on iter, but got {}",
+                            obj.get_type()
+                        )))
+                    }
+                }
+            }
+
+            BuiltinKind::Next => {
+                if args.len() != 1 {
+                    return Err(builtin_error(format!(
+                        "next() expects one argument, {} provided.",
+                        args.len()
+                    )));
+                }
+
+                let obj = args[0].as_ref();
+                match obj {
+                    Object::Iter(it) => {
+                        let iter_res = it.try_borrow_mut();
+                        if iter_res.is_err() {
+                            return Err(builtin_error(
+                                "next() called on a running generator".to_string(),
+                            ));
+                        }
+
+                        let next_res = iter_res.unwrap().next(platform, gp, c, th, ffi);
+                        if next_res.is_err() {
+                            return Err(next_res.unwrap_err());
+                        }
+
+                        let next_obj = next_res.unwrap();
+                        if next_obj.is_none() {
+                            return Err(builtin_error(
+                                "next() called on ended iterator".to_string(),
+                            ));
+                        }
+
+                        return Ok(next_obj.unwrap());
+                    }
+                    _ => {
+                        return Err(builtin_error(format!(
+                            "has_next() can be applied only on iter, but got {}",
+                            obj.get_type()
+                        )));
+                    }
+                }
+            }
+
+            BuiltinKind::Sort => {
+                if args.len() != 2 {
+                    return Err(builtin_error(format!(
+                        "sort() function two arguments, got {}",
+                        args.len()
+                    )));
+                }
+
+                // the elements of an iterator are sorted into a new array:
+                if let Object::Iter(it) = args[0].as_ref() {
+                    let iter_res = it.try_borrow_mut();
+                    if iter_res.is_err() {
+                        return Err(builtin_error(
+                            "sort() called on a running generator".to_string(),
+                        ));
+                    }
+
+                    let mut iterator = iter_res.unwrap();
+                    let mut elements = vec![];
+                    loop {
+                        let next_res = iterator.next(platform, gp, c, th, ffi);
+                        if next_res.is_err() {
+                            return Err(next_res.unwrap_err());
+                        }
+
+                        let next_obj = next_res.unwrap();
+                        if next_obj.is_none() {
+                            break;
+                        }
+                        elements.push(next_obj.unwrap());
+                    }
+
+                    let sorted = Rc::new(Object::Array(RefCell::new(Array {
+                        name: "sort".to_string(),
+                        elements: elements,
+                    })));
+
+                    let sort_args = vec![sorted.clone(), args[1].clone()];
+                    let sort_res = BuiltinKind::Sort.call(sort_args, platform, gp, c, th, ffi);
+                    if sort_res.is_err() {
+                        return Err(sort_res.unwrap_err());
+                    }
+
+                    return Ok(sorted);
+                }
+
+                let arr = match (args[0].as_ref(), args[1].as_ref()) {
+                    (Object::Array(arr), Object::Bool(_))
+                    | (Object::Array(arr), Object::ClosureContext(_))
+                    | (Object::Array(arr), Object::Builtins(_)) => arr,
+                    (_, _) => {
+                        return Err(builtin_error(format!(
+                            "sort() takes two parameters of type array (or iter) and bool (or func), but got {} and {}",
+                            args[0].get_type(),
+                            args[1].get_type()
+                        )))
+                    }
+                };
+
+                // sort a copy, so a failing comparison leaves the array untouched:
+                let mut elements = arr.borrow().get_values();
+                if let Object::Bool(ascending) = args[1].as_ref() {
+                    let mut failed = None;
+                    elements.sort_by(|a, b| {
+                        let (first, second) = if *ascending { (a, b) } else { (b, a) };
+                        match Comparision::order(first, second) {
+                            Some(ordering) => return ordering,
+                            None => {
+                                if failed.is_none() {
+                                    failed = Some((a.get_type(), b.get_type()));
+                                }
+                                return Ordering::Equal;
+                            }
+                        }
+                    });
+
+                    if failed.is_some() {
+                        let (ltype, rtype) = failed.unwrap();
+                        return Err(builtin_error(format!(
+                            "sort() cannot order {} and {}",
+                            ltype, rtype
+                        )));
+                    }
+                } else {
+                    let sort_res = BosonVM::with_sandbox(gp, c, th, ffi, |vm| {
+                        return array::sort_with_comparator(
+                            "sort", vm, &args[1], elements, platform,
+                        );
+                    });
+                    if sort_res.is_err() {
+                        return Err(sort_res.unwrap_err());
+                    }
+                    elements = sort_res.unwrap();
+                }
+
+                arr.borrow_mut().elements = elements;
+                return Ok(Rc::new(Object::Noval));
+            }
+
+            _ => {
+                let result = self.exec(args, platform, gp, c, th, ffi);
+                if result.is_err() {
+                    return Err(builtin_error(result.unwrap_err()));
+                }
+
+                return Ok(result.unwrap());
+            }
+        }
+    }
+
     pub fn exec(
         &self,
         args: Vec<Rc<Object>>,
@@ -182,7 +407,7 @@ impl BuiltinKind {
     ) -> Result<Rc<Object>, String> {
         match self {
             BuiltinKind::Print => {
-                if args.len() == 0 {
+                if args.is_empty() {
                     return Err("print() takes atleast one argument, 0 provided".to_string());
                 }
 
@@ -193,7 +418,7 @@ impl BuiltinKind {
                     fmt_string.push_str(&format!("{} ", args[idx].describe()));
                 }
 
-                fmt_string.push_str(&format!("{}", args[length - 1].describe()));
+                fmt_string.push_str(&args[length - 1].describe().to_string());
 
                 // call the platform print function:
                 let print_fn = platform.print;
@@ -208,7 +433,7 @@ impl BuiltinKind {
 
                 let mut fmt_string = String::new();
                 if length == 0 {
-                    fmt_string.push_str("\n");
+                    fmt_string.push('\n');
                 } else {
                     for idx in 0..length - 1 {
                         fmt_string.push_str(&format!("{} ", args[idx].describe()));
@@ -243,13 +468,11 @@ impl BuiltinKind {
 
                 let obj = args[0].as_ref();
                 match obj {
-                    Object::Str(st) => Ok(Rc::new(Object::Int(st.len() as i64))),
+                    Object::Str(st) => Ok(Rc::new(Object::Int(st.chars().count() as i64))),
                     Object::Array(arr) => {
                         Ok(Rc::new(Object::Int(arr.borrow().elements.len() as i64)))
                     }
-                    Object::HashTable(ht) => {
-                        Ok(Rc::new(Object::Int(ht.borrow().entries.len() as i64)))
-                    }
+                    Object::HashTable(ht) => Ok(Rc::new(Object::Int(ht.borrow().length() as i64))),
                     Object::ByteBuffer(buffer) => {
                         Ok(Rc::new(Object::Int(buffer.borrow().length as i64)))
                     }
@@ -283,7 +506,7 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Builtins => {
-                if args.len() != 0 {
+                if !args.is_empty() {
                     return Err(format!(
                         "builtins() takes zero arguments, {} provided",
                         args.len()
@@ -329,7 +552,7 @@ impl BuiltinKind {
             }
 
             BuiltinKind::TimeUnix => {
-                if args.len() != 0 {
+                if !args.is_empty() {
                     return Err(format!(
                         "unix_time() takes zero arguments, {} provided",
                         args.len()
@@ -348,7 +571,7 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Args => {
-                if args.len() != 0 {
+                if !args.is_empty() {
                     return Err(format!(
                         "args() takes zero arguments, {} provided",
                         args.len()
@@ -387,8 +610,8 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Env => {
-                if args.len() == 0 {
-                    return Err(format!("get_env() takes atleast one argument, 0 provided",));
+                if args.is_empty() {
+                    return Err("get_env() takes atleast one argument, 0 provided".to_string());
                 }
 
                 let env_name_obj = args[0].as_ref();
@@ -417,7 +640,7 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Envs => {
-                if args.len() != 0 {
+                if !args.is_empty() {
                     return Err(format!(
                         "envs() takes zero arguments, {} provided",
                         args.len()
@@ -426,10 +649,7 @@ impl BuiltinKind {
                 // get envs:
                 let get_envs_fn = platform.get_envs;
                 let envs = get_envs_fn();
-                let mut env_table = HashTable {
-                    name: "envs".to_string(),
-                    entries: HashMap::new(),
-                };
+                let mut env_table = HashTable::new("envs".to_string());
                 for (key, value) in envs {
                     env_table.set(Rc::new(Object::Str(key)), Rc::new(Object::Str(value)));
                 }
@@ -498,7 +718,7 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Platform => {
-                if args.len() != 0 {
+                if !args.is_empty() {
                     return Err(format!(
                         "arch() takes zero arguments, {} provided",
                         args.len()
@@ -508,10 +728,7 @@ impl BuiltinKind {
                 let platform_info_fn = platform.get_platform_info;
                 let platform_info_vec = platform_info_fn();
 
-                let mut platform_table = HashTable {
-                    name: "platform".to_string(),
-                    entries: HashMap::new(),
-                };
+                let mut platform_table = HashTable::new("platform".to_string());
 
                 platform_table.set(
                     Rc::new(Object::Str("arch".to_string())),
@@ -582,20 +799,29 @@ impl BuiltinKind {
                         return Ok(Rc::new(Object::Int(*i)));
                     }
 
+                    Object::BigInt(_) => {
+                        return Ok(args[0].clone());
+                    }
+
+                    // digits of any length, big values become big integers:
                     Object::Str(st) => {
-                        let result = st.trim().replace("\n", "").parse::<i64>();
-                        if result.is_err() {
+                        let result = bigint::parse(&st.trim().replace("\n", ""));
+                        if result.is_none() {
                             return Err(format!("String {} cannot be converted to integer.", st));
                         }
 
-                        let result_i64 = result.unwrap();
-                        return Ok(Rc::new(Object::Int(result_i64)));
+                        return Ok(result.unwrap());
                     }
 
                     Object::Byte(byte) => return Ok(Rc::new(Object::Int(*byte as i64))),
 
                     Object::Float(f) => {
-                        return Ok(Rc::new(Object::Int(f.round() as i64)));
+                        let rounded = f.round();
+                        let result = BigInt::from_f64(rounded);
+                        if result.is_none() {
+                            return Ok(Rc::new(Object::Int(rounded as i64)));
+                        }
+                        return Ok(bigint::from_bigint(result.unwrap()));
                     }
 
                     Object::ByteBuffer(bytes) => {
@@ -662,7 +888,7 @@ impl BuiltinKind {
                     }
 
                     Object::Bool(b) => {
-                        return Ok(Rc::new(Object::Byte(if *b { 1 as u8 } else { 0 as u8 })));
+                        return Ok(Rc::new(Object::Byte(if *b { 1_u8 } else { 0_u8 })));
                     }
                     _ => {
                         return Err(format!(
@@ -686,6 +912,10 @@ impl BuiltinKind {
                         return Ok(Rc::new(Object::Float(*i as f64)));
                     }
 
+                    Object::BigInt(b) => {
+                        return Ok(Rc::new(Object::Float(bigint::to_f64(b))));
+                    }
+
                     Object::Str(st) => {
                         let result = st.trim().replace("\n", "").parse::<f64>();
                         if result.is_err() {
@@ -728,8 +958,8 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Bytes => {
-                if args.len() == 0 {
-                    return Err(format!("bytes() takes atleast one argument, zero provided",));
+                if args.is_empty() {
+                    return Err("bytes() takes atleast one argument, zero provided".to_string());
                 }
 
                 match args[0].as_ref() {
@@ -778,10 +1008,8 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Char => {
-                if args.len() == 0 {
-                    return Err(format!(
-                        "exec() expects atleast one argument, zero provided."
-                    ));
+                if args.is_empty() {
+                    return Err("exec() expects atleast one argument, zero provided.".to_string());
                 }
 
                 match args[0].as_ref() {
@@ -798,10 +1026,8 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Exec => {
-                if args.len() == 0 {
-                    return Err(format!(
-                        "exec() expects atleast one argument, zero provided."
-                    ));
+                if args.is_empty() {
+                    return Err("exec() expects atleast one argument, zero provided.".to_string());
                 }
 
                 let exec_fn = platform.exec;
@@ -832,10 +1058,10 @@ impl BuiltinKind {
             }
 
             BuiltinKind::ExecRaw => {
-                if args.len() == 0 {
-                    return Err(format!(
-                        "exec_raw() expects atleast one argument, zero provided.",
-                    ));
+                if args.is_empty() {
+                    return Err(
+                        "exec_raw() expects atleast one argument, zero provided.".to_string()
+                    );
                 }
 
                 let exec_fn = platform.exec;
@@ -867,63 +1093,17 @@ impl BuiltinKind {
                     ));
                 }
 
-                let object_to_iter = args[0].as_ref();
-                let iter_res = iter::ObjectIterator::new(Rc::new(object_to_iter.clone()));
-                if iter_res.is_err() {
-                    return Err(iter_res.unwrap_err());
-                }
-
-                return Ok(Rc::new(Object::Iter(RefCell::new(iter_res.unwrap()))));
-            }
-
-            BuiltinKind::HasNext => {
-                if args.len() != 1 {
-                    return Err(format!(
-                        "has_next() expects one argument, {} provided.",
-                        args.len()
-                    ));
-                }
-
-                let obj = args[0].as_ref();
-                match obj {
-                    Object::Iter(it) => {
-                        let has_next = it.borrow().has_next();
-                        return Ok(Rc::new(Object::Bool(has_next)));
-                    }
-                    _ => {
-                        return Err(format!(
-                            "has_next() can be applied only on iter, but got {}",
-                            obj.get_type()
-                        ))
-                    }
-                }
+                return iter::ObjectIterator::iter_object(args[0].clone());
             }
 
-            BuiltinKind::Next => {
-                if args.len() != 1 {
-                    return Err(format!(
-                        "next() expects one argument, {} provided.",
-                        args.len()
-                    ));
+            // these run boson code, see call():
+            BuiltinKind::HasNext | BuiltinKind::Next | BuiltinKind::Sort => {
+                let result = self.call(args, platform, gp, c, th, ffi);
+                if result.is_err() {
+                    return Err(result.unwrap_err().message);
                 }
 
-                let obj = args[0].as_ref();
-                match obj {
-                    Object::Iter(it) => {
-                        let next_obj = it.borrow_mut().next();
-                        if next_obj.is_none() {
-                            return Err(format!("next() called on ended iterator",));
-                        }
-
-                        return Ok(next_obj.unwrap());
-                    }
-                    _ => {
-                        return Err(format!(
-                            "has_next() can be applied only on iter, but got {}",
-                            obj.get_type()
-                        ));
-                    }
-                }
+                return Ok(result.unwrap());
             }
 
             BuiltinKind::SleepMs => {
@@ -965,7 +1145,7 @@ impl BuiltinKind {
                 match args[0].as_ref() {
                     Object::Float(duration_f_s) => {
                         let sleep_fn = platform.sleep;
-                        sleep_fn(&(*duration_f_s * 1000 as f64));
+                        sleep_fn(&(*duration_f_s * 1000_f64));
                     }
                     Object::Int(duration_i_ms) => {
                         let sleep_fn = platform.sleep;
@@ -992,13 +1172,13 @@ impl BuiltinKind {
 
                 match (args[0].as_ref(), args[1].as_ref()) {
                     (Object::ClosureContext(ctx), Object::Array(params)) => {
-                        let n_parms_required = ctx.as_ref().compiled_fn.as_ref().num_parameters;
+                        let subroutine = ctx.as_ref().compiled_fn.as_ref();
                         let n_provided = params.borrow().elements.len();
-                        if n_parms_required != n_provided {
+                        if !subroutine.accepts_arguments(n_provided) {
                             return Err(format!(
                                 "Function {} requires {} parameters, provided {}",
-                                ctx.as_ref().compiled_fn.name,
-                                n_parms_required,
+                                subroutine.name,
+                                subroutine.describe_arity(0),
                                 n_provided
                             ));
                         }
@@ -1040,7 +1220,7 @@ impl BuiltinKind {
 
             BuiltinKind::CallAsync => {
                 if !ENABLE_CONCURRENCY {
-                    return Err(format!("BosonVM has concurrency disabled."));
+                    return Err("BosonVM has concurrency disabled.".to_string());
                 }
 
                 if args.len() != 2 {
@@ -1052,23 +1232,26 @@ impl BuiltinKind {
 
                 match (args[0].as_ref(), args[1].as_ref()) {
                     (Object::ClosureContext(ctx), Object::Array(params)) => {
-                        let n_parms_required = ctx.as_ref().compiled_fn.as_ref().num_parameters;
+                        let subroutine = ctx.as_ref().compiled_fn.as_ref();
                         let n_provided = params.borrow().elements.len();
-                        if n_parms_required != n_provided {
+                        if !subroutine.accepts_arguments(n_provided) {
                             return Err(format!(
                                 "Function {} requires {} parameters, provided {}",
-                                ctx.as_ref().compiled_fn.name,
-                                n_parms_required,
+                                subroutine.name,
+                                subroutine.describe_arity(0),
                                 n_provided
                             ));
                         }
 
                         let params = params.borrow().elements.clone();
                         // call the async function
-                        let thread_params =
-                            ThreadParams::new(ctx.clone(), params, gp.clone(), c.clone());
+                        let thread_params = ThreadParams::new(ctx.clone(), params, gp, c);
+                        if thread_params.is_err() {
+                            return Err(thread_params.unwrap_err());
+                        }
 
-                        let thread_create_res = th.create_thread_sandbox(thread_params, platform);
+                        let thread_create_res =
+                            th.create_thread_sandbox(thread_params.unwrap(), platform);
                         if thread_create_res.is_err() {
                             return Err(thread_create_res.unwrap_err());
                         }
@@ -1080,7 +1263,7 @@ impl BuiltinKind {
                         }
 
                         // thread has finished execution successfully:
-                        let sandbox_result = thread_exec_res.unwrap().result;
+                        let sandbox_result = thread_exec_res.unwrap().get_result();
                         if sandbox_result.is_err() {
                             let error = sandbox_result.unwrap_err();
                             return Err(format!(
@@ -1109,18 +1292,18 @@ impl BuiltinKind {
                     ));
                 }
 
-                let display_obj = args.get(0).map(|o| o.describe());
+                let display_obj = args.first().map(|o| o.describe());
                 let readline_fn = platform.read_line;
                 let result = readline_fn(display_obj);
                 if result.is_err() {
-                    return Err(result.unwrap());
+                    return Err(result.unwrap_err());
                 }
 
                 return Ok(Rc::new(Object::Str(result.unwrap())));
             }
             BuiltinKind::Wait => {
                 if !ENABLE_CONCURRENCY {
-                    return Err(format!("BosonVM has concurrency disabled."));
+                    return Err("BosonVM has concurrency disabled.".to_string());
                 }
 
                 if args.len() != 1 {
@@ -1139,7 +1322,7 @@ impl BuiltinKind {
                             ));
                         }
 
-                        let sandbox_result = thread_exec_res.unwrap().result;
+                        let sandbox_result = thread_exec_res.unwrap().get_result();
                         if sandbox_result.is_err() {
                             let error = sandbox_result.unwrap_err();
                             return Err(format!(
@@ -1276,7 +1459,7 @@ impl BuiltinKind {
                     // only the file path is provided
                     match args[0].as_ref() {
                         Object::Str(st) => {
-                            let result = __read(st.clone(), None, None, &platform);
+                            let result = __read(st.clone(), None, None, platform);
                             return result;
                         }
                         _ => {
@@ -1289,7 +1472,7 @@ impl BuiltinKind {
                 } else if args.len() == 2 {
                     match (args[0].as_ref(), args[1].as_ref()) {
                         (Object::Str(st), Object::Int(i)) => {
-                            let result = __read(st.clone(), Some(*i as u64), None, &platform);
+                            let result = __read(st.clone(), Some(*i as u64), None, platform);
                             return result;
                         }
                         _ => {
@@ -1303,7 +1486,7 @@ impl BuiltinKind {
                 } else if args.len() == 3 {
                     match (args[0].as_ref(), args[1].as_ref(), args[2].as_ref()) {
                         (Object::Str(st), Object::Int(i), Object::Int(j)) => {
-                            let result = __read(st.clone(), Some(*i as u64), Some(*j as u64), &platform);
+                            let result = __read(st.clone(), Some(*i as u64), Some(*j as u64), platform);
                             return result;
                         }
                         _ => {
@@ -1352,7 +1535,7 @@ impl BuiltinKind {
             }
 
             BuiltinKind::SRead => {
-                if args.len() != 0 {
+                if !args.is_empty() {
                     return Err(format!(
                         "stdin() takes no arguments, provided {}",
                         args.len()
@@ -1390,7 +1573,7 @@ impl BuiltinKind {
                         return Ok(result.unwrap());
                     }
                     _ => {
-                        return Err(format!(""));
+                        return Err(String::new());
                     }
                 }
             }
@@ -1407,14 +1590,13 @@ impl BuiltinKind {
                 match path_obj {
                     Object::Str(path_str) => {
                         // process path string
-                        let processed_path = if path_str.starts_with("std::") {
+                        let processed_path = if let Some(mod_name) = path_str.strip_prefix("std::")
+                        {
                             let stdlib_path_fn = platform.get_stdlib_path;
                             let std_path = stdlib_path_fn();
 
-                            let mod_name = path_str[5..].to_string();
-
                             Path::new(&std_path)
-                                .join(&format!("lib{}.so", mod_name))
+                                .join(format!("lib{}.so", mod_name))
                                 .to_str()
                                 .unwrap()
                                 .to_string()
@@ -1553,7 +1735,7 @@ impl BuiltinKind {
             }
 
             BuiltinKind::Rand => {
-                if args.len() != 0 && args.len() != 2 {
+                if !args.is_empty() && args.len() != 2 {
                     return Err(format!(
                         "rand() function either zero or two arguments, provided {}",
                         args.len()
@@ -1563,11 +1745,10 @@ impl BuiltinKind {
                 if args.len() == 2 {
                     let (lb, ub) = match (args[0].as_ref(), args[1].as_ref()) {
                         (Object::Int(i1), Object::Int(i2)) => (*i1 as f64, *i2 as f64),
-                        (Object::Float(f1), Object::Float(f2)) => (*f1 as f64, *f2 as f64),
+                        (Object::Float(f1), Object::Float(f2)) => ((*f1), (*f2)),
                         (_, _) => {
-                            return Err(format!(
-                                "rand() function takes int or float as argument, but got "
-                            ))
+                            return Err("rand() function takes int or float as argument, but got "
+                                .to_string())
                         }
                     };
 
@@ -1579,31 +1760,6 @@ impl BuiltinKind {
                 }
             }
 
-            BuiltinKind::Sort => {
-                if args.len() != 2 {
-                    return Err(format!("sort() function two arguments, got {}", args.len()));
-                }
-
-                let (mut array_ref, reverse) = match (args[0].as_ref(), args[1].as_ref()) {
-                    (Object::Array(arr), Object::Bool(b)) => (arr.borrow_mut(), *b),
-                    (_, _) => {
-                        return Err(format!(
-                            "sort() takes two parameters of type array and bool, but got {} and {}",
-                            args[0].get_type(),
-                            args[1].get_type()
-                        ))
-                    }
-                };
-
-                if reverse {
-                    array_ref.elements.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                } else {
-                    array_ref.elements.sort_by(|a, b| b.partial_cmp(a).unwrap())
-                }
-
-                Ok(Rc::new(Object::Noval))
-            }
-
             BuiltinKind::SetAt => {
                 if args.len() != 3 {
                     return Err(format!(
@@ -1637,8 +1793,8 @@ impl BuiltinKind {
                 match (args[0].as_ref(), args[1].as_ref(), args[2].as_ref()) {
                     (Object::Array(types_array), Object::Array(data), _) => {
                         let data_buffer_result = encode_boson_types(
-                            &types_array.borrow().get_values_ref(),
-                            &data.borrow().get_values_ref(),
+                            types_array.borrow().get_values_ref(),
+                            data.borrow().get_values_ref(),
                             args[2].clone(),
                         );
 
@@ -1665,6 +1821,151 @@ impl BuiltinKind {
                 return caller(args);
             }
 
+            BuiltinKind::Attrs => {
+                if args.len() != 1 {
+                    return Err(format!("attrs() takes 1 argument, provided {}", args.len()));
+                }
+
+                return Ok(Rc::new(Object::Array(RefCell::new(Array {
+                    name: "attrs".to_string(),
+                    elements: args[0].attrs(),
+                }))));
+            }
+
+            // range(stop), range(start, stop) and range(start, stop, step):
+            BuiltinKind::Range => {
+                if args.is_empty() || args.len() > 3 {
+                    return Err(format!(
+                        "range() takes 1 to 3 arguments, provided {}",
+                        args.len()
+                    ));
+                }
+
+                let mut bounds = vec![];
+                for arg in &args {
+                    match arg.as_ref() {
+                        Object::Int(i) => bounds.push(*i),
+                        _ => {
+                            return Err(format!(
+                                "range() expects int arguments, got {}",
+                                arg.get_type()
+                            ));
+                        }
+                    }
+                }
+
+                let (start, stop, step) = match bounds.len() {
+                    1 => (0, bounds[0], 1),
+                    2 => (bounds[0], bounds[1], 1),
+                    _ => (bounds[0], bounds[1], bounds[2]),
+                };
+
+                let range_res = iter::ObjectIterator::new_range(start, stop, step);
+                if range_res.is_err() {
+                    return Err(range_res.unwrap_err());
+                }
+
+                return Ok(Rc::new(Object::Iter(RefCell::new(range_res.unwrap()))));
+            }
+
+            // format(template, ...args), see types/format.rs for the template syntax:
+            BuiltinKind::Format => {
+                if args.is_empty() {
+                    return Err("format() takes at least 1 argument, provided 0".to_string());
+                }
+
+                let template = match args[0].as_ref() {
+                    Object::Str(st) => st,
+                    _ => {
+                        return Err(format!(
+                            "format() expects a string template, got {}",
+                            args[0].get_type()
+                        ));
+                    }
+                };
+
+                let result = format::format_template(template, &args[1..]);
+                if result.is_err() {
+                    return Err(result.unwrap_err());
+                }
+
+                return Ok(Rc::new(Object::Str(result.unwrap())));
+            }
+
+            BuiltinKind::Channel => {
+                if args.len() > 1 {
+                    return Err(format!(
+                        "channel() takes at max 1 argument, provided {}",
+                        args.len()
+                    ));
+                }
+
+                // no capacity means an unbounded channel:
+                if args.is_empty() {
+                    return Ok(Rc::new(Object::Channel(Channel::new(0))));
+                }
+
+                match args[0].as_ref() {
+                    Object::Int(capacity) => {
+                        if *capacity <= 0 {
+                            return Err(format!(
+                                "channel() capacity must be greater than zero, got {}",
+                                capacity
+                            ));
+                        }
+
+                        return Ok(Rc::new(Object::Channel(Channel::new(*capacity as usize))));
+                    }
+                    _ => {
+                        return Err(format!(
+                            "channel() takes int as argument, but got {}.",
+                            args[0].get_type(),
+                        ))
+                    }
+                }
+            }
+
+            BuiltinKind::Mutex => {
+                if args.len() > 1 {
+                    return Err(format!(
+                        "mutex() takes at max 1 argument, provided {}",
+                        args.len()
+                    ));
+                }
+
+                let value = args.first().cloned().unwrap_or(Rc::new(Object::Noval));
+                let mutex = BosonMutex::new(&value);
+                if mutex.is_err() {
+                    return Err(mutex.unwrap_err());
+                }
+                return Ok(Rc::new(Object::Mutex(mutex.unwrap())));
+            }
+
+            BuiltinKind::Atomic => {
+                if args.len() > 1 {
+                    return Err(format!(
+                        "atomic() takes at max 1 argument, provided {}",
+                        args.len()
+                    ));
+                }
+
+                if args.is_empty() {
+                    return Ok(Rc::new(Object::Atomic(AtomicCounter::new(0))));
+                }
+
+                match args[0].as_ref() {
+                    Object::Int(value) => {
+                        return Ok(Rc::new(Object::Atomic(AtomicCounter::new(*value))));
+                    }
+                    _ => {
+                        return Err(format!(
+                            "atomic() takes int as argument, but got {}.",
+                            args[0].get_type(),
+                        ))
+                    }
+                }
+            }
+
             _ => return Err("Trying to invoke invalid builtin".to_string()),
         }
     }
@@ -1691,6 +1992,34 @@ impl BuiltinKind {
         return Some(builtin_kind);
     }
 
+    /*
+        Builtins that can be called from a pure function, they depend
+        only on their arguments and have no side effects.
+    */
+    pub fn is_pure(&self) -> bool {
+        matches!(
+            self,
+            BuiltinKind::Truthy
+                | BuiltinKind::Length
+                | BuiltinKind::String
+                | BuiltinKind::Int
+                | BuiltinKind::Float
+                | BuiltinKind::Bool
+                | BuiltinKind::Byte
+                | BuiltinKind::Char
+                | BuiltinKind::Iter
+                | BuiltinKind::Bytes
+                | BuiltinKind::TypeOf
+                | BuiltinKind::CreateArray
+                | BuiltinKind::CreateBuffer
+                | BuiltinKind::Sort
+                | BuiltinKind::EncodePacked
+                | BuiltinKind::Attrs
+                | BuiltinKind::Range
+                | BuiltinKind::Format
+        )
+    }
+
     pub fn get_names() -> Vec<String> {
         let builtin_size = BuiltinKind::EndMark as usize;
 
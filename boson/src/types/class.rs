@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::types::array::Array;
+use crate::types::object::AttributeResolver;
+use crate::types::object::Object;
+
+// needed by attribute function call resolver
+use crate::api;
+use crate::compiler;
+use crate::vm;
+
+use api::Platform;
+use compiler::symtab::ConstantPool;
+use vm::ffi::BosonFFI;
+use vm::global::GlobalPool;
+use vm::stack::DataStack;
+use vm::thread::BosonThreads;
+
+use std::cell::RefCell;
+
+/*
+    User type created by a class (or struct) declaration. Calling the
+    class creates an instance, init is called with the new instance and
+    the call arguments when the class defines it, otherwise the arguments
+    are assigned to the fields in declaration order.
+*/
+#[derive(Clone, Debug)]
+pub struct ClassType {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: HashMap<String, Rc<Object>>,
+}
+
+impl ClassType {
+    pub fn describe(&self) -> String {
+        return format!("Class(name={})", self.name);
+    }
+
+    pub fn get_method(&self, name: &String) -> Option<Rc<Object>> {
+        return self.methods.get(name).cloned();
+    }
+
+    pub fn field_index(&self, name: &String) -> Option<usize> {
+        return self.fields.iter().position(|field| field == name);
+    }
+
+    /*
+        Checks the number of arguments of a method call, the
+        instance is not counted.
+    */
+    pub fn check_arguments(&self, method: &String, n_args: usize) -> Option<String> {
+        let func = self.methods.get(method);
+        if func.is_none() {
+            return None;
+        }
+
+        if let Object::ClosureContext(ctx) = func.unwrap().as_ref() {
+            let subroutine = ctx.compiled_fn.as_ref();
+            if !subroutine.accepts_arguments(n_args + 1) {
+                // the instance is left out of the count:
+                let expected = subroutine.describe_arity(1);
+                if method == "init" {
+                    return Some(format!(
+                        "{} expects {} arguments, given {}",
+                        self.name, expected, n_args
+                    ));
+                }
+
+                return Some(format!(
+                    "Method {} of {} expects {} arguments, given {}",
+                    method, self.name, expected, n_args
+                ));
+            }
+        }
+
+        return None;
+    }
+
+    /*
+        Turns the keyword arguments of a constructor call into positional
+        ones, matched against the parameters of init or, without init,
+        against the fields.
+    */
+    pub fn resolve_keywords(
+        &self,
+        args: Vec<Rc<Object>>,
+        kwargs: &[(String, Rc<Object>)],
+    ) -> Result<Vec<Rc<Object>>, String> {
+        let init = self.methods.get("init");
+        if init.is_some() {
+            match init.unwrap().as_ref() {
+                Object::ClosureContext(ctx) => return ctx.resolve_keywords(args, kwargs, 1),
+                _ => return Err(format!("init of {} is not a function", self.name)),
+            }
+        }
+
+        let mut slots: Vec<Option<Rc<Object>>> = args.into_iter().map(Some).collect();
+        slots.resize(self.fields.len().max(slots.len()), None);
+        for (name, value) in kwargs.iter() {
+            let idx = self.field_index(name);
+            if idx.is_none() {
+                return Err(format!("{} has no field {}", self.name, name));
+            }
+
+            if slots[idx.unwrap()].is_some() {
+                return Err(format!(
+                    "{} got multiple values for field {}",
+                    self.name, name
+                ));
+            }
+            slots[idx.unwrap()] = Some(value.clone());
+        }
+
+        let mut resolved = vec![];
+        for (idx, slot) in slots.into_iter().enumerate() {
+            if slot.is_none() {
+                return Err(format!(
+                    "{} is missing the field {}",
+                    self.name, self.fields[idx]
+                ));
+            }
+            resolved.push(slot.unwrap());
+        }
+
+        return Ok(resolved);
+    }
+
+    fn method_names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self.methods.keys().collect();
+        names.sort();
+        return names;
+    }
+}
+
+impl AttributeResolver for ClassType {
+    fn attrs(&self) -> Vec<Rc<Object>> {
+        return self
+            .method_names()
+            .into_iter()
+            .map(|name| Rc::new(Object::Str(name.clone())))
+            .collect();
+    }
+
+    fn resolve_get_attr(&self, keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+        let f_key = &keys[0];
+        let value = match f_key.as_ref() {
+            Object::Str(st) => match st.as_ref() {
+                "__name__" => Rc::new(Object::Str(self.name.clone())),
+                "__fields__" => {
+                    let fields = self
+                        .fields
+                        .iter()
+                        .map(|field| Rc::new(Object::Str(field.clone())))
+                        .collect();
+                    Rc::new(Object::Array(RefCell::new(Array {
+                        name: format!("{}_fields", self.name),
+                        elements: fields,
+                    })))
+                }
+                _ => {
+                    let method = self.get_method(st);
+                    if method.is_none() {
+                        return Err(format!("Class {} has no method {}", self.name, st));
+                    }
+
+                    method.unwrap()
+                }
+            },
+            _ => {
+                return Err(format!(
+                    "Expected string attribute, got {}",
+                    f_key.get_type()
+                ));
+            }
+        };
+
+        if keys.len() == 1 {
+            return Ok(value);
+        }
+
+        return value.resolve_get_attr(&keys[1..]);
+    }
+
+    fn resolve_set_attr(&self, _keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        return Some(format!("Attributes of class {} are read only.", self.name));
+    }
+
+    fn resolve_call_attr(
+        &mut self,
+        keys: &[Rc<Object>],
+        _args: &[Rc<Object>],
+        _ds: &mut DataStack,
+        _platform: &mut Platform,
+        _gp: &mut GlobalPool,
+        _c: &mut ConstantPool,
+        _th: &mut BosonThreads,
+        _ffi: &mut BosonFFI,
+    ) -> Result<Rc<Object>, String> {
+        // methods are called by the VM, only unknown names end up here.
+        return Err(format!(
+            "Class {} has no method {}",
+            self.name,
+            keys[keys.len() - 1]
+        ));
+    }
+}
+
+impl Hash for ClassType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.fields.hash(state);
+    }
+}
+
+impl PartialEq for ClassType {
+    fn eq(&self, other: &ClassType) -> bool {
+        self.name == other.name && self.fields == other.fields
+    }
+}
+
+impl PartialOrd for ClassType {
+    fn partial_cmp(&self, _other: &ClassType) -> Option<Ordering> {
+        None
+    }
+}
+
+/*
+    An object of a user type, values are stored in the order of
+    the class fields.
+*/
+#[derive(Clone, Debug)]
+pub struct Instance {
+    pub class: Rc<ClassType>,
+    pub values: Vec<Rc<Object>>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<ClassType>) -> Instance {
+        let mut values = vec![];
+        values.resize(class.fields.len(), Rc::new(Object::Noval));
+        return Instance {
+            class: class,
+            values: values,
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        let fields: Vec<String> = self
+            .class
+            .fields
+            .iter()
+            .zip(self.values.iter())
+            .map(|(field, value)| format!("{}={}", field, value.describe()))
+            .collect();
+        return format!("{}({})", self.class.name, fields.join(", "));
+    }
+
+    pub fn get_field(&self, name: &String) -> Result<Rc<Object>, String> {
+        let idx = self.class.field_index(name);
+        if idx.is_some() {
+            return Ok(self.values[idx.unwrap()].clone());
+        }
+
+        if self.class.methods.contains_key(name) {
+            return Err(format!(
+                "{} is a method of {}, call it as {}(...)",
+                name, self.class.name, name
+            ));
+        }
+
+        return Err(format!("{} has no attribute {}", self.class.name, name));
+    }
+
+    pub fn set_field(&mut self, key: &Rc<Object>, value: Rc<Object>) -> Option<String> {
+        let name = match key.as_ref() {
+            Object::Str(st) => st,
+            _ => return Some(format!("Expected string attribute, got {}", key.get_type())),
+        };
+
+        let idx = self.class.field_index(name);
+        if idx.is_none() {
+            return Some(format!("{} has no field {}", self.class.name, name));
+        }
+
+        self.values[idx.unwrap()] = value;
+        return None;
+    }
+}
+
+impl AttributeResolver for Instance {
+    fn attrs(&self) -> Vec<Rc<Object>> {
+        let mut names: Vec<Rc<Object>> = self
+            .class
+            .fields
+            .iter()
+            .map(|field| Rc::new(Object::Str(field.clone())))
+            .collect();
+        names.extend(self.class.attrs());
+        return names;
+    }
+
+    fn resolve_get_attr(&self, keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+        let f_key = &keys[0];
+        let value = match f_key.as_ref() {
+            Object::Str(st) => match st.as_ref() {
+                "__class__" => Rc::new(Object::Class(self.class.clone())),
+                _ => {
+                    let result = self.get_field(st);
+                    if result.is_err() {
+                        return Err(result.unwrap_err());
+                    }
+
+                    result.unwrap()
+                }
+            },
+            _ => {
+                return Err(format!(
+                    "Expected string attribute, got {}",
+                    f_key.get_type()
+                ));
+            }
+        };
+
+        if keys.len() == 1 {
+            return Ok(value);
+        }
+
+        return value.resolve_get_attr(&keys[1..]);
+    }
+
+    fn resolve_set_attr(&self, keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        // the instance is borrowed mutably by Object::resolve_set_attr, which
+        // calls set_field instead.
+        return Some(format!(
+            "Cannot assign {} on a shared reference to an instance of {}.",
+            keys[0].describe(),
+            self.class.name
+        ));
+    }
+
+    fn resolve_call_attr(
+        &mut self,
+        keys: &[Rc<Object>],
+        _args: &[Rc<Object>],
+        _ds: &mut DataStack,
+        _platform: &mut Platform,
+        _gp: &mut GlobalPool,
+        _c: &mut ConstantPool,
+        _th: &mut BosonThreads,
+        _ffi: &mut BosonFFI,
+    ) -> Result<Rc<Object>, String> {
+        // methods are called by the VM, only unknown names end up here.
+        return Err(format!(
+            "{} has no method {}",
+            self.class.name,
+            keys[keys.len() - 1]
+        ));
+    }
+}
+
+impl Hash for Instance {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.class.hash(state);
+        self.values.hash(state);
+    }
+}
+
+impl PartialEq for Instance {
+    fn eq(&self, other: &Instance) -> bool {
+        self.class == other.class && self.values == other.values
+    }
+}
+
+impl PartialOrd for Instance {
+    fn partial_cmp(&self, _other: &Instance) -> Option<Ordering> {
+        None
+    }
+}
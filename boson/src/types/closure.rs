@@ -1,18 +1,107 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 use std::vec::Vec;
 
+use crate::types::array::Array;
 use crate::types::object::Object;
 use crate::types::subroutine::Subroutine;
 
+/*
+    Results of a pure function keyed by its arguments. Only calls with
+    scalar arguments and results are remembered, arrays and other
+    containers can change after the call.
+*/
+#[derive(Debug)]
+pub struct MemoTable {
+    entries: RefCell<HashMap<Vec<Rc<Object>>, Rc<Object>>>,
+}
+
+impl Default for MemoTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoTable {
+    pub fn new() -> MemoTable {
+        return MemoTable {
+            entries: RefCell::new(HashMap::new()),
+        };
+    }
+
+    fn is_scalar(obj: &Object) -> bool {
+        matches!(
+            obj,
+            Object::Noval
+                | Object::Int(_)
+                | Object::Bool(_)
+                | Object::Char(_)
+                | Object::Str(_)
+                | Object::Float(_)
+                | Object::Byte(_)
+        )
+    }
+
+    pub fn key(args: &[Rc<Object>]) -> Option<Vec<Rc<Object>>> {
+        if !args.iter().all(|arg| MemoTable::is_scalar(arg)) {
+            return None;
+        }
+
+        return Some(args.to_vec());
+    }
+
+    pub fn get(&self, key: &Vec<Rc<Object>>) -> Option<Rc<Object>> {
+        return self.entries.borrow().get(key).cloned();
+    }
+
+    pub fn insert(&self, key: Vec<Rc<Object>>, result: &Rc<Object>) {
+        if MemoTable::is_scalar(result) {
+            self.entries.borrow_mut().insert(key, result.clone());
+        }
+    }
+}
+
+impl PartialOrd for MemoTable {
+    fn partial_cmp(&self, _other: &MemoTable) -> Option<Ordering> {
+        None
+    }
+}
+
+impl PartialEq for MemoTable {
+    fn eq(&self, _other: &MemoTable) -> bool {
+        false
+    }
+}
+
 #[derive(Clone, Debug, PartialOrd)]
 pub struct ClosureContext {
     pub compiled_fn: Rc<Subroutine>,
     pub free_objects: Vec<Rc<Object>>,
     pub bytecode_size: usize,
+    // shared by the copies made for every call.
+    pub memo: Option<Rc<MemoTable>>,
 }
 
 impl ClosureContext {
+    pub fn new(compiled_fn: Rc<Subroutine>, free_objects: Vec<Rc<Object>>) -> ClosureContext {
+        let bytecode_size = compiled_fn.bytecode.len();
+        let memo = if compiled_fn.is_pure {
+            Some(Rc::new(MemoTable::new()))
+        } else {
+            None
+        };
+
+        return ClosureContext {
+            compiled_fn: compiled_fn,
+            free_objects: free_objects,
+            bytecode_size: bytecode_size,
+            memo: memo,
+        };
+    }
+
     pub fn describe(&self) -> String {
         return self.compiled_fn.describe();
     }
@@ -20,6 +109,113 @@ impl ClosureContext {
     pub fn get_objects(&self) -> &Vec<Rc<Object>> {
         return &self.free_objects;
     }
+
+    // default values of the parameters, IClosure keeps them after the free objects.
+    pub fn get_defaults(&self) -> &[Rc<Object>] {
+        let n_defaults = self.compiled_fn.num_defaults;
+        return &self.free_objects[self.free_objects.len() - n_defaults..];
+    }
+
+    /*
+        Binds the arguments of a call to the parameters, the missing ones
+        take their default values and the extra ones are collected in an
+        array for the variadic parameter. Returns a value per parameter.
+    */
+    pub fn bind_arguments(&self, mut args: Vec<Rc<Object>>) -> Result<Vec<Rc<Object>>, String> {
+        let subroutine = self.compiled_fn.as_ref();
+        if !subroutine.accepts_arguments(args.len()) {
+            return Err(format!(
+                "Function {} expects {} arguments, given {}",
+                subroutine.name,
+                subroutine.describe_arity(0),
+                args.len()
+            ));
+        }
+
+        let n_fixed = subroutine.num_parameters - subroutine.is_variadic as usize;
+        let rest = if args.len() > n_fixed {
+            args.split_off(n_fixed)
+        } else {
+            vec![]
+        };
+
+        let defaults = self.get_defaults();
+        let first_default = n_fixed - subroutine.num_defaults;
+        while args.len() < n_fixed {
+            args.push(defaults[args.len() - first_default].clone());
+        }
+
+        if subroutine.is_variadic {
+            args.push(Rc::new(Object::Array(RefCell::new(Array {
+                name: subroutine.parameter_names[n_fixed].clone(),
+                elements: rest,
+            }))));
+        }
+
+        return Ok(args);
+    }
+
+    /*
+        Turns keyword arguments into positional ones, the parameters left
+        out before a keyword argument take their default values. skip is
+        the number of parameters given by the caller, like the instance
+        of a method.
+    */
+    pub fn resolve_keywords(
+        &self,
+        args: Vec<Rc<Object>>,
+        kwargs: &[(String, Rc<Object>)],
+        skip: usize,
+    ) -> Result<Vec<Rc<Object>>, String> {
+        let subroutine = self.compiled_fn.as_ref();
+        let n_fixed = subroutine.num_parameters - subroutine.is_variadic as usize;
+        let names = &subroutine.parameter_names[skip.min(n_fixed)..n_fixed];
+
+        let mut slots: Vec<Option<Rc<Object>>> = args.into_iter().map(Some).collect();
+        for (name, value) in kwargs.iter() {
+            let idx = names.iter().position(|param| param == name);
+            if idx.is_none() {
+                return Err(format!(
+                    "Function {} has no parameter named {}",
+                    subroutine.name, name
+                ));
+            }
+
+            let idx = idx.unwrap();
+            if idx < slots.len() && slots[idx].is_some() {
+                return Err(format!(
+                    "Function {} got multiple values for parameter {}",
+                    subroutine.name, name
+                ));
+            }
+
+            if idx >= slots.len() {
+                slots.resize(idx + 1, None);
+            }
+            slots[idx] = Some(value.clone());
+        }
+
+        let defaults = self.get_defaults();
+        let first_default = n_fixed - subroutine.num_defaults;
+        let mut resolved = vec![];
+        for (idx, slot) in slots.into_iter().enumerate() {
+            let param_idx = idx + skip;
+            match slot {
+                Some(value) => resolved.push(value),
+                None if param_idx >= first_default => {
+                    resolved.push(defaults[param_idx - first_default].clone())
+                }
+                None => {
+                    return Err(format!(
+                        "Function {} is missing the argument {}",
+                        subroutine.name, subroutine.parameter_names[param_idx]
+                    ))
+                }
+            }
+        }
+
+        return Ok(resolved);
+    }
 }
 
 impl PartialEq for ClosureContext {
@@ -65,18 +65,18 @@ impl NativeModuleRef {
 }
 
 impl AttributeResolver for NativeModuleRef {
-    fn resolve_get_attr(&self, _keys: &Vec<Rc<Object>>) -> Result<Rc<Object>, String> {
+    fn resolve_get_attr(&self, _keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
         Ok(Rc::new(Object::Noval))
     }
 
-    fn resolve_set_attr(&self, _keys: &Vec<Rc<Object>>, _value: Rc<Object>) -> Option<String> {
-        None
+    fn resolve_set_attr(&self, _keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        Some("Native module does not support attribute assignment.".to_string())
     }
 
     fn resolve_call_attr(
         &mut self,
-        keys: &Vec<Rc<Object>>,
-        args: &Vec<Rc<Object>>,
+        keys: &[Rc<Object>],
+        args: &[Rc<Object>],
         _ds: &mut DataStack,
         _platform: &mut Platform,
         _gp: &mut GlobalPool,
@@ -87,7 +87,8 @@ impl AttributeResolver for NativeModuleRef {
         let method = keys[0].as_ref();
         match method {
             Object::Str(method_str) => {
-                let ffi_exec_result = ffi.exec(self.handle as usize, method_str.clone(), args);
+                let ffi_exec_result =
+                    ffi.exec(self.handle as usize, method_str.clone(), &args.to_vec());
                 if ffi_exec_result.is_err() {
                     return Err(ffi_exec_result.unwrap_err());
                 }
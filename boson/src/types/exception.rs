@@ -1,8 +1,15 @@
-use crate::vm::errors::StackFrame;
+use crate::vm::errors::TraceEntry;
+use crate::vm::errors::VMError;
 use crate::vm::errors::VMErrorKind;
 
+use crate::isa::InstructionKind;
+use crate::types::array::Array;
+use crate::types::object::Object;
+
+use std::cell::RefCell;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::rc::Rc;
 
 use std::cmp::Ordering;
 
@@ -11,7 +18,7 @@ pub struct Exception {
     handle_name: String,
     exception_string: String,
     root_error_type: VMErrorKind,
-    pub stack_trace: Vec<StackFrame>, // stack trace is represented backwards, 0th element is the current function,
+    pub stack_trace: Vec<TraceEntry>, // stack trace is represented backwards, 0th element is the current function,
                                       // len - 1 is the root function or usually main.
 }
 
@@ -20,7 +27,7 @@ impl Exception {
         name: String,
         message: String,
         err_kind: VMErrorKind,
-        trace: Vec<StackFrame>,
+        trace: Vec<TraceEntry>,
     ) -> Exception {
         return Exception {
             handle_name: name,
@@ -30,15 +37,86 @@ impl Exception {
         };
     }
 
+    pub fn new_from_vm_error(error: &VMError, trace: Vec<TraceEntry>) -> Exception {
+        return Exception::new(
+            format!("{:?}", error.t),
+            error.message.clone(),
+            error.t.clone(),
+            trace,
+        );
+    }
+
+    // converts the exception back to VMError, used when
+    // there is no handler left to catch it.
+    pub fn as_vm_error(&self) -> VMError {
+        let mut error = VMError::new(
+            self.exception_string.clone(),
+            self.root_error_type.clone(),
+            Some(InstructionKind::IRaise),
+            0,
+        );
+
+        error.location = self
+            .stack_trace
+            .first()
+            .and_then(|entry| entry.location.clone());
+        error.trace = self.stack_trace.clone();
+        return error;
+    }
+
+    pub fn get_name(&self) -> &String {
+        return &self.handle_name;
+    }
+
+    pub fn get_message(&self) -> &String {
+        return &self.exception_string;
+    }
+
+    pub fn get_kind(&self) -> &VMErrorKind {
+        return &self.root_error_type;
+    }
+
     pub fn describe(&self) -> String {
         return format!("{:?}: {}", self.root_error_type, self.exception_string);
     }
+
+    pub fn attrs(&self) -> Vec<Rc<Object>> {
+        return vec![
+            Rc::new(Object::Str(String::from("message"))),
+            Rc::new(Object::Str(String::from("kind"))),
+            Rc::new(Object::Str(String::from("trace"))),
+        ];
+    }
+
+    pub fn get_attribute(&self, key: &String) -> Result<Rc<Object>, String> {
+        match key.as_ref() {
+            "message" => return Ok(Rc::new(Object::Str(self.exception_string.clone()))),
+            "kind" => return Ok(Rc::new(Object::Str(format!("{:?}", self.root_error_type)))),
+            "trace" => {
+                // most recent call last, like the traceback of uncaught errors:
+                let elements = self
+                    .stack_trace
+                    .iter()
+                    .rev()
+                    .map(|entry| Rc::new(Object::Str(format!("{}", entry))))
+                    .collect();
+
+                return Ok(Rc::new(Object::Array(RefCell::new(Array {
+                    name: "trace".to_string(),
+                    elements: elements,
+                }))));
+            }
+            _ => return Err(format!("Attribute {} not found for type exception", key)),
+        }
+    }
 }
 
-// hash and partial equality:
+// exceptions are equal by value, the stack trace is not compared:
 impl PartialEq for Exception {
     fn eq(&self, other: &Exception) -> bool {
         self.handle_name == other.handle_name
+            && self.exception_string == other.exception_string
+            && self.root_error_type == other.root_error_type
     }
 }
 
@@ -0,0 +1,369 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_bigint::Sign;
+
+use crate::config::MAX_STRING_SIZE;
+use crate::types::bigint;
+use crate::types::object::Object;
+
+/*
+    Format specifier of a replacement field, the part after the colon
+    in {:>8.2f}, written as [[fill]align][sign][#][0][width][.precision][type].
+*/
+#[derive(Clone, Debug)]
+pub struct FormatSpec {
+    pub fill: char,
+    pub align: Option<char>,
+    pub sign: char,
+    pub alternate: bool,
+    pub zero_pad: bool,
+    pub width: usize,
+    pub precision: Option<usize>,
+    pub kind: Option<char>,
+}
+
+fn is_align(ch: char) -> bool {
+    return ch == '<' || ch == '>' || ch == '^';
+}
+
+// reads the digits at the start of chars, None when there are none.
+fn read_number(chars: &[char], pos: &mut usize) -> Result<Option<usize>, String> {
+    let start = *pos;
+    while *pos < chars.len() && chars[*pos].is_ascii_digit() {
+        *pos += 1;
+    }
+
+    if start == *pos {
+        return Ok(None);
+    }
+
+    // the padding is built in memory, so the width cannot be arbitrarily large:
+    let digits: String = chars[start..*pos].iter().collect();
+    match digits.parse::<usize>() {
+        Ok(number) if number <= MAX_STRING_SIZE => return Ok(Some(number)),
+        _ => {
+            return Err(format!(
+                "Format width or precision {} is larger than the limit of {}",
+                digits, MAX_STRING_SIZE
+            ))
+        }
+    }
+}
+
+impl FormatSpec {
+    pub fn parse(spec: &str) -> Result<FormatSpec, String> {
+        let chars: Vec<char> = spec.chars().collect();
+        let mut format_spec = FormatSpec {
+            fill: ' ',
+            align: None,
+            sign: '-',
+            alternate: false,
+            zero_pad: false,
+            width: 0,
+            precision: None,
+            kind: None,
+        };
+
+        let mut pos = 0;
+        if chars.len() >= 2 && is_align(chars[1]) {
+            format_spec.fill = chars[0];
+            format_spec.align = Some(chars[1]);
+            pos = 2;
+        } else if !chars.is_empty() && is_align(chars[0]) {
+            format_spec.align = Some(chars[0]);
+            pos = 1;
+        }
+
+        if pos < chars.len() && (chars[pos] == '+' || chars[pos] == '-' || chars[pos] == ' ') {
+            format_spec.sign = chars[pos];
+            pos += 1;
+        }
+
+        if pos < chars.len() && chars[pos] == '#' {
+            format_spec.alternate = true;
+            pos += 1;
+        }
+
+        if pos < chars.len() && chars[pos] == '0' {
+            format_spec.zero_pad = true;
+            pos += 1;
+        }
+
+        let width = read_number(&chars, &mut pos);
+        if width.is_err() {
+            return Err(width.unwrap_err());
+        }
+        format_spec.width = width.unwrap().unwrap_or(0);
+
+        if pos < chars.len() && chars[pos] == '.' {
+            pos += 1;
+            let precision = read_number(&chars, &mut pos);
+            if precision.is_err() {
+                return Err(precision.unwrap_err());
+            }
+
+            if precision.as_ref().unwrap().is_none() {
+                return Err(format!(
+                    "Format specifier {} is missing the precision",
+                    spec
+                ));
+            }
+            format_spec.precision = precision.unwrap();
+        }
+
+        if pos < chars.len() {
+            format_spec.kind = Some(chars[pos]);
+            pos += 1;
+        }
+
+        if pos != chars.len() {
+            return Err(format!("Invalid format specifier {}", spec));
+        }
+
+        return Ok(format_spec);
+    }
+
+    fn sign_of(&self, negative: bool) -> &'static str {
+        if negative {
+            return "-";
+        }
+
+        match self.sign {
+            '+' => return "+",
+            ' ' => return " ",
+            _ => return "",
+        }
+    }
+
+    /*
+        Pads a formatted value to the width. Zero padding goes between the
+        sign (or the 0x prefix) and the digits, numbers are aligned to the
+        right and everything else to the left by default.
+    */
+    fn pad(&self, prefix: &str, body: String, numeric: bool) -> String {
+        let length = prefix.chars().count() + body.chars().count();
+        if length >= self.width {
+            return format!("{}{}", prefix, body);
+        }
+
+        let missing = self.width - length;
+        if self.zero_pad && self.align.is_none() && numeric {
+            return format!("{}{}{}", prefix, "0".repeat(missing), body);
+        }
+
+        let fill = if self.zero_pad && self.align.is_none() {
+            '0'
+        } else {
+            self.fill
+        };
+
+        let default_align = if numeric { '>' } else { '<' };
+        let (left, right) = match self.align.unwrap_or(default_align) {
+            '<' => (0, missing),
+            '^' => (missing / 2, missing - missing / 2),
+            _ => (missing, 0),
+        };
+
+        let left_fill: String = std::iter::repeat_n(fill, left).collect();
+        let right_fill: String = std::iter::repeat_n(fill, right).collect();
+        return format!("{}{}{}{}", left_fill, prefix, body, right_fill);
+    }
+
+    fn format_int(&self, value: i64) -> Result<String, String> {
+        let magnitude = value.unsigned_abs();
+        let (radix_prefix, digits) = match self.kind {
+            None | Some('d') => ("", magnitude.to_string()),
+            Some('x') => ("0x", format!("{:x}", magnitude)),
+            Some('X') => ("0X", format!("{:X}", magnitude)),
+            Some('b') => ("0b", format!("{:b}", magnitude)),
+            Some('o') => ("0o", format!("{:o}", magnitude)),
+            Some('c') => {
+                let ch = std::char::from_u32(value as u32);
+                if value < 0 || ch.is_none() {
+                    return Err(format!("{} is not a valid character code", value));
+                }
+                return Ok(self.pad("", ch.unwrap().to_string(), false));
+            }
+            Some('f') | Some('e') | Some('E') | Some('%') => {
+                return self.format_float(value as f64)
+            }
+            Some('s') => return Ok(self.pad("", value.to_string(), true)),
+            Some(kind) => return Err(format!("Unknown format type '{}' for int", kind)),
+        };
+
+        let radix_prefix = if self.alternate { radix_prefix } else { "" };
+        let prefix = format!("{}{}", self.sign_of(value < 0), radix_prefix);
+        return Ok(self.pad(&prefix, digits, true));
+    }
+
+    // the decimal, hex, binary and octal types of ints that overflow an i64:
+    fn format_big_int(&self, value: &BigInt) -> Result<String, String> {
+        let magnitude = value.magnitude();
+        let (radix_prefix, digits) = match self.kind {
+            None | Some('d') => ("", magnitude.to_str_radix(10)),
+            Some('x') => ("0x", magnitude.to_str_radix(16)),
+            Some('X') => ("0X", magnitude.to_str_radix(16).to_uppercase()),
+            Some('b') => ("0b", magnitude.to_str_radix(2)),
+            Some('o') => ("0o", magnitude.to_str_radix(8)),
+            Some('c') => return Err(format!("{} is not a valid character code", value)),
+            Some('f') | Some('e') | Some('E') | Some('%') => {
+                return self.format_float(bigint::to_f64(value))
+            }
+            Some('s') => return Ok(self.pad("", value.to_string(), true)),
+            Some(kind) => return Err(format!("Unknown format type '{}' for int", kind)),
+        };
+
+        let radix_prefix = if self.alternate { radix_prefix } else { "" };
+        let prefix = format!(
+            "{}{}",
+            self.sign_of(value.sign() == Sign::Minus),
+            radix_prefix
+        );
+        return Ok(self.pad(&prefix, digits, true));
+    }
+
+    fn format_float(&self, value: f64) -> Result<String, String> {
+        let magnitude = value.abs();
+        let body = match self.kind {
+            None => match self.precision {
+                Some(precision) => format!("{:.*}", precision, magnitude),
+                None => Object::Float(magnitude).describe(),
+            },
+            Some('f') => format!("{:.*}", self.precision.unwrap_or(6), magnitude),
+            Some('%') => format!("{:.*}%", self.precision.unwrap_or(6), magnitude * 100.0),
+            Some('e') | Some('E') if !magnitude.is_finite() => format!("{}", magnitude),
+            Some('e') | Some('E') => {
+                // 1.5e3 is written as 1.500000e+03:
+                let formatted = format!("{:.*e}", self.precision.unwrap_or(6), magnitude);
+                let (mantissa, exponent) = formatted.split_at(formatted.find('e').unwrap());
+                let exponent: i32 = exponent[1..].parse().unwrap();
+                let sign = if exponent < 0 { '-' } else { '+' };
+                let body = format!("{}e{}{:02}", mantissa, sign, exponent.abs());
+                if self.kind == Some('E') {
+                    body.to_uppercase()
+                } else {
+                    body
+                }
+            }
+            Some('s') => Object::Float(magnitude).describe(),
+            Some(kind) => return Err(format!("Unknown format type '{}' for float", kind)),
+        };
+
+        let prefix = self.sign_of(value.is_sign_negative() && value != 0.0);
+        return Ok(self.pad(prefix, body, true));
+    }
+
+    fn format_text(&self, text: String) -> Result<String, String> {
+        match self.kind {
+            None | Some('s') => {}
+            Some(kind) => return Err(format!("Unknown format type '{}' for string", kind)),
+        }
+
+        let body = match self.precision {
+            Some(precision) => text.chars().take(precision).collect(),
+            None => text,
+        };
+
+        return Ok(self.pad("", body, false));
+    }
+
+    pub fn format_object(&self, value: &Rc<Object>) -> Result<String, String> {
+        match value.as_ref() {
+            Object::Int(i) => return self.format_int(*i),
+            Object::BigInt(b) => return self.format_big_int(b),
+            Object::Float(f) => return self.format_float(*f),
+            Object::Byte(b) => return self.format_int(*b as i64),
+            Object::Char(c) if self.kind == Some('d') => return self.format_int(*c as i64),
+            Object::ByteBuffer(buffer) => {
+                let text = buffer.borrow().get_as_string();
+                if text.is_err() {
+                    return Err(text.unwrap_err());
+                }
+                return self.format_text(text.unwrap());
+            }
+            _ => return self.format_text(value.describe()),
+        }
+    }
+}
+
+/*
+    Fills the replacement fields of a template: {} takes the next
+    argument, {1} the argument at that position and {:spec} or {1:spec}
+    formats it, {{ and }} are written as single braces.
+*/
+pub fn format_template(template: &str, args: &[Rc<Object>]) -> Result<String, String> {
+    let chars: Vec<char> = template.chars().collect();
+    let mut result = String::new();
+    let mut next_arg = 0;
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let ch = chars[pos];
+        if ch == '}' {
+            if pos + 1 < chars.len() && chars[pos + 1] == '}' {
+                result.push('}');
+                pos += 2;
+                continue;
+            }
+            return Err("Single } in format template, use }} for a brace".to_string());
+        }
+
+        if ch != '{' {
+            result.push(ch);
+            pos += 1;
+            continue;
+        }
+
+        if pos + 1 < chars.len() && chars[pos + 1] == '{' {
+            result.push('{');
+            pos += 2;
+            continue;
+        }
+
+        let end = chars[pos..].iter().position(|c| *c == '}');
+        if end.is_none() {
+            return Err("Unterminated { in format template".to_string());
+        }
+
+        let field: String = chars[pos + 1..pos + end.unwrap()].iter().collect();
+        pos += end.unwrap() + 1;
+
+        let (name, spec) = match field.find(':') {
+            Some(idx) => (&field[..idx], &field[idx + 1..]),
+            None => (field.as_str(), ""),
+        };
+
+        let arg_idx = if name.is_empty() {
+            next_arg += 1;
+            next_arg - 1
+        } else {
+            match name.trim().parse::<usize>() {
+                Ok(idx) => idx,
+                Err(_) => return Err(format!("Invalid format field {{{}}}", field)),
+            }
+        };
+
+        if arg_idx >= args.len() {
+            return Err(format!(
+                "format() has no argument for field {}, given {} arguments",
+                arg_idx,
+                args.len()
+            ));
+        }
+
+        let format_spec = FormatSpec::parse(spec);
+        if format_spec.is_err() {
+            return Err(format_spec.unwrap_err());
+        }
+
+        let formatted = format_spec.unwrap().format_object(&args[arg_idx]);
+        if formatted.is_err() {
+            return Err(formatted.unwrap_err());
+        }
+
+        result.push_str(&formatted.unwrap());
+    }
+
+    return Ok(result);
+}
@@ -2,11 +2,14 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::iter::FromIterator;
 use std::rc::Rc;
 use std::vec::Vec;
 
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+
 use crate::types::array::Array;
+use crate::types::bigint;
 use crate::types::object::AttributeResolver;
 use crate::types::object::Object;
 
@@ -24,49 +27,203 @@ use vm::thread::BosonThreads;
 
 use std::cmp::Ordering;
 
+/*
+    Entries are kept in the order they were inserted in, keys(), values(),
+    items(), iteration and printing follow that order. The index maps a key
+    to the position of its entry. Deleting an entry leaves a hole in its
+    place, the holes are dropped once there are more of them than entries.
+    order is the insertion number of an entry, it only grows, so iterators
+    can find their position again after the holes were dropped.
+
+    Numbers that are equal by value are the same key, the index stores them
+    as ints (see index_key()) and the entry keeps the key first inserted.
+*/
+#[derive(Clone, Debug)]
+pub struct HashEntry {
+    pub order: usize,
+    // None once the entry is deleted.
+    pub pair: Option<(Rc<Object>, Rc<Object>)>,
+}
+
 #[derive(Clone, Debug)]
 pub struct HashTable {
     pub name: String,
-    pub entries: HashMap<Rc<Object>, Rc<Object>>,
+    index: HashMap<Rc<Object>, usize>,
+    entries: Vec<HashEntry>,
+    next_order: usize,
 }
 
+const METHODS: &[&str] = &[
+    "keys",
+    "values",
+    "items",
+    "get",
+    "contains",
+    "delete",
+    "pop",
+    "setdefault",
+    "merge",
+    "update",
+    "clear",
+];
+
 impl HashTable {
+    pub fn new(name: String) -> HashTable {
+        return HashTable {
+            name: name,
+            index: HashMap::new(),
+            entries: vec![],
+            next_order: 0,
+        };
+    }
+
     pub fn describe(&self) -> String {
-        let combined_pairs: Vec<String> = (&self.entries)
-            .into_iter()
+        let combined_pairs: Vec<String> = self
+            .ordered()
             .map(|(key, value)| format!("{}: {}", key.describe(), value.describe()))
             .collect();
 
         return format!("HashTable({{{}}})", combined_pairs.join(", "));
     }
 
+    fn ordered(&self) -> impl Iterator<Item = (&Rc<Object>, &Rc<Object>)> {
+        return self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.pair.as_ref().map(|(key, value)| (key, value)));
+    }
+
     pub fn keys(&self) -> Vec<Rc<Object>> {
-        return Vec::from_iter(self.entries.keys().cloned());
+        return self.ordered().map(|(key, _)| key.clone()).collect();
+    }
+
+    // 1.0 and the raw byte 1 are indexed as the int 1, None for other keys:
+    fn index_key(key: &Object) -> Option<Object> {
+        match key {
+            Object::Byte(byte) => return Some(Object::Int(*byte as i64)),
+            Object::Float(f) if f.is_finite() && f.fract() == 0.0 => {
+                let int = bigint::from_bigint(BigInt::from_f64(*f).unwrap());
+                return Some(int.as_ref().clone());
+            }
+            _ => return None,
+        }
+    }
+
+    fn index_key_rc(key: &Rc<Object>) -> Rc<Object> {
+        let normalised = HashTable::index_key(key);
+        if normalised.is_some() {
+            return Rc::new(normalised.unwrap());
+        }
+
+        return key.clone();
+    }
+
+    fn position(&self, key: &Object) -> Option<usize> {
+        let normalised = HashTable::index_key(key);
+        return self.index.get(normalised.as_ref().unwrap_or(key)).cloned();
     }
 
     pub fn set(&mut self, key: Rc<Object>, value: Rc<Object>) {
-        self.entries.insert(key, value);
+        let index_key = HashTable::index_key_rc(&key);
+        let existing = self.index.get(&index_key);
+        if existing.is_some() {
+            let pos = *existing.unwrap();
+            let (old_key, _) = self.entries[pos].pair.take().unwrap();
+            self.entries[pos].pair = Some((old_key, value));
+            return;
+        }
+
+        self.index.insert(index_key, self.entries.len());
+        self.entries.push(HashEntry {
+            order: self.next_order,
+            pair: Some((key, value)),
+        });
+        self.next_order += 1;
+    }
+
+    pub fn delete(&mut self, key: &Rc<Object>) -> Option<Rc<Object>> {
+        let removed = self.index.remove(&HashTable::index_key_rc(key));
+        if removed.is_none() {
+            return None;
+        }
+
+        let pair = self.entries[removed.unwrap()].pair.take();
+        if self.entries.len() > 2 * self.index.len() {
+            self.compact();
+        }
+
+        return pair.map(|(_, value)| value);
+    }
+
+    // drops the holes left by deleted entries:
+    fn compact(&mut self) {
+        self.entries.retain(|entry| entry.pair.is_some());
+        for (pos, entry) in self.entries.iter().enumerate() {
+            let (key, _) = entry.pair.as_ref().unwrap();
+            self.index.insert(HashTable::index_key_rc(key), pos);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.entries.clear();
+    }
+
+    pub fn contains(&self, key: &Rc<Object>) -> bool {
+        return self.position(key).is_some();
+    }
+
+    /*
+        The first entry inserted at or after the given insertion number,
+        pos is where it was found the last time and is tried first.
+        Returns the position and the entry, used by iterators.
+    */
+    pub fn entry_from(&self, pos: usize, order: usize) -> Option<(usize, &HashEntry)> {
+        let start = if pos <= self.entries.len()
+            && (pos == 0 || self.entries[pos - 1].order < order)
+            && self
+                .entries
+                .get(pos)
+                .is_none_or(|entry| entry.order >= order)
+        {
+            pos
+        } else {
+            self.entries.partition_point(|entry| entry.order < order)
+        };
+
+        return self.entries[start..]
+            .iter()
+            .enumerate()
+            .find(|(_, entry)| entry.pair.is_some())
+            .map(|(offset, entry)| (start + offset, entry));
     }
 
     pub fn values(&self) -> Vec<Rc<Object>> {
-        return Vec::from_iter(self.entries.values().cloned());
+        return self.ordered().map(|(_, value)| value.clone()).collect();
+    }
+
+    pub fn items(&self) -> Vec<(Rc<Object>, Rc<Object>)> {
+        return self
+            .ordered()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
     }
 
     pub fn length(&self) -> usize {
-        return self.entries.len();
+        return self.index.len();
     }
 
     pub fn get(&self, key: &Rc<Object>) -> Result<Rc<Object>, String> {
-        let result = self.entries.get(key);
-        if result.is_none() {
-            return Err(format!("Key {} not found.", key.describe()));
+        let result = self.get_ref(key);
+        if result.is_err() {
+            return Err(result.unwrap_err());
         }
 
         return Ok(result.unwrap().clone());
     }
 
     pub fn get_ref(&self, key: &Rc<Object>) -> Result<&Rc<Object>, String> {
-        let result = self.entries.get(key);
+        let result = self.lookup(key);
         if result.is_none() {
             return Err(format!("Key {} not found.", key.describe()));
         }
@@ -74,13 +231,27 @@ impl HashTable {
         return Ok(result.unwrap());
     }
 
+    fn lookup(&self, key: &Object) -> Option<&Rc<Object>> {
+        let pos = self.position(key);
+        if pos.is_none() {
+            return None;
+        }
+
+        return self.entries[pos.unwrap()]
+            .pair
+            .as_ref()
+            .map(|(_, value)| value);
+    }
+
     // attrs:
     pub fn attrs(&self) -> Vec<Rc<Object>> {
-        return vec![
-            Rc::new(Object::Str(String::from("keys"))),
-            Rc::new(Object::Str(String::from("__name__"))),
-            Rc::new(Object::Str(String::from("values"))),
-        ];
+        let mut attrs = vec![Rc::new(Object::Str(String::from("__name__")))];
+        attrs.extend(
+            METHODS
+                .iter()
+                .map(|name| Rc::new(Object::Str(name.to_string()))),
+        );
+        return attrs;
     }
 
     pub fn get_attribute(&self, key: &String) -> Result<Rc<Object>, String> {
@@ -88,7 +259,7 @@ impl HashTable {
             "__name__" => return Ok(Rc::new(Object::Str(self.name.clone()))),
             _ => {
                 let key_owned = Object::Str(key.to_owned());
-                let val_opt = self.entries.get(&key_owned);
+                let val_opt = self.lookup(&key_owned);
                 if val_opt.is_some() {
                     return Ok(val_opt.unwrap().clone());
                 }
@@ -98,43 +269,156 @@ impl HashTable {
         }
     }
 
+    // config.port = 8080 is the same as config["port"] = 8080
+    pub fn set_attribute(&mut self, key: &Rc<Object>, value: Rc<Object>) -> Option<String> {
+        match key.as_ref() {
+            Object::Str(st) if st == "__name__" => {
+                return Some(format!(
+                    "__name__ of hash table {} is read only.",
+                    self.name
+                ));
+            }
+            Object::Str(_) => {
+                self.set(key.clone(), value);
+                return None;
+            }
+            _ => return Some(format!("Expected string attribute, got {}", key.get_type())),
+        }
+    }
+
+    /*
+        Methods of the hash table type, called as map.get("key", 0).
+        update, delete, pop, setdefault and clear modify the table in place,
+        merge returns a new table with the entries of both, the entries of
+        the argument win when a key is in both.
+    */
     pub fn call_attribute(
         &mut self,
         key: &String,
-        args: &Vec<Rc<Object>>,
+        args: &[Rc<Object>],
     ) -> Result<Rc<Object>, String> {
+        if !METHODS.contains(&key.as_str()) {
+            return Err(format!(
+                "Attribute {} not found for type {}",
+                key, "HashTable"
+            ));
+        }
+
+        let (min_args, max_args) = match key.as_ref() {
+            "keys" | "values" | "items" | "clear" => (0, 0),
+            "get" | "pop" => (1, 2),
+            "setdefault" => (2, 2),
+            _ => (1, 1),
+        };
+
+        if args.len() < min_args || args.len() > max_args {
+            if min_args == max_args {
+                return Err(format!(
+                    "{}() takes {} argument(s), provided {}.",
+                    key,
+                    min_args,
+                    args.len()
+                ));
+            }
+
+            return Err(format!(
+                "{}() takes {} to {} arguments, provided {}.",
+                key,
+                min_args,
+                max_args,
+                args.len()
+            ));
+        }
+
         match key.as_ref() {
-            "keys" => {
-                if args.len() != 0 {
-                    return Err(format!(
-                        "keys() takes zero arguments, provided {}.",
-                        args.len()
-                    ));
+            "keys" => return Ok(self.new_array(self.keys())),
+            "values" => return Ok(self.new_array(self.values())),
+            "items" => {
+                let pairs = self
+                    .items()
+                    .into_iter()
+                    .map(|(key, value)| self.new_array(vec![key, value]))
+                    .collect();
+                return Ok(self.new_array(pairs));
+            }
+            "get" => {
+                let result = self.lookup(&args[0]);
+                if result.is_some() {
+                    return Ok(result.unwrap().clone());
                 }
 
-                let key_array = self.keys();
-                let array_obj = Array {
-                    elements: key_array,
-                    name: self.name.clone(),
-                };
+                if args.len() == 2 {
+                    return Ok(args[1].clone());
+                }
 
-                return Ok(Rc::new(Object::Array(RefCell::new(array_obj))));
+                return Ok(Rc::new(Object::Noval));
             }
-            "values" => {
-                if args.len() != 0 {
-                    return Err(format!(
-                        "keys() takes zero arguments, provided {}.",
-                        args.len()
-                    ));
+            "contains" => {
+                return Ok(Rc::new(Object::Bool(self.contains(&args[0]))));
+            }
+            "delete" => {
+                let removed = self.delete(&args[0]);
+                return Ok(Rc::new(Object::Bool(removed.is_some())));
+            }
+            "pop" => {
+                let removed = self.delete(&args[0]);
+                if removed.is_some() {
+                    return Ok(removed.unwrap());
                 }
 
-                let key_array = self.values();
-                let array_obj = Array {
-                    elements: key_array,
-                    name: self.name.clone(),
+                if args.len() == 2 {
+                    return Ok(args[1].clone());
+                }
+
+                return Err(format!("pop() key {} not found.", args[0].describe()));
+            }
+            "setdefault" => {
+                let result = self.lookup(&args[0]);
+                if result.is_some() {
+                    return Ok(result.unwrap().clone());
+                }
+
+                self.set(args[0].clone(), args[1].clone());
+                return Ok(args[1].clone());
+            }
+            "merge" | "update" => {
+                let other_items = match args[0].as_ref() {
+                    Object::HashTable(other) => match other.try_borrow() {
+                        Ok(other) => other.items(),
+                        // the table is being merged with itself:
+                        Err(_) => self.items(),
+                    },
+                    _ => {
+                        return Err(format!(
+                            "{}() expects hashmap as argument, got {}",
+                            key,
+                            args[0].get_type()
+                        ))
+                    }
                 };
 
-                return Ok(Rc::new(Object::Array(RefCell::new(array_obj))));
+                if key == "update" {
+                    for (item_key, item_value) in other_items {
+                        self.set(item_key, item_value);
+                    }
+
+                    return Ok(Rc::new(Object::Noval));
+                }
+
+                let mut merged = HashTable::new(format!("{}_merge", self.name));
+                for (item_key, item_value) in self.items() {
+                    merged.set(item_key, item_value);
+                }
+
+                for (item_key, item_value) in other_items {
+                    merged.set(item_key, item_value);
+                }
+
+                return Ok(Rc::new(Object::HashTable(RefCell::new(merged))));
+            }
+            "clear" => {
+                self.clear();
+                return Ok(Rc::new(Object::Noval));
             }
             _ => {
                 return Err(format!(
@@ -144,6 +428,13 @@ impl HashTable {
             }
         }
     }
+
+    fn new_array(&self, elements: Vec<Rc<Object>>) -> Rc<Object> {
+        return Rc::new(Object::Array(RefCell::new(Array {
+            elements: elements,
+            name: self.name.clone(),
+        })));
+    }
 }
 
 impl Hash for HashTable {
@@ -168,46 +459,50 @@ impl fmt::Display for HashTable {
 
 impl AttributeResolver for HashTable {
     fn attrs(&self) -> Vec<Rc<Object>> {
-        return vec![
-            Rc::new(Object::Str(String::from("keys"))),
-            Rc::new(Object::Str(String::from("__name__"))),
-            Rc::new(Object::Str(String::from("values"))),
-        ];
+        return HashTable::attrs(self);
     }
 
-    fn resolve_get_attr(&self, keys: &Vec<Rc<Object>>) -> Result<Rc<Object>, String> {
+    fn resolve_get_attr(&self, keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
         let f_key = &keys[0];
 
-        match f_key.as_ref() {
-            Object::Str(st) => {
-                match st.as_ref() {
-                    // base attributes
-                    "__name__" => return Ok(Rc::new(Object::Str(self.name.clone()))),
-                    _ => {
-                        let val_opt = self.entries.get(f_key);
-                        if val_opt.is_some() {
-                            return Ok(val_opt.unwrap().clone());
-                        }
-
+        if let Object::Str(st) = f_key.as_ref() {
+            match st.as_ref() {
+                // base attributes
+                "__name__" => return Ok(Rc::new(Object::Str(self.name.clone()))),
+                _ => {
+                    let val_opt = self.lookup(f_key);
+                    if val_opt.is_none() {
                         return Ok(Rc::new(Object::Noval));
                     }
+
+                    let value = val_opt.unwrap().clone();
+                    if keys.len() == 1 {
+                        return Ok(value);
+                    }
+
+                    // resolve the remaining attributes on the value:
+                    return value.resolve_get_attr(&keys[1..]);
                 }
             }
-
-            _ => {}
         }
 
         return Ok(Rc::new(Object::Noval));
     }
 
-    fn resolve_set_attr(&self, _keys: &Vec<Rc<Object>>, _value: Rc<Object>) -> Option<String> {
-        return None;
+    fn resolve_set_attr(&self, keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        // the table is borrowed mutably by Object::resolve_set_attr, which
+        // calls set_attribute instead.
+        return Some(format!(
+            "Cannot assign {} on a shared reference to hash table {}.",
+            keys[0].describe(),
+            self.name
+        ));
     }
 
     fn resolve_call_attr(
         &mut self,
-        keys: &Vec<Rc<Object>>,
-        args: &Vec<Rc<Object>>,
+        keys: &[Rc<Object>],
+        args: &[Rc<Object>],
         _ds: &mut DataStack,
         _platform: &mut Platform,
         _gp: &mut GlobalPool,
@@ -217,46 +512,7 @@ impl AttributeResolver for HashTable {
     ) -> Result<Rc<Object>, String> {
         let key = &keys[0];
         match key.as_ref() {
-            Object::Str(st) => match st.as_ref() {
-                "keys" => {
-                    if args.len() != 0 {
-                        return Err(format!(
-                            "keys() takes zero arguments, provided {}.",
-                            args.len()
-                        ));
-                    }
-
-                    let key_array = self.keys();
-                    let array_obj = Array {
-                        elements: key_array,
-                        name: self.name.clone(),
-                    };
-
-                    return Ok(Rc::new(Object::Array(RefCell::new(array_obj))));
-                }
-                "values" => {
-                    if args.len() != 0 {
-                        return Err(format!(
-                            "keys() takes zero arguments, provided {}.",
-                            args.len()
-                        ));
-                    }
-
-                    let key_array = self.values();
-                    let array_obj = Array {
-                        elements: key_array,
-                        name: self.name.clone(),
-                    };
-
-                    return Ok(Rc::new(Object::Array(RefCell::new(array_obj))));
-                }
-                _ => {
-                    return Err(format!(
-                        "Attribute {} not found for type {}",
-                        key, "HashTable"
-                    ));
-                }
-            },
+            Object::Str(st) => return self.call_attribute(st, args),
             _ => {
                 return Err(format!("Expected string attribute, got {}", key.get_type()));
             }
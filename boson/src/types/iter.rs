@@ -1,61 +1,81 @@
-use crate::types::object;
-
-use object::Object;
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::rc::Rc;
 
+use crate::api::Platform;
+use crate::compiler::symtab::ConstantPool;
+use crate::types::closure::ClosureContext;
+use crate::types::object::Object;
+use crate::vm::errors::VMError;
+use crate::vm::ffi::BosonFFI;
+use crate::vm::frames::ExecutionFrame;
+use crate::vm::global::GlobalPool;
+use crate::vm::thread::BosonThreads;
+use crate::vm::BosonVM;
+
 /*
-    This is a dummy iterator, not a real one.
-    Optimal performance not gaurenteed.
+    A suspended generator: its frame and the data stack it runs on.
+    Generators are resumed on a sandbox VM of their own, the frame
+    always starts at the bottom of the saved stack.
 */
+#[derive(Clone, Debug)]
+pub struct GeneratorState {
+    pub frame: ExecutionFrame,
+    pub stack: Vec<Rc<Object>>,
+    pub stack_pointer: i64,
+}
 
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug)]
+pub enum IterSource {
+    // arrays, strings and byte buffers are read at the current position on
+    // every step, elements appended inside the loop are visited as well.
+    Sequence(Rc<Object>),
+    // the keys of a hash table, read from the live table on every step,
+    // order is the insertion number of the next entry to visit.
+    Table { table: Rc<Object>, order: usize },
+    // elements copied out of another iterator, like one sent to a thread.
+    Snapshot(Vec<Rc<Object>>),
+    Range { current: i64, stop: i64, step: i64 },
+    // None once the generator has returned.
+    Generator(Option<GeneratorState>),
+}
+
+/*
+    Iterators produce their elements one at a time, nothing is copied
+    up front. Stepping a generator runs boson code, so next() and has_next()
+    take the state of the VM.
+*/
+#[derive(Clone, Debug)]
 pub struct ObjectIterator {
-    pub idx: usize,
-    pub size: usize,
-    pub elements: Vec<Rc<Object>>,
+    pub source: IterSource,
+    // position in the source, a byte offset for strings.
+    pub pos: usize,
+    // number of elements handed out so far.
+    pub count: usize,
+    // element produced by has_next() on a generator, returned by the next call to next().
+    pub peeked: Option<Rc<Object>>,
 }
 
 impl ObjectIterator {
+    fn from_source(source: IterSource) -> ObjectIterator {
+        return ObjectIterator {
+            source: source,
+            pos: 0,
+            count: 0,
+            peeked: None,
+        };
+    }
+
     pub fn new(obj: Rc<Object>) -> Result<ObjectIterator, String> {
         match obj.as_ref() {
-            Object::Array(arr) => {
-                return Ok(ObjectIterator {
-                    idx: 0,
-                    size: arr.borrow().elements.len(),
-                    elements: arr.borrow().elements.clone(),
-                });
-            }
-            Object::HashTable(ht) => {
-                let table = ht.borrow();
-                let length = table.length();
-                return Ok(ObjectIterator {
-                    idx: 0,
-                    size: length,
-                    elements: table.keys(),
-                });
-            }
-            Object::Str(st) => {
-                let vec_string: Vec<Rc<Object>> =
-                    st.chars().map(|c| Rc::new(Object::Char(c))).collect();
-                return Ok(ObjectIterator {
-                    idx: 0,
-                    size: vec_string.len(),
-                    elements: vec_string,
-                });
+            Object::Array(_) | Object::Str(_) | Object::ByteBuffer(_) => {
+                return Ok(ObjectIterator::from_source(IterSource::Sequence(obj)));
             }
-            Object::ByteBuffer(buffer) => {
-                let byte_vec: Vec<Rc<Object>> = buffer
-                    .borrow()
-                    .data
-                    .iter()
-                    .map(|b| Rc::new(Object::Byte(*b)))
-                    .collect();
-
-                return Ok(ObjectIterator {
-                    idx: 0,
-                    size: byte_vec.len(),
-                    elements: byte_vec,
-                });
+            Object::HashTable(_) => {
+                return Ok(ObjectIterator::from_source(IterSource::Table {
+                    table: obj,
+                    order: 0,
+                }));
             }
             _ => {
                 return Err(format!(
@@ -66,24 +86,311 @@ impl ObjectIterator {
         }
     }
 
-    pub fn next(&mut self) -> Option<Rc<Object>> {
-        if self.idx >= self.size {
-            return None;
+    // iterators are iterated as they are, other objects get a new iterator.
+    pub fn iter_object(obj: Rc<Object>) -> Result<Rc<Object>, String> {
+        if let Object::Iter(_) = obj.as_ref() {
+            return Ok(obj);
+        }
+
+        let iter_res = ObjectIterator::new(obj);
+        if iter_res.is_err() {
+            return Err(iter_res.unwrap_err());
+        }
+
+        return Ok(Rc::new(Object::Iter(RefCell::new(iter_res.unwrap()))));
+    }
+
+    pub fn new_snapshot(elements: Vec<Rc<Object>>, count: usize) -> ObjectIterator {
+        let mut iterator = ObjectIterator::from_source(IterSource::Snapshot(elements));
+        iterator.count = count;
+        return iterator;
+    }
+
+    pub fn new_range(start: i64, stop: i64, step: i64) -> Result<ObjectIterator, String> {
+        if step == 0 {
+            return Err("range() step cannot be zero".to_string());
+        }
+
+        return Ok(ObjectIterator::from_source(IterSource::Range {
+            current: start,
+            stop: stop,
+            step: step,
+        }));
+    }
+
+    // the arguments become the first locals of the generator frame.
+    pub fn new_generator(closure: Rc<ClosureContext>, args: Vec<Rc<Object>>) -> ObjectIterator {
+        let n_locals = closure.compiled_fn.num_locals;
+        let mut stack = args;
+        stack.resize(n_locals, Rc::new(Object::Noval));
+
+        let state = GeneratorState {
+            frame: ExecutionFrame::new(closure, 0),
+            stack: stack,
+            stack_pointer: n_locals as i64,
+        };
+
+        return ObjectIterator::from_source(IterSource::Generator(Some(state)));
+    }
+
+    pub fn is_generator(&self) -> bool {
+        matches!(self.source, IterSource::Generator(_))
+    }
+
+    // next element of the sources that can be read without running code.
+    fn step(&mut self) -> Option<Rc<Object>> {
+        match &mut self.source {
+            IterSource::Sequence(obj) => match obj.as_ref() {
+                Object::Array(arr) => {
+                    let element = arr.borrow().elements.get(self.pos).cloned();
+                    if element.is_some() {
+                        self.pos += 1;
+                    }
+                    return element;
+                }
+                Object::Str(st) => {
+                    let ch = st.get(self.pos..).and_then(|rest| rest.chars().next());
+                    if ch.is_none() {
+                        return None;
+                    }
+
+                    self.pos += ch.unwrap().len_utf8();
+                    return Some(Rc::new(Object::Char(ch.unwrap())));
+                }
+                Object::ByteBuffer(buffer) => {
+                    let byte = buffer.borrow().data.get(self.pos).cloned();
+                    if byte.is_none() {
+                        return None;
+                    }
+
+                    self.pos += 1;
+                    return Some(Rc::new(Object::Byte(byte.unwrap())));
+                }
+                _ => return None,
+            },
+            IterSource::Table { table, order } => match table.as_ref() {
+                Object::HashTable(ht) => {
+                    let ht = ht.borrow();
+                    let found = ht.entry_from(self.pos, *order);
+                    if found.is_none() {
+                        return None;
+                    }
+
+                    let (pos, entry) = found.unwrap();
+                    self.pos = pos + 1;
+                    *order = entry.order + 1;
+                    return entry.pair.as_ref().map(|(key, _)| key.clone());
+                }
+                _ => return None,
+            },
+            IterSource::Snapshot(elements) => {
+                let element = elements.get(self.pos).cloned();
+                if element.is_some() {
+                    self.pos += 1;
+                }
+                return element;
+            }
+            IterSource::Range {
+                current,
+                stop,
+                step,
+            } => {
+                let in_range = if *step > 0 {
+                    *current < *stop
+                } else {
+                    *current > *stop
+                };
+
+                if !in_range {
+                    return None;
+                }
+
+                let value = *current;
+                // an overflowing step ends the range:
+                *current = value.checked_add(*step).unwrap_or(*stop);
+                return Some(Rc::new(Object::Int(value)));
+            }
+            IterSource::Generator(_) => return None,
         }
-        let object = self.elements[self.idx].clone();
-        self.idx += 1;
-        return Some(object);
+    }
+
+    // runs the generator up to its next yield.
+    fn resume(
+        &mut self,
+        platform: &mut Platform,
+        gp: &mut GlobalPool,
+        c: &mut ConstantPool,
+        th: &mut BosonThreads,
+        ffi: &mut BosonFFI,
+    ) -> Result<Option<Rc<Object>>, VMError> {
+        let state = match &mut self.source {
+            IterSource::Generator(state) => state.take(),
+            _ => None,
+        };
+
+        if state.is_none() {
+            return Ok(None);
+        }
+
+        let result = BosonVM::with_sandbox(gp, c, th, ffi, |vm| {
+            vm.resume_generator(state.unwrap(), platform)
+        });
+
+        // a generator that failed is finished, like one that returned,
+        // its error is passed on as it is.
+        if result.is_err() {
+            return Err(result.unwrap_err());
+        }
+
+        let (value, next_state) = result.unwrap();
+        if next_state.is_none() {
+            return Ok(None);
+        }
+
+        self.source = IterSource::Generator(next_state);
+        return Ok(Some(value));
+    }
+
+    pub fn next(
+        &mut self,
+        platform: &mut Platform,
+        gp: &mut GlobalPool,
+        c: &mut ConstantPool,
+        th: &mut BosonThreads,
+        ffi: &mut BosonFFI,
+    ) -> Result<Option<Rc<Object>>, VMError> {
+        let mut element = self.peeked.take();
+        if element.is_none() {
+            if self.is_generator() {
+                let result = self.resume(platform, gp, c, th, ffi);
+                if result.is_err() {
+                    return Err(result.unwrap_err());
+                }
+
+                element = result.unwrap();
+            } else {
+                element = self.step();
+            }
+        }
+
+        if element.is_some() {
+            self.count += 1;
+        }
+
+        return Ok(element);
+    }
+
+    pub fn has_next(
+        &mut self,
+        platform: &mut Platform,
+        gp: &mut GlobalPool,
+        c: &mut ConstantPool,
+        th: &mut BosonThreads,
+        ffi: &mut BosonFFI,
+    ) -> Result<bool, VMError> {
+        if self.peeked.is_some() {
+            return Ok(true);
+        }
+
+        if !self.is_generator() {
+            return Ok(self.may_have_next());
+        }
+
+        // a generator only knows once it has run to its next yield:
+        let result = self.resume(platform, gp, c, th, ffi);
+        if result.is_err() {
+            return Err(result.unwrap_err());
+        }
+
+        self.peeked = result.unwrap();
+        return Ok(self.peeked.is_some());
+    }
+
+    // exact for every source but generators, which are not run here.
+    pub fn may_have_next(&self) -> bool {
+        if self.peeked.is_some() {
+            return true;
+        }
+
+        match &self.source {
+            IterSource::Sequence(obj) => match obj.as_ref() {
+                Object::Array(arr) => return self.pos < arr.borrow().elements.len(),
+                Object::Str(st) => return self.pos < st.len(),
+                Object::ByteBuffer(buffer) => return self.pos < buffer.borrow().data.len(),
+                _ => return false,
+            },
+            IterSource::Table { table, order } => match table.as_ref() {
+                Object::HashTable(ht) => return ht.borrow().entry_from(self.pos, *order).is_some(),
+                _ => return false,
+            },
+            IterSource::Snapshot(elements) => return self.pos < elements.len(),
+            IterSource::Range {
+                current,
+                stop,
+                step,
+            } => {
+                if *step > 0 {
+                    return current < stop;
+                }
+                return current > stop;
+            }
+            IterSource::Generator(state) => return state.is_some(),
+        }
+    }
+
+    // elements left in a source that does not run code, used to send iterators to threads.
+    pub fn remaining(&self) -> Vec<Rc<Object>> {
+        let mut copy = self.clone();
+        let mut elements = vec![];
+        if copy.peeked.is_some() {
+            elements.push(copy.peeked.take().unwrap());
+        }
+
+        loop {
+            let element = copy.step();
+            if element.is_none() {
+                break;
+            }
+            elements.push(element.unwrap());
+        }
+
+        return elements;
     }
 
     pub fn get_pos(&self) -> usize {
-        return self.idx;
+        return self.count;
     }
 
     pub fn describe(&self) -> String {
-        return format!("Iterator(position={} ,size={})", self.idx, self.size);
+        match &self.source {
+            IterSource::Range {
+                current,
+                stop,
+                step,
+            } => return format!("Range(current={}, stop={}, step={})", current, stop, step),
+            IterSource::Generator(state) => {
+                let name = match state {
+                    Some(state) => state.frame.context.compiled_fn.name.clone(),
+                    None => "<finished>".to_string(),
+                };
+                return format!("Generator(function={}, position={})", name, self.count);
+            }
+            _ => return format!("Iterator(position={})", self.count),
+        }
+    }
+}
+
+/*
+    Iterators are compared by identity only, two of them are never equal.
+*/
+impl PartialEq for ObjectIterator {
+    fn eq(&self, _other: &ObjectIterator) -> bool {
+        false
     }
+}
 
-    pub fn has_next(&self) -> bool {
-        return self.idx < self.size;
+impl PartialOrd for ObjectIterator {
+    fn partial_cmp(&self, _other: &ObjectIterator) -> Option<Ordering> {
+        None
     }
 }
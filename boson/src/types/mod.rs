@@ -1,11 +1,19 @@
 pub mod array;
+pub mod bigint;
 pub mod buffer;
 pub mod builtins;
+pub mod class;
 pub mod closure;
 pub mod dyn_module;
 pub mod exception;
+pub mod format;
 pub mod hash;
 pub mod iter;
+pub mod module;
 pub mod object;
+pub mod sendable;
+pub mod slice;
+pub mod string;
 pub mod subroutine;
+pub mod sync;
 pub mod th;
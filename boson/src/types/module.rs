@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::types::object::AttributeResolver;
+use crate::types::object::Object;
+
+// needed by attribute function call resolver
+use crate::api;
+use crate::compiler;
+use crate::vm;
+
+use api::Platform;
+use compiler::symtab::ConstantPool;
+use vm::ffi::BosonFFI;
+use vm::global::GlobalPool;
+use vm::stack::DataStack;
+use vm::thread::BosonThreads;
+
+/*
+    Namespace object created by `use "path" as name`, holds the
+    top-level globals of the imported file.
+*/
+#[derive(Clone, Debug)]
+pub struct Module {
+    pub name: String,
+    pub path: String,
+    pub entries: HashMap<String, Rc<Object>>,
+}
+
+impl Module {
+    pub fn describe(&self) -> String {
+        return format!("Module(name={}, path={})", self.name, self.path);
+    }
+
+    pub fn get(&self, key: &String) -> Result<Rc<Object>, String> {
+        let result = self.entries.get(key);
+        if result.is_none() {
+            return Err(format!("Module {} has no attribute {}", self.name, key));
+        }
+
+        return Ok(result.unwrap().clone());
+    }
+}
+
+impl AttributeResolver for Module {
+    fn attrs(&self) -> Vec<Rc<Object>> {
+        let mut names: Vec<&String> = self.entries.keys().collect();
+        names.sort();
+
+        return names
+            .into_iter()
+            .map(|name| Rc::new(Object::Str(name.clone())))
+            .collect();
+    }
+
+    fn resolve_get_attr(&self, keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+        let f_key = &keys[0];
+        let value = match f_key.as_ref() {
+            Object::Str(st) => match st.as_ref() {
+                "__name__" => Rc::new(Object::Str(self.name.clone())),
+                "__path__" => Rc::new(Object::Str(self.path.clone())),
+                _ => {
+                    let result = self.get(st);
+                    if result.is_err() {
+                        return Err(result.unwrap_err());
+                    }
+
+                    result.unwrap()
+                }
+            },
+            _ => {
+                return Err(format!(
+                    "Expected string attribute, got {}",
+                    f_key.get_type()
+                ));
+            }
+        };
+
+        if keys.len() == 1 {
+            return Ok(value);
+        }
+
+        // resolve the remaining attributes on the module member:
+        return value.resolve_get_attr(&keys[1..]);
+    }
+
+    fn resolve_set_attr(&self, _keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        return Some(format!("Attributes of module {} are read only.", self.name));
+    }
+
+    fn resolve_call_attr(
+        &mut self,
+        keys: &[Rc<Object>],
+        _args: &[Rc<Object>],
+        _ds: &mut DataStack,
+        _platform: &mut Platform,
+        _gp: &mut GlobalPool,
+        _c: &mut ConstantPool,
+        _th: &mut BosonThreads,
+        _ffi: &mut BosonFFI,
+    ) -> Result<Rc<Object>, String> {
+        // module functions are called by the VM, only non callable
+        // members end up here.
+        return Err(format!(
+            "Attribute {} of module {} is not callable",
+            keys[keys.len() - 1],
+            self.name
+        ));
+    }
+}
+
+impl Hash for Module {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.path.hash(state);
+    }
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Module) -> bool {
+        self.path == other.path
+    }
+}
+
+impl PartialOrd for Module {
+    fn partial_cmp(&self, _other: &Module) -> Option<Ordering> {
+        None
+    }
+}
@@ -4,15 +4,26 @@ use std::hash::{Hash, Hasher};
 use std::mem;
 use std::rc::Rc;
 
+use num_bigint::BigInt;
+
+use crate::types::array;
 use crate::types::array::Array;
 use crate::types::buffer::Buffer;
 use crate::types::builtins::BuiltinKind;
+use crate::types::class::ClassType;
+use crate::types::class::Instance;
 use crate::types::closure::ClosureContext;
 use crate::types::dyn_module::NativeModuleRef;
 use crate::types::exception::Exception;
 use crate::types::hash::HashTable;
 use crate::types::iter::ObjectIterator;
+use crate::types::module::Module;
+use crate::types::slice;
+use crate::types::string;
 use crate::types::subroutine::Subroutine;
+use crate::types::sync::AtomicCounter;
+use crate::types::sync::BosonMutex;
+use crate::types::sync::Channel;
 use crate::types::th::ThreadBlock;
 
 // needed by attribute function call resolver
@@ -29,12 +40,12 @@ use vm::thread::BosonThreads;
 
 pub trait AttributeResolver {
     fn attrs(&self) -> Vec<Rc<Object>>;
-    fn resolve_set_attr(&self, _keys: &Vec<Rc<Object>>, _value: Rc<Object>) -> Option<String>;
-    fn resolve_get_attr(&self, keys: &Vec<Rc<Object>>) -> Result<Rc<Object>, String>;
+    fn resolve_set_attr(&self, _keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String>;
+    fn resolve_get_attr(&self, keys: &[Rc<Object>]) -> Result<Rc<Object>, String>;
     fn resolve_call_attr(
         &mut self,
-        keys: &Vec<Rc<Object>>,
-        args: &Vec<Rc<Object>>,
+        keys: &[Rc<Object>],
+        args: &[Rc<Object>],
         ds: &mut DataStack,
         platform: &mut Platform,
         gp: &mut GlobalPool,
@@ -48,6 +59,8 @@ pub trait AttributeResolver {
 pub enum Object {
     Noval,
     Int(i64),
+    // an int outside of the i64 range, see types/bigint.rs:
+    BigInt(BigInt),
     Bool(bool),
     Char(char),
     Str(String),
@@ -63,6 +76,14 @@ pub enum Object {
     Exception(Rc<Exception>),
     Thread(RefCell<ThreadBlock>),
     NativeModule(RefCell<NativeModuleRef>),
+    Module(Rc<Module>),
+    Channel(Channel),
+    Mutex(BosonMutex),
+    Atomic(AtomicCounter),
+    Class(Rc<ClassType>),
+    Instance(RefCell<Instance>),
+    // a local captured by a closure, shared by the frame and its closures:
+    Cell(RefCell<Rc<Object>>),
 }
 
 impl Eq for Object {}
@@ -71,18 +92,33 @@ impl Hash for Object {
     fn hash<H: Hasher>(&self, state: &mut H) {
         match self {
             Object::Int(i) => i.hash(state),
+            Object::BigInt(b) => b.hash(state),
             Object::Bool(b) => b.hash(state),
             Object::Char(c) => c.hash(state),
             Object::Str(st) => st.hash(state),
             Object::Float(f) => f.to_string().hash(state),
-            Object::Array(arr) => arr.borrow().hash(state),
-            Object::HashTable(ht) => ht.borrow().hash(state),
+            // a container that is being changed, like h in h[[h]] = 1, is
+            // hashed by its type only:
+            Object::Array(arr) => match arr.try_borrow() {
+                Ok(arr) => arr.hash(state),
+                Err(_) => "array".hash(state),
+            },
+            Object::HashTable(ht) => match ht.try_borrow() {
+                Ok(ht) => ht.hash(state),
+                Err(_) => "hashtable".hash(state),
+            },
             Object::Subroutine(sub) => sub.hash(state),
             Object::ClosureContext(ctx) => ctx.hash(state),
             Object::Exception(exc) => exc.hash(state),
             Object::Byte(byte) => byte.hash(state),
             Object::ByteBuffer(buff) => buff.borrow().hash(state),
             Object::NativeModule(native) => native.borrow().handle.hash(state),
+            Object::Module(module) => module.hash(state),
+            Object::Channel(ch) => ch.hash(state),
+            Object::Mutex(mutex) => mutex.hash(state),
+            Object::Atomic(atomic) => atomic.hash(state),
+            Object::Class(class) => class.hash(state),
+            Object::Instance(inst) => inst.borrow().hash(state),
             // No hash for iterators and thread block
             _ => "undef".hash(state),
         }
@@ -93,6 +129,7 @@ impl Object {
     pub fn describe(&self) -> String {
         match self {
             Object::Int(i) => i.to_string(),
+            Object::BigInt(b) => b.to_string(),
             Object::Char(c) => c.to_string(),
             Object::Str(st) => st.clone(),
             Object::Float(f) => f.to_string(),
@@ -108,13 +145,20 @@ impl Object {
             Object::ByteBuffer(buff) => buff.borrow().describe(),
             Object::Thread(th) => th.borrow().describe(),
             Object::NativeModule(native) => native.borrow().describe(),
+            Object::Module(module) => module.describe(),
+            Object::Channel(ch) => ch.describe(),
+            Object::Mutex(mutex) => mutex.describe(),
+            Object::Atomic(atomic) => atomic.describe(),
+            Object::Class(class) => class.describe(),
+            Object::Instance(inst) => inst.borrow().describe(),
+            Object::Cell(cell) => cell.borrow().describe(),
             _ => String::from("undef"),
         }
     }
 
     pub fn get_type(&self) -> String {
         match self {
-            Object::Int(_) => "int".to_string(),
+            Object::Int(_) | Object::BigInt(_) => "int".to_string(),
             Object::Char(_) => "char".to_string(),
             Object::Str(_) => "string".to_string(),
             Object::Float(_) => "float".to_string(),
@@ -129,21 +173,29 @@ impl Object {
                 "func".to_string()
             }
             Object::NativeModule(_) => "native".to_string(),
+            Object::Module(_) => "module".to_string(),
+            Object::Channel(_) => "channel".to_string(),
+            Object::Mutex(_) => "mutex".to_string(),
+            Object::Atomic(_) => "atomic".to_string(),
+            Object::Class(_) => "class".to_string(),
+            Object::Instance(inst) => inst.borrow().class.name.clone(),
+            Object::Cell(_) => "cell".to_string(),
             _ => "unknown".to_string(),
         }
     }
 
     pub fn is_true(&self) -> bool {
         match self {
-            Object::Bool(val) => val.clone(),
+            Object::Bool(val) => *val,
             Object::Noval => false,
-            Object::Str(str) => *str != "",
+            Object::Str(str) => !str.is_empty(),
             Object::Int(i) => *i != 0,
+            Object::BigInt(_) => true,
             Object::Char(c) => *c != '\0',
-            Object::Array(a) => a.borrow().elements.len() != 0,
+            Object::Array(a) => !a.borrow().elements.is_empty(),
             Object::ByteBuffer(buff) => buff.borrow().length != 0,
-            Object::HashTable(h) => h.borrow().entries.len() != 0,
-            Object::Iter(it) => it.borrow().has_next(),
+            Object::HashTable(h) => h.borrow().length() != 0,
+            Object::Iter(it) => it.borrow().may_have_next(),
             Object::Byte(b) => *b != 0,
             _ => true,
         }
@@ -152,10 +204,11 @@ impl Object {
     pub fn get_indexed(&self, idx: &Rc<Object>) -> Result<Rc<Object>, String> {
         match (self, idx.as_ref()) {
             (Object::Array(arr), Object::Int(i)) => {
-                if *i < 0 {
-                    return Err(format!("Index {} must be greater than or equal to zero", i));
+                let pos = slice::resolve_index(*i, arr.borrow().elements.len());
+                if pos.is_err() {
+                    return Err(pos.unwrap_err());
                 }
-                let result = arr.borrow().get_object(*i as usize);
+                let result = arr.borrow().get_object(pos.unwrap());
                 if result.is_err() {
                     return Err(result.unwrap_err());
                 }
@@ -163,11 +216,12 @@ impl Object {
                 return Ok(result.unwrap());
             }
             (Object::ByteBuffer(buffer), Object::Int(i)) => {
-                if *i < 0 {
-                    return Err(format!("Index {} must be greater than or equal to zero", i));
+                let pos = slice::resolve_index(*i, buffer.borrow().data.len());
+                if pos.is_err() {
+                    return Err(pos.unwrap_err());
                 }
 
-                let result = buffer.borrow().get_byte_at(*i as usize);
+                let result = buffer.borrow().get_byte_at(pos.unwrap());
                 if result.is_err() {
                     return Err(result.unwrap_err());
                 }
@@ -185,11 +239,14 @@ impl Object {
                 return Ok(result.unwrap());
             }
             (Object::Str(st), Object::Int(i)) => {
-                if *i < 0 {
-                    return Err(format!("Index {} must be greater than or equal to zero", i));
+                // the length is only counted for negative indices:
+                let length = if *i < 0 { st.chars().count() } else { 0 };
+                let pos = slice::resolve_index(*i, length);
+                if pos.is_err() {
+                    return Err(pos.unwrap_err());
                 }
 
-                let ch = st.chars().nth(*i as usize);
+                let ch = st.chars().nth(pos.unwrap());
                 if ch.is_none() {
                     return Err(format!("String \"{}\" index out of bounds for {}", st, i));
                 }
@@ -206,30 +263,32 @@ impl Object {
         }
     }
 
-    pub fn set_indexed(&mut self, idx: &Rc<Object>, data: Rc<Object>) -> Option<String> {
-        match (&self, idx.as_ref()) {
+    pub fn set_indexed(&self, idx: &Rc<Object>, data: Rc<Object>) -> Option<String> {
+        match (self, idx.as_ref()) {
             (Object::Array(arr), Object::Int(i)) => {
-                if *i < 0 {
-                    return Some(format!("Index {} must be greater than or equal to zero", i));
+                let pos = slice::resolve_index(*i, arr.borrow().elements.len());
+                if pos.is_err() {
+                    return Some(pos.unwrap_err());
                 }
 
                 // set object at index:
-                let result = arr.borrow_mut().set_object(*i as usize, data);
+                let result = arr.borrow_mut().set_object(pos.unwrap(), data);
                 if result.is_some() {
                     return Some(result.unwrap());
                 }
                 return None;
             }
             (Object::ByteBuffer(buffer), Object::Int(i)) => {
-                if *i < 0 {
-                    return Some(format!("Index {} must be greater than or equal to zero", i));
+                let pos = slice::resolve_index(*i, buffer.borrow().data.len());
+                if pos.is_err() {
+                    return Some(pos.unwrap_err());
                 }
 
                 match data.as_ref() {
                     Object::Byte(byte) => {
-                        let result = buffer.borrow_mut().set_byte_at(*i as usize, *byte);
-                        if result.is_none() {
-                            return Some(format!("Index {} out of bounds", i));
+                        let result = buffer.borrow_mut().set_byte_at(pos.unwrap(), *byte);
+                        if result.is_some() {
+                            return Some(result.unwrap());
                         }
 
                         return None;
@@ -243,6 +302,10 @@ impl Object {
                 }
             }
             (Object::HashTable(ht), _) => {
+                if std::ptr::eq(self, idx.as_ref()) {
+                    return Some("A hash table cannot be a key of itself".to_string());
+                }
+
                 ht.borrow_mut().set(idx.clone(), data);
                 return None;
             }
@@ -264,37 +327,84 @@ impl fmt::Display for Object {
 }
 
 impl AttributeResolver for Object {
-    fn resolve_set_attr(&self, _keys: &Vec<Rc<Object>>, _value: Rc<Object>) -> Option<String> {
-        // to be implemented
-        return None;
+    fn resolve_set_attr(&self, keys: &[Rc<Object>], value: Rc<Object>) -> Option<String> {
+        // a.b.c = 1 resolves a.b and assigns c on it:
+        if keys.len() > 1 {
+            let parent_result = self.resolve_get_attr(&keys[..keys.len() - 1]);
+            if parent_result.is_err() {
+                return Some(parent_result.unwrap_err());
+            }
+
+            // missing hash table keys resolve to undef:
+            let parent = parent_result.unwrap();
+            if parent.as_ref() == &Object::Noval {
+                return Some(format!(
+                    "Cannot assign attribute {} on an undefined value.",
+                    keys[keys.len() - 1].describe()
+                ));
+            }
+
+            return parent.resolve_set_attr(&keys[keys.len() - 1..], value);
+        }
+
+        match self {
+            Object::HashTable(ht) => return ht.borrow_mut().set_attribute(&keys[0], value),
+            Object::Module(module) => return module.resolve_set_attr(keys, value),
+            Object::NativeModule(native) => return native.borrow().resolve_set_attr(keys, value),
+            Object::Channel(ch) => return ch.resolve_set_attr(keys, value),
+            Object::Mutex(mutex) => return mutex.resolve_set_attr(keys, value),
+            Object::Atomic(atomic) => return atomic.resolve_set_attr(keys, value),
+            Object::Class(class) => return class.resolve_set_attr(keys, value),
+            Object::Instance(inst) => return inst.borrow_mut().set_field(&keys[0], value),
+            _ => {
+                return Some(format!(
+                    "Object of type {} does not support attribute assignment.",
+                    self.get_type()
+                ))
+            }
+        }
     }
 
-    fn resolve_get_attr(&self, keys: &Vec<Rc<Object>>) -> Result<Rc<Object>, String> {
+    fn resolve_get_attr(&self, keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
         let f_key = &keys[0];
 
-        match f_key.as_ref() {
-            Object::Str(st) => {
-                match st.as_ref() {
-                    // base attributes
-                    "__address__" => return Ok(Rc::new(Object::Str(format!("{:p}", self)))),
-                    "__size__" => return Ok(Rc::new(Object::Int(mem::size_of::<Self>() as i64))),
-                    _ => {}
-                }
+        if let Object::Str(st) = f_key.as_ref() {
+            match st.as_ref() {
+                // base attributes
+                "__address__" => return Ok(Rc::new(Object::Str(format!("{:p}", self)))),
+                "__size__" => return Ok(Rc::new(Object::Int(mem::size_of::<Self>() as i64))),
+                _ => {}
+            }
 
-                match self {
-                    Object::HashTable(ht) => {
-                        return ht.borrow().resolve_get_attr(keys);
-                    }
-                    _ => {
-                        return Err(format!(
-                            "Object of type {} does not have attribute resolver.",
-                            self.get_type()
-                        ))
-                    }
+            match self {
+                Object::HashTable(ht) => {
+                    return ht.borrow().resolve_get_attr(keys);
+                }
+                Object::Exception(exc) => {
+                    return exc.get_attribute(st);
+                }
+                Object::Module(module) => {
+                    return module.resolve_get_attr(keys);
+                }
+                Object::Str(value) => {
+                    return string::resolve_get_attr(value, keys);
+                }
+                Object::Array(arr) => {
+                    return array::resolve_get_attr(&arr.borrow(), keys);
+                }
+                Object::Class(class) => {
+                    return class.resolve_get_attr(keys);
+                }
+                Object::Instance(inst) => {
+                    return inst.borrow().resolve_get_attr(keys);
+                }
+                _ => {
+                    return Err(format!(
+                        "Object of type {} does not have attribute resolver.",
+                        self.get_type()
+                    ))
                 }
             }
-
-            _ => {}
         }
 
         return Ok(Rc::new(Object::Noval));
@@ -302,8 +412,8 @@ impl AttributeResolver for Object {
 
     fn resolve_call_attr(
         &mut self,
-        keys: &Vec<Rc<Object>>,
-        args: &Vec<Rc<Object>>,
+        keys: &[Rc<Object>],
+        args: &[Rc<Object>],
         ds: &mut DataStack,
         platform: &mut Platform,
         gp: &mut GlobalPool,
@@ -315,12 +425,34 @@ impl AttributeResolver for Object {
             Object::HashTable(ht) => {
                 return ht
                     .borrow_mut()
-                    .resolve_call_attr(&keys, &args, ds, platform, gp, c, th, ffi)
+                    .resolve_call_attr(keys, args, ds, platform, gp, c, th, ffi)
             }
             Object::NativeModule(nt) => {
                 return nt
                     .borrow_mut()
-                    .resolve_call_attr(&keys, &args, ds, platform, gp, c, th, ffi)
+                    .resolve_call_attr(keys, args, ds, platform, gp, c, th, ffi)
+            }
+            Object::Str(st) => return string::resolve_call_attr(st, keys, args),
+            Object::Array(arr) => {
+                let result = array::resolve_call_attr(arr, keys, args, platform, gp, c, th, ffi);
+                if result.is_err() {
+                    return Err(result.unwrap_err().message);
+                }
+                return Ok(result.unwrap());
+            }
+            Object::Channel(ch) => {
+                return ch.resolve_call_attr(keys, args, ds, platform, gp, c, th, ffi)
+            }
+            Object::Mutex(mutex) => {
+                return mutex.resolve_call_attr(keys, args, ds, platform, gp, c, th, ffi)
+            }
+            Object::Atomic(atomic) => {
+                return atomic.resolve_call_attr(keys, args, ds, platform, gp, c, th, ffi)
+            }
+            Object::Instance(inst) => {
+                return inst
+                    .borrow_mut()
+                    .resolve_call_attr(keys, args, ds, platform, gp, c, th, ffi)
             }
             _ => {
                 return Err(format!(
@@ -342,16 +474,36 @@ impl AttributeResolver for Object {
             Object::HashTable(ht) => {
                 main_attrs.extend(ht.borrow().attrs());
             }
+            Object::Exception(exc) => {
+                main_attrs.extend(exc.attrs());
+            }
+            Object::Module(module) => {
+                main_attrs.extend(module.attrs());
+            }
+            Object::Str(_) => {
+                main_attrs.extend(string::attrs());
+            }
+            Object::Array(_) => {
+                main_attrs.extend(array::attrs());
+            }
+            Object::Channel(ch) => {
+                main_attrs.extend(ch.attrs());
+            }
+            Object::Mutex(mutex) => {
+                main_attrs.extend(mutex.attrs());
+            }
+            Object::Atomic(atomic) => {
+                main_attrs.extend(atomic.attrs());
+            }
+            Object::Class(class) => {
+                main_attrs.extend(class.attrs());
+            }
+            Object::Instance(inst) => {
+                main_attrs.extend(inst.borrow().attrs());
+            }
             _ => {}
         }
 
         return main_attrs;
     }
 }
-
-// make Object capable of sharing between threads.
-// by design, Object will always be thread safe as each thread will
-// get it's own copy of Object.
-// so this unsafe impl is just a cover-up to fool the compiler to make Object
-// sharebale across threads.
-unsafe impl Send for Object {}
@@ -0,0 +1,439 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+
+use crate::types::array::Array;
+use crate::types::buffer::Buffer;
+use crate::types::builtins::BuiltinKind;
+use crate::types::class::ClassType;
+use crate::types::class::Instance;
+use crate::types::closure::ClosureContext;
+use crate::types::dyn_module::NativeModuleRef;
+use crate::types::exception::Exception;
+use crate::types::hash::HashTable;
+use crate::types::iter::IterSource;
+use crate::types::iter::ObjectIterator;
+use crate::types::module::Module;
+use crate::types::object::Object;
+use crate::types::subroutine::Subroutine;
+use crate::types::sync::AtomicCounter;
+use crate::types::sync::BosonMutex;
+use crate::types::sync::Channel;
+use crate::types::th::ThreadBlock;
+use crate::vm::errors::TraceEntry;
+use crate::vm::errors::VMErrorKind;
+
+/*
+    Objects are built on Rc and RefCell, which cannot cross threads.
+    A value passed to (or returned from) a thread is deep-copied into a
+    SendableObject which owns all its data, the receiving thread rebuilds
+    fresh objects out of it, so no reference count is ever shared between
+    two threads.
+
+    Arrays, hash tables, byte buffers, class instances and captured variable
+    cells are stored once in the context and referred by their index, this
+    keeps sharing and cycles between them intact in the copy.
+
+    Channels, mutexes and atomics are the only objects which are not copied,
+    they are Arc handles meant to be shared between threads.
+*/
+#[derive(Debug, Clone)]
+pub enum SendableObject {
+    Noval,
+    Int(i64),
+    BigInt(BigInt),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    Float(f64),
+    Byte(u8),
+    Subroutine(Subroutine),
+    ClosureContext(Subroutine, Vec<SendableObject>),
+    Container(usize),
+    Builtins(BuiltinKind),
+    Iter(usize, Vec<SendableObject>),
+    Range(i64, i64, i64, usize),
+    Exception(String, String, VMErrorKind, Vec<TraceEntry>),
+    Thread(ThreadBlock),
+    NativeModule(i64),
+    Module(String, String, Vec<(String, SendableObject)>),
+    Channel(Channel),
+    Mutex(BosonMutex),
+    Atomic(AtomicCounter),
+    Class(String, Vec<String>, Vec<(String, SendableObject)>),
+}
+
+#[derive(Debug, Clone)]
+enum SendableContainer {
+    Array(String, Vec<SendableObject>),
+    HashTable(String, Vec<(SendableObject, SendableObject)>),
+    ByteBuffer(Buffer),
+    Instance(SendableObject, Vec<SendableObject>),
+    Cell(SendableObject),
+}
+
+#[derive(Debug, Clone)]
+pub struct SendableContext {
+    containers: Vec<SendableContainer>,
+    // address of the container object -> index in containers
+    seen: HashMap<usize, usize>,
+}
+
+impl Default for SendableContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SendableContext {
+    pub fn new() -> SendableContext {
+        return SendableContext {
+            containers: vec![],
+            seen: HashMap::new(),
+        };
+    }
+
+    fn encode_all(&mut self, objects: &[Rc<Object>]) -> Result<Vec<SendableObject>, String> {
+        let mut encoded = vec![];
+        for obj in objects.iter() {
+            let result = self.encode(obj);
+            if result.is_err() {
+                return Err(result.unwrap_err());
+            }
+            encoded.push(result.unwrap());
+        }
+
+        return Ok(encoded);
+    }
+
+    fn encode_container(&mut self, obj: &Rc<Object>) -> Result<SendableObject, String> {
+        let key = Rc::as_ptr(obj) as usize;
+        let existing = self.seen.get(&key);
+        if existing.is_some() {
+            return Ok(SendableObject::Container(*existing.unwrap()));
+        }
+
+        // reserve the slot before encoding the elements, elements can
+        // refer back to this container.
+        let idx = self.containers.len();
+        self.containers
+            .push(SendableContainer::Array(String::new(), vec![]));
+        self.seen.insert(key, idx);
+
+        let container = match obj.as_ref() {
+            Object::Array(arr) => {
+                let array = arr.borrow();
+                let elements = self.encode_all(&array.elements);
+                if elements.is_err() {
+                    return Err(elements.unwrap_err());
+                }
+                SendableContainer::Array(array.name.clone(), elements.unwrap())
+            }
+            Object::HashTable(ht) => {
+                let table = ht.borrow();
+                let mut entries = vec![];
+                for (k, v) in table.items().iter() {
+                    let key = self.encode(k);
+                    if key.is_err() {
+                        return Err(key.unwrap_err());
+                    }
+                    let value = self.encode(v);
+                    if value.is_err() {
+                        return Err(value.unwrap_err());
+                    }
+                    entries.push((key.unwrap(), value.unwrap()));
+                }
+                SendableContainer::HashTable(table.name.clone(), entries)
+            }
+            Object::ByteBuffer(buffer) => SendableContainer::ByteBuffer(buffer.borrow().clone()),
+            Object::Instance(inst) => {
+                let instance = inst.borrow();
+                let class = self.encode_class(&instance.class);
+                if class.is_err() {
+                    return Err(class.unwrap_err());
+                }
+                let values = self.encode_all(&instance.values);
+                if values.is_err() {
+                    return Err(values.unwrap_err());
+                }
+                SendableContainer::Instance(class.unwrap(), values.unwrap())
+            }
+            Object::Cell(cell) => {
+                let value = self.encode(&cell.borrow());
+                if value.is_err() {
+                    return Err(value.unwrap_err());
+                }
+                SendableContainer::Cell(value.unwrap())
+            }
+            _ => SendableContainer::Array(String::new(), vec![]),
+        };
+
+        self.containers[idx] = container;
+        return Ok(SendableObject::Container(idx));
+    }
+
+    /*
+        Fails for generators, their state is a suspended frame of the
+        sending thread which cannot run anywhere else.
+    */
+    pub fn encode(&mut self, obj: &Rc<Object>) -> Result<SendableObject, String> {
+        let encoded = match obj.as_ref() {
+            Object::Noval => SendableObject::Noval,
+            Object::Int(i) => SendableObject::Int(*i),
+            Object::BigInt(b) => SendableObject::BigInt(b.clone()),
+            Object::Bool(b) => SendableObject::Bool(*b),
+            Object::Char(c) => SendableObject::Char(*c),
+            Object::Str(st) => SendableObject::Str(st.clone()),
+            Object::Float(f) => SendableObject::Float(*f),
+            Object::Byte(b) => SendableObject::Byte(*b),
+            Object::Subroutine(sub) => SendableObject::Subroutine(sub.as_ref().clone()),
+            Object::ClosureContext(ctx) => {
+                let free_objects = self.encode_all(&ctx.free_objects);
+                if free_objects.is_err() {
+                    return Err(free_objects.unwrap_err());
+                }
+                SendableObject::ClosureContext(
+                    ctx.compiled_fn.as_ref().clone(),
+                    free_objects.unwrap(),
+                )
+            }
+            Object::Array(_)
+            | Object::HashTable(_)
+            | Object::ByteBuffer(_)
+            | Object::Instance(_)
+            | Object::Cell(_) => return self.encode_container(obj),
+            Object::Builtins(kind) => SendableObject::Builtins(*kind),
+            Object::Iter(it) => {
+                let iter = it.borrow();
+                match iter.source {
+                    IterSource::Range {
+                        current,
+                        stop,
+                        step,
+                    } => SendableObject::Range(current, stop, step, iter.count),
+                    IterSource::Generator(_) => {
+                        return Err("generator is not sendable".to_string());
+                    }
+                    // the rest of the elements are copied:
+                    _ => {
+                        let elements = self.encode_all(&iter.remaining());
+                        if elements.is_err() {
+                            return Err(elements.unwrap_err());
+                        }
+                        SendableObject::Iter(iter.count, elements.unwrap())
+                    }
+                }
+            }
+            Object::Exception(exc) => SendableObject::Exception(
+                exc.get_name().clone(),
+                exc.get_message().clone(),
+                exc.get_kind().clone(),
+                exc.stack_trace.clone(),
+            ),
+            Object::Thread(th) => SendableObject::Thread(th.borrow().clone()),
+            Object::NativeModule(native) => SendableObject::NativeModule(native.borrow().handle),
+            Object::Module(module) => {
+                let mut entries: Vec<(String, SendableObject)> = vec![];
+                for (k, v) in module.entries.iter() {
+                    let value = self.encode(v);
+                    if value.is_err() {
+                        return Err(value.unwrap_err());
+                    }
+                    entries.push((k.clone(), value.unwrap()));
+                }
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                SendableObject::Module(module.name.clone(), module.path.clone(), entries)
+            }
+            Object::Channel(ch) => SendableObject::Channel(ch.clone()),
+            Object::Mutex(mutex) => SendableObject::Mutex(mutex.clone()),
+            Object::Atomic(atomic) => SendableObject::Atomic(atomic.clone()),
+            Object::Class(class) => return self.encode_class(class),
+        };
+
+        return Ok(encoded);
+    }
+
+    fn encode_class(&mut self, class: &ClassType) -> Result<SendableObject, String> {
+        let mut methods: Vec<(String, SendableObject)> = vec![];
+        for (k, v) in class.methods.iter() {
+            let method = self.encode(v);
+            if method.is_err() {
+                return Err(method.unwrap_err());
+            }
+            methods.push((k.clone(), method.unwrap()));
+        }
+        methods.sort_by(|a, b| a.0.cmp(&b.0));
+        return Ok(SendableObject::Class(
+            class.name.clone(),
+            class.fields.clone(),
+            methods,
+        ));
+    }
+
+    pub fn decoder(&self) -> ObjectDecoder<'_> {
+        return ObjectDecoder {
+            context: self,
+            built: vec![None; self.containers.len()],
+        };
+    }
+}
+
+/*
+    Rebuilds objects from a SendableContext, must be created on the
+    thread that is going to own the objects.
+*/
+pub struct ObjectDecoder<'a> {
+    context: &'a SendableContext,
+    built: Vec<Option<Rc<Object>>>,
+}
+
+impl<'a> ObjectDecoder<'a> {
+    fn decode_container(&mut self, idx: usize) -> Rc<Object> {
+        if self.built[idx].is_some() {
+            return self.built[idx].as_ref().unwrap().clone();
+        }
+
+        let context = self.context;
+        match &context.containers[idx] {
+            SendableContainer::Array(name, elements) => {
+                let obj = Rc::new(Object::Array(RefCell::new(Array {
+                    name: name.clone(),
+                    elements: vec![],
+                })));
+                self.built[idx] = Some(obj.clone());
+
+                let decoded: Vec<Rc<Object>> = elements.iter().map(|e| self.decode(e)).collect();
+                if let Object::Array(arr) = obj.as_ref() {
+                    arr.borrow_mut().elements = decoded;
+                }
+                return obj;
+            }
+            SendableContainer::HashTable(name, entries) => {
+                let obj = Rc::new(Object::HashTable(RefCell::new(HashTable::new(
+                    name.clone(),
+                ))));
+                self.built[idx] = Some(obj.clone());
+
+                let decoded: Vec<(Rc<Object>, Rc<Object>)> = entries
+                    .iter()
+                    .map(|(k, v)| (self.decode(k), self.decode(v)))
+                    .collect();
+                if let Object::HashTable(ht) = obj.as_ref() {
+                    let mut table = ht.borrow_mut();
+                    for (key, value) in decoded {
+                        table.set(key, value);
+                    }
+                }
+                return obj;
+            }
+            SendableContainer::ByteBuffer(buffer) => {
+                let obj = Rc::new(Object::ByteBuffer(RefCell::new(buffer.clone())));
+                self.built[idx] = Some(obj.clone());
+                return obj;
+            }
+            SendableContainer::Instance(class, values) => {
+                let class = match self.decode(class).as_ref() {
+                    Object::Class(class) => class.clone(),
+                    _ => Rc::new(ClassType {
+                        name: String::new(),
+                        fields: vec![],
+                        methods: HashMap::new(),
+                    }),
+                };
+
+                let obj = Rc::new(Object::Instance(RefCell::new(Instance {
+                    class: class,
+                    values: vec![],
+                })));
+                self.built[idx] = Some(obj.clone());
+
+                let decoded: Vec<Rc<Object>> = values.iter().map(|v| self.decode(v)).collect();
+                if let Object::Instance(inst) = obj.as_ref() {
+                    inst.borrow_mut().values = decoded;
+                }
+                return obj;
+            }
+            SendableContainer::Cell(value) => {
+                let obj = Rc::new(Object::Cell(RefCell::new(Rc::new(Object::Noval))));
+                self.built[idx] = Some(obj.clone());
+
+                let decoded = self.decode(value);
+                if let Object::Cell(cell) = obj.as_ref() {
+                    *cell.borrow_mut() = decoded;
+                }
+                return obj;
+            }
+        }
+    }
+
+    pub fn decode(&mut self, obj: &SendableObject) -> Rc<Object> {
+        let decoded = match obj {
+            SendableObject::Noval => Object::Noval,
+            SendableObject::Int(i) => Object::Int(*i),
+            SendableObject::BigInt(b) => Object::BigInt(b.clone()),
+            SendableObject::Bool(b) => Object::Bool(*b),
+            SendableObject::Char(c) => Object::Char(*c),
+            SendableObject::Str(st) => Object::Str(st.clone()),
+            SendableObject::Float(f) => Object::Float(*f),
+            SendableObject::Byte(b) => Object::Byte(*b),
+            SendableObject::Subroutine(sub) => Object::Subroutine(Rc::new(sub.clone())),
+            SendableObject::ClosureContext(sub, free_objects) => {
+                let free_objects = free_objects.iter().map(|o| self.decode(o)).collect();
+                Object::ClosureContext(Rc::new(ClosureContext::new(
+                    Rc::new(sub.clone()),
+                    free_objects,
+                )))
+            }
+            SendableObject::Container(idx) => {
+                return self.decode_container(*idx);
+            }
+            SendableObject::Builtins(kind) => Object::Builtins(*kind),
+            SendableObject::Iter(count, elements) => {
+                let elements: Vec<Rc<Object>> = elements.iter().map(|e| self.decode(e)).collect();
+                Object::Iter(RefCell::new(ObjectIterator::new_snapshot(elements, *count)))
+            }
+            SendableObject::Range(current, stop, step, count) => {
+                // the step was checked when the range was created.
+                let mut range = ObjectIterator::new_range(*current, *stop, *step).unwrap();
+                range.count = *count;
+                Object::Iter(RefCell::new(range))
+            }
+            SendableObject::Exception(name, message, kind, trace) => Object::Exception(Rc::new(
+                Exception::new(name.clone(), message.clone(), kind.clone(), trace.clone()),
+            )),
+            SendableObject::Thread(th) => Object::Thread(RefCell::new(th.clone())),
+            SendableObject::NativeModule(handle) => {
+                Object::NativeModule(RefCell::new(NativeModuleRef::new(*handle)))
+            }
+            SendableObject::Module(name, path, entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.decode(v)))
+                    .collect();
+                Object::Module(Rc::new(Module {
+                    name: name.clone(),
+                    path: path.clone(),
+                    entries: entries,
+                }))
+            }
+            SendableObject::Channel(ch) => Object::Channel(ch.clone()),
+            SendableObject::Mutex(mutex) => Object::Mutex(mutex.clone()),
+            SendableObject::Atomic(atomic) => Object::Atomic(atomic.clone()),
+            SendableObject::Class(name, fields, methods) => {
+                let methods = methods
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.decode(v)))
+                    .collect();
+                Object::Class(Rc::new(ClassType {
+                    name: name.clone(),
+                    fields: fields.clone(),
+                    methods: methods,
+                }))
+            }
+        };
+
+        return Rc::new(decoded);
+    }
+}
@@ -0,0 +1,313 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::types::array::Array;
+use crate::types::buffer::Buffer;
+use crate::types::object::Object;
+
+/*
+    Bounds of xs[start:stop:step] resolved against a sequence, following
+    python: negative positions count from the end, positions out of range
+    are clamped and a negative step walks the sequence backwards.
+*/
+#[derive(Clone, Debug)]
+pub struct SliceBounds {
+    pub start: i64,
+    pub stop: i64,
+    pub step: i64,
+}
+
+fn get_slice_part(name: &str, part: &Rc<Object>) -> Result<Option<i64>, String> {
+    match part.as_ref() {
+        Object::Noval => return Ok(None),
+        Object::Int(i) => return Ok(Some(*i)),
+        _ => {
+            return Err(format!(
+                "Slice {} must be an integer, got {}",
+                name,
+                part.get_type()
+            ))
+        }
+    }
+}
+
+// resolves a negative index from the end, used by indexing as well.
+pub fn resolve_index(idx: i64, length: usize) -> Result<usize, String> {
+    if idx >= 0 {
+        return Ok(idx as usize);
+    }
+
+    let resolved = idx + length as i64;
+    if resolved < 0 {
+        return Err(format!("Index {} out of bounds for length {}", idx, length));
+    }
+
+    return Ok(resolved as usize);
+}
+
+impl SliceBounds {
+    pub fn new(
+        length: usize,
+        start: &Rc<Object>,
+        stop: &Rc<Object>,
+        step: &Rc<Object>,
+    ) -> Result<SliceBounds, String> {
+        let start_res = get_slice_part("start", start);
+        if start_res.is_err() {
+            return Err(start_res.unwrap_err());
+        }
+
+        let stop_res = get_slice_part("stop", stop);
+        if stop_res.is_err() {
+            return Err(stop_res.unwrap_err());
+        }
+
+        let step_res = get_slice_part("step", step);
+        if step_res.is_err() {
+            return Err(step_res.unwrap_err());
+        }
+
+        let step = step_res.unwrap().unwrap_or(1);
+        if step == 0 {
+            return Err("Slice step cannot be zero".to_string());
+        }
+
+        let length = length as i64;
+        // a backward slice can stop before the first element, at -1:
+        let (lower, upper) = if step > 0 {
+            (0, length)
+        } else {
+            (-1, length - 1)
+        };
+
+        let clamp = |pos: i64| {
+            let resolved = if pos < 0 { pos + length } else { pos };
+            return resolved.max(lower).min(upper);
+        };
+
+        let start = match start_res.unwrap() {
+            Some(pos) => clamp(pos),
+            None => {
+                if step > 0 {
+                    lower
+                } else {
+                    upper
+                }
+            }
+        };
+
+        let stop = match stop_res.unwrap() {
+            Some(pos) => clamp(pos),
+            None => {
+                if step > 0 {
+                    upper
+                } else {
+                    lower
+                }
+            }
+        };
+
+        return Ok(SliceBounds {
+            start: start,
+            stop: stop,
+            step: step,
+        });
+    }
+
+    pub fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![];
+        let mut pos = self.start;
+        while (self.step > 0 && pos < self.stop) || (self.step < 0 && pos > self.stop) {
+            positions.push(pos as usize);
+            pos += self.step;
+        }
+
+        return positions;
+    }
+}
+
+pub fn get_sliced(
+    obj: &Object,
+    start: &Rc<Object>,
+    stop: &Rc<Object>,
+    step: &Rc<Object>,
+) -> Result<Rc<Object>, String> {
+    match obj {
+        Object::Array(arr) => {
+            let array = arr.borrow();
+            let bounds = SliceBounds::new(array.elements.len(), start, stop, step);
+            if bounds.is_err() {
+                return Err(bounds.unwrap_err());
+            }
+
+            let elements = bounds
+                .unwrap()
+                .positions()
+                .into_iter()
+                .map(|pos| array.elements[pos].clone())
+                .collect();
+
+            return Ok(Rc::new(Object::Array(RefCell::new(Array {
+                name: format!("{}_slice", array.name),
+                elements: elements,
+            }))));
+        }
+        Object::Str(st) => {
+            let chars: Vec<char> = st.chars().collect();
+            let bounds = SliceBounds::new(chars.len(), start, stop, step);
+            if bounds.is_err() {
+                return Err(bounds.unwrap_err());
+            }
+
+            let sliced: String = bounds
+                .unwrap()
+                .positions()
+                .into_iter()
+                .map(|pos| chars[pos])
+                .collect();
+
+            return Ok(Rc::new(Object::Str(sliced)));
+        }
+        Object::ByteBuffer(buffer) => {
+            let buffer = buffer.borrow();
+            let bounds = SliceBounds::new(buffer.data.len(), start, stop, step);
+            if bounds.is_err() {
+                return Err(bounds.unwrap_err());
+            }
+
+            let data = bounds
+                .unwrap()
+                .positions()
+                .into_iter()
+                .map(|pos| buffer.data[pos])
+                .collect();
+
+            return Ok(Rc::new(Object::ByteBuffer(RefCell::new(Buffer::from_u8(
+                data,
+                format!("{}_slice", buffer.name),
+                buffer.is_little_endian,
+            )))));
+        }
+        _ => {
+            return Err(format!(
+                "Object of type {} does not support slicing",
+                obj.get_type()
+            ));
+        }
+    }
+}
+
+// bytes assigned to a buffer slice, from a buffer or an array of bytes.
+fn get_slice_bytes(data: &Rc<Object>) -> Result<Vec<u8>, String> {
+    match data.as_ref() {
+        Object::ByteBuffer(buffer) => return Ok(buffer.borrow().data.clone()),
+        Object::Array(arr) => {
+            let mut bytes = vec![];
+            for element in arr.borrow().elements.iter() {
+                match element.as_ref() {
+                    Object::Byte(byte) => bytes.push(*byte),
+                    Object::Int(i) if *i >= 0 && *i <= 255 => bytes.push(*i as u8),
+                    _ => {
+                        return Err(format!(
+                            "Cannot assign {} to a slice of RawBuffer",
+                            element.describe()
+                        ))
+                    }
+                }
+            }
+
+            return Ok(bytes);
+        }
+        _ => {
+            return Err(format!(
+                "Cannot assign {} to a slice of RawBuffer",
+                data.get_type()
+            ))
+        }
+    }
+}
+
+/*
+    Replaces the elements picked by the slice. A slice with step 1 can
+    be replaced by any number of elements, an extended slice needs as
+    many elements as it picks.
+*/
+fn replace_sliced<T: Clone>(
+    target: &mut Vec<T>,
+    bounds: &SliceBounds,
+    values: Vec<T>,
+) -> Option<String> {
+    if bounds.step == 1 {
+        let start = bounds.start as usize;
+        let stop = bounds.stop.max(bounds.start) as usize;
+        target.splice(start..stop, values);
+        return None;
+    }
+
+    let positions = bounds.positions();
+    if positions.len() != values.len() {
+        return Some(format!(
+            "Cannot assign {} elements to an extended slice of {} elements",
+            values.len(),
+            positions.len()
+        ));
+    }
+
+    for (pos, value) in positions.into_iter().zip(values) {
+        target[pos] = value;
+    }
+
+    return None;
+}
+
+pub fn set_sliced(
+    obj: &Object,
+    start: &Rc<Object>,
+    stop: &Rc<Object>,
+    step: &Rc<Object>,
+    data: Rc<Object>,
+) -> Option<String> {
+    match obj {
+        Object::Array(arr) => {
+            let values = match data.as_ref() {
+                Object::Array(values) => values.borrow().elements.clone(),
+                _ => {
+                    return Some(format!(
+                        "Cannot assign {} to a slice of Array, expected Array",
+                        data.get_type()
+                    ))
+                }
+            };
+
+            let mut array = arr.borrow_mut();
+            let bounds = SliceBounds::new(array.elements.len(), start, stop, step);
+            if bounds.is_err() {
+                return Some(bounds.unwrap_err());
+            }
+
+            return replace_sliced(&mut array.elements, &bounds.unwrap(), values);
+        }
+        Object::ByteBuffer(buffer) => {
+            let bytes = get_slice_bytes(&data);
+            if bytes.is_err() {
+                return Some(bytes.unwrap_err());
+            }
+
+            let mut buffer = buffer.borrow_mut();
+            let bounds = SliceBounds::new(buffer.data.len(), start, stop, step);
+            if bounds.is_err() {
+                return Some(bounds.unwrap_err());
+            }
+
+            let error = replace_sliced(&mut buffer.data, &bounds.unwrap(), bytes.unwrap());
+            buffer.length = buffer.data.len();
+            return error;
+        }
+        _ => {
+            return Some(format!(
+                "Object of type {} does not support slice assignment",
+                obj.get_type()
+            ));
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::config::MAX_STRING_SIZE;
+use crate::types::array::Array;
+use crate::types::object::AttributeResolver;
+use crate::types::object::Object;
+
+/*
+    Methods of the string type, called as "hello".upper().
+    Strings are immutable, every method returns a new object.
+    Positions are counted in characters, same as indexing.
+*/
+
+const METHODS: &[&str] = &[
+    "split",
+    "join",
+    "trim",
+    "trim_start",
+    "trim_end",
+    "replace",
+    "find",
+    "rfind",
+    "contains",
+    "count",
+    "starts_with",
+    "ends_with",
+    "upper",
+    "lower",
+    "capitalize",
+    "repeat",
+    "pad_start",
+    "pad_end",
+    "center",
+    "chars",
+    "lines",
+    "is_digit",
+    "is_alpha",
+    "is_alnum",
+    "is_space",
+    "is_upper",
+    "is_lower",
+];
+
+const PROPERTIES: &[&str] = &["length"];
+
+pub fn attrs() -> Vec<Rc<Object>> {
+    return PROPERTIES
+        .iter()
+        .chain(METHODS.iter())
+        .map(|name| Rc::new(Object::Str(name.to_string())))
+        .collect();
+}
+
+pub fn resolve_get_attr(st: &str, keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+    let name = match keys[0].as_ref() {
+        Object::Str(name) => name,
+        _ => {
+            return Err(format!(
+                "Expected string attribute, got {}",
+                keys[0].get_type()
+            ))
+        }
+    };
+
+    let value = match name.as_ref() {
+        "length" => Rc::new(Object::Int(st.chars().count() as i64)),
+        _ => {
+            if METHODS.contains(&name.as_str()) {
+                return Err(format!(
+                    "{} is a method of string, call it as {}()",
+                    name, name
+                ));
+            }
+
+            return Err(format!("Attribute {} not found for type string", name));
+        }
+    };
+
+    if keys.len() == 1 {
+        return Ok(value);
+    }
+
+    return value.resolve_get_attr(&keys[1..]);
+}
+
+fn new_str(st: String) -> Rc<Object> {
+    return Rc::new(Object::Str(st));
+}
+
+fn new_array(method: &str, elements: Vec<Rc<Object>>) -> Rc<Object> {
+    return Rc::new(Object::Array(RefCell::new(Array {
+        name: format!("string_{}", method),
+        elements: elements,
+    })));
+}
+
+fn check_n_args(method: &str, args: &[Rc<Object>], min: usize, max: usize) -> Option<String> {
+    if args.len() < min || args.len() > max {
+        if min == max {
+            return Some(format!(
+                "{}() takes {} argument(s), provided {}",
+                method,
+                min,
+                args.len()
+            ));
+        }
+
+        return Some(format!(
+            "{}() takes {} to {} arguments, provided {}",
+            method,
+            min,
+            max,
+            args.len()
+        ));
+    }
+
+    return None;
+}
+
+fn get_str_arg<'a>(method: &str, args: &'a [Rc<Object>], idx: usize) -> Result<&'a String, String> {
+    match args[idx].as_ref() {
+        Object::Str(st) => return Ok(st),
+        _ => {
+            return Err(format!(
+                "{}() expects string as argument {}, got {}",
+                method,
+                idx + 1,
+                args[idx].get_type()
+            ))
+        }
+    }
+}
+
+fn get_int_arg(method: &str, args: &[Rc<Object>], idx: usize) -> Result<i64, String> {
+    match args[idx].as_ref() {
+        Object::Int(i) => return Ok(*i),
+        _ => {
+            return Err(format!(
+                "{}() expects int as argument {}, got {}",
+                method,
+                idx + 1,
+                args[idx].get_type()
+            ))
+        }
+    }
+}
+
+// fill argument of the pad functions, a single space if not provided.
+fn get_fill_arg(method: &str, args: &[Rc<Object>], idx: usize) -> Result<char, String> {
+    if args.len() <= idx {
+        return Ok(' ');
+    }
+
+    match args[idx].as_ref() {
+        Object::Char(c) => return Ok(*c),
+        Object::Str(st) if st.chars().count() == 1 => return Ok(st.chars().next().unwrap()),
+        _ => {
+            return Err(format!(
+                "{}() expects a single character as fill, got {}",
+                method,
+                args[idx].describe()
+            ))
+        }
+    }
+}
+
+// converts a byte offset into a character position.
+fn char_pos(st: &str, byte_offset: Option<usize>) -> i64 {
+    match byte_offset {
+        Some(offset) => return st[..offset].chars().count() as i64,
+        None => return -1,
+    }
+}
+
+// true if the string is not empty and every character passes the check.
+fn classify(st: &str, check: fn(&char) -> bool) -> Rc<Object> {
+    let result = !st.is_empty() && st.chars().all(|c| check(&c));
+    return Rc::new(Object::Bool(result));
+}
+
+// true if the string has at least one cased character and all of
+// them are in the given case.
+fn classify_case(st: &str, upper: bool) -> Rc<Object> {
+    let has_cased = st.chars().any(|c| c.is_uppercase() || c.is_lowercase());
+    let wrong_case = st.chars().any(|c| {
+        if upper {
+            c.is_lowercase()
+        } else {
+            c.is_uppercase()
+        }
+    });
+    return Rc::new(Object::Bool(has_cased && !wrong_case));
+}
+
+fn pad(st: &str, width: i64, fill: char, left: bool, right: bool) -> String {
+    let length = st.chars().count() as i64;
+    if width <= length {
+        return st.to_string();
+    }
+
+    let n_fill = (width - length) as usize;
+    let (n_left, n_right) = match (left, right) {
+        (true, true) => (n_fill / 2, n_fill - n_fill / 2),
+        (true, false) => (n_fill, 0),
+        _ => (0, n_fill),
+    };
+
+    let mut result = String::new();
+    result.push_str(&fill.to_string().repeat(n_left));
+    result.push_str(st);
+    result.push_str(&fill.to_string().repeat(n_right));
+    return result;
+}
+
+pub fn resolve_call_attr(
+    st: &str,
+    keys: &[Rc<Object>],
+    args: &[Rc<Object>],
+) -> Result<Rc<Object>, String> {
+    if keys.len() != 1 {
+        return Err("Nested attributes are not supported for type string".to_string());
+    }
+
+    let method = match keys[0].as_ref() {
+        Object::Str(method) => method.as_str(),
+        _ => return Err(format!("invalid method type, found {}", keys[0].get_type())),
+    };
+
+    let (min_args, max_args) = match method {
+        "join" | "find" | "rfind" | "contains" | "count" | "starts_with" | "ends_with"
+        | "repeat" => (1, 1),
+        "split" => (0, 2),
+        "replace" => (2, 3),
+        "pad_start" | "pad_end" | "center" => (1, 2),
+        _ => (0, 0),
+    };
+
+    if !METHODS.contains(&method) {
+        return Err(format!("Attribute {} not found for type string", method));
+    }
+
+    let args_error = check_n_args(method, args, min_args, max_args);
+    if args_error.is_some() {
+        return Err(args_error.unwrap());
+    }
+
+    match method {
+        "split" => {
+            // without a separator, splits on whitespace:
+            if args.is_empty() {
+                let parts = st.split_whitespace().map(|s| new_str(s.to_string()));
+                return Ok(new_array(method, parts.collect()));
+            }
+
+            let sep_res = get_str_arg(method, args, 0);
+            if sep_res.is_err() {
+                return Err(sep_res.unwrap_err());
+            }
+
+            let sep = sep_res.unwrap();
+            if sep.is_empty() {
+                return Err("split() separator cannot be empty".to_string());
+            }
+
+            let parts: Vec<Rc<Object>> = if args.len() == 2 {
+                let max_res = get_int_arg(method, args, 1);
+                if max_res.is_err() {
+                    return Err(max_res.unwrap_err());
+                }
+
+                // at max N splits, so N + 1 parts
+                let max_splits = max_res.unwrap();
+                if max_splits < 0 {
+                    return Err("split() max splits cannot be negative".to_string());
+                }
+
+                st.splitn(max_splits as usize + 1, sep.as_str())
+                    .map(|s| new_str(s.to_string()))
+                    .collect()
+            } else {
+                st.split(sep.as_str())
+                    .map(|s| new_str(s.to_string()))
+                    .collect()
+            };
+
+            return Ok(new_array(method, parts));
+        }
+        "join" => match args[0].as_ref() {
+            Object::Array(arr) => {
+                let parts: Vec<String> =
+                    arr.borrow().elements.iter().map(|e| e.describe()).collect();
+                return Ok(new_str(parts.join(st)));
+            }
+            _ => {
+                return Err(format!(
+                    "join() expects array as argument, got {}",
+                    args[0].get_type()
+                ))
+            }
+        },
+        "trim" => return Ok(new_str(st.trim().to_string())),
+        "trim_start" => return Ok(new_str(st.trim_start().to_string())),
+        "trim_end" => return Ok(new_str(st.trim_end().to_string())),
+        "replace" => {
+            let from_res = get_str_arg(method, args, 0);
+            if from_res.is_err() {
+                return Err(from_res.unwrap_err());
+            }
+
+            let to_res = get_str_arg(method, args, 1);
+            if to_res.is_err() {
+                return Err(to_res.unwrap_err());
+            }
+
+            let (from, to) = (from_res.unwrap(), to_res.unwrap());
+            if args.len() == 3 {
+                let count_res = get_int_arg(method, args, 2);
+                if count_res.is_err() {
+                    return Err(count_res.unwrap_err());
+                }
+
+                let count = count_res.unwrap();
+                if count < 0 {
+                    return Err("replace() count cannot be negative".to_string());
+                }
+
+                return Ok(new_str(st.replacen(from.as_str(), to, count as usize)));
+            }
+
+            return Ok(new_str(st.replace(from.as_str(), to)));
+        }
+        "find" | "rfind" | "contains" | "count" | "starts_with" | "ends_with" => {
+            let sub_res = get_str_arg(method, args, 0);
+            if sub_res.is_err() {
+                return Err(sub_res.unwrap_err());
+            }
+
+            let sub = sub_res.unwrap().as_str();
+            let result = match method {
+                "find" => Object::Int(char_pos(st, st.find(sub))),
+                "rfind" => Object::Int(char_pos(st, st.rfind(sub))),
+                "contains" => Object::Bool(st.contains(sub)),
+                "count" => {
+                    if sub.is_empty() {
+                        return Err("count() substring cannot be empty".to_string());
+                    }
+                    Object::Int(st.matches(sub).count() as i64)
+                }
+                "starts_with" => Object::Bool(st.starts_with(sub)),
+                _ => Object::Bool(st.ends_with(sub)),
+            };
+
+            return Ok(Rc::new(result));
+        }
+        "upper" => return Ok(new_str(st.to_uppercase())),
+        "lower" => return Ok(new_str(st.to_lowercase())),
+        "capitalize" => {
+            let mut chars = st.chars();
+            let result = match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(|c| c.to_lowercase()))
+                    .collect(),
+                None => String::new(),
+            };
+            return Ok(new_str(result));
+        }
+        "repeat" => {
+            let n_res = get_int_arg(method, args, 0);
+            if n_res.is_err() {
+                return Err(n_res.unwrap_err());
+            }
+
+            let n = n_res.unwrap();
+            if n < 0 {
+                return Err(format!("repeat() count cannot be negative, got {}", n));
+            }
+
+            let size = (st.len() as u128) * (n as u128);
+            if size > MAX_STRING_SIZE as u128 {
+                return Err(format!(
+                    "repeat() result of {} bytes is larger than the limit of {} bytes",
+                    size, MAX_STRING_SIZE
+                ));
+            }
+
+            return Ok(new_str(st.repeat(n as usize)));
+        }
+        "pad_start" | "pad_end" | "center" => {
+            let width_res = get_int_arg(method, args, 0);
+            if width_res.is_err() {
+                return Err(width_res.unwrap_err());
+            }
+
+            let fill_res = get_fill_arg(method, args, 1);
+            if fill_res.is_err() {
+                return Err(fill_res.unwrap_err());
+            }
+
+            let (width, fill) = (width_res.unwrap(), fill_res.unwrap());
+            if width > MAX_STRING_SIZE as i64 {
+                return Err(format!(
+                    "{}() width {} is larger than the limit of {} bytes",
+                    method, width, MAX_STRING_SIZE
+                ));
+            }
+
+            let result = match method {
+                "pad_start" => pad(st, width, fill, true, false),
+                "pad_end" => pad(st, width, fill, false, true),
+                _ => pad(st, width, fill, true, true),
+            };
+
+            return Ok(new_str(result));
+        }
+        "chars" => {
+            let chars = st.chars().map(|c| Rc::new(Object::Char(c)));
+            return Ok(new_array(method, chars.collect()));
+        }
+        "lines" => {
+            let lines = st.lines().map(|l| new_str(l.to_string()));
+            return Ok(new_array(method, lines.collect()));
+        }
+        "is_digit" => return Ok(classify(st, char::is_ascii_digit)),
+        "is_alpha" => return Ok(classify(st, |c| c.is_alphabetic())),
+        "is_alnum" => return Ok(classify(st, |c| c.is_alphanumeric())),
+        "is_space" => return Ok(classify(st, |c| c.is_whitespace())),
+        "is_upper" => return Ok(classify_case(st, true)),
+        "is_lower" => return Ok(classify_case(st, false)),
+        _ => return Err(format!("Attribute {} not found for type string", method)),
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
+use crate::compiler::spans::SpanTable;
 use crate::compiler::CompiledInstructions;
 
 #[derive(Clone, Debug, PartialOrd)]
@@ -10,6 +11,17 @@ pub struct Subroutine {
     pub num_locals: usize,
     pub num_parameters: usize,
     pub is_local_scope: bool,
+    pub spans: SpanTable,
+    // results of pure functions are memoized by their closures.
+    pub is_pure: bool,
+    // calls to generator functions return an iterator over what they yield.
+    pub is_generator: bool,
+    // keyword arguments are matched against the names of the parameters.
+    pub parameter_names: Vec<String>,
+    // the last parameters, before a variadic one, have default values.
+    pub num_defaults: usize,
+    // the last parameter collects the extra arguments in an array.
+    pub is_variadic: bool,
 }
 
 impl Subroutine {
@@ -29,6 +41,34 @@ impl Subroutine {
         return self.num_parameters;
     }
 
+    // parameters that have to be given, the ones without a default value.
+    pub fn n_required(&self) -> usize {
+        return self.num_parameters - self.num_defaults - self.is_variadic as usize;
+    }
+
+    pub fn accepts_arguments(&self, n_args: usize) -> bool {
+        if n_args < self.n_required() {
+            return false;
+        }
+
+        return self.is_variadic || n_args <= self.num_parameters;
+    }
+
+    // the number of arguments taken, skip leaves out the first parameters.
+    pub fn describe_arity(&self, skip: usize) -> String {
+        let n_required = self.n_required().saturating_sub(skip);
+        if self.is_variadic {
+            return format!("at least {}", n_required);
+        }
+
+        let n_params = self.num_parameters.saturating_sub(skip);
+        if self.num_defaults > 0 {
+            return format!("{} to {}", n_required, n_params);
+        }
+
+        return format!("{}", n_params);
+    }
+
     pub fn describe(&self) -> String {
         return format!("Function<{}>", self.name);
     }
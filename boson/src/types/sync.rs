@@ -0,0 +1,600 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::sync::atomic;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+
+use crate::types::object::AttributeResolver;
+use crate::types::object::Object;
+use crate::types::sendable::SendableContext;
+use crate::types::sendable::SendableObject;
+
+// needed by attribute function call resolver
+use crate::api;
+use crate::compiler;
+use crate::vm;
+
+use api::Platform;
+use compiler::symtab::ConstantPool;
+use vm::ffi::BosonFFI;
+use vm::global::GlobalPool;
+use vm::stack::DataStack;
+use vm::thread::BosonThreads;
+
+/*
+    Synchronization primitives shared between threads.
+
+    Unlike every other object, these are not copied when passed to a thread,
+    all the copies refer to the same Arc. Values stored in them are deep
+    copied in sendable form (see types/sendable.rs), so each thread receives
+    its own objects.
+*/
+
+// a value in sendable form along with the context of its containers.
+#[derive(Debug, Clone)]
+pub struct SharedValue {
+    context: SendableContext,
+    value: SendableObject,
+}
+
+impl SharedValue {
+    pub fn new(obj: &Rc<Object>) -> Result<SharedValue, String> {
+        let mut context = SendableContext::new();
+        let value = context.encode(obj);
+        if value.is_err() {
+            return Err(value.unwrap_err());
+        }
+
+        return Ok(SharedValue {
+            context: context,
+            value: value.unwrap(),
+        });
+    }
+
+    pub fn get(&self) -> Rc<Object> {
+        return self.context.decoder().decode(&self.value);
+    }
+}
+
+fn check_n_args(method: &str, args: &[Rc<Object>], n: usize) -> Option<String> {
+    if args.len() != n {
+        return Some(format!(
+            "{}() takes {} argument(s), provided {}",
+            method,
+            n,
+            args.len()
+        ));
+    }
+
+    return None;
+}
+
+fn get_method_name(keys: &[Rc<Object>]) -> Result<String, String> {
+    if keys.len() != 1 {
+        return Err("Nested attributes are not supported".to_string());
+    }
+
+    match keys[0].as_ref() {
+        Object::Str(st) => return Ok(st.clone()),
+        _ => return Err(format!("invalid method type, found {}", keys[0].get_type())),
+    }
+}
+
+fn names_to_attrs(names: &[&str]) -> Vec<Rc<Object>> {
+    return names
+        .iter()
+        .map(|name| Rc::new(Object::Str(name.to_string())))
+        .collect();
+}
+
+#[derive(Debug)]
+struct ChannelState {
+    items: VecDeque<SharedValue>,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct ChannelInner {
+    state: Mutex<ChannelState>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    // 0 is unbounded
+    capacity: usize,
+}
+
+/*
+    FIFO channel, send() blocks when the channel is full, recv() blocks
+    until a value is available. Once closed, send() fails and recv() drains
+    the remaining values and then returns none.
+*/
+#[derive(Debug, Clone)]
+pub struct Channel {
+    inner: Arc<ChannelInner>,
+}
+
+impl Channel {
+    pub fn new(capacity: usize) -> Channel {
+        return Channel {
+            inner: Arc::new(ChannelInner {
+                state: Mutex::new(ChannelState {
+                    items: VecDeque::new(),
+                    closed: false,
+                }),
+                not_empty: Condvar::new(),
+                not_full: Condvar::new(),
+                capacity: capacity,
+            }),
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        return format!("Channel(capacity={})", self.inner.capacity);
+    }
+
+    pub fn send(&self, obj: &Rc<Object>) -> Option<String> {
+        let value = SharedValue::new(obj);
+        if value.is_err() {
+            return Some(value.unwrap_err());
+        }
+        let value = value.unwrap();
+
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && self.inner.capacity != 0 && state.items.len() >= self.inner.capacity
+        {
+            state = self.inner.not_full.wait(state).unwrap();
+        }
+
+        if state.closed {
+            return Some("send() on a closed channel".to_string());
+        }
+
+        state.items.push_back(value);
+        self.inner.not_empty.notify_one();
+        return None;
+    }
+
+    pub fn recv(&self) -> Rc<Object> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && state.items.is_empty() {
+            state = self.inner.not_empty.wait(state).unwrap();
+        }
+
+        let value = state.items.pop_front();
+        self.inner.not_full.notify_one();
+        std::mem::drop(state);
+
+        match value {
+            Some(value) => return value.get(),
+            None => return Rc::new(Object::Noval),
+        }
+    }
+
+    pub fn try_recv(&self) -> Rc<Object> {
+        let mut state = self.inner.state.lock().unwrap();
+        let value = state.items.pop_front();
+        self.inner.not_full.notify_one();
+        std::mem::drop(state);
+
+        match value {
+            Some(value) => return value.get(),
+            None => return Rc::new(Object::Noval),
+        }
+    }
+
+    pub fn close(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.closed = true;
+        self.inner.not_empty.notify_all();
+        self.inner.not_full.notify_all();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.inner.state.lock().unwrap().items.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        return self.inner.state.lock().unwrap().closed;
+    }
+}
+
+impl AttributeResolver for Channel {
+    fn attrs(&self) -> Vec<Rc<Object>> {
+        return names_to_attrs(&["send", "recv", "try_recv", "close", "len", "is_closed"]);
+    }
+
+    fn resolve_get_attr(&self, _keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+        return Err("Channel does not have attributes, only methods.".to_string());
+    }
+
+    fn resolve_set_attr(&self, _keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        return Some("Channel does not support attribute assignment.".to_string());
+    }
+
+    fn resolve_call_attr(
+        &mut self,
+        keys: &[Rc<Object>],
+        args: &[Rc<Object>],
+        _ds: &mut DataStack,
+        _platform: &mut Platform,
+        _gp: &mut GlobalPool,
+        _c: &mut ConstantPool,
+        _th: &mut BosonThreads,
+        _ffi: &mut BosonFFI,
+    ) -> Result<Rc<Object>, String> {
+        let method_res = get_method_name(keys);
+        if method_res.is_err() {
+            return Err(method_res.unwrap_err());
+        }
+
+        let method = method_res.unwrap();
+        let n_args = if method == "send" { 1 } else { 0 };
+        let args_error = check_n_args(&method, args, n_args);
+        if args_error.is_some() {
+            return Err(args_error.unwrap());
+        }
+
+        match method.as_ref() {
+            "send" => {
+                let send_error = self.send(&args[0]);
+                if send_error.is_some() {
+                    return Err(send_error.unwrap());
+                }
+                return Ok(Rc::new(Object::Noval));
+            }
+            "recv" => return Ok(self.recv()),
+            "try_recv" => return Ok(self.try_recv()),
+            "close" => {
+                self.close();
+                return Ok(Rc::new(Object::Noval));
+            }
+            "len" => return Ok(Rc::new(Object::Int(self.len() as i64))),
+            "is_closed" => return Ok(Rc::new(Object::Bool(self.is_closed()))),
+            _ => return Err(format!("Channel has no method {}", method)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MutexState {
+    locked: bool,
+    value: SharedValue,
+}
+
+#[derive(Debug)]
+struct MutexInner {
+    state: Mutex<MutexState>,
+    released: Condvar,
+}
+
+/*
+    Lock guarding a single value, lock() blocks until the lock is acquired.
+    The guarded value is read and written with get() and set(), which
+    do not require the lock themselves, the lock is only needed to make
+    a sequence of get() and set() calls atomic.
+*/
+#[derive(Debug, Clone)]
+pub struct BosonMutex {
+    inner: Arc<MutexInner>,
+}
+
+impl BosonMutex {
+    pub fn new(value: &Rc<Object>) -> Result<BosonMutex, String> {
+        let shared = SharedValue::new(value);
+        if shared.is_err() {
+            return Err(shared.unwrap_err());
+        }
+
+        return Ok(BosonMutex {
+            inner: Arc::new(MutexInner {
+                state: Mutex::new(MutexState {
+                    locked: false,
+                    value: shared.unwrap(),
+                }),
+                released: Condvar::new(),
+            }),
+        });
+    }
+
+    pub fn describe(&self) -> String {
+        return format!("Mutex(locked={})", self.is_locked());
+    }
+
+    pub fn lock(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+        while state.locked {
+            state = self.inner.released.wait(state).unwrap();
+        }
+        state.locked = true;
+    }
+
+    pub fn try_lock(&self) -> bool {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.locked {
+            return false;
+        }
+        state.locked = true;
+        return true;
+    }
+
+    pub fn unlock(&self) -> Option<String> {
+        let mut state = self.inner.state.lock().unwrap();
+        if !state.locked {
+            return Some("unlock() on a mutex which is not locked".to_string());
+        }
+        state.locked = false;
+        self.inner.released.notify_one();
+        return None;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        return self.inner.state.lock().unwrap().locked;
+    }
+
+    pub fn get(&self) -> Rc<Object> {
+        let value = self.inner.state.lock().unwrap().value.clone();
+        return value.get();
+    }
+
+    pub fn set(&self, obj: &Rc<Object>) -> Option<String> {
+        let value = SharedValue::new(obj);
+        if value.is_err() {
+            return Some(value.unwrap_err());
+        }
+
+        self.inner.state.lock().unwrap().value = value.unwrap();
+        return None;
+    }
+}
+
+impl AttributeResolver for BosonMutex {
+    fn attrs(&self) -> Vec<Rc<Object>> {
+        return names_to_attrs(&["lock", "try_lock", "unlock", "is_locked", "get", "set"]);
+    }
+
+    fn resolve_get_attr(&self, _keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+        return Err("Mutex does not have attributes, only methods.".to_string());
+    }
+
+    fn resolve_set_attr(&self, _keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        return Some("Mutex does not support attribute assignment.".to_string());
+    }
+
+    fn resolve_call_attr(
+        &mut self,
+        keys: &[Rc<Object>],
+        args: &[Rc<Object>],
+        _ds: &mut DataStack,
+        _platform: &mut Platform,
+        _gp: &mut GlobalPool,
+        _c: &mut ConstantPool,
+        _th: &mut BosonThreads,
+        _ffi: &mut BosonFFI,
+    ) -> Result<Rc<Object>, String> {
+        let method_res = get_method_name(keys);
+        if method_res.is_err() {
+            return Err(method_res.unwrap_err());
+        }
+
+        let method = method_res.unwrap();
+        let n_args = if method == "set" { 1 } else { 0 };
+        let args_error = check_n_args(&method, args, n_args);
+        if args_error.is_some() {
+            return Err(args_error.unwrap());
+        }
+
+        match method.as_ref() {
+            "lock" => {
+                self.lock();
+                return Ok(Rc::new(Object::Noval));
+            }
+            "try_lock" => return Ok(Rc::new(Object::Bool(self.try_lock()))),
+            "unlock" => {
+                let unlock_error = self.unlock();
+                if unlock_error.is_some() {
+                    return Err(unlock_error.unwrap());
+                }
+                return Ok(Rc::new(Object::Noval));
+            }
+            "is_locked" => return Ok(Rc::new(Object::Bool(self.is_locked()))),
+            "get" => return Ok(self.get()),
+            "set" => {
+                let set_error = self.set(&args[0]);
+                if set_error.is_some() {
+                    return Err(set_error.unwrap());
+                }
+                return Ok(Rc::new(Object::Noval));
+            }
+            _ => return Err(format!("Mutex has no method {}", method)),
+        }
+    }
+}
+
+/*
+    Integer counter updated atomically, add(), sub(), incr() and decr()
+    return the updated value.
+*/
+#[derive(Debug, Clone)]
+pub struct AtomicCounter {
+    value: Arc<atomic::AtomicI64>,
+}
+
+impl AtomicCounter {
+    pub fn new(value: i64) -> AtomicCounter {
+        return AtomicCounter {
+            value: Arc::new(atomic::AtomicI64::new(value)),
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        return format!("Atomic({})", self.get());
+    }
+
+    pub fn get(&self) -> i64 {
+        return self.value.load(atomic::Ordering::SeqCst);
+    }
+
+    pub fn set(&self, value: i64) {
+        self.value.store(value, atomic::Ordering::SeqCst);
+    }
+
+    pub fn add(&self, value: i64) -> i64 {
+        return self
+            .value
+            .fetch_add(value, atomic::Ordering::SeqCst)
+            .wrapping_add(value);
+    }
+
+    pub fn compare_and_swap(&self, current: i64, new: i64) -> bool {
+        return self
+            .value
+            .compare_exchange(
+                current,
+                new,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            )
+            .is_ok();
+    }
+}
+
+fn get_int_arg(method: &str, obj: &Rc<Object>) -> Result<i64, String> {
+    match obj.as_ref() {
+        Object::Int(i) => return Ok(*i),
+        _ => {
+            return Err(format!(
+                "{}() expects an int argument, got {}",
+                method,
+                obj.get_type()
+            ))
+        }
+    }
+}
+
+impl AttributeResolver for AtomicCounter {
+    fn attrs(&self) -> Vec<Rc<Object>> {
+        return names_to_attrs(&["get", "set", "add", "sub", "incr", "decr", "cas"]);
+    }
+
+    fn resolve_get_attr(&self, _keys: &[Rc<Object>]) -> Result<Rc<Object>, String> {
+        return Err("Atomic does not have attributes, only methods.".to_string());
+    }
+
+    fn resolve_set_attr(&self, _keys: &[Rc<Object>], _value: Rc<Object>) -> Option<String> {
+        return Some("Atomic does not support attribute assignment.".to_string());
+    }
+
+    fn resolve_call_attr(
+        &mut self,
+        keys: &[Rc<Object>],
+        args: &[Rc<Object>],
+        _ds: &mut DataStack,
+        _platform: &mut Platform,
+        _gp: &mut GlobalPool,
+        _c: &mut ConstantPool,
+        _th: &mut BosonThreads,
+        _ffi: &mut BosonFFI,
+    ) -> Result<Rc<Object>, String> {
+        let method_res = get_method_name(keys);
+        if method_res.is_err() {
+            return Err(method_res.unwrap_err());
+        }
+
+        let method = method_res.unwrap();
+        let n_args = match method.as_ref() {
+            "set" | "add" | "sub" => 1,
+            "cas" => 2,
+            _ => 0,
+        };
+        let args_error = check_n_args(&method, args, n_args);
+        if args_error.is_some() {
+            return Err(args_error.unwrap());
+        }
+
+        let mut int_args = vec![];
+        for arg in args {
+            let int_res = get_int_arg(&method, arg);
+            if int_res.is_err() {
+                return Err(int_res.unwrap_err());
+            }
+            int_args.push(int_res.unwrap());
+        }
+
+        let result = match method.as_ref() {
+            "get" => Object::Int(self.get()),
+            "set" => {
+                self.set(int_args[0]);
+                Object::Noval
+            }
+            "add" => Object::Int(self.add(int_args[0])),
+            "sub" => Object::Int(self.add(int_args[0].wrapping_neg())),
+            "incr" => Object::Int(self.add(1)),
+            "decr" => Object::Int(self.add(-1)),
+            "cas" => Object::Bool(self.compare_and_swap(int_args[0], int_args[1])),
+            _ => return Err(format!("Atomic has no method {}", method)),
+        };
+
+        return Ok(Rc::new(result));
+    }
+}
+
+// two handles are equal when they refer to the same primitive.
+impl PartialEq for Channel {
+    fn eq(&self, other: &Channel) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl PartialEq for BosonMutex {
+    fn eq(&self, other: &BosonMutex) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl PartialEq for AtomicCounter {
+    fn eq(&self, other: &AtomicCounter) -> bool {
+        Arc::ptr_eq(&self.value, &other.value)
+    }
+}
+
+impl PartialOrd for Channel {
+    fn partial_cmp(&self, _other: &Channel) -> Option<Ordering> {
+        None
+    }
+}
+
+impl PartialOrd for BosonMutex {
+    fn partial_cmp(&self, _other: &BosonMutex) -> Option<Ordering> {
+        None
+    }
+}
+
+impl PartialOrd for AtomicCounter {
+    fn partial_cmp(&self, _other: &AtomicCounter) -> Option<Ordering> {
+        None
+    }
+}
+
+impl Hash for Channel {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.inner) as usize).hash(state);
+    }
+}
+
+impl Hash for BosonMutex {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.inner) as usize).hash(state);
+    }
+}
+
+impl Hash for AtomicCounter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.value) as usize).hash(state);
+    }
+}
@@ -1,5 +1,15 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::ptr;
 use std::rc::Rc;
 
+use num_bigint::BigInt;
+use num_bigint::Sign;
+use num_traits::ToPrimitive;
+
+use crate::config::MAX_INT_BITS;
+use crate::types::bigint;
 use crate::types::object;
 use crate::vm::errors;
 
@@ -24,22 +34,54 @@ impl Arithmetic {
         );
     }
 
+    // an integer result of more than MAX_INT_BITS bits:
+    fn check_int_bits(bits: f64, operation: &str) -> Option<ISAError> {
+        if bits > MAX_INT_BITS as f64 {
+            return Some(ISAError::new(
+                format!(
+                    "Operation {} results in an integer larger than {} bits",
+                    operation, MAX_INT_BITS
+                ),
+                ISAErrorKind::OverflowError,
+            ));
+        }
+
+        return None;
+    }
+
+    // number of bits of base ** exp, estimated before computing it:
+    fn pow_bits(base: &BigInt, exp: u32) -> f64 {
+        let magnitude = base.magnitude();
+        let log2 = if magnitude.bits() <= 1000 {
+            magnitude.to_f64().unwrap().log2()
+        } else {
+            magnitude.bits() as f64
+        };
+
+        // 0 and 1 stay as they are, whatever the power is:
+        if log2 <= 0.0 {
+            return 0.0;
+        }
+        return log2 * exp as f64;
+    }
+
     #[inline(always)]
     pub fn add(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
         match (left.as_ref(), right.as_ref()) {
             (Object::Int(lval), Object::Int(rval)) => {
+                // ints that overflow are promoted to big integers:
                 let result = (*lval).checked_add(*rval);
                 if result.is_none() {
-                    return Err(Arithmetic::new_overflow_err(left, right, "add".to_string()));
+                    return Ok(bigint::from_bigint(BigInt::from(*lval) + *rval));
                 }
                 return Ok(Rc::new(Object::Int(result.unwrap())));
             }
             (Object::Int(lval), Object::Float(rval)) => {
-                let result = lval.clone() as f64 + rval;
+                let result = *lval as f64 + rval;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Int(rval)) => {
-                let result = lval + rval.clone() as f64;
+                let result = lval + *rval as f64;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Float(rval)) => {
@@ -52,6 +94,18 @@ impl Arithmetic {
                 return Ok(Rc::new(Object::Str(result)));
             }
             _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    return Ok(bigint::from_bigint(lval + rval));
+                }
+
+                let floats = bigint::float_operands(left, right);
+                if floats.is_some() {
+                    let (lval, rval) = floats.unwrap();
+                    return Arithmetic::add(&lval, &rval);
+                }
+
                 // throw a panic
                 let l_type = left.get_type();
                 let r_type = right.get_type();
@@ -71,18 +125,19 @@ impl Arithmetic {
     pub fn sub(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
         match (left.as_ref(), right.as_ref()) {
             (Object::Int(lval), Object::Int(rval)) => {
+                // ints that overflow are promoted to big integers:
                 let result = (*lval).checked_sub(*rval);
                 if result.is_none() {
-                    return Err(Arithmetic::new_overflow_err(left, right, "add".to_string()));
+                    return Ok(bigint::from_bigint(BigInt::from(*lval) - *rval));
                 }
                 return Ok(Rc::new(Object::Int(result.unwrap())));
             }
             (Object::Int(lval), Object::Float(rval)) => {
-                let result = lval.clone() as f64 - rval;
+                let result = *lval as f64 - rval;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Int(rval)) => {
-                let result = lval - rval.clone() as f64;
+                let result = lval - *rval as f64;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Float(rval)) => {
@@ -90,6 +145,18 @@ impl Arithmetic {
                 return Ok(Rc::new(Object::Float(result)));
             }
             _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    return Ok(bigint::from_bigint(lval - rval));
+                }
+
+                let floats = bigint::float_operands(left, right);
+                if floats.is_some() {
+                    let (lval, rval) = floats.unwrap();
+                    return Arithmetic::sub(&lval, &rval);
+                }
+
                 // throw a panic
                 let l_type = left.get_type();
                 let r_type = right.get_type();
@@ -109,18 +176,19 @@ impl Arithmetic {
     pub fn mul(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
         match (left.as_ref(), right.as_ref()) {
             (Object::Int(lval), Object::Int(rval)) => {
+                // ints that overflow are promoted to big integers:
                 let result = (*lval).checked_mul(*rval);
                 if result.is_none() {
-                    return Err(Arithmetic::new_overflow_err(left, right, "add".to_string()));
+                    return Ok(bigint::from_bigint(BigInt::from(*lval) * *rval));
                 }
                 return Ok(Rc::new(Object::Int(result.unwrap())));
             }
             (Object::Int(lval), Object::Float(rval)) => {
-                let result = lval.clone() as f64 * rval;
+                let result = *lval as f64 * rval;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Int(rval)) => {
-                let result = lval * rval.clone() as f64;
+                let result = lval * *rval as f64;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Float(rval)) => {
@@ -128,6 +196,18 @@ impl Arithmetic {
                 return Ok(Rc::new(Object::Float(result)));
             }
             _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    return Ok(bigint::from_bigint(lval * rval));
+                }
+
+                let floats = bigint::float_operands(left, right);
+                if floats.is_some() {
+                    let (lval, rval) = floats.unwrap();
+                    return Arithmetic::mul(&lval, &rval);
+                }
+
                 // throw a panic
                 let l_type = left.get_type();
                 let r_type = right.get_type();
@@ -165,7 +245,7 @@ impl Arithmetic {
                     ));
                 }
 
-                let result = lval.clone() as f64 / rval;
+                let result = *lval as f64 / rval;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Int(rval)) => {
@@ -176,7 +256,7 @@ impl Arithmetic {
                     ));
                 }
 
-                let result = lval / rval.clone() as f64;
+                let result = lval / *rval as f64;
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Float(rval)) => {
@@ -190,6 +270,25 @@ impl Arithmetic {
                 return Ok(Rc::new(Object::Float(result)));
             }
             _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    if rval.sign() == Sign::NoSign {
+                        return Err(ISAError::new(
+                            format!("Divide by zero {}/{}", lval, rval),
+                            ISAErrorKind::DivideByZeroError,
+                        ));
+                    }
+                    let result = bigint::to_f64(&lval) / bigint::to_f64(&rval);
+                    return Ok(Rc::new(Object::Float(result)));
+                }
+
+                let floats = bigint::float_operands(left, right);
+                if floats.is_some() {
+                    let (lval, rval) = floats.unwrap();
+                    return Arithmetic::div(&lval, &rval);
+                }
+
                 // throw a panic
                 let l_type = left.get_type();
                 let r_type = right.get_type();
@@ -205,6 +304,7 @@ impl Arithmetic {
         }
     }
 
+    // the remainder of floor_div(), it has the sign of the divisor, -7 % 2 is 1:
     #[inline(always)]
     pub fn modulus(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
         match (left.as_ref(), right.as_ref()) {
@@ -216,7 +316,11 @@ impl Arithmetic {
                     ));
                 }
 
-                let result = lval % rval;
+                // only the smallest int modulo -1 overflows, the remainder is 0:
+                let mut result = (*lval).checked_rem(*rval).unwrap_or(0);
+                if result != 0 && ((result < 0) != (*rval < 0)) {
+                    result += rval;
+                }
                 return Ok(Rc::new(Object::Int(result)));
             }
             (Object::Int(lval), Object::Float(rval)) => {
@@ -227,7 +331,7 @@ impl Arithmetic {
                     ));
                 }
 
-                let result = lval.clone() as f64 % rval;
+                let result = Arithmetic::float_modulus(*lval as f64, *rval);
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Int(rval)) => {
@@ -238,7 +342,7 @@ impl Arithmetic {
                     ));
                 }
 
-                let result = lval * rval.clone() as f64;
+                let result = Arithmetic::float_modulus(*lval, *rval as f64);
                 return Ok(Rc::new(Object::Float(result)));
             }
             (Object::Float(lval), Object::Float(rval)) => {
@@ -248,10 +352,32 @@ impl Arithmetic {
                         ISAErrorKind::DivideByZeroError,
                     ));
                 }
-                let result = lval % rval;
+                let result = Arithmetic::float_modulus(*lval, *rval);
                 return Ok(Rc::new(Object::Float(result)));
             }
             _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    if rval.sign() == Sign::NoSign {
+                        return Err(ISAError::new(
+                            format!("Divide by zero {}%{}", lval, rval),
+                            ISAErrorKind::DivideByZeroError,
+                        ));
+                    }
+                    let mut result = &lval % &rval;
+                    if result.sign() != Sign::NoSign && (result.sign() != rval.sign()) {
+                        result += rval;
+                    }
+                    return Ok(bigint::from_bigint(result));
+                }
+
+                let floats = bigint::float_operands(left, right);
+                if floats.is_some() {
+                    let (lval, rval) = floats.unwrap();
+                    return Arithmetic::modulus(&lval, &rval);
+                }
+
                 // throw a panic
                 let l_type = left.get_type();
                 let r_type = right.get_type();
@@ -266,6 +392,187 @@ impl Arithmetic {
             }
         }
     }
+
+    fn float_modulus(lval: f64, rval: f64) -> f64 {
+        let result = lval % rval;
+        if result != 0.0 && ((result < 0.0) != (rval < 0.0)) {
+            return result + rval;
+        }
+        return result;
+    }
+
+    #[inline(always)]
+    pub fn pow(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
+        match (left.as_ref(), right.as_ref()) {
+            (Object::Int(lval), Object::Int(rval)) => {
+                // a negative power of an int is a fraction:
+                if *rval < 0 {
+                    if *lval == 0 {
+                        return Err(ISAError::new(
+                            format!("Divide by zero {}**{}", lval, rval),
+                            ISAErrorKind::DivideByZeroError,
+                        ));
+                    }
+
+                    let result = (*lval as f64).powf(*rval as f64);
+                    return Ok(Rc::new(Object::Float(result)));
+                }
+
+                let exp = u32::try_from(*rval);
+                if exp.is_err() {
+                    return Err(Arithmetic::new_overflow_err(left, right, "pow".to_string()));
+                }
+
+                let exp = exp.unwrap();
+                let result = (*lval).checked_pow(exp);
+                if result.is_none() {
+                    let base = BigInt::from(*lval);
+                    let error = Arithmetic::check_int_bits(Arithmetic::pow_bits(&base, exp), "pow");
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+                    return Ok(bigint::from_bigint(base.pow(exp)));
+                }
+                return Ok(Rc::new(Object::Int(result.unwrap())));
+            }
+            (Object::Byte(lval), Object::Byte(rval)) => {
+                let result = (*lval).checked_pow(*rval as u32);
+                if result.is_none() {
+                    return Err(Arithmetic::new_overflow_err(left, right, "pow".to_string()));
+                }
+                return Ok(Rc::new(Object::Byte(result.unwrap())));
+            }
+            (Object::Int(lval), Object::Float(rval)) => {
+                let result = (*lval as f64).powf(*rval);
+                return Ok(Rc::new(Object::Float(result)));
+            }
+            (Object::Float(lval), Object::Int(rval)) => {
+                let result = lval.powf(*rval as f64);
+                return Ok(Rc::new(Object::Float(result)));
+            }
+            (Object::Float(lval), Object::Float(rval)) => {
+                let result = lval.powf(*rval);
+                return Ok(Rc::new(Object::Float(result)));
+            }
+            _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    // a negative power is a fraction:
+                    if rval.sign() == Sign::Minus {
+                        let result = bigint::to_f64(&lval).powf(bigint::to_f64(&rval));
+                        return Ok(Rc::new(Object::Float(result)));
+                    }
+
+                    let exp = rval.to_u32();
+                    if exp.is_none() {
+                        return Err(Arithmetic::new_overflow_err(left, right, "pow".to_string()));
+                    }
+
+                    let exp = exp.unwrap();
+                    let error = Arithmetic::check_int_bits(Arithmetic::pow_bits(&lval, exp), "pow");
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+                    return Ok(bigint::from_bigint(lval.pow(exp)));
+                }
+
+                let floats = bigint::float_operands(left, right);
+                if floats.is_some() {
+                    let (lval, rval) = floats.unwrap();
+                    return Arithmetic::pow(&lval, &rval);
+                }
+
+                let l_type = left.get_type();
+                let r_type = right.get_type();
+
+                return Err(ISAError::new(
+                    format!(
+                        "Operation Pow is not applicable between {} {}",
+                        l_type, r_type
+                    ),
+                    ISAErrorKind::TypeError,
+                ));
+            }
+        }
+    }
+
+    // rounds towards negative infinity, -7 // 2 is -4:
+    #[inline(always)]
+    pub fn floor_div(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
+        let is_zero = match right.as_ref() {
+            Object::Int(rval) => *rval == 0,
+            Object::Byte(rval) => *rval == 0,
+            Object::Float(rval) => *rval == 0.0,
+            _ => false,
+        };
+
+        if is_zero {
+            return Err(ISAError::new(
+                format!("Divide by zero {}//{}", left.describe(), right.describe()),
+                ISAErrorKind::DivideByZeroError,
+            ));
+        }
+
+        match (left.as_ref(), right.as_ref()) {
+            (Object::Int(lval), Object::Int(rval)) => {
+                // only the smallest int divided by -1 overflows:
+                let quotient = (*lval).checked_div(*rval);
+                if quotient.is_none() {
+                    return Ok(bigint::from_bigint(-BigInt::from(*lval)));
+                }
+
+                let mut result = quotient.unwrap();
+                if lval % rval != 0 && ((*lval < 0) != (*rval < 0)) {
+                    result -= 1;
+                }
+                return Ok(Rc::new(Object::Int(result)));
+            }
+            (Object::Byte(lval), Object::Byte(rval)) => {
+                return Ok(Rc::new(Object::Byte(lval / rval)));
+            }
+            (Object::Int(lval), Object::Float(rval)) => {
+                let result = (*lval as f64 / rval).floor();
+                return Ok(Rc::new(Object::Float(result)));
+            }
+            (Object::Float(lval), Object::Int(rval)) => {
+                let result = (lval / *rval as f64).floor();
+                return Ok(Rc::new(Object::Float(result)));
+            }
+            (Object::Float(lval), Object::Float(rval)) => {
+                let result = (lval / rval).floor();
+                return Ok(Rc::new(Object::Float(result)));
+            }
+            _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    let mut result = &lval / &rval;
+                    if (&lval % &rval).sign() != Sign::NoSign && (lval.sign() != rval.sign()) {
+                        result -= 1;
+                    }
+                    return Ok(bigint::from_bigint(result));
+                }
+
+                let floats = bigint::float_operands(left, right);
+                if floats.is_some() {
+                    let (lval, rval) = floats.unwrap();
+                    return Arithmetic::floor_div(&lval, &rval);
+                }
+
+                let l_type = left.get_type();
+                let r_type = right.get_type();
+
+                return Err(ISAError::new(
+                    format!(
+                        "Operation FloorDiv is not applicable between {} {}",
+                        l_type, r_type
+                    ),
+                    ISAErrorKind::TypeError,
+                ));
+            }
+        }
+    }
 }
 
 impl Bitwise {
@@ -277,6 +584,12 @@ impl Bitwise {
                 return Ok(Rc::new(Object::Int(result)));
             }
             _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    return Ok(bigint::from_bigint(lval & rval));
+                }
+
                 let l_type = left.get_type();
                 let r_type = right.get_type();
                 return Err(ISAError::new(
@@ -298,6 +611,12 @@ impl Bitwise {
                 return Ok(Rc::new(Object::Int(result)));
             }
             _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    return Ok(bigint::from_bigint(lval | rval));
+                }
+
                 let l_type = left.get_type();
                 let r_type = right.get_type();
                 return Err(ISAError::new(
@@ -311,6 +630,170 @@ impl Bitwise {
         }
     }
 
+    #[inline(always)]
+    pub fn xor(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
+        match (left.as_ref(), right.as_ref()) {
+            (Object::Int(lval), Object::Int(rval)) => {
+                let result = lval ^ rval;
+                return Ok(Rc::new(Object::Int(result)));
+            }
+            (Object::Byte(lval), Object::Byte(rval)) => {
+                let result = lval ^ rval;
+                return Ok(Rc::new(Object::Byte(result)));
+            }
+            _ => {
+                let big = bigint::big_operands(left, right);
+                if big.is_some() {
+                    let (lval, rval) = big.unwrap();
+                    return Ok(bigint::from_bigint(lval ^ rval));
+                }
+
+                let l_type = left.get_type();
+                let r_type = right.get_type();
+                return Err(ISAError::new(
+                    format!(
+                        "Operation Xor is not applicable between {} and {}",
+                        l_type, r_type
+                    ),
+                    ISAErrorKind::TypeError,
+                ));
+            }
+        }
+    }
+
+    // the number of bits to shift by, an int or a byte:
+    fn shift_count(
+        left: &Rc<Object>,
+        right: &Rc<Object>,
+        operation: &str,
+    ) -> Result<u32, ISAError> {
+        let count = match (left.as_ref(), right.as_ref()) {
+            (Object::Int(_), Object::Int(count))
+            | (Object::BigInt(_), Object::Int(count))
+            | (Object::Byte(_), Object::Int(count)) => *count,
+            (Object::Int(_), Object::Byte(count))
+            | (Object::BigInt(_), Object::Byte(count))
+            | (Object::Byte(_), Object::Byte(count)) => *count as i64,
+            // a big count is out of the i64 range, it only keeps its sign:
+            (Object::Int(_), Object::BigInt(count))
+            | (Object::BigInt(_), Object::BigInt(count))
+            | (Object::Byte(_), Object::BigInt(count)) => {
+                if count.sign() == Sign::Minus {
+                    i64::MIN
+                } else {
+                    i64::MAX
+                }
+            }
+            _ => {
+                let l_type = left.get_type();
+                let r_type = right.get_type();
+                return Err(ISAError::new(
+                    format!(
+                        "Operation {} is not applicable between {} and {}",
+                        operation, l_type, r_type
+                    ),
+                    ISAErrorKind::TypeError,
+                ));
+            }
+        };
+
+        if count < 0 {
+            return Err(ISAError::new(
+                format!("Negative shift count {}", count),
+                ISAErrorKind::InvalidOperation,
+            ));
+        }
+
+        return Ok(count.min(u32::MAX as i64) as u32);
+    }
+
+    // bits shifted out of the value are an overflow:
+    #[inline(always)]
+    pub fn shl(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
+        let count_res = Bitwise::shift_count(left, right, "ShiftLeft");
+        if count_res.is_err() {
+            return Err(count_res.unwrap_err());
+        }
+
+        let count = count_res.unwrap();
+        let bits = match left.as_ref() {
+            Object::Int(lval) => BigInt::from(*lval).bits(),
+            Object::BigInt(lval) => lval.bits(),
+            _ => 0,
+        };
+
+        // zero stays zero, however far it is shifted:
+        if bits > 0 {
+            let error = Arithmetic::check_int_bits((bits + count as u64) as f64, "shift left");
+            if error.is_some() {
+                return Err(error.unwrap());
+            }
+        }
+
+        let result = match left.as_ref() {
+            Object::Int(lval) => {
+                let shifted = (*lval)
+                    .checked_shl(count)
+                    .filter(|shifted| shifted >> count == *lval);
+                if shifted.is_none() && *lval != 0 {
+                    return Ok(bigint::from_bigint(BigInt::from(*lval) << count));
+                }
+                shifted.map(Object::Int)
+            }
+            Object::BigInt(lval) => return Ok(bigint::from_bigint(lval << count)),
+            Object::Byte(lval) => (*lval)
+                .checked_shl(count)
+                .filter(|shifted| shifted >> count == *lval)
+                .map(Object::Byte),
+            _ => None,
+        };
+
+        // shifting zero never overflows:
+        let is_zero = match left.as_ref() {
+            Object::Int(lval) => *lval == 0,
+            Object::Byte(lval) => *lval == 0,
+            _ => false,
+        };
+
+        if result.is_none() && is_zero {
+            return Ok(left.clone());
+        }
+
+        if result.is_none() {
+            return Err(Arithmetic::new_overflow_err(
+                left,
+                right,
+                "shift left".to_string(),
+            ));
+        }
+        return Ok(Rc::new(result.unwrap()));
+    }
+
+    // shifts keep the sign of ints, shifting by 64 or more leaves 0 or -1:
+    #[inline(always)]
+    pub fn shr(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
+        let count_res = Bitwise::shift_count(left, right, "ShiftRight");
+        if count_res.is_err() {
+            return Err(count_res.unwrap_err());
+        }
+
+        let count = count_res.unwrap();
+        match left.as_ref() {
+            Object::Int(lval) => {
+                let result = *lval >> count.min(63);
+                return Ok(Rc::new(Object::Int(result)));
+            }
+            Object::BigInt(lval) => {
+                return Ok(bigint::from_bigint(lval >> count));
+            }
+            Object::Byte(lval) => {
+                let result = (*lval).checked_shr(count).unwrap_or(0);
+                return Ok(Rc::new(Object::Byte(result)));
+            }
+            _ => return Ok(left.clone()),
+        }
+    }
+
     #[inline(always)]
     pub fn not(obj: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
         match obj.as_ref() {
@@ -318,6 +801,9 @@ impl Bitwise {
                 let result = !val;
                 return Ok(Rc::new(Object::Int(result)));
             }
+            Object::BigInt(val) => {
+                return Ok(bigint::from_bigint(!val));
+            }
             _ => {
                 let val_type = obj.get_type();
                 return Err(ISAError::new(
@@ -354,6 +840,216 @@ impl Logical {
 pub struct Comparision {}
 
 impl Comparision {
+    /*
+        Equality of values: numbers are equal to numbers of other types with
+        the same value, arrays, hash tables, buffers and instances are equal
+        when their contents are, values of unrelated types are never equal.
+    */
+    pub fn equals(left: &Object, right: &Object) -> bool {
+        return Comparision::equals_visiting(left, right, &mut HashSet::new());
+    }
+
+    /*
+        visiting holds the addresses of the pairs of containers being compared,
+        meeting a pair again means both values lead back to themselves the same
+        way, they are equal if everything else is, so the cycle is not followed.
+    */
+    fn enter_pair<T>(left: &T, right: &T, visiting: &mut HashSet<(usize, usize)>) -> bool {
+        let pair = (left as *const T as usize, right as *const T as usize);
+        return visiting.insert(pair);
+    }
+
+    fn leave_pair<T>(left: &T, right: &T, visiting: &mut HashSet<(usize, usize)>) {
+        let pair = (left as *const T as usize, right as *const T as usize);
+        visiting.remove(&pair);
+    }
+
+    fn equals_visiting(
+        left: &Object,
+        right: &Object,
+        visiting: &mut HashSet<(usize, usize)>,
+    ) -> bool {
+        match (left, right) {
+            (Object::Array(larr), Object::Array(rarr)) => {
+                if ptr::eq(larr, rarr) {
+                    return true;
+                }
+
+                if !Comparision::enter_pair(larr, rarr, visiting) {
+                    return true;
+                }
+
+                let result = {
+                    let (lval, rval) = (larr.borrow(), rarr.borrow());
+                    lval.elements.len() == rval.elements.len()
+                        && lval
+                            .elements
+                            .iter()
+                            .zip(rval.elements.iter())
+                            .all(|(l, r)| Comparision::equals_visiting(l, r, visiting))
+                };
+
+                Comparision::leave_pair(larr, rarr, visiting);
+                return result;
+            }
+            (Object::HashTable(lht), Object::HashTable(rht)) => {
+                if ptr::eq(lht, rht) {
+                    return true;
+                }
+
+                if !Comparision::enter_pair(lht, rht, visiting) {
+                    return true;
+                }
+
+                // the order of insertion does not matter:
+                let result = {
+                    let (lval, rval) = (lht.borrow(), rht.borrow());
+                    lval.length() == rval.length()
+                        && lval.items().iter().all(|(key, value)| {
+                            let other = rval.get_ref(key);
+                            return other.is_ok()
+                                && Comparision::equals_visiting(value, other.unwrap(), visiting);
+                        })
+                };
+
+                Comparision::leave_pair(lht, rht, visiting);
+                return result;
+            }
+            (Object::ByteBuffer(lbuf), Object::ByteBuffer(rbuf)) => {
+                return ptr::eq(lbuf, rbuf) || lbuf.borrow().data == rbuf.borrow().data;
+            }
+            (Object::Instance(linst), Object::Instance(rinst)) => {
+                if ptr::eq(linst, rinst) {
+                    return true;
+                }
+
+                if !Comparision::enter_pair(linst, rinst, visiting) {
+                    return true;
+                }
+
+                let result = {
+                    let (lval, rval) = (linst.borrow(), rinst.borrow());
+                    lval.class == rval.class
+                        && lval
+                            .values
+                            .iter()
+                            .zip(rval.values.iter())
+                            .all(|(l, r)| Comparision::equals_visiting(l, r, visiting))
+                };
+
+                Comparision::leave_pair(linst, rinst, visiting);
+                return result;
+            }
+            _ => {}
+        }
+
+        let ordering = Comparision::compare_numbers(left, right);
+        if ordering.is_some() {
+            return ordering.unwrap() == Ordering::Equal;
+        }
+
+        return left == right;
+    }
+
+    /*
+        Ordering of values: numbers by value, chars, strings and bools by
+        themselves, arrays and buffers lexicographically. None for values
+        that cannot be ordered, like values of unrelated types or nan.
+    */
+    pub fn order(left: &Object, right: &Object) -> Option<Ordering> {
+        return Comparision::order_visiting(left, right, &mut HashSet::new());
+    }
+
+    // arrays met again while they are being ordered are taken as equal, see equals_visiting().
+    fn order_visiting(
+        left: &Object,
+        right: &Object,
+        visiting: &mut HashSet<(usize, usize)>,
+    ) -> Option<Ordering> {
+        match (left, right) {
+            (Object::Char(lval), Object::Char(rval)) => return lval.partial_cmp(rval),
+            (Object::Str(lval), Object::Str(rval)) => return lval.partial_cmp(rval),
+            (Object::Bool(lval), Object::Bool(rval)) => return lval.partial_cmp(rval),
+            (Object::ByteBuffer(lbuf), Object::ByteBuffer(rbuf)) => {
+                if ptr::eq(lbuf, rbuf) {
+                    return Some(Ordering::Equal);
+                }
+                return lbuf.borrow().data.partial_cmp(&rbuf.borrow().data);
+            }
+            (Object::Array(larr), Object::Array(rarr)) => {
+                if ptr::eq(larr, rarr) {
+                    return Some(Ordering::Equal);
+                }
+
+                if !Comparision::enter_pair(larr, rarr, visiting) {
+                    return Some(Ordering::Equal);
+                }
+
+                let mut result = None;
+                {
+                    let (lval, rval) = (larr.borrow(), rarr.borrow());
+                    for (l, r) in lval.elements.iter().zip(rval.elements.iter()) {
+                        let ordering = Comparision::order_visiting(l, r, visiting);
+                        if ordering != Some(Ordering::Equal) {
+                            result = Some(ordering);
+                            break;
+                        }
+                    }
+
+                    // a prefix goes first:
+                    if result.is_none() {
+                        result = Some(lval.elements.len().partial_cmp(&rval.elements.len()));
+                    }
+                }
+
+                Comparision::leave_pair(larr, rarr, visiting);
+                return result.unwrap();
+            }
+            _ => return Comparision::compare_numbers(left, right),
+        }
+    }
+
+    fn is_number(obj: &Object) -> bool {
+        matches!(
+            obj,
+            Object::Int(_) | Object::BigInt(_) | Object::Float(_) | Object::Byte(_)
+        )
+    }
+
+    // ints, big integers, bytes and floats compared by value:
+    fn compare_numbers(left: &Object, right: &Object) -> Option<Ordering> {
+        match (left, right) {
+            (Object::Int(lval), Object::Int(rval)) => return lval.partial_cmp(rval),
+            (Object::Float(lval), Object::Float(rval)) => return lval.partial_cmp(rval),
+            (Object::Byte(lval), Object::Byte(rval)) => return lval.partial_cmp(rval),
+            _ => {}
+        }
+
+        if !Comparision::is_number(left) || !Comparision::is_number(right) {
+            return None;
+        }
+
+        let as_float = |obj: &Object| match obj {
+            Object::Float(val) => *val,
+            Object::Int(val) => *val as f64,
+            Object::Byte(val) => *val as f64,
+            Object::BigInt(val) => bigint::to_f64(val),
+            _ => f64::NAN,
+        };
+
+        let as_integer = |obj: &Object| match obj {
+            Object::Byte(val) => BigInt::from(*val),
+            _ => bigint::as_bigint(obj).unwrap(),
+        };
+
+        match (left, right) {
+            (Object::Float(_), _) | (_, Object::Float(_)) => {
+                return as_float(left).partial_cmp(&as_float(right));
+            }
+            _ => return as_integer(left).partial_cmp(&as_integer(right)),
+        }
+    }
+
     #[inline(always)]
     pub fn gt(left: &Rc<Object>, right: &Rc<Object>) -> Result<Rc<Object>, ISAError> {
         match (left.as_ref(), right.as_ref()) {
@@ -378,6 +1074,17 @@ impl Comparision {
                 return Ok(Rc::new(Object::Bool(result)));
             }
             _ => {
+                let ordering = Comparision::order(left, right);
+                if ordering.is_some() {
+                    let result = ordering.unwrap() == Ordering::Greater;
+                    return Ok(Rc::new(Object::Bool(result)));
+                }
+
+                // numbers that cannot be ordered, like nan:
+                if Comparision::is_number(left) && Comparision::is_number(right) {
+                    return Ok(Rc::new(Object::Bool(false)));
+                }
+
                 let l_type = left.get_type();
                 let r_type = right.get_type();
 
@@ -416,6 +1123,17 @@ impl Comparision {
                 return Ok(Rc::new(Object::Bool(result)));
             }
             _ => {
+                let ordering = Comparision::order(left, right);
+                if ordering.is_some() {
+                    let result = ordering.unwrap() != Ordering::Less;
+                    return Ok(Rc::new(Object::Bool(result)));
+                }
+
+                // numbers that cannot be ordered, like nan:
+                if Comparision::is_number(left) && Comparision::is_number(right) {
+                    return Ok(Rc::new(Object::Bool(false)));
+                }
+
                 let l_type = left.get_type();
                 let r_type = right.get_type();
 
@@ -454,6 +1172,17 @@ impl Comparision {
                 return Ok(Rc::new(Object::Bool(result)));
             }
             _ => {
+                let ordering = Comparision::order(left, right);
+                if ordering.is_some() {
+                    let result = ordering.unwrap() == Ordering::Less;
+                    return Ok(Rc::new(Object::Bool(result)));
+                }
+
+                // numbers that cannot be ordered, like nan:
+                if Comparision::is_number(left) && Comparision::is_number(right) {
+                    return Ok(Rc::new(Object::Bool(false)));
+                }
+
                 let l_type = left.get_type();
                 let r_type = right.get_type();
 
@@ -492,6 +1221,17 @@ impl Comparision {
                 return Ok(Rc::new(Object::Bool(result)));
             }
             _ => {
+                let ordering = Comparision::order(left, right);
+                if ordering.is_some() {
+                    let result = ordering.unwrap() != Ordering::Greater;
+                    return Ok(Rc::new(Object::Bool(result)));
+                }
+
+                // numbers that cannot be ordered, like nan:
+                if Comparision::is_number(left) && Comparision::is_number(right) {
+                    return Ok(Rc::new(Object::Bool(false)));
+                }
+
                 let l_type = left.get_type();
                 let r_type = right.get_type();
 
@@ -533,17 +1273,17 @@ impl Comparision {
                 let result = (*lval as f64) == *rval;
                 return Ok(Rc::new(Object::Bool(result)));
             }
+            // none is only equal to none, this lets values like the
+            // result of recv() on a closed channel be checked.
+            (Object::Noval, _) | (_, Object::Noval) => {
+                let result =
+                    (*left.as_ref() == Object::Noval) == (*right.as_ref() == Object::Noval);
+                return Ok(Rc::new(Object::Bool(result)));
+            }
             _ => {
-                let l_type = left.get_type();
-                let r_type = right.get_type();
-
-                return Err(ISAError::new(
-                    format!(
-                        "Comparision operation Equals is not applicable between {} {}",
-                        l_type, r_type
-                    ),
-                    ISAErrorKind::TypeError,
-                ));
+                // values of different types are never equal:
+                let result = Comparision::equals(left, right);
+                return Ok(Rc::new(Object::Bool(result)));
             }
         }
     }
@@ -575,17 +1315,15 @@ impl Comparision {
                 let result = (*lval as f64) != *rval;
                 return Ok(Rc::new(Object::Bool(result)));
             }
+            (Object::Noval, _) | (_, Object::Noval) => {
+                let result =
+                    (*left.as_ref() == Object::Noval) != (*right.as_ref() == Object::Noval);
+                return Ok(Rc::new(Object::Bool(result)));
+            }
             _ => {
-                let l_type = left.get_type();
-                let r_type = right.get_type();
-
-                return Err(ISAError::new(
-                    format!(
-                        "Comparision operation Not Equals is not applicable between {} {}",
-                        l_type, r_type
-                    ),
-                    ISAErrorKind::TypeError,
-                ));
+                // values of different types are never equal:
+                let result = !Comparision::equals(left, right);
+                return Ok(Rc::new(Object::Bool(result)));
             }
         }
     }
@@ -4,21 +4,29 @@ use crate::config;
 use crate::isa;
 use crate::types::array;
 use crate::types::builtins;
+use crate::types::class;
+use crate::types::closure;
+use crate::types::exception;
 use crate::types::hash;
 use crate::types::iter;
+use crate::types::module;
 use crate::types::object;
+use crate::types::slice;
+use crate::types::string;
 use crate::types::th;
 use crate::vm::alu;
 use crate::vm::errors;
 use crate::vm::ffi::BosonFFI;
 use crate::vm::frames;
 use crate::vm::global;
+use crate::vm::jit;
 use crate::vm::stack;
 use crate::vm::thread;
 
 use std::cell::Ref;
 use std::cell::RefCell;
 use std::cell::RefMut;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::rc::Rc;
 
@@ -29,18 +37,26 @@ use alu::Logical;
 use api::Platform;
 use array::Array;
 use builtins::BuiltinKind;
+use class::ClassType;
+use class::Instance;
+use closure::MemoTable;
 use config::ENABLE_CONCURRENCY;
 use errors::ISAError;
 use errors::ISAErrorKind;
+use errors::TraceEntry;
 use errors::VMError;
 use errors::VMErrorKind;
+use exception::Exception;
 use frames::ExecutionFrame;
 use global::GlobalPool;
 use hash::HashTable;
 use isa::InstructionKind;
 use iter::ObjectIterator;
+use jit::BosonJIT;
+use module::Module;
 use object::AttributeResolver;
 use object::Object;
+use stack::CallStack;
 use stack::DataStack;
 use th::ThreadBlock;
 
@@ -107,9 +123,100 @@ impl Controls {
         }
 
         let free_object = free_obj_result.unwrap();
-        // push it to the stack:
-        let push_result = ds.push_object(free_object, InstructionKind::ILoadFree);
+        // push the value held by the cell:
+        let value = match free_object.as_ref() {
+            Object::Cell(cell) => cell.borrow().clone(),
+            _ => free_object.clone(),
+        };
+
+        let push_result = ds.push_object(value, InstructionKind::ILoadFree);
+        if push_result.is_err() {
+            return Some(push_result.unwrap_err());
+        }
+
+        return None;
+    }
+
+    pub fn store_free(
+        ds: &mut DataStack,
+        frame: &mut RefMut<ExecutionFrame>,
+        idx: usize,
+    ) -> Option<VMError> {
+        let free_obj_result = frame.get_free(idx, InstructionKind::IStoreFree);
+        if free_obj_result.is_err() {
+            return Some(free_obj_result.unwrap_err());
+        }
+
+        let popped_result = ds.pop_object(InstructionKind::IStoreFree);
+        if popped_result.is_err() {
+            return Some(popped_result.unwrap_err());
+        }
+
+        match free_obj_result.unwrap().as_ref() {
+            Object::Cell(cell) => {
+                *cell.borrow_mut() = popped_result.unwrap();
+            }
+            _ => {
+                return Some(VMError::new(
+                    format!("Free variable {} is not a captured variable", idx),
+                    VMErrorKind::UnknownFreeVariable,
+                    Some(InstructionKind::IStoreFree),
+                    0,
+                ));
+            }
+        }
+
+        return None;
+    }
+
+    pub fn capture_free(
+        ds: &mut DataStack,
+        frame: &mut RefMut<ExecutionFrame>,
+        idx: usize,
+    ) -> Option<VMError> {
+        let free_obj_result = frame.get_free(idx, InstructionKind::ICaptureFree);
+        if free_obj_result.is_err() {
+            return Some(free_obj_result.unwrap_err());
+        }
+
+        // the cell itself is passed on to the inner closure:
+        let push_result = ds.push_object(free_obj_result.unwrap(), InstructionKind::ICaptureFree);
+        if push_result.is_err() {
+            return Some(push_result.unwrap_err());
+        }
+
+        return None;
+    }
+
+    pub fn capture_local(
+        ds: &mut DataStack,
+        pos: usize,
+        f: &RefMut<ExecutionFrame>,
+    ) -> Option<VMError> {
+        let bp = f.get_bp();
+        let local_object_res = ds.stack.get(bp + pos);
+        if local_object_res.is_none() {
+            return Some(VMError::new(
+                "Stack overflow!".to_string(),
+                VMErrorKind::DataStackOverflow,
+                Some(InstructionKind::ICaptureLocal),
+                0,
+            ));
+        }
+
+        // the local is moved into a cell the first time it is captured,
+        // loads and stores of the frame go through the cell from then on:
+        let local_object = local_object_res.unwrap().clone();
+        let cell = match local_object.as_ref() {
+            Object::Cell(_) => local_object,
+            _ => {
+                let cell = Rc::new(Object::Cell(RefCell::new(local_object)));
+                ds.stack[bp + pos] = cell.clone();
+                cell
+            }
+        };
 
+        let push_result = ds.push_object(cell, InstructionKind::ICaptureLocal);
         if push_result.is_err() {
             return Some(push_result.unwrap_err());
         }
@@ -144,6 +251,11 @@ impl Controls {
         }
 
         let bp = f.get_bp();
+        if let Object::Cell(cell) = ds.stack[bp + pos].as_ref() {
+            *cell.borrow_mut() = popped_result.unwrap();
+            return Ok((bp + pos) as i64);
+        }
+
         ds.stack[bp + pos] = popped_result.unwrap();
 
         return Ok((bp + pos) as i64);
@@ -165,7 +277,10 @@ impl Controls {
             ));
         }
 
-        let local_object = local_object_res.unwrap().clone();
+        let local_object = match local_object_res.unwrap().as_ref() {
+            Object::Cell(cell) => cell.borrow().clone(),
+            _ => local_object_res.unwrap().clone(),
+        };
         // push the object to stack:
         let push_result = ds.push_object(local_object, InstructionKind::ILoadLocal);
         if push_result.is_err() {
@@ -195,7 +310,13 @@ impl Controls {
         ds: &mut DataStack,
         pos: usize,
     ) -> Result<i64, VMError> {
-        let element = cp.get_object(pos).unwrap();
+        let mut element = cp.get_object(pos).unwrap();
+        // buffers can be changed, every b"..." gets its own copy:
+        if let Object::ByteBuffer(buffer) = element.as_ref() {
+            let copy = buffer.borrow().clone();
+            element = Rc::new(Object::ByteBuffer(RefCell::new(copy)));
+        }
+
         let result = ds.push_object(element, InstructionKind::IConstant);
         return result;
     }
@@ -234,6 +355,11 @@ impl Controls {
             InstructionKind::IMod => Arithmetic::modulus(&left, &right),
             InstructionKind::IAnd => Bitwise::and(&left, &right),
             InstructionKind::IOr => Bitwise::or(&left, &right),
+            InstructionKind::IXor => Bitwise::xor(&left, &right),
+            InstructionKind::IShl => Bitwise::shl(&left, &right),
+            InstructionKind::IShr => Bitwise::shr(&left, &right),
+            InstructionKind::IPow => Arithmetic::pow(&left, &right),
+            InstructionKind::IFloorDiv => Arithmetic::floor_div(&left, &right),
             InstructionKind::ILOr => Logical::or(&left, &right),
             InstructionKind::ILAnd => Logical::and(&left, &right),
             InstructionKind::ILGt => Comparision::gt(&left, &right),
@@ -356,6 +482,12 @@ impl Controls {
             returned_obj = returned_obj_res.unwrap();
         }
 
+        // remember the result of a pure function call:
+        if frame.memo_key.is_some() && frame.context.memo.is_some() {
+            let memo = frame.context.memo.as_ref().unwrap();
+            memo.insert(frame.memo_key.clone().unwrap(), &returned_obj);
+        }
+
         let local_boundary = frame.get_bp();
         // clear off the stack till this point:
 
@@ -379,6 +511,7 @@ impl Controls {
         platform: &mut Platform,
         threads: &mut thread::BosonThreads,
         ffi: &mut BosonFFI,
+        jit: &mut BosonJIT,
     ) -> Result<Option<RefCell<ExecutionFrame>>, VMError> {
         // pop the function:
 
@@ -399,14 +532,13 @@ impl Controls {
                 let mut args = popped_args.unwrap();
                 args.reverse();
                 // call the builtin:
-                let exec_result = func.exec(args, platform, global_pool, constants, threads, ffi);
+                let exec_result = func.call(args, platform, global_pool, constants, threads, ffi);
                 if exec_result.is_err() {
-                    return Err(VMError::new(
-                        exec_result.unwrap_err(),
-                        VMErrorKind::BuiltinFunctionError,
-                        Some(inst.clone()),
-                        0,
-                    ));
+                    let mut error = exec_result.unwrap_err();
+                    if error.instruction.is_none() {
+                        error.instruction = Some(inst.clone());
+                    }
+                    return Err(error);
                 }
 
                 let result_obj = exec_result.unwrap();
@@ -421,26 +553,116 @@ impl Controls {
                 let closure = ctx.as_ref();
                 let subroutine = closure.compiled_fn.as_ref();
 
-                if subroutine.num_parameters != n_args {
-                    return Err(VMError::new(
-                        format!(
-                            "Function {} expects {} arguments, given {}",
-                            subroutine.name, subroutine.num_parameters, n_args
-                        ),
-                        VMErrorKind::FunctionArgumentsError,
-                        Some(InstructionKind::ICall),
-                        0,
-                    ));
+                // missing arguments take their defaults and the extra ones are
+                // collected for the variadic parameter, the frame gets a value
+                // per parameter:
+                let mut n_args = n_args;
+                if n_args != subroutine.num_parameters || subroutine.is_variadic {
+                    let popped_args = Controls::pop_n(ds, n_args, inst);
+                    if popped_args.is_err() {
+                        return Err(popped_args.unwrap_err());
+                    }
+
+                    let mut args = popped_args.unwrap();
+                    args.reverse();
+
+                    let bind_result = closure.bind_arguments(args);
+                    if bind_result.is_err() {
+                        return Err(VMError::new(
+                            bind_result.unwrap_err(),
+                            VMErrorKind::FunctionArgumentsError,
+                            Some(InstructionKind::ICall),
+                            0,
+                        ));
+                    }
+
+                    n_args = subroutine.num_parameters;
+                    let push_res = ds.push_objects(inst.clone(), bind_result.unwrap());
+                    if push_res.is_err() {
+                        return Err(push_res.unwrap_err());
+                    }
                 }
 
-                let frame_bp = if ds.stack_pointer <= 0 {
-                    0
-                } else {
-                    ds.stack.len() - n_args
-                };
+                // calling a generator function returns an iterator, the body runs
+                // as the iterator is stepped:
+                if subroutine.is_generator {
+                    let popped_args = Controls::pop_n(ds, n_args, inst);
+                    if popped_args.is_err() {
+                        return Err(popped_args.unwrap_err());
+                    }
+
+                    let mut args = popped_args.unwrap();
+                    args.reverse();
+
+                    let generator = ObjectIterator::new_generator(ctx.clone(), args);
+                    let push_res = ds
+                        .push_object(Rc::new(Object::Iter(RefCell::new(generator))), inst.clone());
+                    if push_res.is_err() {
+                        return Err(push_res.unwrap_err());
+                    }
+
+                    return Ok(None);
+                }
+
+                // pure functions are called once for the same arguments:
+                let mut memo_key = None;
+                if closure.memo.is_some() && ds.stack.len() >= n_args {
+                    memo_key = MemoTable::key(&ds.stack[ds.stack.len() - n_args..]);
+                }
+
+                if memo_key.is_some() {
+                    let memo = closure.memo.as_ref().unwrap();
+                    let cached = memo.get(memo_key.as_ref().unwrap());
+                    if cached.is_some() {
+                        let popped_args = Controls::pop_n(ds, n_args, inst);
+                        if popped_args.is_err() {
+                            return Err(popped_args.unwrap_err());
+                        }
+
+                        let push_res = ds.push_object(cached.unwrap(), inst.clone());
+                        if push_res.is_err() {
+                            return Err(push_res.unwrap_err());
+                        }
+
+                        return Ok(None);
+                    }
+                }
+
+                // hot numeric subroutines are executed as native code:
+                if jit.is_enabled() && ds.stack.len() >= n_args {
+                    let args = &ds.stack[ds.stack.len() - n_args..];
+                    let jit_result = jit.execute(&closure.compiled_fn, args, constants);
+                    if jit_result.is_some() {
+                        let popped_args = Controls::pop_n(ds, n_args, inst);
+                        if popped_args.is_err() {
+                            return Err(popped_args.unwrap_err());
+                        }
+
+                        let result = jit_result.unwrap();
+                        if memo_key.is_some() {
+                            closure
+                                .memo
+                                .as_ref()
+                                .unwrap()
+                                .insert(memo_key.unwrap(), &result);
+                        }
+
+                        let push_res = ds.push_object(result, inst.clone());
+                        if push_res.is_err() {
+                            return Err(push_res.unwrap_err());
+                        }
+
+                        return Ok(None);
+                    }
+                }
+
+                // the arguments are on top of the stack, whatever the caller
+                // left below them is kept when the frame returns:
+                let frame_bp = ds.stack.len() - n_args;
 
                 // allocate the stack for local variables and frame:
-                let new_frame = ExecutionFrame::new(Rc::new(closure.clone()), frame_bp);
+                let mut new_frame = ExecutionFrame::new(Rc::new(closure.clone()), frame_bp);
+                new_frame.memo_key = memo_key;
 
                 let n_locals = closure.compiled_fn.num_locals;
                 let n_params = closure.compiled_fn.num_parameters;
@@ -453,10 +675,78 @@ impl Controls {
                     return Err(push_res.unwrap_err());
                 }
 
-                // set the new stack pointer:
-                ds.stack_pointer = (new_frame.base_pointer + n_locals) as i64;
                 return Ok(Some(RefCell::new(new_frame)));
             }
+            Object::Class(class) => {
+                let popped_args = Controls::pop_n(ds, n_args, inst);
+                if popped_args.is_err() {
+                    return Err(popped_args.unwrap_err());
+                }
+
+                let mut args = popped_args.unwrap();
+                args.reverse();
+
+                let mut instance = Instance::new(class.clone());
+                let init = class.get_method(&String::from("init"));
+
+                if init.is_none() {
+                    // without init the arguments are the fields, in order:
+                    if args.len() != class.fields.len() {
+                        return Err(VMError::new(
+                            format!(
+                                "{} expects {} arguments, given {}",
+                                class.name,
+                                class.fields.len(),
+                                args.len()
+                            ),
+                            VMErrorKind::FunctionArgumentsError,
+                            Some(inst.clone()),
+                            0,
+                        ));
+                    }
+
+                    instance.values = args;
+                    let push_res = ds.push_object(
+                        Rc::new(Object::Instance(RefCell::new(instance))),
+                        inst.clone(),
+                    );
+                    if push_res.is_err() {
+                        return Err(push_res.unwrap_err());
+                    }
+
+                    return Ok(None);
+                }
+
+                let error = class.check_arguments(&String::from("init"), n_args);
+                if error.is_some() {
+                    return Err(VMError::new(
+                        error.unwrap(),
+                        VMErrorKind::FunctionArgumentsError,
+                        Some(inst.clone()),
+                        0,
+                    ));
+                }
+
+                // call init with the new instance as the first argument:
+                args.insert(0, Rc::new(Object::Instance(RefCell::new(instance))));
+                args.push(init.unwrap());
+                let push_res = ds.push_objects(inst.clone(), args);
+                if push_res.is_err() {
+                    return Err(push_res.unwrap_err());
+                }
+
+                return Controls::execute_call(
+                    inst,
+                    ds,
+                    n_args + 1,
+                    global_pool,
+                    constants,
+                    platform,
+                    threads,
+                    ffi,
+                    jit,
+                );
+            }
             _ => {
                 return Err(VMError::new(
                     format!("Cannot call {}", popped_obj.as_ref().describe()),
@@ -541,7 +831,7 @@ impl Controls {
             return Err(popped_res.unwrap_err());
         }
 
-        let mut hash_table = HashMap::new();
+        let mut ht = HashTable::new("todo".to_string());
         let mut popped = popped_res.unwrap();
         popped.reverse();
 
@@ -551,14 +841,9 @@ impl Controls {
             idx += 1;
             let value = popped[idx].clone();
             idx += 1;
-            hash_table.insert(key, value);
+            ht.set(key, value);
         }
 
-        let ht = HashTable {
-            name: "todo".to_string(),
-            entries: hash_table,
-        };
-
         let ht_obj = Rc::new(Object::HashTable(RefCell::new(ht)));
         let push_res = ds.push_object(ht_obj, inst.clone());
 
@@ -569,6 +854,101 @@ impl Controls {
         return Ok(push_res.unwrap());
     }
 
+    pub fn build_module(
+        inst: &InstructionKind,
+        ds: &mut DataStack,
+        length: usize,
+    ) -> Result<i64, VMError> {
+        let popped_res = Controls::pop_n(ds, length, inst);
+        if popped_res.is_err() {
+            return Err(popped_res.unwrap_err());
+        }
+
+        let mut entries = HashMap::new();
+        let mut popped = popped_res.unwrap();
+        popped.reverse();
+
+        let mut idx = 0;
+        while idx < length {
+            let key = popped[idx].describe();
+            idx += 1;
+            let value = popped[idx].clone();
+            idx += 1;
+            entries.insert(key, value);
+        }
+
+        // module path and name are pushed before the entries:
+        let info_res = Controls::pop_n(ds, 2, inst);
+        if info_res.is_err() {
+            return Err(info_res.unwrap_err());
+        }
+
+        let info = info_res.unwrap();
+        let module = Module {
+            name: info[1].describe(),
+            path: info[0].describe(),
+            entries: entries,
+        };
+
+        let module_obj = Rc::new(Object::Module(Rc::new(module)));
+        let push_res = ds.push_object(module_obj, inst.clone());
+
+        if push_res.is_err() {
+            return Err(push_res.unwrap_err());
+        }
+
+        return Ok(push_res.unwrap());
+    }
+
+    pub fn build_class(
+        inst: &InstructionKind,
+        ds: &mut DataStack,
+        n_fields: usize,
+        n_method_entries: usize,
+    ) -> Result<i64, VMError> {
+        let methods_res = Controls::pop_n(ds, n_method_entries, inst);
+        if methods_res.is_err() {
+            return Err(methods_res.unwrap_err());
+        }
+
+        let mut methods = HashMap::new();
+        let mut popped = methods_res.unwrap();
+        popped.reverse();
+
+        let mut idx = 0;
+        while idx < n_method_entries {
+            let name = popped[idx].describe();
+            idx += 1;
+            let method = popped[idx].clone();
+            idx += 1;
+            methods.insert(name, method);
+        }
+
+        // the class name is pushed before the field names:
+        let fields_res = Controls::pop_n(ds, n_fields + 1, inst);
+        if fields_res.is_err() {
+            return Err(fields_res.unwrap_err());
+        }
+
+        let mut info = fields_res.unwrap();
+        info.reverse();
+
+        let class = ClassType {
+            name: info[0].describe(),
+            fields: info[1..].iter().map(|field| field.describe()).collect(),
+            methods: methods,
+        };
+
+        let class_obj = Rc::new(Object::Class(Rc::new(class)));
+        let push_res = ds.push_object(class_obj, inst.clone());
+
+        if push_res.is_err() {
+            return Err(push_res.unwrap_err());
+        }
+
+        return Ok(push_res.unwrap());
+    }
+
     pub fn create_closure(
         ds: &mut DataStack,
         constants: &ConstantPool,
@@ -580,8 +960,9 @@ impl Controls {
         if popped_res.is_err() {
             return Some(popped_res.unwrap_err());
         }
-        // get free objects:
-        let free_objects = popped_res.unwrap();
+        // get free objects, in the order they were pushed:
+        let mut free_objects = popped_res.unwrap();
+        free_objects.reverse();
 
         // retrive  the function from constant pool:
         let function_res = constants.get_object(func_idx);
@@ -598,6 +979,18 @@ impl Controls {
 
         match function.as_ref() {
             Object::Subroutine(sub) => {
+                // the default values of the parameters were pushed before the
+                // free objects, the closure keeps them after the free objects:
+                let popped_defaults =
+                    Controls::pop_n(ds, sub.num_defaults, &InstructionKind::IClosure);
+                if popped_defaults.is_err() {
+                    return Some(popped_defaults.unwrap_err());
+                }
+
+                let mut defaults = popped_defaults.unwrap();
+                defaults.reverse();
+                free_objects.extend(defaults);
+
                 // create a closure:
                 let closure_obj = ExecutionFrame::new_closure(sub.clone(), free_objects);
                 // load the closure on data-stack:
@@ -648,7 +1041,8 @@ impl Controls {
 
         let popped_object = popped_res.unwrap();
 
-        let iter_res = ObjectIterator::new(popped_object);
+        // iterating an iterator continues from where it is:
+        let iter_res = ObjectIterator::iter_object(popped_object);
         if iter_res.is_err() {
             return Some(VMError::new(
                 iter_res.unwrap_err(),
@@ -658,8 +1052,7 @@ impl Controls {
             ));
         }
 
-        let iter_object = Rc::new(Object::Iter(RefCell::new(iter_res.unwrap())));
-        let push_res = ds.push_object(iter_object, InstructionKind::IIter);
+        let push_res = ds.push_object(iter_res.unwrap(), InstructionKind::IIter);
         if push_res.is_err() {
             return Some(push_res.unwrap_err());
         }
@@ -672,32 +1065,48 @@ impl Controls {
         jmp_pos: usize,
         frame: &mut RefMut<ExecutionFrame>,
         enumerate: bool,
+        platform: &mut Platform,
+        gp: &mut GlobalPool,
+        c: &mut ConstantPool,
+        th: &mut thread::BosonThreads,
+        ffi: &mut BosonFFI,
     ) -> Result<bool, VMError> {
-        let top_ref_res = ds.get_top_ref(InstructionKind::IIterNext);
-        if top_ref_res.is_err() {
-            return Err(top_ref_res.unwrap_err());
+        // the loop loads its iterator from a variable before every step:
+        let popped_res = ds.pop_object(InstructionKind::IIterNext);
+        if popped_res.is_err() {
+            return Err(popped_res.unwrap_err());
         }
 
-        let top_ref = top_ref_res.unwrap();
+        let popped_iter = popped_res.unwrap();
 
-        match top_ref.as_ref() {
+        match popped_iter.as_ref() {
             Object::Iter(iter) => {
-                let mut iterator = iter.borrow_mut();
+                let iter_res = iter.try_borrow_mut();
+                if iter_res.is_err() {
+                    return Err(VMError::new(
+                        "Generator is already running".to_string(),
+                        VMErrorKind::IterationError,
+                        Some(InstructionKind::IIterNext),
+                        0,
+                    ));
+                }
+
+                let mut iterator = iter_res.unwrap();
 
                 let mut current_pos = 0;
                 if enumerate {
                     current_pos = iterator.get_pos();
                 }
 
-                let obj = iterator.next();
+                // errors raised by a generator are passed on as they are:
+                let next_res = iterator.next(platform, gp, c, th, ffi);
+                if next_res.is_err() {
+                    return Err(next_res.unwrap_err());
+                }
+
+                let obj = next_res.unwrap();
 
                 if obj.is_none() {
-                    // pop the end
-                    drop(iterator);
-                    let popped_result = ds.pop_object(InstructionKind::IIterNext);
-                    if popped_result.is_err() {
-                        return Err(popped_result.unwrap_err());
-                    }
                     let result = Controls::jump(frame, jmp_pos);
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -725,7 +1134,7 @@ impl Controls {
             }
             _ => {
                 return Err(VMError::new(
-                    format!("Cannot iterate over {}", top_ref.get_type()),
+                    format!("Cannot iterate over {}", popped_iter.get_type()),
                     VMErrorKind::IterationError,
                     Some(InstructionKind::IIter),
                     0,
@@ -734,6 +1143,12 @@ impl Controls {
         }
     }
 
+    /*
+        a[i] = v and a.k = v both compile to ISetIndex. Arrays, buffers and
+        hash tables are changed in place, so every variable holding them sees
+        the change, like a.push(v). Other objects, like instances and modules,
+        resolve a string index as an attribute.
+    */
     pub fn set_indexed(ds: &mut DataStack) -> Option<VMError> {
         let pop_result = Controls::pop_n(ds, 3, &InstructionKind::ISetIndex);
 
@@ -746,11 +1161,30 @@ impl Controls {
         let popped_right = popped_objects.get(2).unwrap().clone();
 
         let obj_target = popped_objects.get(1).unwrap();
-        let index_target = popped_objects.get(0).unwrap();
+        let index_target = popped_objects.first().unwrap();
+
+        let is_attribute = match (obj_target.as_ref(), index_target.as_ref()) {
+            (Object::Array(_), _) | (Object::ByteBuffer(_), _) | (Object::HashTable(_), _) => false,
+            (_, Object::Str(_)) => true,
+            _ => false,
+        };
+
+        if is_attribute {
+            let error =
+                obj_target.resolve_set_attr(std::slice::from_ref(index_target), popped_right);
+            if error.is_some() {
+                return Some(VMError::new(
+                    error.unwrap(),
+                    VMErrorKind::AttributeError,
+                    Some(InstructionKind::ISetIndex),
+                    0,
+                ));
+            }
+
+            return None;
+        }
 
-        // call set on the object
-        let mut new_object = obj_target.as_ref().clone();
-        let error = new_object.set_indexed(index_target, popped_right);
+        let error = obj_target.set_indexed(index_target, popped_right);
         if error.is_some() {
             return Some(VMError::new(
                 error.unwrap(),
@@ -760,16 +1194,236 @@ impl Controls {
             ));
         }
 
-        // push the object back to stack:
-        let push_result = ds.push_object(Rc::new(new_object), InstructionKind::ISetIndex);
-        if push_result.is_err() {
-            return Some(push_result.unwrap_err());
-        }
         return None;
     }
 
-    pub fn execute_thread(
-        inst: &InstructionKind,
+    pub fn get_slice(ds: &mut DataStack) -> Option<VMError> {
+        let pop_result = Controls::pop_n(ds, 4, &InstructionKind::ISlice);
+        if pop_result.is_err() {
+            return Some(pop_result.unwrap_err());
+        }
+
+        // popped in the reverse order: step, stop, start, object
+        let popped_objects = pop_result.unwrap();
+        let slice_result = slice::get_sliced(
+            popped_objects[3].as_ref(),
+            &popped_objects[2],
+            &popped_objects[1],
+            &popped_objects[0],
+        );
+
+        if slice_result.is_err() {
+            return Some(VMError::new(
+                slice_result.unwrap_err(),
+                VMErrorKind::IndexError,
+                Some(InstructionKind::ISlice),
+                0,
+            ));
+        }
+
+        let push_result = ds.push_object(slice_result.unwrap(), InstructionKind::ISlice);
+        if push_result.is_err() {
+            return Some(push_result.unwrap_err());
+        }
+
+        return None;
+    }
+
+    pub fn set_slice(ds: &mut DataStack) -> Option<VMError> {
+        let pop_result = Controls::pop_n(ds, 5, &InstructionKind::ISetSlice);
+        if pop_result.is_err() {
+            return Some(pop_result.unwrap_err());
+        }
+
+        // popped in the reverse order: step, stop, start, object, value
+        let popped_objects = pop_result.unwrap();
+
+        // the slice is replaced in place, like set_indexed:
+        let error = slice::set_sliced(
+            &popped_objects[3],
+            &popped_objects[2],
+            &popped_objects[1],
+            &popped_objects[0],
+            popped_objects[4].clone(),
+        );
+
+        if error.is_some() {
+            return Some(VMError::new(
+                error.unwrap(),
+                VMErrorKind::IndexError,
+                Some(InstructionKind::ISetSlice),
+                0,
+            ));
+        }
+
+        return None;
+    }
+
+    pub fn execute_match_test(
+        ds: &mut DataStack,
+        inst: &InstructionKind,
+        operand: usize,
+    ) -> Option<VMError> {
+        let n_popped = match inst {
+            InstructionKind::IMatchRange => 3,
+            InstructionKind::IMatchArray | InstructionKind::IMatchArrayRest => 1,
+            _ => 2,
+        };
+
+        let pop_result = Controls::pop_n(ds, n_popped, inst);
+        if pop_result.is_err() {
+            return Some(pop_result.unwrap_err());
+        }
+
+        // popped in the reverse order, the value under test is the last one:
+        let popped_objects = pop_result.unwrap();
+        let value = popped_objects[n_popped - 1].as_ref();
+
+        let matched = match inst {
+            // values of other types never match:
+            InstructionKind::IMatchValue => Comparision::equals(value, popped_objects[0].as_ref()),
+            InstructionKind::IMatchRange => {
+                let lower = Comparision::order(value, popped_objects[1].as_ref());
+                let upper = Comparision::order(value, popped_objects[0].as_ref());
+                let below_end = if operand == 1 {
+                    upper == Some(Ordering::Less) || upper == Some(Ordering::Equal)
+                } else {
+                    upper == Some(Ordering::Less)
+                };
+
+                below_end && (lower == Some(Ordering::Greater) || lower == Some(Ordering::Equal))
+            }
+            InstructionKind::IMatchType => match popped_objects[0].as_ref() {
+                Object::Str(name) => value.get_type() == *name,
+                _ => false,
+            },
+            InstructionKind::IMatchArray => match value {
+                Object::Array(arr) => arr.borrow().elements.len() == operand,
+                _ => false,
+            },
+            InstructionKind::IMatchArrayRest => match value {
+                Object::Array(arr) => arr.borrow().elements.len() >= operand,
+                _ => false,
+            },
+            InstructionKind::IMatchKey => match value {
+                Object::HashTable(ht) => ht.borrow().contains(&popped_objects[0]),
+                _ => false,
+            },
+            _ => false,
+        };
+
+        let push_result = ds.push_object(Rc::new(Object::Bool(matched)), inst.clone());
+        if push_result.is_err() {
+            return Some(push_result.unwrap_err());
+        }
+
+        return None;
+    }
+
+    /*
+        Destructuring, the value is replaced by its parts pushed in the
+        reverse order, the first part ends up on top and is stored first.
+    */
+    pub fn unpack_array(ds: &mut DataStack, count: usize) -> Option<VMError> {
+        let pop_result = ds.pop_object(InstructionKind::IUnpackArray);
+        if pop_result.is_err() {
+            return Some(pop_result.unwrap_err());
+        }
+
+        let value = pop_result.unwrap();
+        let elements = match value.as_ref() {
+            Object::Array(arr) => arr.borrow().elements.clone(),
+            _ => {
+                return Some(VMError::new(
+                    format!(
+                        "Cannot destructure {} into {} names, expected Array",
+                        value.get_type(),
+                        count
+                    ),
+                    VMErrorKind::TypeError,
+                    Some(InstructionKind::IUnpackArray),
+                    0,
+                ));
+            }
+        };
+
+        if elements.len() != count {
+            return Some(VMError::new(
+                format!(
+                    "Cannot destructure Array of {} elements into {} names",
+                    elements.len(),
+                    count
+                ),
+                VMErrorKind::IndexError,
+                Some(InstructionKind::IUnpackArray),
+                0,
+            ));
+        }
+
+        for element in elements.into_iter().rev() {
+            let push_result = ds.push_object(element, InstructionKind::IUnpackArray);
+            if push_result.is_err() {
+                return Some(push_result.unwrap_err());
+            }
+        }
+
+        return None;
+    }
+
+    pub fn unpack_hash(ds: &mut DataStack, count: usize) -> Option<VMError> {
+        let pop_result = Controls::pop_n(ds, count + 1, &InstructionKind::IUnpackHash);
+        if pop_result.is_err() {
+            return Some(pop_result.unwrap_err());
+        }
+
+        // popped in the reverse order: the keys from the last one, then the value.
+        let popped_objects = pop_result.unwrap();
+        let value = popped_objects[count].as_ref();
+
+        let mut parts = vec![];
+        for key in popped_objects[0..count].iter() {
+            let part = match value {
+                Object::HashTable(ht) => match ht.borrow().get(key) {
+                    Ok(value) => Ok(value),
+                    Err(_) => Err(format!(
+                        "Cannot destructure HashTable, key {} not found",
+                        key.describe()
+                    )),
+                },
+                Object::Instance(instance) => match key.as_ref() {
+                    Object::Str(name) => instance.borrow().get_field(name),
+                    _ => Err(format!("Expected string attribute, got {}", key.get_type())),
+                },
+                _ => Err(format!(
+                    "Cannot destructure {} by keys, expected HashTable or an instance",
+                    value.get_type()
+                )),
+            };
+
+            if part.is_err() {
+                return Some(VMError::new(
+                    part.unwrap_err(),
+                    VMErrorKind::IndexError,
+                    Some(InstructionKind::IUnpackHash),
+                    0,
+                ));
+            }
+
+            parts.push(part.unwrap());
+        }
+
+        for part in parts.into_iter() {
+            let push_result = ds.push_object(part, InstructionKind::IUnpackHash);
+            if push_result.is_err() {
+                return Some(push_result.unwrap_err());
+            }
+        }
+
+        return None;
+    }
+
+    pub fn execute_thread(
+        inst: &InstructionKind,
         ds: &mut DataStack,
         n_args: usize,
         global_pool: &mut GlobalPool,
@@ -798,11 +1452,13 @@ impl Controls {
         match popped_obj.as_ref() {
             Object::ClosureContext(ctx) => {
                 let subroutine = ctx.as_ref().compiled_fn.as_ref();
-                if subroutine.num_parameters != n_args {
+                if !subroutine.accepts_arguments(n_args) {
                     return Some(VMError::new(
                         format!(
                             "Function {} expects {} arguments, given {}",
-                            subroutine.name, subroutine.num_parameters, n_args
+                            subroutine.name,
+                            subroutine.describe_arity(0),
+                            n_args
                         ),
                         VMErrorKind::FunctionArgumentsError,
                         Some(inst.clone()),
@@ -820,14 +1476,18 @@ impl Controls {
                 args.reverse();
 
                 // wrap parameters in a thread-type:
-                let thread_params = thread::ThreadParams::new(
-                    ctx.clone(),
-                    args,
-                    global_pool.clone(),
-                    constants.clone(),
-                );
+                let thread_params =
+                    thread::ThreadParams::new(ctx.clone(), args, global_pool, constants);
+                if thread_params.is_err() {
+                    return Some(VMError::new(
+                        thread_params.unwrap_err(),
+                        VMErrorKind::ThreadCreateError,
+                        Some(inst.clone()),
+                        0,
+                    ));
+                }
 
-                let create_result = threads.create_thread_sandbox(thread_params, platform);
+                let create_result = threads.create_thread_sandbox(thread_params.unwrap(), platform);
                 if create_result.is_err() {
                     return Some(VMError::new(
                         create_result.unwrap_err(),
@@ -861,7 +1521,7 @@ impl Controls {
                     }
 
                     // unwrap the resut object
-                    let sandbox_result = thread_result.unwrap().result;
+                    let sandbox_result = thread_result.unwrap().get_result();
                     if sandbox_result.is_err() {
                         return Some(sandbox_result.unwrap_err());
                     }
@@ -950,7 +1610,7 @@ impl Controls {
     }
 
     pub fn get_attr(ds: &mut DataStack, inst: &InstructionKind, n_attrs: usize) -> Option<VMError> {
-        let attrs_popped_res = Controls::pop_n(ds, n_attrs, &inst);
+        let attrs_popped_res = Controls::pop_n(ds, n_attrs, inst);
         if attrs_popped_res.is_err() {
             return Some(attrs_popped_res.unwrap_err());
         }
@@ -987,21 +1647,58 @@ impl Controls {
         return None;
     }
 
+    pub fn set_attr(ds: &mut DataStack, inst: &InstructionKind, n_attrs: usize) -> Option<VMError> {
+        let attrs_popped_res = Controls::pop_n(ds, n_attrs, inst);
+        if attrs_popped_res.is_err() {
+            return Some(attrs_popped_res.unwrap_err());
+        }
+
+        let mut attrs = attrs_popped_res.unwrap();
+        attrs.reverse();
+
+        // the parent is pushed after the value being assigned:
+        let pop_obj_res = ds.pop_object(inst.clone());
+        if pop_obj_res.is_err() {
+            return Some(pop_obj_res.unwrap_err());
+        }
+
+        let obj = pop_obj_res.unwrap();
+        let pop_value_res = ds.pop_object(inst.clone());
+        if pop_value_res.is_err() {
+            return Some(pop_value_res.unwrap_err());
+        }
+
+        // the object is changed in place, nothing is pushed back:
+        let error = obj.resolve_set_attr(&attrs, pop_value_res.unwrap());
+        if error.is_some() {
+            return Some(VMError::new(
+                error.unwrap(),
+                VMErrorKind::AttributeError,
+                Some(inst.clone()),
+                0,
+            ));
+        }
+
+        return None;
+    }
+
     pub fn call_attr(
         ds: &mut DataStack,
         inst: &InstructionKind,
         n_attrs: usize,
         n_params: usize,
+        kwargs: &[(String, Rc<Object>)],
         platform: &mut Platform,
         gp: &mut GlobalPool,
         c: &mut ConstantPool,
         th: &mut thread::BosonThreads,
         ffi: &mut BosonFFI,
-    ) -> Option<VMError> {
+        jit: &mut BosonJIT,
+    ) -> Result<Option<RefCell<ExecutionFrame>>, VMError> {
         // pop N objects, which act as attributes
         let pop_res = Controls::pop_n(ds, n_attrs, inst);
         if pop_res.is_err() {
-            return Some(pop_res.unwrap_err());
+            return Err(pop_res.unwrap_err());
         }
 
         let mut attrs = pop_res.unwrap();
@@ -1010,20 +1707,209 @@ impl Controls {
         // parent assign object:
         let parent_obj_res = ds.pop_object(inst.clone());
         if parent_obj_res.is_err() {
-            return Some(parent_obj_res.unwrap_err());
+            return Err(parent_obj_res.unwrap_err());
         }
 
-        let parent_obj = parent_obj_res.unwrap();
+        let mut parent_obj = parent_obj_res.unwrap();
+
+        // a.b.c() calls c on a.b, resolve all but the last attribute:
+        while attrs.len() > 1 {
+            let attr = attrs.remove(0);
+            let get_res = parent_obj.resolve_get_attr(&[attr]);
+            if get_res.is_err() {
+                return Err(VMError::new(
+                    get_res.unwrap_err(),
+                    VMErrorKind::AttributeError,
+                    Some(inst.clone()),
+                    0,
+                ));
+            }
+
+            parent_obj = get_res.unwrap();
+        }
 
         // pop all the parameters:
-        let param_pop_result = Controls::pop_n(ds, n_params, &inst);
+        let param_pop_result = Controls::pop_n(ds, n_params, inst);
         if param_pop_result.is_err() {
-            return Some(param_pop_result.unwrap_err());
+            return Err(param_pop_result.unwrap_err());
         }
 
         let mut params = param_pop_result.unwrap();
         params.reverse();
 
+        // module members are regular objects, resolve the callable
+        // and call it like a normal function:
+        if let Object::Module(module) = parent_obj.as_ref() {
+            let func_res = module.resolve_get_attr(&attrs);
+            if func_res.is_err() {
+                return Err(VMError::new(
+                    func_res.unwrap_err(),
+                    VMErrorKind::AttributeError,
+                    Some(inst.clone()),
+                    0,
+                ));
+            }
+
+            let func = func_res.unwrap();
+            let resolved = Controls::resolve_keywords(inst, &func, params, kwargs, 0);
+            if resolved.is_err() {
+                return Err(resolved.unwrap_err());
+            }
+
+            let params = resolved.unwrap();
+            let n_params = params.len();
+            for param in params {
+                let push_result = ds.push_object(param, inst.clone());
+                if push_result.is_err() {
+                    return Err(push_result.unwrap_err());
+                }
+            }
+
+            let push_result = ds.push_object(func, inst.clone());
+            if push_result.is_err() {
+                return Err(push_result.unwrap_err());
+            }
+
+            return Controls::execute_call(inst, ds, n_params, gp, c, platform, th, ffi, jit);
+        }
+
+        // methods are called with the instance as the first argument,
+        // callables stored in fields are called as they are:
+        if let Object::Instance(instance) = parent_obj.as_ref() {
+            let name = attrs[0].describe();
+            let class = instance.borrow().class.clone();
+            let method = class.get_method(&name);
+
+            // the instance is the first parameter of a method:
+            let skip = if method.is_some() { 1 } else { 0 };
+            let callable = if method.is_some() {
+                method.clone().unwrap()
+            } else {
+                let field_res = instance.borrow().get_field(&name);
+                if field_res.is_err() {
+                    return Err(VMError::new(
+                        field_res.unwrap_err(),
+                        VMErrorKind::AttributeError,
+                        Some(inst.clone()),
+                        0,
+                    ));
+                }
+
+                field_res.unwrap()
+            };
+
+            let resolved = Controls::resolve_keywords(inst, &callable, params, kwargs, skip);
+            if resolved.is_err() {
+                return Err(resolved.unwrap_err());
+            }
+
+            let params = resolved.unwrap();
+            let n_params = params.len();
+            let mut n_args = n_params;
+            let func = if method.is_some() {
+                let error = class.check_arguments(&name, n_params);
+                if error.is_some() {
+                    return Err(VMError::new(
+                        error.unwrap(),
+                        VMErrorKind::FunctionArgumentsError,
+                        Some(inst.clone()),
+                        0,
+                    ));
+                }
+
+                let push_result = ds.push_object(parent_obj.clone(), inst.clone());
+                if push_result.is_err() {
+                    return Err(push_result.unwrap_err());
+                }
+
+                n_args += 1;
+                callable
+            } else {
+                callable
+            };
+
+            for param in params {
+                let push_result = ds.push_object(param, inst.clone());
+                if push_result.is_err() {
+                    return Err(push_result.unwrap_err());
+                }
+            }
+
+            let push_result = ds.push_object(func, inst.clone());
+            if push_result.is_err() {
+                return Err(push_result.unwrap_err());
+            }
+
+            return Controls::execute_call(inst, ds, n_args, gp, c, platform, th, ffi, jit);
+        }
+
+        // Point.move(p, 1, 2) calls the method with an explicit instance:
+        if let Object::Class(class) = parent_obj.as_ref() {
+            let func_res = class.resolve_get_attr(&attrs);
+            if func_res.is_err() {
+                return Err(VMError::new(
+                    func_res.unwrap_err(),
+                    VMErrorKind::AttributeError,
+                    Some(inst.clone()),
+                    0,
+                ));
+            }
+
+            let func = func_res.unwrap();
+            let resolved = Controls::resolve_keywords(inst, &func, params, kwargs, 0);
+            if resolved.is_err() {
+                return Err(resolved.unwrap_err());
+            }
+
+            let params = resolved.unwrap();
+            let n_params = params.len();
+            for param in params {
+                let push_result = ds.push_object(param, inst.clone());
+                if push_result.is_err() {
+                    return Err(push_result.unwrap_err());
+                }
+            }
+
+            let push_result = ds.push_object(func, inst.clone());
+            if push_result.is_err() {
+                return Err(push_result.unwrap_err());
+            }
+
+            return Controls::execute_call(inst, ds, n_params, gp, c, platform, th, ffi, jit);
+        }
+
+        if !kwargs.is_empty() {
+            return Err(VMError::new(
+                format!(
+                    "Attributes of {} do not take keyword arguments",
+                    parent_obj.get_type()
+                ),
+                VMErrorKind::FunctionArgumentsError,
+                Some(inst.clone()),
+                0,
+            ));
+        }
+
+        // errors raised by array callbacks keep their kind and trace:
+        if let Object::Array(arr) = parent_obj.as_ref() {
+            let call_result =
+                array::resolve_call_attr(arr, &attrs, &params, platform, gp, c, th, ffi);
+            if call_result.is_err() {
+                let mut error = call_result.unwrap_err();
+                if error.instruction.is_none() {
+                    error.instruction = Some(inst.clone());
+                }
+                return Err(error);
+            }
+
+            let push_result = ds.push_object(call_result.unwrap(), inst.clone());
+            if push_result.is_err() {
+                return Err(push_result.unwrap_err());
+            }
+
+            return Ok(None);
+        }
+
         let call_result = match parent_obj.as_ref() {
             Object::HashTable(ht) => ht
                 .borrow_mut()
@@ -1031,8 +1917,18 @@ impl Controls {
             Object::NativeModule(nt) => nt
                 .borrow_mut()
                 .resolve_call_attr(&attrs, &params, ds, platform, gp, c, th, ffi),
+            Object::Str(st) => string::resolve_call_attr(st, &attrs, &params),
+            Object::Channel(ch) => ch
+                .clone()
+                .resolve_call_attr(&attrs, &params, ds, platform, gp, c, th, ffi),
+            Object::Mutex(mutex) => mutex
+                .clone()
+                .resolve_call_attr(&attrs, &params, ds, platform, gp, c, th, ffi),
+            Object::Atomic(atomic) => atomic
+                .clone()
+                .resolve_call_attr(&attrs, &params, ds, platform, gp, c, th, ffi),
             _ => {
-                return Some(VMError::new(
+                return Err(VMError::new(
                     format!(
                         "Object of type {} does not support attribute assignment.",
                         parent_obj.get_type()
@@ -1045,7 +1941,7 @@ impl Controls {
         };
 
         if call_result.is_err() {
-            return Some(VMError::new(
+            return Err(VMError::new(
                 call_result.unwrap_err(),
                 VMErrorKind::AttributeError,
                 Some(inst.clone()),
@@ -1057,9 +1953,249 @@ impl Controls {
         // push the object
         let push_result = ds.push_object(object, inst.clone());
         if push_result.is_err() {
-            return Some(push_result.unwrap_err());
+            return Err(push_result.unwrap_err());
+        }
+
+        return Ok(None);
+    }
+
+    // keyword arguments of a call turned into positional ones, see ClosureContext.
+    fn resolve_keywords(
+        inst: &InstructionKind,
+        callable: &Rc<Object>,
+        args: Vec<Rc<Object>>,
+        kwargs: &[(String, Rc<Object>)],
+        skip: usize,
+    ) -> Result<Vec<Rc<Object>>, VMError> {
+        if kwargs.is_empty() {
+            return Ok(args);
+        }
+
+        let resolved = match callable.as_ref() {
+            Object::ClosureContext(ctx) => ctx.resolve_keywords(args, kwargs, skip),
+            Object::Class(class) => class.resolve_keywords(args, kwargs),
+            Object::Builtins(kind) => Err(format!(
+                "Builtin function {} does not take keyword arguments",
+                kind.desribe()
+            )),
+            _ => Err(format!(
+                "{} does not take keyword arguments",
+                callable.describe()
+            )),
+        };
+
+        if resolved.is_err() {
+            return Err(VMError::new(
+                resolved.unwrap_err(),
+                VMErrorKind::FunctionArgumentsError,
+                Some(inst.clone()),
+                0,
+            ));
+        }
+
+        return Ok(resolved.unwrap());
+    }
+
+    /*
+        ICallSpread: the positional arguments come as arrays followed by a
+        hash table of keyword arguments and the function, or the parent and
+        the attributes of an attribute call.
+    */
+    pub fn call_spread(
+        ds: &mut DataStack,
+        inst: &InstructionKind,
+        n_chunks: usize,
+        n_attrs: usize,
+        platform: &mut Platform,
+        gp: &mut GlobalPool,
+        c: &mut ConstantPool,
+        th: &mut thread::BosonThreads,
+        ffi: &mut BosonFFI,
+        jit: &mut BosonJIT,
+    ) -> Result<Option<RefCell<ExecutionFrame>>, VMError> {
+        // the function, or the attributes and their parent:
+        let pop_res = Controls::pop_n(ds, n_attrs + 1, inst);
+        if pop_res.is_err() {
+            return Err(pop_res.unwrap_err());
+        }
+
+        let mut callable = pop_res.unwrap();
+        callable.reverse();
+
+        let kwargs_res = ds.pop_object(inst.clone());
+        if kwargs_res.is_err() {
+            return Err(kwargs_res.unwrap_err());
+        }
+
+        let mut kwargs = vec![];
+        if let Object::HashTable(ht) = kwargs_res.unwrap().as_ref() {
+            for (key, value) in ht.borrow().items() {
+                kwargs.push((key.describe(), value));
+            }
+        }
+
+        let chunks_res = Controls::pop_n(ds, n_chunks, inst);
+        if chunks_res.is_err() {
+            return Err(chunks_res.unwrap_err());
+        }
+
+        let mut args = vec![];
+        for chunk in chunks_res.unwrap().iter().rev() {
+            match chunk.as_ref() {
+                Object::Array(arr) => args.extend(arr.borrow().elements.iter().cloned()),
+                _ => {
+                    return Err(VMError::new(
+                        format!(
+                            "Cannot spread {} into the arguments of a call, expected Array",
+                            chunk.get_type()
+                        ),
+                        VMErrorKind::TypeError,
+                        Some(inst.clone()),
+                        0,
+                    ));
+                }
+            }
         }
 
+        if n_attrs > 0 {
+            let n_params = args.len();
+            args.extend(callable);
+            let push_res = ds.push_objects(inst.clone(), args);
+            if push_res.is_err() {
+                return Err(push_res.unwrap_err());
+            }
+
+            return Controls::call_attr(
+                ds, inst, n_attrs, n_params, &kwargs, platform, gp, c, th, ffi, jit,
+            );
+        }
+
+        let resolved = Controls::resolve_keywords(inst, &callable[0], args, &kwargs, 0);
+        if resolved.is_err() {
+            return Err(resolved.unwrap_err());
+        }
+
+        let mut args = resolved.unwrap();
+        let n_args = args.len();
+        args.push(callable[0].clone());
+        let push_res = ds.push_objects(inst.clone(), args);
+        if push_res.is_err() {
+            return Err(push_res.unwrap_err());
+        }
+
+        return Controls::execute_call(inst, ds, n_args, gp, c, platform, th, ffi, jit);
+    }
+
+    pub fn push_exception_handler(
+        cf: &mut RefMut<ExecutionFrame>,
+        ds: &mut DataStack,
+        catch_pos: usize,
+    ) -> Option<VMError> {
+        if catch_pos >= cf.bytecode_size {
+            return Some(VMError::new(
+                format!("Exception handler position {} out of bounds", catch_pos),
+                VMErrorKind::IPOutOfBounds,
+                Some(InstructionKind::IPushExcHandle),
+                catch_pos,
+            ));
+        }
+
+        cf.push_handler(catch_pos, ds.stack_pointer);
         return None;
     }
+
+    pub fn pop_exception_handler(cf: &mut RefMut<ExecutionFrame>) -> Option<VMError> {
+        let handler = cf.pop_handler();
+        if handler.is_none() {
+            return Some(VMError::new(
+                "No exception handler to pop".to_string(),
+                VMErrorKind::StackCorruption,
+                Some(InstructionKind::IPopExcHandle),
+                0,
+            ));
+        }
+
+        return None;
+    }
+
+    pub fn get_stack_trace(cs: &CallStack) -> Vec<TraceEntry> {
+        // 0th element is the current function, last one is main
+        let trace: Vec<TraceEntry> = cs
+            .stack
+            .iter()
+            .take((cs.stack_pointer + 1) as usize)
+            .rev()
+            .enumerate()
+            .map(|(idx, frame)| TraceEntry::new(&frame.borrow(), idx == 0))
+            .collect();
+        return trace;
+    }
+
+    pub fn get_raised_exception(
+        cs: &CallStack,
+        ds: &mut DataStack,
+    ) -> Result<Rc<Exception>, VMError> {
+        let popped_res = ds.pop_object(InstructionKind::IRaise);
+        if popped_res.is_err() {
+            return Err(popped_res.unwrap_err());
+        }
+
+        let popped_obj = popped_res.unwrap();
+        match popped_obj.as_ref() {
+            // exception objects are raised as they are, this is how rethrow works.
+            Object::Exception(exc) => return Ok(exc.clone()),
+            _ => {
+                let trace = Controls::get_stack_trace(cs);
+                return Ok(Rc::new(Exception::new(
+                    "UserException".to_string(),
+                    popped_obj.describe(),
+                    VMErrorKind::UserException,
+                    trace,
+                )));
+            }
+        }
+    }
+
+    pub fn unwind_exception(
+        cs: &mut CallStack,
+        ds: &mut DataStack,
+        exception: Rc<Exception>,
+    ) -> Result<bool, VMError> {
+        // nothing is unwound if none of the frames can handle the exception,
+        // this leaves the VM state intact for debugging.
+        let is_handled = cs.stack.iter().any(|frame| frame.borrow().has_handlers());
+        if !is_handled {
+            return Ok(false);
+        }
+
+        // pop all the frames till the nearest handler:
+        while !cs.top_ref().has_handlers() {
+            let pop_result = cs.pop_frame();
+            if pop_result.is_err() {
+                return Err(pop_result.unwrap_err());
+            }
+        }
+
+        let mut frame = cs.top();
+        let handler = frame.pop_handler().unwrap();
+
+        // restore the data stack to the state when the handler was pushed:
+        ds.stack.truncate((handler.stack_pointer + 1) as usize);
+        ds.stack_pointer = handler.stack_pointer;
+
+        let push_res = ds.push_object(
+            Rc::new(Object::Exception(exception)),
+            InstructionKind::IRaise,
+        );
+        if push_res.is_err() {
+            return Err(push_res.unwrap_err());
+        }
+
+        let jmp_result = Controls::jump(&mut frame, handler.catch_pos);
+        if jmp_result.is_err() {
+            return Err(jmp_result.unwrap_err());
+        }
+
+        return Ok(true);
+    }
 }
@@ -1,7 +1,8 @@
+use crate::compiler::spans::SourceLocation;
 use crate::isa;
 use crate::vm::frames::ExecutionFrame;
 
-use std::rc::Rc;
+use std::fmt;
 
 use isa::InstructionKind;
 
@@ -35,6 +36,7 @@ pub enum VMErrorKind {
     ThreadCreateError,
     ThreadWaitError,
     AttributeError,
+    UserException,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +45,8 @@ pub struct VMError {
     pub t: VMErrorKind,
     pub instruction: Option<InstructionKind>,
     pub pos: usize,
+    pub location: Option<SourceLocation>,
+    pub trace: Vec<TraceEntry>,
 }
 
 impl VMError {
@@ -57,6 +61,8 @@ impl VMError {
             t: t,
             instruction: instruction,
             pos: pos,
+            location: None,
+            trace: vec![],
         };
     }
 
@@ -68,6 +74,8 @@ impl VMError {
                     t: VMErrorKind::TypeError,
                     instruction: Some(inst),
                     pos: 0,
+                    location: None,
+                    trace: vec![],
                 };
             }
             ISAErrorKind::OverflowError => {
@@ -76,6 +84,8 @@ impl VMError {
                     t: VMErrorKind::OverflowError,
                     instruction: Some(inst),
                     pos: 0,
+                    location: None,
+                    trace: vec![],
                 };
             }
             ISAErrorKind::DivideByZeroError => {
@@ -84,6 +94,8 @@ impl VMError {
                     t: VMErrorKind::DivideByZeroError,
                     instruction: Some(inst),
                     pos: 0,
+                    location: None,
+                    trace: vec![],
                 };
             }
             _ => {
@@ -92,12 +104,51 @@ impl VMError {
                     t: VMErrorKind::IllegalOperation,
                     instruction: Some(inst),
                     pos: 0,
+                    location: None,
+                    trace: vec![],
                 };
             }
         }
     }
 }
 
+/*
+    One line of the traceback, the subroutine that was running and the
+    offset of the instruction it was executing, frames below the top one
+    are at the call they are waiting on.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub name: String,
+    pub offset: usize,
+    pub location: Option<SourceLocation>,
+}
+
+impl TraceEntry {
+    pub fn new(frame: &ExecutionFrame, is_top: bool) -> TraceEntry {
+        let offset = if is_top {
+            frame.get_ip()
+        } else {
+            frame.last_call
+        };
+
+        return TraceEntry {
+            name: frame.context.compiled_fn.name.clone(),
+            offset: offset,
+            location: frame.context.compiled_fn.spans.lookup(offset),
+        };
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{} in {}, offset {}", location, self.name, self.offset),
+            None => write!(f, "<bytecode> in {}, offset {}", self.name, self.offset),
+        }
+    }
+}
+
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub enum ISAErrorKind {
     DivideByZeroError,
@@ -117,5 +168,3 @@ impl ISAError {
         return ISAError { message: msg, t: t };
     }
 }
-
-pub type StackFrame = Rc<ExecutionFrame>; // state of each frame when exception occured
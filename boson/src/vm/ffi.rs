@@ -30,7 +30,7 @@ impl BosonFFI {
         params: Rc<Object>,
     ) -> Result<(usize, DynamicModuleResult), FFIError> {
         unsafe {
-            let handle_result = libloading::Library::new(path.clone());
+            let handle_result = libloading::Library::new(path);
             if handle_result.is_err() {
                 return Err(format!(
                     "failed to load dynlib {}, error={}",
@@ -1,4 +1,5 @@
 use crate::compiler;
+use crate::compiler::spans::SourceLocation;
 use crate::isa;
 use crate::types::closure;
 use crate::types::object;
@@ -17,10 +18,13 @@ use isa::Operands;
 use object::Object;
 use subroutine::Subroutine;
 
+// catch_pos is the position of the catch block in the current frame,
+// stack_pointer is the data stack pointer when the handler was pushed,
+// the stack is restored to this point before jumping into the catch block.
 #[derive(Debug, Clone)]
 pub struct ExceptionHandler {
-    pub exception_handler: Rc<ClosureContext>,
-    pub finally_handler: Option<Rc<ClosureContext>>,
+    pub catch_pos: usize,
+    pub stack_pointer: i64,
 }
 
 pub type ExceptionHandleStack = Vec<ExceptionHandler>;
@@ -32,11 +36,15 @@ pub struct ExecutionFrame {
     pub base_pointer: usize,
     pub bytecode_size: usize,
     pub handlers: ExceptionHandleStack,
+    // offset of the last call that pushed a frame on top of this one:
+    pub last_call: usize,
+    // arguments of a memoized pure function call:
+    pub memo_key: Option<Vec<Rc<Object>>>,
 }
 
 impl ExecutionFrame {
     pub fn new(context: Rc<ClosureContext>, base_pointer: usize) -> ExecutionFrame {
-        let bytecode_size = context.as_ref().bytecode_size.clone();
+        let bytecode_size = context.as_ref().bytecode_size;
 
         return ExecutionFrame {
             context: context,
@@ -44,17 +52,16 @@ impl ExecutionFrame {
             base_pointer: base_pointer,
             bytecode_size: bytecode_size,
             handlers: vec![],
+            last_call: 0,
+            memo_key: None,
         };
     }
 
     pub fn new_closure(func: Rc<Subroutine>, free_objects: Vec<Rc<Object>>) -> Rc<Object> {
-        let b_size = func.as_ref().bytecode.len();
-
-        return Rc::new(Object::ClosureContext(Rc::new(ClosureContext {
-            compiled_fn: func,
-            bytecode_size: b_size,
-            free_objects: free_objects,
-        })));
+        return Rc::new(Object::ClosureContext(Rc::new(ClosureContext::new(
+            func,
+            free_objects,
+        ))));
     }
 
     pub fn get_free(&mut self, idx: usize, inst: InstructionKind) -> Result<Rc<Object>, VMError> {
@@ -77,22 +84,35 @@ impl ExecutionFrame {
         n_locals: usize,
         n_params: usize,
     ) -> ExecutionFrame {
-        let closure = ClosureContext {
-            compiled_fn: Rc::new(Subroutine {
+        let closure = ClosureContext::new(
+            Rc::new(Subroutine {
                 name: fn_name,
                 bytecode: bytecode.instructions.clone(),
                 num_locals: n_locals,
                 num_parameters: n_params,
                 is_local_scope: false,
+                spans: bytecode.spans.clone(),
+                is_pure: false,
+                is_generator: false,
+                parameter_names: vec![],
+                num_defaults: 0,
+                is_variadic: false,
             }),
-            free_objects: vec![],
-            bytecode_size: bytecode.instructions.len(),
-        };
+            vec![],
+        );
 
         let frame = ExecutionFrame::new(Rc::new(closure), 0);
         return frame;
     }
 
+    pub fn get_location(&self) -> Option<SourceLocation> {
+        return self
+            .context
+            .compiled_fn
+            .spans
+            .lookup(self.instruction_pointer);
+    }
+
     pub fn get_ip(&self) -> usize {
         return self.instruction_pointer;
     }
@@ -142,6 +162,21 @@ impl ExecutionFrame {
         return encoded_string;
     }
 
+    pub fn push_handler(&mut self, catch_pos: usize, stack_pointer: i64) {
+        self.handlers.push(ExceptionHandler {
+            catch_pos: catch_pos,
+            stack_pointer: stack_pointer,
+        });
+    }
+
+    pub fn pop_handler(&mut self) -> Option<ExceptionHandler> {
+        return self.handlers.pop();
+    }
+
+    pub fn has_handlers(&self) -> bool {
+        return !self.handlers.is_empty();
+    }
+
     pub fn get_function_name(&self) -> String {
         self.context.as_ref().compiled_fn.name.clone()
     }
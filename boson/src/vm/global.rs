@@ -16,12 +16,11 @@ pub struct GlobalPool {
     pub max_size: usize,
 }
 
-// make GlobalPool capable of sharing between threads.
-// by design, GlobalPool will always be thread safe as each thread will
-// get it's own copy of global pool.
-// so this unsafe impl is just a cover-up to fool the compiler to make global pool
-// sharebale across threads.
-unsafe impl Send for GlobalPool {}
+impl Default for GlobalPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl GlobalPool {
     pub fn new() -> GlobalPool {
@@ -0,0 +1,1019 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::env;
+use std::rc::Rc;
+
+use cranelift::prelude::*;
+use cranelift_jit::JITBuilder;
+use cranelift_jit::JITModule;
+use cranelift_module::default_libcall_names;
+use cranelift_module::Linkage;
+use cranelift_module::Module;
+
+use crate::compiler::symtab::ConstantPool;
+use crate::config::ENABLE_JIT;
+use crate::config::JIT_CALL_THRESHOLD;
+use crate::isa::InstructionKind;
+use crate::isa::InstructionPacker;
+use crate::isa::Operands;
+use crate::types::object::Object;
+use crate::types::subroutine::Subroutine;
+
+/*
+    Tiered execution:
+        Every call to a subroutine is counted, once a subroutine crosses
+        JIT_CALL_THRESHOLD calls, it is compiled to native code for the types
+        of the arguments it was called with. Only subroutines made of
+        int/float/bool arithmetic, locals and jumps are compiled.
+
+        The native function has the signature:
+            fn(args: *const i64, result: *mut i64) -> i64
+        floats are passed as their bit patterns and bools as 0/1. A non-zero
+        status means the native code hit a case it cannot handle (overflow,
+        divide by zero), the call is then re-executed by the interpreter, this is
+        safe because compiled subroutines have no side effects.
+*/
+
+const STATUS_OK: i64 = 0;
+const STATUS_DEOPT: i64 = 1;
+
+type NativeFunction = extern "C" fn(*const i64, *mut i64) -> i64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JitType {
+    Int,
+    Float,
+    Bool,
+    Noval,
+}
+
+impl JitType {
+    pub fn from_object(obj: &Object) -> Option<JitType> {
+        match obj {
+            Object::Int(_) => Some(JitType::Int),
+            Object::Float(_) => Some(JitType::Float),
+            Object::Bool(_) => Some(JitType::Bool),
+            _ => None,
+        }
+    }
+
+    fn get_ir_type(&self) -> Type {
+        match self {
+            JitType::Float => types::F64,
+            _ => types::I64,
+        }
+    }
+
+    fn get_index(&self) -> usize {
+        match self {
+            JitType::Int => 0,
+            JitType::Float => 1,
+            _ => 2,
+        }
+    }
+
+    fn encode(obj: &Object) -> i64 {
+        match obj {
+            Object::Int(i) => *i,
+            Object::Float(f) => f.to_bits() as i64,
+            Object::Bool(b) => *b as i64,
+            _ => 0,
+        }
+    }
+
+    fn decode(&self, raw: i64) -> Rc<Object> {
+        match self {
+            JitType::Int => Rc::new(Object::Int(raw)),
+            JitType::Float => Rc::new(Object::Float(f64::from_bits(raw as u64))),
+            JitType::Bool => Rc::new(Object::Bool(raw != 0)),
+            JitType::Noval => Rc::new(Object::Noval),
+        }
+    }
+}
+
+struct DecodedInstruction {
+    pos: usize,
+    inst: InstructionKind,
+    operands: Operands,
+    next: usize,
+}
+
+// types of the data stack and locals at a given point of the subroutine,
+// locals that are not yet assigned are None.
+#[derive(Debug, Clone, PartialEq)]
+struct TypeState {
+    stack: Vec<JitType>,
+    locals: Vec<Option<JitType>>,
+}
+
+impl TypeState {
+    fn merge(&self, other: &TypeState) -> Result<TypeState, String> {
+        if self.stack != other.stack {
+            return Err("Data stack types differ at a branch".to_string());
+        }
+
+        let mut locals = vec![];
+        for (left, right) in self.locals.iter().zip(other.locals.iter()) {
+            match (left, right) {
+                (Some(l), Some(r)) => {
+                    if l != r {
+                        return Err("Local changes its type at a branch".to_string());
+                    }
+                    locals.push(Some(*l));
+                }
+                _ => locals.push(None),
+            }
+        }
+
+        return Ok(TypeState {
+            stack: self.stack.clone(),
+            locals: locals,
+        });
+    }
+
+    fn pop(&mut self) -> Result<JitType, String> {
+        let popped = self.stack.pop();
+        if popped.is_none() {
+            return Err("Data stack underflow".to_string());
+        }
+
+        return Ok(popped.unwrap());
+    }
+}
+
+struct CompiledVariant {
+    params: Vec<JitType>,
+    ret: JitType,
+    native: Option<NativeFunction>,
+}
+
+struct JitEntry {
+    // keeps the subroutine alive, entries are keyed by its address.
+    _subroutine: Rc<Subroutine>,
+    calls: usize,
+    variants: Vec<CompiledVariant>,
+}
+
+pub struct BosonJIT {
+    enabled: bool,
+    // created on the first compilation, sandbox VMs rarely get there:
+    module: Option<JITModule>,
+    entries: HashMap<usize, JitEntry>,
+    n_compiled: usize,
+}
+
+impl Default for BosonJIT {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BosonJIT {
+    pub fn new() -> BosonJIT {
+        let disabled = env::var("BOSON_JIT").is_ok_and(|value| value == "0");
+        return BosonJIT::new_enabled(ENABLE_JIT && !disabled);
+    }
+
+    // ignores ENABLE_JIT and BOSON_JIT, used to compare both tiers:
+    pub fn new_enabled(enabled: bool) -> BosonJIT {
+        return BosonJIT {
+            enabled: enabled,
+            module: None,
+            entries: HashMap::new(),
+            n_compiled: 0,
+        };
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        return self.enabled;
+    }
+
+    // argument types of the native versions compiled for the subroutine:
+    pub fn get_native_variants(&self, subroutine: &Rc<Subroutine>) -> Vec<Vec<JitType>> {
+        let entry = self.entries.get(&(Rc::as_ptr(subroutine) as usize));
+        if entry.is_none() {
+            return vec![];
+        }
+
+        return entry
+            .unwrap()
+            .variants
+            .iter()
+            .filter(|variant| variant.native.is_some())
+            .map(|variant| variant.params.clone())
+            .collect();
+    }
+
+    fn get_module(&mut self) -> Option<&mut JITModule> {
+        if self.module.is_none() {
+            let builder =
+                JITBuilder::with_flags(&[("opt_level", "speed")], default_libcall_names());
+            if builder.is_err() {
+                self.enabled = false;
+                return None;
+            }
+            self.module = Some(JITModule::new(builder.unwrap()));
+        }
+
+        return self.module.as_mut();
+    }
+
+    /*
+        Counts the call and executes the native version of the subroutine
+        if there is one for the given argument types, returns None if the call
+        should be executed by the interpreter.
+    */
+    pub fn execute(
+        &mut self,
+        subroutine: &Rc<Subroutine>,
+        args: &[Rc<Object>],
+        constants: &ConstantPool,
+    ) -> Option<Rc<Object>> {
+        if !self.enabled {
+            return None;
+        }
+
+        // guard: only numeric arguments are handled by native code
+        let mut params = vec![];
+        for arg in args {
+            let arg_type = JitType::from_object(arg.as_ref());
+            if arg_type.is_none() {
+                return None;
+            }
+            params.push(arg_type.unwrap());
+        }
+
+        let key = Rc::as_ptr(subroutine) as usize;
+        let entry = self.entries.entry(key).or_insert(JitEntry {
+            _subroutine: subroutine.clone(),
+            calls: 0,
+            variants: vec![],
+        });
+
+        entry.calls += 1;
+
+        let mut variant_idx = entry.variants.iter().position(|v| v.params == params);
+        if variant_idx.is_none() {
+            if entry.calls < JIT_CALL_THRESHOLD {
+                return None;
+            }
+
+            let n_compiled = self.n_compiled;
+            let module = self.get_module();
+            if module.is_none() {
+                return None;
+            }
+
+            let compile_result = BosonJIT::compile(
+                module.unwrap(),
+                subroutine.as_ref(),
+                &params,
+                constants,
+                n_compiled,
+            );
+
+            self.n_compiled += 1;
+            let entry = self.entries.get_mut(&key).unwrap();
+            let variant = match compile_result {
+                Ok((native, ret)) => CompiledVariant {
+                    params: params.clone(),
+                    ret: ret,
+                    native: Some(native),
+                },
+                // not compilable for these types, stays in the interpreter.
+                Err(_) => CompiledVariant {
+                    params: params.clone(),
+                    ret: JitType::Noval,
+                    native: None,
+                },
+            };
+
+            entry.variants.push(variant);
+            variant_idx = Some(entry.variants.len() - 1);
+        }
+
+        let variant = &self.entries.get(&key).unwrap().variants[variant_idx.unwrap()];
+        if variant.native.is_none() {
+            return None;
+        }
+
+        let raw_args: Vec<i64> = args.iter().map(|arg| JitType::encode(arg)).collect();
+        let mut raw_result: i64 = 0;
+
+        let native = variant.native.unwrap();
+        let status = native(raw_args.as_ptr(), &mut raw_result);
+        if status != STATUS_OK {
+            return None;
+        }
+
+        return Some(variant.ret.decode(raw_result));
+    }
+
+    fn decode(bytecode: &[u8]) -> Vec<DecodedInstruction> {
+        let mut decoded = vec![];
+        let mut idx = 0;
+
+        while idx < bytecode.len() {
+            let inst: InstructionKind = unsafe { ::std::mem::transmute(bytecode[idx]) };
+            let (operands, next_offset) =
+                InstructionPacker::decode_instruction(&inst, &bytecode[idx + 1..]);
+            let next = idx + next_offset + 1;
+
+            decoded.push(DecodedInstruction {
+                pos: idx,
+                inst: inst,
+                operands: operands,
+                next: next,
+            });
+
+            idx = next;
+        }
+
+        return decoded;
+    }
+
+    fn get_constant_type(constants: &ConstantPool, idx: usize) -> Result<JitType, String> {
+        let constant = constants.get_object(idx);
+        if constant.is_none() {
+            return Err(format!("Constant {} not found", idx));
+        }
+
+        let constant_type = JitType::from_object(constant.unwrap().as_ref());
+        if constant_type.is_none() {
+            return Err("Only numeric constants are supported".to_string());
+        }
+
+        return Ok(constant_type.unwrap());
+    }
+
+    // result type of a binary operation, same rules as the ALU.
+    fn get_binary_type(inst: &InstructionKind, left: JitType, right: JitType) -> Option<JitType> {
+        let is_numeric = |t: JitType| t == JitType::Int || t == JitType::Float;
+        let both_int = left == JitType::Int && right == JitType::Int;
+
+        match inst {
+            InstructionKind::IAdd | InstructionKind::ISub | InstructionKind::IMul => {
+                if both_int {
+                    return Some(JitType::Int);
+                }
+                if is_numeric(left) && is_numeric(right) {
+                    return Some(JitType::Float);
+                }
+                return None;
+            }
+            InstructionKind::IDiv => {
+                if is_numeric(left) && is_numeric(right) {
+                    return Some(JitType::Float);
+                }
+                return None;
+            }
+            InstructionKind::IMod | InstructionKind::IAnd | InstructionKind::IOr => {
+                if both_int {
+                    return Some(JitType::Int);
+                }
+                return None;
+            }
+            InstructionKind::ILGt
+            | InstructionKind::ILGte
+            | InstructionKind::ILLt
+            | InstructionKind::ILLTe
+            | InstructionKind::ILEq
+            | InstructionKind::ILNe => {
+                if is_numeric(left) && is_numeric(right) {
+                    return Some(JitType::Bool);
+                }
+                return None;
+            }
+            InstructionKind::ILAnd | InstructionKind::ILOr => {
+                return Some(JitType::Bool);
+            }
+            _ => None,
+        }
+    }
+
+    // applies the instruction to the type state.
+    fn transfer(
+        state: &mut TypeState,
+        decoded: &DecodedInstruction,
+        constants: &ConstantPool,
+        ret: &mut Option<JitType>,
+    ) -> Result<(), String> {
+        match decoded.inst {
+            InstructionKind::INoOp
+            | InstructionKind::IBlockStart
+            | InstructionKind::IBlockEnd
+            | InstructionKind::IJump => {}
+            InstructionKind::IConstant => {
+                let constant_type = BosonJIT::get_constant_type(constants, decoded.operands[0]);
+                if constant_type.is_err() {
+                    return Err(constant_type.unwrap_err());
+                }
+                state.stack.push(constant_type.unwrap());
+            }
+            InstructionKind::ILoadLocal => {
+                let local = state.locals.get(decoded.operands[0]).cloned().flatten();
+                if local.is_none() {
+                    return Err("Local loaded before assignment".to_string());
+                }
+                state.stack.push(local.unwrap());
+            }
+            InstructionKind::IStoreLocal => {
+                if decoded.operands[0] >= state.locals.len() {
+                    return Err("Local out of bounds".to_string());
+                }
+
+                let value = state.pop();
+                if value.is_err() {
+                    return Err(value.unwrap_err());
+                }
+                state.locals[decoded.operands[0]] = Some(value.unwrap());
+            }
+            InstructionKind::INotJump => {
+                let cond = state.pop();
+                if cond.is_err() {
+                    return Err(cond.unwrap_err());
+                }
+            }
+            InstructionKind::INeg => {
+                let value = state.pop();
+                if value.is_err() || value.unwrap() != JitType::Int {
+                    return Err("Negate is applicable only on int".to_string());
+                }
+                state.stack.push(JitType::Int);
+            }
+            InstructionKind::ILNot => {
+                let value = state.pop();
+                if value.is_err() {
+                    return Err(value.unwrap_err());
+                }
+                state.stack.push(JitType::Bool);
+            }
+            InstructionKind::IRetVal | InstructionKind::IRet => {
+                let ret_type = if decoded.inst == InstructionKind::IRetVal {
+                    let value = state.pop();
+                    if value.is_err() {
+                        return Err(value.unwrap_err());
+                    }
+                    value.unwrap()
+                } else {
+                    JitType::Noval
+                };
+
+                if ret.is_some() && ret.unwrap() != ret_type {
+                    return Err("Subroutine returns values of different types".to_string());
+                }
+                *ret = Some(ret_type);
+            }
+            InstructionKind::IAdd
+            | InstructionKind::ISub
+            | InstructionKind::IMul
+            | InstructionKind::IDiv
+            | InstructionKind::IMod
+            | InstructionKind::IAnd
+            | InstructionKind::IOr
+            | InstructionKind::ILGt
+            | InstructionKind::ILGte
+            | InstructionKind::ILLt
+            | InstructionKind::ILLTe
+            | InstructionKind::ILEq
+            | InstructionKind::ILNe
+            | InstructionKind::ILAnd
+            | InstructionKind::ILOr => {
+                let right = state.pop();
+                let left = state.pop();
+                if right.is_err() || left.is_err() {
+                    return Err("Data stack underflow".to_string());
+                }
+
+                let result =
+                    BosonJIT::get_binary_type(&decoded.inst, left.unwrap(), right.unwrap());
+                if result.is_none() {
+                    return Err(format!("{} not supported", decoded.inst.as_string()));
+                }
+                state.stack.push(result.unwrap());
+            }
+            _ => {
+                return Err(format!("{} not supported", decoded.inst.as_string()));
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn is_terminator(inst: &InstructionKind) -> bool {
+        matches!(
+            inst,
+            InstructionKind::IJump | InstructionKind::IRet | InstructionKind::IRetVal
+        )
+    }
+
+    /*
+        Finds the type state at the start of every basic block, fails if
+        the types are not the same on all the paths reaching a block.
+    */
+    fn infer_types(
+        instructions: &[DecodedInstruction],
+        leaders: &HashSet<usize>,
+        entry_state: TypeState,
+        constants: &ConstantPool,
+    ) -> Result<(HashMap<usize, TypeState>, JitType), String> {
+        let index_of: HashMap<usize, usize> = instructions
+            .iter()
+            .enumerate()
+            .map(|(idx, decoded)| (decoded.pos, idx))
+            .collect();
+
+        let mut block_states: HashMap<usize, TypeState> = HashMap::new();
+        block_states.insert(0, entry_state);
+
+        let mut ret: Option<JitType> = None;
+        let mut worklist = vec![0];
+
+        while let Some(block_start) = worklist.pop() {
+            let mut state = block_states.get(&block_start).unwrap().clone();
+            let mut idx = *index_of.get(&block_start).unwrap();
+
+            loop {
+                let decoded = &instructions[idx];
+                let result = BosonJIT::transfer(&mut state, decoded, constants, &mut ret);
+                if result.is_err() {
+                    return Err(result.unwrap_err());
+                }
+
+                // successors of this instruction that start a block:
+                let mut successors = vec![];
+                match decoded.inst {
+                    InstructionKind::IJump => successors.push(decoded.operands[0]),
+                    InstructionKind::INotJump => {
+                        successors.push(decoded.operands[0]);
+                        successors.push(decoded.next);
+                    }
+                    InstructionKind::IRet | InstructionKind::IRetVal => {}
+                    _ => {
+                        if leaders.contains(&decoded.next) {
+                            successors.push(decoded.next);
+                        }
+                    }
+                }
+
+                for successor in successors {
+                    if !index_of.contains_key(&successor) {
+                        return Err("Control reaches the end of the subroutine".to_string());
+                    }
+
+                    let existing = block_states.get(&successor);
+                    let merged = if existing.is_some() {
+                        let merge_result = existing.unwrap().merge(&state);
+                        if merge_result.is_err() {
+                            return Err(merge_result.unwrap_err());
+                        }
+                        merge_result.unwrap()
+                    } else {
+                        state.clone()
+                    };
+
+                    if existing.is_none() || *existing.unwrap() != merged {
+                        block_states.insert(successor, merged);
+                        worklist.push(successor);
+                    }
+                }
+
+                if BosonJIT::is_terminator(&decoded.inst)
+                    || decoded.inst == InstructionKind::INotJump
+                    || leaders.contains(&decoded.next)
+                {
+                    break;
+                }
+
+                idx += 1;
+                if idx >= instructions.len() {
+                    return Err("Control reaches the end of the subroutine".to_string());
+                }
+            }
+        }
+
+        return Ok((block_states, ret.unwrap_or(JitType::Noval)));
+    }
+
+    fn compile(
+        module: &mut JITModule,
+        subroutine: &Subroutine,
+        params: &[JitType],
+        constants: &ConstantPool,
+        n_compiled: usize,
+    ) -> Result<(NativeFunction, JitType), String> {
+        let instructions = BosonJIT::decode(subroutine.get_bytecode());
+        if instructions.is_empty() {
+            return Err("Empty subroutine".to_string());
+        }
+
+        // block leaders: entry, jump targets and the instruction after a jump
+        let mut leaders = HashSet::new();
+        leaders.insert(0);
+        for decoded in &instructions {
+            match decoded.inst {
+                InstructionKind::IJump | InstructionKind::INotJump => {
+                    leaders.insert(decoded.operands[0]);
+                    leaders.insert(decoded.next);
+                }
+                InstructionKind::IRet | InstructionKind::IRetVal => {
+                    leaders.insert(decoded.next);
+                }
+                _ => {}
+            }
+        }
+
+        let n_locals = subroutine.get_n_locals();
+        let mut locals = vec![None; n_locals];
+        for (idx, param) in params.iter().enumerate() {
+            locals[idx] = Some(*param);
+        }
+
+        let entry_state = TypeState {
+            stack: vec![],
+            locals: locals,
+        };
+
+        let inference_result =
+            BosonJIT::infer_types(&instructions, &leaders, entry_state.clone(), constants);
+        if inference_result.is_err() {
+            return Err(inference_result.unwrap_err());
+        }
+
+        let (block_states, ret_type) = inference_result.unwrap();
+
+        let pointer_type = module.target_config().pointer_type();
+        let mut ctx = module.make_context();
+        ctx.func.signature.params.push(AbiParam::new(pointer_type));
+        ctx.func.signature.params.push(AbiParam::new(pointer_type));
+        ctx.func.signature.returns.push(AbiParam::new(types::I64));
+
+        let mut fn_ctx = FunctionBuilderContext::new();
+        let mut builder = FunctionBuilder::new(&mut ctx.func, &mut fn_ctx);
+        let mut translator = Translator {
+            builder: &mut builder,
+            declared: HashSet::new(),
+            n_locals: n_locals,
+        };
+
+        let error = translator.translate(
+            &instructions,
+            &leaders,
+            &block_states,
+            entry_state,
+            constants,
+        );
+        if error.is_some() {
+            return Err(error.unwrap());
+        }
+
+        builder.seal_all_blocks();
+        builder.finalize();
+
+        let fn_name = format!("__boson_jit_{}_{}", subroutine.get_name(), n_compiled);
+        let declare_result = module.declare_function(&fn_name, Linkage::Local, &ctx.func.signature);
+        if declare_result.is_err() {
+            return Err(format!("{}", declare_result.unwrap_err()));
+        }
+
+        let func_id = declare_result.unwrap();
+        let define_result = module.define_function(func_id, &mut ctx);
+        if define_result.is_err() {
+            return Err(format!("{:?}", define_result.unwrap_err()));
+        }
+
+        module.clear_context(&mut ctx);
+        let finalize_result = module.finalize_definitions();
+        if finalize_result.is_err() {
+            return Err(format!("{}", finalize_result.unwrap_err()));
+        }
+
+        let code = module.get_finalized_function(func_id);
+        let native = unsafe { ::std::mem::transmute::<*const u8, NativeFunction>(code) };
+
+        return Ok((native, ret_type));
+    }
+}
+
+/*
+    Translates the stack based bytecode into cranelift IR. Every local and
+    every data stack slot is a cranelift variable (one per type), the stack
+    depth and types at each instruction are known from the type inference.
+*/
+struct Translator<'a, 'b> {
+    builder: &'a mut FunctionBuilder<'b>,
+    declared: HashSet<usize>,
+    n_locals: usize,
+}
+
+impl<'a, 'b> Translator<'a, 'b> {
+    fn variable(&mut self, slot: usize, t: JitType) -> Variable {
+        let idx = slot * 3 + t.get_index();
+        let var = Variable::new(idx);
+        if !self.declared.contains(&idx) {
+            self.builder.declare_var(var, t.get_ir_type());
+            self.declared.insert(idx);
+        }
+
+        return var;
+    }
+
+    fn push(&mut self, state: &mut TypeState, t: JitType, value: Value) {
+        let slot = self.n_locals + state.stack.len();
+        let var = self.variable(slot, t);
+        self.builder.def_var(var, value);
+        state.stack.push(t);
+    }
+
+    fn pop(&mut self, state: &mut TypeState) -> (JitType, Value) {
+        let t = state.stack.pop().unwrap();
+        let slot = self.n_locals + state.stack.len();
+        let var = self.variable(slot, t);
+        return (t, self.builder.use_var(var));
+    }
+
+    fn float_value(&mut self, t: JitType, value: Value) -> Value {
+        if t == JitType::Float {
+            return value;
+        }
+
+        return self.builder.ins().fcvt_from_sint(types::F64, value);
+    }
+
+    // truthiness of the value, same as Object::is_true, floats are always true
+    fn truth_value(&mut self, t: JitType, value: Value) -> Value {
+        match t {
+            JitType::Float => self.builder.ins().iconst(types::I8, 1),
+            _ => self.builder.ins().icmp_imm(IntCC::NotEqual, value, 0),
+        }
+    }
+
+    fn deopt_if(&mut self, cond: Value, deopt_block: Block) {
+        let next_block = self.builder.create_block();
+        self.builder
+            .ins()
+            .brif(cond, deopt_block, &[], next_block, &[]);
+        self.builder.switch_to_block(next_block);
+    }
+
+    fn translate_binary(
+        &mut self,
+        inst: &InstructionKind,
+        state: &mut TypeState,
+        deopt_block: Block,
+    ) -> Option<String> {
+        let (right_t, right) = self.pop(state);
+        let (left_t, left) = self.pop(state);
+
+        let result_t = BosonJIT::get_binary_type(inst, left_t, right_t);
+        if result_t.is_none() {
+            return Some(format!("{} not supported", inst.as_string()));
+        }
+
+        let result_t = result_t.unwrap();
+        let both_int = left_t == JitType::Int && right_t == JitType::Int;
+
+        let result = match inst {
+            InstructionKind::IAdd | InstructionKind::ISub | InstructionKind::IMul if both_int => {
+                let (value, overflow) = match inst {
+                    InstructionKind::IAdd => self.builder.ins().sadd_overflow(left, right),
+                    InstructionKind::ISub => self.builder.ins().ssub_overflow(left, right),
+                    _ => self.builder.ins().smul_overflow(left, right),
+                };
+                self.deopt_if(overflow, deopt_block);
+                value
+            }
+            InstructionKind::IAdd | InstructionKind::ISub | InstructionKind::IMul => {
+                let l = self.float_value(left_t, left);
+                let r = self.float_value(right_t, right);
+                match inst {
+                    InstructionKind::IAdd => self.builder.ins().fadd(l, r),
+                    InstructionKind::ISub => self.builder.ins().fsub(l, r),
+                    _ => self.builder.ins().fmul(l, r),
+                }
+            }
+            InstructionKind::IDiv => {
+                let l = self.float_value(left_t, left);
+                let r = self.float_value(right_t, right);
+                let zero = self.builder.ins().f64const(0.0);
+                let is_zero = self.builder.ins().fcmp(FloatCC::Equal, r, zero);
+                self.deopt_if(is_zero, deopt_block);
+                self.builder.ins().fdiv(l, r)
+            }
+            InstructionKind::IMod => {
+                let is_zero = self.builder.ins().icmp_imm(IntCC::Equal, right, 0);
+                self.deopt_if(is_zero, deopt_block);
+                // srem truncates, the remainder takes the sign of the divisor instead:
+                let rem = self.builder.ins().srem(left, right);
+                let signs = self.builder.ins().bxor(rem, right);
+                let signs_differ = self.builder.ins().icmp_imm(IntCC::SignedLessThan, signs, 0);
+                let non_zero = self.builder.ins().icmp_imm(IntCC::NotEqual, rem, 0);
+                let adjust = self.builder.ins().band(signs_differ, non_zero);
+                let adjusted = self.builder.ins().iadd(rem, right);
+                self.builder.ins().select(adjust, adjusted, rem)
+            }
+            InstructionKind::IAnd => self.builder.ins().band(left, right),
+            InstructionKind::IOr => self.builder.ins().bor(left, right),
+            InstructionKind::ILAnd | InstructionKind::ILOr => {
+                let l = self.truth_value(left_t, left);
+                let r = self.truth_value(right_t, right);
+                let value = if *inst == InstructionKind::ILAnd {
+                    self.builder.ins().band(l, r)
+                } else {
+                    self.builder.ins().bor(l, r)
+                };
+                self.builder.ins().uextend(types::I64, value)
+            }
+            _ => {
+                let cmp = if both_int {
+                    let cc = match inst {
+                        InstructionKind::ILGt => IntCC::SignedGreaterThan,
+                        InstructionKind::ILGte => IntCC::SignedGreaterThanOrEqual,
+                        InstructionKind::ILLt => IntCC::SignedLessThan,
+                        InstructionKind::ILLTe => IntCC::SignedLessThanOrEqual,
+                        InstructionKind::ILEq => IntCC::Equal,
+                        _ => IntCC::NotEqual,
+                    };
+                    self.builder.ins().icmp(cc, left, right)
+                } else {
+                    let l = self.float_value(left_t, left);
+                    let r = self.float_value(right_t, right);
+                    let cc = match inst {
+                        InstructionKind::ILGt => FloatCC::GreaterThan,
+                        InstructionKind::ILGte => FloatCC::GreaterThanOrEqual,
+                        InstructionKind::ILLt => FloatCC::LessThan,
+                        InstructionKind::ILLTe => FloatCC::LessThanOrEqual,
+                        InstructionKind::ILEq => FloatCC::Equal,
+                        _ => FloatCC::NotEqual,
+                    };
+                    self.builder.ins().fcmp(cc, l, r)
+                };
+                self.builder.ins().uextend(types::I64, cmp)
+            }
+        };
+
+        self.push(state, result_t, result);
+        return None;
+    }
+
+    fn translate(
+        &mut self,
+        instructions: &Vec<DecodedInstruction>,
+        leaders: &HashSet<usize>,
+        block_states: &HashMap<usize, TypeState>,
+        entry_state: TypeState,
+        constants: &ConstantPool,
+    ) -> Option<String> {
+        // one cranelift block for every reachable basic block
+        let mut blocks: HashMap<usize, Block> = HashMap::new();
+        for pos in block_states.keys() {
+            blocks.insert(*pos, self.builder.create_block());
+        }
+
+        let entry_block = self.builder.create_block();
+        let deopt_block = self.builder.create_block();
+
+        self.builder
+            .append_block_params_for_function_params(entry_block);
+        self.builder.switch_to_block(entry_block);
+
+        let args_ptr = self.builder.block_params(entry_block)[0];
+        let result_ptr = self.builder.block_params(entry_block)[1];
+
+        // load the parameters into the local variables:
+        for (idx, local) in entry_state.locals.iter().enumerate() {
+            if local.is_none() {
+                continue;
+            }
+
+            let t = local.unwrap();
+            let value = self.builder.ins().load(
+                t.get_ir_type(),
+                MemFlags::trusted(),
+                args_ptr,
+                (idx * 8) as i32,
+            );
+            let var = self.variable(idx, t);
+            self.builder.def_var(var, value);
+        }
+
+        self.builder.ins().jump(blocks[&0], &[]);
+
+        let mut state: Option<TypeState> = None;
+        for decoded in instructions {
+            if leaders.contains(&decoded.pos) {
+                // fall through from the previous block:
+                if state.is_some() {
+                    self.builder.ins().jump(blocks[&decoded.pos], &[]);
+                }
+
+                state = block_states.get(&decoded.pos).cloned();
+                if state.is_some() {
+                    self.builder.switch_to_block(blocks[&decoded.pos]);
+                }
+            }
+
+            // unreachable code is not translated
+            if state.is_none() {
+                continue;
+            }
+
+            let mut terminated = false;
+            let current = state.as_mut().unwrap();
+            match decoded.inst {
+                InstructionKind::INoOp
+                | InstructionKind::IBlockStart
+                | InstructionKind::IBlockEnd => {}
+                InstructionKind::IConstant => {
+                    let constant = constants.get_object(decoded.operands[0]).unwrap();
+                    let t = JitType::from_object(constant.as_ref()).unwrap();
+                    let value = match constant.as_ref() {
+                        Object::Float(f) => self.builder.ins().f64const(*f),
+                        _ => self
+                            .builder
+                            .ins()
+                            .iconst(types::I64, JitType::encode(constant.as_ref())),
+                    };
+                    self.push(current, t, value);
+                }
+                InstructionKind::ILoadLocal => {
+                    let idx = decoded.operands[0];
+                    let t = current.locals[idx].unwrap();
+                    let var = self.variable(idx, t);
+                    let value = self.builder.use_var(var);
+                    self.push(current, t, value);
+                }
+                InstructionKind::IStoreLocal => {
+                    let idx = decoded.operands[0];
+                    let (t, value) = self.pop(current);
+                    let var = self.variable(idx, t);
+                    self.builder.def_var(var, value);
+                    current.locals[idx] = Some(t);
+                }
+                InstructionKind::IJump => {
+                    self.builder.ins().jump(blocks[&decoded.operands[0]], &[]);
+                    terminated = true;
+                }
+                InstructionKind::INotJump => {
+                    let (t, value) = self.pop(current);
+                    let cond = self.truth_value(t, value);
+                    self.builder.ins().brif(
+                        cond,
+                        blocks[&decoded.next],
+                        &[],
+                        blocks[&decoded.operands[0]],
+                        &[],
+                    );
+                    terminated = true;
+                }
+                InstructionKind::INeg => {
+                    let (t, value) = self.pop(current);
+                    let result = self.builder.ins().bnot(value);
+                    self.push(current, t, result);
+                }
+                InstructionKind::ILNot => {
+                    let (t, value) = self.pop(current);
+                    let cond = self.truth_value(t, value);
+                    let inverted = self.builder.ins().bxor_imm(cond, 1);
+                    let result = self.builder.ins().uextend(types::I64, inverted);
+                    self.push(current, JitType::Bool, result);
+                }
+                InstructionKind::IRetVal | InstructionKind::IRet => {
+                    if decoded.inst == InstructionKind::IRetVal {
+                        let (t, value) = self.pop(current);
+                        let raw = if t == JitType::Float {
+                            self.builder
+                                .ins()
+                                .bitcast(types::I64, MemFlags::new(), value)
+                        } else {
+                            value
+                        };
+                        self.builder
+                            .ins()
+                            .store(MemFlags::trusted(), raw, result_ptr, 0);
+                    }
+
+                    let status = self.builder.ins().iconst(types::I64, STATUS_OK);
+                    self.builder.ins().return_(&[status]);
+                    terminated = true;
+                }
+                _ => {
+                    let error = self.translate_binary(&decoded.inst, current, deopt_block);
+                    if error.is_some() {
+                        return error;
+                    }
+                }
+            }
+
+            if terminated {
+                state = None;
+            }
+        }
+
+        self.builder.switch_to_block(deopt_block);
+        let status = self.builder.ins().iconst(types::I64, STATUS_DEOPT);
+        self.builder.ins().return_(&[status]);
+
+        return None;
+    }
+}
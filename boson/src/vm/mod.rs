@@ -4,10 +4,13 @@ pub mod errors;
 pub mod ffi;
 pub mod frames;
 pub mod global;
+pub mod jit;
 pub mod stack;
 pub mod thread;
 
+use std::cell::Cell;
 use std::cell::RefCell;
+use std::mem;
 use std::rc::Rc;
 
 use controls::Controls;
@@ -21,10 +24,15 @@ use stack::DataStack;
 use crate::api::Platform;
 use crate::compiler::symtab::ConstantPool;
 use crate::compiler::CompiledBytecode;
+use crate::config::MAX_SANDBOX_DEPTH;
 use crate::isa::InstructionKind;
 use crate::types::closure;
+use crate::types::exception;
+use crate::types::iter;
 use crate::types::object;
 
+use exception::Exception;
+use iter::GeneratorState;
 use object::Object;
 
 // defer - taken from: https://stackoverflow.com/questions/29963449/golang-like-defer-in-rust
@@ -38,6 +46,41 @@ impl<F: FnOnce()> Drop for ScopeCall<F> {
     }
 }
 
+thread_local! {
+    // number of sandbox VMs running on this thread, shared by all of them.
+    static SANDBOX_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/*
+    Every sandbox VM runs on the native stack of the VM that started it, so
+    their nesting is not limited by the call stack of any single VM. Counts
+    the nesting up to MAX_SANDBOX_DEPTH, the returned guard leaves the sandbox
+    when it is dropped.
+*/
+fn enter_sandbox() -> Result<ScopeCall<fn()>, VMError> {
+    let depth = SANDBOX_DEPTH.with(|d| d.get());
+    if depth >= MAX_SANDBOX_DEPTH {
+        return Err(VMError::new(
+            format!(
+                "Stack Overflow! sandbox nesting exceeds {}",
+                MAX_SANDBOX_DEPTH
+            ),
+            VMErrorKind::CallStackOverflow,
+            Some(InstructionKind::ICall),
+            0,
+        ));
+    }
+
+    SANDBOX_DEPTH.with(|d| d.set(depth + 1));
+    return Ok(ScopeCall {
+        c: Some(leave_sandbox),
+    });
+}
+
+fn leave_sandbox() {
+    SANDBOX_DEPTH.with(|d| d.set(d.get() - 1));
+}
+
 pub struct BosonVM {
     pub constants: ConstantPool,
     pub globals: GlobalPool,
@@ -45,6 +88,7 @@ pub struct BosonVM {
     pub call_stack: CallStack,
     pub threads: thread::BosonThreads,
     pub vm_ffi: ffi::BosonFFI,
+    pub jit: jit::BosonJIT,
 }
 
 pub struct BosonVMContext<'a> {
@@ -55,6 +99,7 @@ pub struct BosonVMContext<'a> {
     pub constants: &'a mut ConstantPool,
     pub threads: &'a mut thread::BosonThreads,
     pub vm_ffi: &'a mut ffi::BosonFFI,
+    pub jit: &'a mut jit::BosonJIT,
 }
 
 impl BosonVM {
@@ -75,6 +120,7 @@ impl BosonVM {
             globals: globals,
             threads: thread::BosonThreads::new_empty(),
             vm_ffi: ffi::BosonFFI::empty(),
+            jit: jit::BosonJIT::new(),
         };
     }
 
@@ -93,6 +139,7 @@ impl BosonVM {
             globals: globals,
             threads: thread::BosonThreads::new_empty(),
             vm_ffi: ffi::BosonFFI::empty(),
+            jit: jit::BosonJIT::new(),
         };
     }
 
@@ -107,6 +154,7 @@ impl BosonVM {
             globals: globals,
             threads: thread::BosonThreads::new_empty(),
             vm_ffi: ffi::BosonFFI::empty(),
+            jit: jit::BosonJIT::new(),
         };
     }
 
@@ -133,6 +181,7 @@ impl BosonVM {
             call_stack: &mut self.call_stack,
             threads: &mut self.threads,
             vm_ffi: &mut self.vm_ffi,
+            jit: &mut self.jit,
         };
 
         return Self::eval_bytecode_from_context(&mut context, pop_last, break_on_ret);
@@ -162,21 +211,73 @@ impl BosonVM {
         globals: GlobalPool,
         constants: ConstantPool,
     ) -> Result<Rc<Object>, VMError> {
-        // create an execution frame for that closure:
-
         // new empty from state will create a VM with an empty call stack.
         let mut vm_instance = BosonVM::new_empty_from_state(globals, constants);
+        return vm_instance.call_closure(closure, params, platform);
+    }
+
+    /*
+        Runs the given function on a sandbox VM which borrows the state of the
+        calling VM, used by builtins and attribute methods that call back into
+        boson code (like array.map()). Unlike execute_sandbox, the globals are not
+        copied, so the changes made by the callbacks are visible to the caller.
+    */
+    pub fn with_sandbox<T, F: FnOnce(&mut BosonVM) -> T>(
+        globals: &mut GlobalPool,
+        constants: &mut ConstantPool,
+        threads: &mut thread::BosonThreads,
+        vm_ffi: &mut ffi::BosonFFI,
+        func: F,
+    ) -> T {
+        let mut vm_instance = BosonVM::new_empty_from_state(
+            mem::replace(
+                globals,
+                GlobalPool {
+                    pool: vec![],
+                    max_size: 0,
+                },
+            ),
+            std::mem::take(constants),
+        );
+
+        vm_instance.threads = mem::replace(threads, thread::BosonThreads::new_empty());
+        vm_instance.vm_ffi = mem::replace(vm_ffi, ffi::BosonFFI::empty());
+
+        let result = func(&mut vm_instance);
+
+        // hand the state back to the caller:
+        *globals = vm_instance.globals;
+        *constants = vm_instance.constants;
+        *threads = vm_instance.threads;
+        *vm_ffi = vm_instance.vm_ffi;
+
+        return result;
+    }
+
+    // calls the closure on top of an empty call stack and returns its result.
+    pub fn call_closure(
+        &mut self,
+        closure: Rc<closure::ClosureContext>,
+        params: Vec<Rc<Object>>,
+        platform: &mut Platform,
+    ) -> Result<Rc<Object>, VMError> {
+        // leaves the sandbox when the guard goes out of scope:
+        let _guard = match enter_sandbox() {
+            Ok(guard) => guard,
+            Err(error) => return Err(error),
+        };
+
         let closure_rc = Rc::new(Object::ClosureContext(closure));
 
         // push the arguments on top of the stack:
         let params_len = params.len();
-        let mut error = Controls::push_objects(params, &mut vm_instance.data_stack);
+        let mut error = Controls::push_objects(params, &mut self.data_stack);
         if error.is_some() {
             return Err(error.unwrap());
         }
 
         // push the closure on to the stack:
-        error = Controls::push_objects(vec![closure_rc], &mut vm_instance.data_stack);
+        error = Controls::push_objects(vec![closure_rc], &mut self.data_stack);
         if error.is_some() {
             return Err(error.unwrap());
         }
@@ -184,13 +285,14 @@ impl BosonVM {
         // execute the function call:
         let exec_frame = Controls::execute_call(
             &InstructionKind::ICall,
-            &mut vm_instance.data_stack,
+            &mut self.data_stack,
             params_len,
-            &mut vm_instance.globals,
-            &mut vm_instance.constants,
+            &mut self.globals,
+            &mut self.constants,
             platform,
-            &mut vm_instance.threads,
-            &mut vm_instance.vm_ffi,
+            &mut self.threads,
+            &mut self.vm_ffi,
+            &mut self.jit,
         );
 
         if exec_frame.is_err() {
@@ -201,19 +303,17 @@ impl BosonVM {
         let unwrapped_frame_res = exec_frame.unwrap();
         if unwrapped_frame_res.is_some() {
             // custom function call, push the execution frame:
-            let frame_push_res = vm_instance.push_new_frame(unwrapped_frame_res.unwrap());
+            let frame_push_res = self.push_new_frame(unwrapped_frame_res.unwrap());
             if frame_push_res.is_some() {
                 return Err(frame_push_res.unwrap());
             }
             // evaluate the frame:
-            let eval_result = vm_instance.eval_bytecode(platform, true, true);
+            let eval_result = self.eval_bytecode(platform, true, true);
             return eval_result;
         }
 
         // last item in the stack:
-        let popped_result = vm_instance
-            .data_stack
-            .pop_object(InstructionKind::IBlockEnd);
+        let popped_result = self.data_stack.pop_object(InstructionKind::IBlockEnd);
         if popped_result.is_err() {
             return Ok(Rc::new(Object::Noval));
         }
@@ -221,13 +321,59 @@ impl BosonVM {
         return Ok(popped_result.unwrap());
     }
 
+    /*
+        Runs a suspended generator until it yields or returns. Returns the
+        yielded value and the new state of the generator, the state is None
+        once the generator has returned.
+    */
+    pub fn resume_generator(
+        &mut self,
+        state: GeneratorState,
+        platform: &mut Platform,
+    ) -> Result<(Rc<Object>, Option<GeneratorState>), VMError> {
+        // leaves the sandbox when the guard goes out of scope:
+        let _guard = match enter_sandbox() {
+            Ok(guard) => guard,
+            Err(error) => return Err(error),
+        };
+
+        self.data_stack.stack = state.stack;
+        self.data_stack.stack_pointer = state.stack_pointer;
+
+        let frame_push_res = self.push_new_frame(RefCell::new(state.frame));
+        if frame_push_res.is_some() {
+            return Err(frame_push_res.unwrap());
+        }
+
+        let eval_result = self.eval_bytecode(platform, true, true);
+        if eval_result.is_err() {
+            return Err(eval_result.unwrap_err());
+        }
+
+        // the frame is gone when the generator returned:
+        if self.call_stack.stack_pointer == -1 {
+            return Ok((eval_result.unwrap(), None));
+        }
+
+        let frame_res = self.call_stack.pop_frame();
+        if frame_res.is_err() {
+            return Err(frame_res.unwrap_err());
+        }
+
+        let next_state = GeneratorState {
+            frame: frame_res.unwrap().into_inner(),
+            stack: mem::take(&mut self.data_stack.stack),
+            stack_pointer: self.data_stack.stack_pointer,
+        };
+
+        return Ok((eval_result.unwrap(), Some(next_state)));
+    }
+
     pub fn dump_ds(&self) -> String {
         let mut result = String::new();
-        let mut idx = 0;
-        for obj in &self.data_stack.stack {
+        for (idx, obj) in self.data_stack.stack.iter().enumerate() {
             let repr = obj.as_ref().describe();
             result.push_str(&format!("{:0>8x} {}\n", idx, repr));
-            idx += 1;
         }
 
         return result;
@@ -237,6 +383,43 @@ impl BosonVM {
         context: &mut BosonVMContext,
         pop_last: bool,
         break_on_ret: bool,
+    ) -> Result<Rc<Object>, VMError> {
+        loop {
+            let result = Self::eval_instructions(context, pop_last, break_on_ret);
+            if result.is_ok() {
+                return result;
+            }
+
+            // the error is converted to an exception and passed on to the nearest
+            // handler, execution resumes from the catch block if one is found.
+            let mut error = result.unwrap_err();
+            if error.location.is_none() && context.call_stack.stack_pointer >= 0 {
+                error.location = context.call_stack.top_ref().get_location();
+            }
+
+            // re-raised exceptions keep the trace of the place they were first raised at.
+            if error.trace.is_empty() {
+                error.trace = Controls::get_stack_trace(context.call_stack);
+            }
+
+            let exception = Rc::new(Exception::new_from_vm_error(&error, error.trace.clone()));
+
+            let unwind_result =
+                Controls::unwind_exception(context.call_stack, context.data_stack, exception);
+            if unwind_result.is_err() {
+                return Err(unwind_result.unwrap_err());
+            }
+
+            if !unwind_result.unwrap() {
+                return Err(error);
+            }
+        }
+    }
+
+    fn eval_instructions(
+        context: &mut BosonVMContext,
+        pop_last: bool,
+        break_on_ret: bool,
     ) -> Result<Rc<Object>, VMError> {
         while context.call_stack.top_ref().has_instructions() {
             let mut frame = context.call_stack.top();
@@ -273,8 +456,7 @@ impl BosonVM {
 
                 InstructionKind::INotJump => {
                     let pos = operands[0];
-                    let result =
-                        Controls::jump_not_truthy(&mut frame, &mut context.data_stack, pos);
+                    let result = Controls::jump_not_truthy(&mut frame, context.data_stack, pos);
                     if result.is_err() {
                         return Err(result.unwrap_err());
                     }
@@ -288,11 +470,8 @@ impl BosonVM {
                 // data load and store instructions:
                 InstructionKind::IConstant => {
                     let const_pos = operands[0];
-                    let result = Controls::load_constant(
-                        &context.constants,
-                        &mut context.data_stack,
-                        const_pos,
-                    );
+                    let result =
+                        Controls::load_constant(context.constants, context.data_stack, const_pos);
 
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -303,11 +482,8 @@ impl BosonVM {
 
                 InstructionKind::IStoreGlobal => {
                     let store_pos = operands[0];
-                    let result = Controls::store_global(
-                        &mut context.globals,
-                        &mut context.data_stack,
-                        store_pos,
-                    );
+                    let result =
+                        Controls::store_global(context.globals, context.data_stack, store_pos);
 
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -318,11 +494,8 @@ impl BosonVM {
 
                 InstructionKind::ILoadGlobal => {
                     let store_pos = operands[0];
-                    let result = Controls::load_global(
-                        &mut context.globals,
-                        &mut context.data_stack,
-                        store_pos,
-                    );
+                    let result =
+                        Controls::load_global(context.globals, context.data_stack, store_pos);
 
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -333,7 +506,40 @@ impl BosonVM {
 
                 InstructionKind::ILoadFree => {
                     let store_pos = operands[0];
-                    let error = Controls::load_free(&mut context.data_stack, &mut frame, store_pos);
+                    let error = Controls::load_free(context.data_stack, &mut frame, store_pos);
+
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IStoreFree => {
+                    let store_pos = operands[0];
+                    let error = Controls::store_free(context.data_stack, &mut frame, store_pos);
+
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::ICaptureFree => {
+                    let store_pos = operands[0];
+                    let error = Controls::capture_free(context.data_stack, &mut frame, store_pos);
+
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::ICaptureLocal => {
+                    let store_pos = operands[0];
+                    let error = Controls::capture_local(context.data_stack, store_pos, &frame);
 
                     if error.is_some() {
                         return Err(error.unwrap());
@@ -344,8 +550,7 @@ impl BosonVM {
 
                 InstructionKind::ILoadLocal => {
                     let store_pos = operands[0];
-                    let result =
-                        Controls::load_local(&mut context.data_stack, store_pos, &mut frame);
+                    let result = Controls::load_local(context.data_stack, store_pos, &frame);
 
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -356,8 +561,7 @@ impl BosonVM {
 
                 InstructionKind::IStoreLocal => {
                     let store_pos = operands[0];
-                    let result =
-                        Controls::store_local(&mut context.data_stack, store_pos, &mut frame);
+                    let result = Controls::store_local(context.data_stack, store_pos, &frame);
 
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -367,7 +571,7 @@ impl BosonVM {
                 }
 
                 InstructionKind::IAssertFail => {
-                    let error = Controls::raise_assertion_error(&mut context.data_stack);
+                    let error = Controls::raise_assertion_error(context.data_stack);
                     if error.is_some() {
                         return Err(error.unwrap());
                     }
@@ -376,7 +580,7 @@ impl BosonVM {
                 }
 
                 InstructionKind::IGetIndex => {
-                    let error = Controls::get_index_value(&mut context.data_stack);
+                    let error = Controls::get_index_value(context.data_stack);
                     if error.is_some() {
                         return Err(error.unwrap());
                     }
@@ -385,7 +589,58 @@ impl BosonVM {
                 }
 
                 InstructionKind::ISetIndex => {
-                    let error = Controls::set_indexed(&mut context.data_stack);
+                    let error = Controls::set_indexed(context.data_stack);
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::ISlice => {
+                    let error = Controls::get_slice(context.data_stack);
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::ISetSlice => {
+                    let error = Controls::set_slice(context.data_stack);
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IMatchValue
+                | InstructionKind::IMatchRange
+                | InstructionKind::IMatchType
+                | InstructionKind::IMatchArray
+                | InstructionKind::IMatchArrayRest
+                | InstructionKind::IMatchKey => {
+                    let operand = if !operands.is_empty() { operands[0] } else { 0 };
+                    let error = Controls::execute_match_test(context.data_stack, &inst, operand);
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IUnpackArray => {
+                    let error = Controls::unpack_array(context.data_stack, operands[0]);
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IUnpackHash => {
+                    let error = Controls::unpack_hash(context.data_stack, operands[0]);
                     if error.is_some() {
                         return Err(error.unwrap());
                     }
@@ -401,6 +656,11 @@ impl BosonVM {
                 | InstructionKind::IMod
                 | InstructionKind::IAnd
                 | InstructionKind::IOr
+                | InstructionKind::IXor
+                | InstructionKind::IShl
+                | InstructionKind::IShr
+                | InstructionKind::IPow
+                | InstructionKind::IFloorDiv
                 | InstructionKind::ILAnd
                 | InstructionKind::ILOr
                 | InstructionKind::ILGt
@@ -409,7 +669,7 @@ impl BosonVM {
                 | InstructionKind::ILLt
                 | InstructionKind::ILEq
                 | InstructionKind::ILNe => {
-                    let error = Controls::execute_binary_op(&inst, &mut context.data_stack);
+                    let error = Controls::execute_binary_op(&inst, context.data_stack);
                     if error.is_some() {
                         return Err(error.unwrap());
                     }
@@ -419,7 +679,7 @@ impl BosonVM {
 
                 // unary operators:
                 InstructionKind::ILNot | InstructionKind::INeg => {
-                    let error = Controls::execute_unary_op(&inst, &mut context.data_stack);
+                    let error = Controls::execute_unary_op(&inst, context.data_stack);
                     if error.is_some() {
                         return Err(error.unwrap());
                     }
@@ -430,7 +690,7 @@ impl BosonVM {
                 // built-ins
                 InstructionKind::ILoadBuiltIn => {
                     let builtin_idx = operands[0];
-                    let result = Controls::load_builtin(&mut context.data_stack, builtin_idx);
+                    let result = Controls::load_builtin(context.data_stack, builtin_idx);
                     if result.is_err() {
                         return Err(result.unwrap_err());
                     }
@@ -444,13 +704,14 @@ impl BosonVM {
 
                     let result = Controls::execute_call(
                         &inst,
-                        &mut context.data_stack,
+                        context.data_stack,
                         args_len,
-                        &mut context.globals,
-                        &mut context.constants,
+                        context.globals,
+                        context.constants,
                         context.platform,
-                        &mut context.threads,
-                        &mut context.vm_ffi,
+                        context.threads,
+                        context.vm_ffi,
+                        context.jit,
                     );
 
                     if result.is_err() {
@@ -461,6 +722,7 @@ impl BosonVM {
                     if new_frame.is_some() {
                         // the previous frame should point to the
                         // next instruction after call
+                        frame.last_call = frame.get_ip();
                         frame.farword_ip(next);
                         // Looking for better way to handle this:
                         std::mem::drop(frame);
@@ -478,12 +740,12 @@ impl BosonVM {
                     let n_args = operands[0];
                     let result = Controls::execute_thread(
                         &inst,
-                        &mut context.data_stack,
+                        context.data_stack,
                         n_args,
-                        &mut context.globals,
-                        &mut context.constants,
+                        context.globals,
+                        context.constants,
                         context.platform,
-                        &mut context.threads,
+                        context.threads,
                         false,
                     );
 
@@ -498,12 +760,12 @@ impl BosonVM {
                     let n_args = operands[0];
                     let result = Controls::execute_thread(
                         &inst,
-                        &mut context.data_stack,
+                        context.data_stack,
                         n_args,
-                        &mut context.globals,
-                        &mut context.constants,
+                        context.globals,
+                        context.constants,
                         context.platform,
-                        &mut context.threads,
+                        context.threads,
                         true,
                     );
 
@@ -517,7 +779,7 @@ impl BosonVM {
                 // build Array and Hash:
                 InstructionKind::IArray => {
                     let length = operands[0];
-                    let result = Controls::build_array(&inst, &mut context.data_stack, length);
+                    let result = Controls::build_array(&inst, context.data_stack, length);
                     if result.is_err() {
                         return Err(result.unwrap_err());
                     }
@@ -527,7 +789,7 @@ impl BosonVM {
 
                 InstructionKind::IHash => {
                     let length = operands[0];
-                    let result = Controls::build_hash(&inst, &mut context.data_stack, length);
+                    let result = Controls::build_hash(&inst, context.data_stack, length);
                     if result.is_err() {
                         return Err(result.unwrap_err());
                     }
@@ -537,8 +799,8 @@ impl BosonVM {
 
                 InstructionKind::IClosure => {
                     let error = Controls::create_closure(
-                        &mut context.data_stack,
-                        &context.constants,
+                        context.data_stack,
+                        context.constants,
                         operands[1],
                         operands[0],
                     );
@@ -560,7 +822,7 @@ impl BosonVM {
                     // execute return: This function cleans up the subroutine's data
                     // on stack
                     let error = Controls::execute_return(
-                        &mut context.data_stack,
+                        context.data_stack,
                         &current_frame_res.unwrap().borrow(),
                         false,
                     );
@@ -569,7 +831,9 @@ impl BosonVM {
                         return Err(error.unwrap());
                     }
 
-                    if break_on_ret {
+                    // stop once the sandboxed function returns, returns from
+                    // the functions it called continue the evaluation.
+                    if break_on_ret && context.call_stack.stack_pointer == -1 {
                         break;
                     }
                 }
@@ -584,7 +848,7 @@ impl BosonVM {
                     // execute return: This function cleans up the subroutine's data
                     // on stack
                     let error = Controls::execute_return(
-                        &mut context.data_stack,
+                        context.data_stack,
                         &current_frame_res.unwrap().borrow(),
                         true,
                     );
@@ -593,13 +857,21 @@ impl BosonVM {
                         return Err(error.unwrap());
                     }
 
-                    if break_on_ret {
+                    if break_on_ret && context.call_stack.stack_pointer == -1 {
                         break;
                     }
                 }
 
+                // only generator frames yield, they are resumed on a sandbox VM
+                // where they are the only frame. The yielded value is left on top
+                // of the stack for resume_generator.
+                InstructionKind::IYield => {
+                    frame.farword_ip(next);
+                    break;
+                }
+
                 InstructionKind::IIter => {
-                    let error = Controls::create_iter(&mut context.data_stack);
+                    let error = Controls::create_iter(context.data_stack);
                     if error.is_some() {
                         return Err(error.unwrap());
                     }
@@ -610,10 +882,15 @@ impl BosonVM {
                 InstructionKind::IIterNext => {
                     let jmp_pos = operands[0];
                     let result = Controls::jump_next_iter(
-                        &mut context.data_stack,
+                        context.data_stack,
                         jmp_pos,
                         &mut frame,
                         false,
+                        context.platform,
+                        context.globals,
+                        context.constants,
+                        context.threads,
+                        context.vm_ffi,
                     );
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -628,10 +905,15 @@ impl BosonVM {
                 InstructionKind::IEnumNext => {
                     let jmp_pos = operands[0];
                     let result = Controls::jump_next_iter(
-                        &mut context.data_stack,
+                        context.data_stack,
                         jmp_pos,
                         &mut frame,
                         true,
+                        context.platform,
+                        context.globals,
+                        context.constants,
+                        context.threads,
+                        context.vm_ffi,
                     );
                     if result.is_err() {
                         return Err(result.unwrap_err());
@@ -646,12 +928,12 @@ impl BosonVM {
                 InstructionKind::IShell => {
                     let result = Controls::exec_shell(
                         &inst,
-                        &mut context.data_stack,
+                        context.data_stack,
                         context.platform,
-                        &mut context.globals,
-                        &mut context.constants,
-                        &mut context.threads,
-                        &mut context.vm_ffi,
+                        context.globals,
+                        context.constants,
+                        context.threads,
+                        context.vm_ffi,
                         false,
                     );
 
@@ -665,12 +947,12 @@ impl BosonVM {
                 InstructionKind::IShellRaw => {
                     let result = Controls::exec_shell(
                         &inst,
-                        &mut context.data_stack,
+                        context.data_stack,
                         context.platform,
-                        &mut context.globals,
-                        &mut context.constants,
-                        &mut context.threads,
-                        &mut context.vm_ffi,
+                        context.globals,
+                        context.constants,
+                        context.threads,
+                        context.vm_ffi,
                         true,
                     );
 
@@ -683,7 +965,7 @@ impl BosonVM {
 
                 InstructionKind::IGetAttr => {
                     let n_attrs = operands[0];
-                    let result = Controls::get_attr(&mut context.data_stack, &inst, n_attrs);
+                    let result = Controls::get_attr(context.data_stack, &inst, n_attrs);
 
                     if result.is_some() {
                         return Err(result.unwrap());
@@ -692,27 +974,158 @@ impl BosonVM {
                     frame.farword_ip(next);
                 }
 
+                InstructionKind::ISetAttr => {
+                    let n_attrs = operands[0];
+                    let error = Controls::set_attr(context.data_stack, &inst, n_attrs);
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
                 InstructionKind::ICallAttr => {
                     let n_attrs = operands[0];
                     let n_params = operands[1];
 
                     let result = Controls::call_attr(
-                        &mut context.data_stack,
+                        context.data_stack,
                         &inst,
                         n_attrs,
                         n_params,
+                        &[],
                         context.platform,
-                        &mut context.globals,
-                        &mut context.constants,
-                        &mut context.threads,
-                        &mut context.vm_ffi,
+                        context.globals,
+                        context.constants,
+                        context.threads,
+                        context.vm_ffi,
+                        context.jit,
                     );
 
-                    if result.is_some() {
-                        return Err(result.unwrap());
+                    if result.is_err() {
+                        return Err(result.unwrap_err());
+                    }
+
+                    let new_frame = result.unwrap();
+                    if new_frame.is_some() {
+                        frame.last_call = frame.get_ip();
                     }
 
                     frame.farword_ip(next);
+                    if new_frame.is_some() {
+                        std::mem::drop(frame);
+                        let push_result = context.call_stack.push_frame(new_frame.unwrap());
+                        if push_result.is_err() {
+                            return Err(push_result.unwrap_err());
+                        }
+                    }
+                }
+
+                InstructionKind::ICallSpread => {
+                    let n_chunks = operands[0];
+                    let n_attrs = operands[1];
+
+                    let result = Controls::call_spread(
+                        context.data_stack,
+                        &inst,
+                        n_chunks,
+                        n_attrs,
+                        context.platform,
+                        context.globals,
+                        context.constants,
+                        context.threads,
+                        context.vm_ffi,
+                        context.jit,
+                    );
+
+                    if result.is_err() {
+                        return Err(result.unwrap_err());
+                    }
+
+                    let new_frame = result.unwrap();
+                    if new_frame.is_some() {
+                        frame.last_call = frame.get_ip();
+                    }
+
+                    frame.farword_ip(next);
+                    if new_frame.is_some() {
+                        std::mem::drop(frame);
+                        let push_result = context.call_stack.push_frame(new_frame.unwrap());
+                        if push_result.is_err() {
+                            return Err(push_result.unwrap_err());
+                        }
+                    }
+                }
+
+                InstructionKind::IModule => {
+                    let length = operands[0];
+                    let result = Controls::build_module(&inst, context.data_stack, length);
+                    if result.is_err() {
+                        return Err(result.unwrap_err());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IClass => {
+                    let n_fields = operands[0];
+                    let n_method_entries = operands[1];
+                    let result = Controls::build_class(
+                        &inst,
+                        context.data_stack,
+                        n_fields,
+                        n_method_entries,
+                    );
+                    if result.is_err() {
+                        return Err(result.unwrap_err());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IPushExcHandle => {
+                    let catch_pos = operands[0];
+                    let error =
+                        Controls::push_exception_handler(&mut frame, context.data_stack, catch_pos);
+
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IPopExcHandle => {
+                    let error = Controls::pop_exception_handler(&mut frame);
+                    if error.is_some() {
+                        return Err(error.unwrap());
+                    }
+
+                    frame.farword_ip(next);
+                }
+
+                InstructionKind::IRaise => {
+                    std::mem::drop(frame);
+                    let exc_result =
+                        Controls::get_raised_exception(context.call_stack, context.data_stack);
+                    if exc_result.is_err() {
+                        return Err(exc_result.unwrap_err());
+                    }
+
+                    let exception = exc_result.unwrap();
+                    let unwind_result = Controls::unwind_exception(
+                        context.call_stack,
+                        context.data_stack,
+                        exception.clone(),
+                    );
+
+                    if unwind_result.is_err() {
+                        return Err(unwind_result.unwrap_err());
+                    }
+
+                    if !unwind_result.unwrap() {
+                        return Err(exception.as_vm_error());
+                    }
                 }
 
                 _ => {
@@ -32,6 +32,12 @@ pub struct CallStack {
     pub max_size: usize,
 }
 
+impl Default for CallStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CallStack {
     pub fn new() -> CallStack {
         return CallStack {
@@ -76,7 +82,7 @@ impl CallStack {
         return self.stack_pointer;
     }
 
-    pub fn top(&mut self) -> RefMut<ExecutionFrame> {
+    pub fn top(&mut self) -> RefMut<'_, ExecutionFrame> {
         return self
             .stack
             .get(self.stack_pointer as usize)
@@ -84,7 +90,7 @@ impl CallStack {
             .borrow_mut();
     }
 
-    pub fn top_ref(&self) -> Ref<ExecutionFrame> {
+    pub fn top_ref(&self) -> Ref<'_, ExecutionFrame> {
         return self
             .stack
             .get(self.stack_pointer as usize)
@@ -99,6 +105,12 @@ pub struct DataStack {
     pub max_size: usize,
 }
 
+impl Default for DataStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DataStack {
     pub fn new() -> DataStack {
         let mut stack = vec![];
@@ -6,6 +6,7 @@ use crate::api;
 use crate::compiler::symtab;
 use crate::types::closure;
 use crate::types::object;
+use crate::types::sendable;
 use crate::vm;
 use crate::vm::global;
 
@@ -15,50 +16,155 @@ use api::PlatformKind;
 use closure::ClosureContext;
 use global::GlobalPool;
 use object::Object;
+use sendable::SendableContext;
+use sendable::SendableObject;
 use symtab::ConstantPool;
 use vm::BosonVM;
 
 use crate::vm::errors;
 use errors::VMError;
 
+/*
+    Values crossing the thread boundary are never shared, the params,
+    globals and constants are deep copied into sendable form before the
+    thread is spawned and rebuilt inside the thread, the result travels
+    back the same way. See types/sendable.rs.
+*/
 #[derive(Debug, Clone)]
 pub struct ThreadReturnType {
-    pub result: Result<Rc<Object>, VMError>,
+    context: SendableContext,
+    result: Result<SendableObject, VMError>,
 }
 
 impl ThreadReturnType {
     pub fn new(result: Result<Rc<Object>, VMError>) -> ThreadReturnType {
-        return ThreadReturnType { result: result };
+        let mut context = SendableContext::new();
+        let result = match result {
+            Ok(obj) => match context.encode(&obj) {
+                Ok(value) => Ok(value),
+                Err(error) => Err(VMError::new(
+                    format!("Thread result cannot be returned: {}", error),
+                    errors::VMErrorKind::ThreadWaitError,
+                    None,
+                    0,
+                )),
+            },
+            Err(error) => Err(error),
+        };
+
+        return ThreadReturnType {
+            context: context,
+            result: result,
+        };
     }
-}
 
-unsafe impl Send for ThreadReturnType {}
+    // rebuilds the result on the calling thread.
+    pub fn get_result(&self) -> Result<Rc<Object>, VMError> {
+        match &self.result {
+            Ok(obj) => Ok(self.context.decoder().decode(obj)),
+            Err(error) => Err(error.clone()),
+        }
+    }
+}
 
+#[derive(Debug)]
 pub struct ThreadParams {
-    closure: Rc<ClosureContext>,
-    params: Vec<Rc<Object>>,
-    globals: GlobalPool,
-    constants: ConstantPool,
+    context: SendableContext,
+    closure: SendableObject,
+    params: Vec<SendableObject>,
+    globals: Vec<(usize, SendableObject)>,
+    constants: Vec<SendableObject>,
 }
 
 impl ThreadParams {
     pub fn new(
         closure: Rc<ClosureContext>,
         params: Vec<Rc<Object>>,
-        globals: GlobalPool,
-        constants: ConstantPool,
-    ) -> ThreadParams {
-        return ThreadParams {
-            closure: closure,
-            params: params,
-            globals: globals,
-            constants: constants,
+        globals: &GlobalPool,
+        constants: &ConstantPool,
+    ) -> Result<ThreadParams, String> {
+        // a single context is used for everything, so a container shared
+        // between a global and a param stays shared in the thread.
+        let mut context = SendableContext::new();
+
+        let closure = context.encode(&Rc::new(Object::ClosureContext(closure)));
+        if closure.is_err() {
+            return Err(closure.unwrap_err());
+        }
+
+        let mut encoded_params = vec![];
+        for param in params.iter() {
+            let encoded = context.encode(param);
+            if encoded.is_err() {
+                return Err(encoded.unwrap_err());
+            }
+            encoded_params.push(encoded.unwrap());
+        }
+
+        // globals are copied whether the thread uses them or not, the ones
+        // that cannot be sent (like the iterator of a running for loop over
+        // a generator) are left out.
+        let mut encoded_globals = vec![];
+        for (idx, obj) in globals.pool.iter().enumerate() {
+            if let Object::Noval = obj.as_ref() {
+                continue;
+            }
+            let encoded = context.encode(obj);
+            if encoded.is_ok() {
+                encoded_globals.push((idx, encoded.unwrap()));
+            }
+        }
+
+        let mut encoded_constants = vec![];
+        for constant in constants.objects.iter() {
+            let encoded = context.encode(constant);
+            if encoded.is_err() {
+                return Err(encoded.unwrap_err());
+            }
+            encoded_constants.push(encoded.unwrap());
+        }
+
+        return Ok(ThreadParams {
+            context: context,
+            closure: closure.unwrap(),
+            params: encoded_params,
+            globals: encoded_globals,
+            constants: encoded_constants,
+        });
+    }
+
+    // must be called on the new thread, builds the objects owned by it.
+    fn build(
+        &self,
+    ) -> (
+        Rc<ClosureContext>,
+        Vec<Rc<Object>>,
+        GlobalPool,
+        ConstantPool,
+    ) {
+        let mut decoder = self.context.decoder();
+
+        let closure = match decoder.decode(&self.closure).as_ref() {
+            Object::ClosureContext(ctx) => ctx.clone(),
+            _ => unreachable!(),
         };
+
+        let params = self.params.iter().map(|p| decoder.decode(p)).collect();
+
+        let mut globals = GlobalPool::new();
+        for (idx, obj) in self.globals.iter() {
+            globals.pool[*idx] = decoder.decode(obj);
+        }
+
+        let mut constants = ConstantPool::new();
+        for obj in self.constants.iter() {
+            constants.set_object(decoder.decode(obj));
+        }
+
+        return (closure, params, globals, constants);
     }
 }
 
-unsafe impl Send for ThreadParams {}
-
 pub struct BosonThreads {
     pub thread_map: HashMap<u64, thread::JoinHandle<ThreadReturnType>>,
     // this counter will increment each time a new thread is created.
@@ -116,13 +222,9 @@ impl BosonThreads {
         };
 
         let handle = thread::spawn(move || {
-            let result = BosonVM::execute_sandbox(
-                thread_params.closure,
-                thread_params.params,
-                &mut new_platform,
-                thread_params.globals,
-                thread_params.constants,
-            );
+            let (closure, params, globals, constants) = thread_params.build();
+            let result =
+                BosonVM::execute_sandbox(closure, params, &mut new_platform, globals, constants);
 
             return ThreadReturnType::new(result);
         });
@@ -0,0 +1,38 @@
+import time
+
+
+def is_prime(n):
+    if n < 2:
+        return False
+
+    d = 2
+    while d * d <= n:
+        if n % d == 0:
+            return False
+        d = d + 1
+    return True
+
+
+def integrate(start, stop, n):
+    step = (stop - start) / n
+    area = 0.0
+    i = 0
+    while i < n:
+        x = start + i * step
+        area = area + x * x * step
+        i = i + 1
+    return area
+
+
+n_primes = 0
+area = 0.0
+
+st = time.time()
+for idx in range(1, 20000):
+    if is_prime(idx):
+        n_primes = n_primes + 1
+    area = area + integrate(0, 1, 100)
+et = time.time()
+
+print(n_primes, area)
+print(et - st)